@@ -0,0 +1,80 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use kiddo::KdTree;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use vivotk::structures::{approx_knn::ApproxKnn, neighbours::NearestNeighbours};
+
+const POINTS: usize = 1_000_000;
+const QUERIES: usize = 10_000;
+const K: usize = 10;
+
+fn random_points(rng: &mut StdRng, n: usize) -> Vec<[f32; 3]> {
+    (0..n)
+        .map(|_| {
+            [
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-10.0..10.0),
+            ]
+        })
+        .collect()
+}
+
+fn recall(exact: &KdTree<f32, usize, 3>, approximate: &ApproxKnn, queries: &[[f32; 3]]) -> f64 {
+    let found: usize = queries
+        .iter()
+        .map(|q| {
+            let approximate = approximate.nearest_neighbours(q, K);
+            exact
+                .nearest_neighbours(q, K)
+                .iter()
+                .filter(|(_, i)| approximate.iter().any(|(_, j)| i == j))
+                .count()
+        })
+        .sum();
+    found as f64 / (queries.len() * K) as f64
+}
+
+/// Compares the query time of the exact kd tree with the approximate forest for a few forest
+/// sizes on a 1M point cloud, and prints the recall of each forest.
+fn bench_knn_queries(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let points = random_points(&mut rng, POINTS);
+    let queries = random_points(&mut rng, QUERIES);
+
+    let mut exact: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in points.iter().enumerate() {
+        exact.add(p, i).unwrap();
+    }
+    c.bench_function("exact_knn", |b| {
+        b.iter(|| {
+            for q in &queries {
+                black_box(exact.nearest_neighbours(q, K));
+            }
+        })
+    });
+
+    for trees in [5, 8, 10] {
+        let approximate =
+            ApproxKnn::with_params(points.clone(), trees, ApproxKnn::DEFAULT_LEAF_SIZE, 0);
+        println!(
+            "approx_knn_{}_trees recall@{}: {:.4}",
+            trees,
+            K,
+            recall(&exact, &approximate, &queries)
+        );
+        c.bench_function(&format!("approx_knn_{}_trees", trees), |b| {
+            b.iter(|| {
+                for q in &queries {
+                    black_box(approximate.nearest_neighbours(q, K));
+                }
+            })
+        });
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_knn_queries
+}
+criterion_main!(benches);
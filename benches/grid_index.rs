@@ -0,0 +1,80 @@
+use cgmath::Point3;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use kiddo::{distance::squared_euclidean, KdTree};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use vivotk::formats::pointxyzrgba::PointXyzRgba;
+use vivotk::structures::grid_index::GridIndex;
+
+const POINTS: usize = 200_000;
+const QUERIES: usize = 10_000;
+const RADIUS: f32 = 0.2;
+
+fn uniform_cloud(rng: &mut StdRng, n: usize) -> Vec<PointXyzRgba> {
+    (0..n)
+        .map(|_| PointXyzRgba {
+            x: rng.gen_range(0.0..10.0),
+            y: rng.gen_range(0.0..10.0),
+            z: rng.gen_range(0.0..10.0),
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        })
+        .collect()
+}
+
+/// Compares building and radius queries of the grid index with the kd tree on a uniform cloud.
+fn bench_uniform_cloud(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let points = uniform_cloud(&mut rng, POINTS);
+    let queries = uniform_cloud(&mut rng, QUERIES);
+
+    c.bench_function("grid_index_build", |b| {
+        b.iter(|| {
+            let mut grid = GridIndex::new(RADIUS);
+            grid.extend(points.iter().copied());
+            black_box(grid)
+        })
+    });
+    c.bench_function("kdtree_build", |b| {
+        b.iter(|| {
+            let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+            for (i, p) in points.iter().enumerate() {
+                tree.add(&[p.x, p.y, p.z], i).unwrap();
+            }
+            black_box(tree)
+        })
+    });
+
+    let mut grid = GridIndex::new(RADIUS);
+    grid.extend(points.iter().copied());
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in points.iter().enumerate() {
+        tree.add(&[p.x, p.y, p.z], i).unwrap();
+    }
+
+    c.bench_function("grid_index_query_radius", |b| {
+        b.iter(|| {
+            for q in &queries {
+                black_box(grid.query_radius(&Point3::new(q.x, q.y, q.z), RADIUS));
+            }
+        })
+    });
+    c.bench_function("kdtree_within", |b| {
+        b.iter(|| {
+            for q in &queries {
+                black_box(
+                    tree.within(&[q.x, q.y, q.z], RADIUS * RADIUS, &squared_euclidean)
+                        .unwrap(),
+                );
+            }
+        })
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_uniform_cloud
+}
+criterion_main!(benches);
@@ -0,0 +1,61 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use kiddo::KdTree;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use vivotk::structures::incremental_kdtree::IncrementalKdTree;
+
+const INITIAL_POINTS: usize = 100_000;
+const FRAMES: usize = 20;
+const POINTS_PER_FRAME: usize = 500;
+
+fn random_points(rng: &mut StdRng, n: usize) -> Vec<[f32; 3]> {
+    (0..n)
+        .map(|_| {
+            [
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-10.0..10.0),
+            ]
+        })
+        .collect()
+}
+
+/// Grows a cloud over a number of frames and queries it after each frame, either rebuilding the
+/// whole kd tree for each frame or updating an incremental kd tree.
+fn bench_streaming_construction(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let initial = random_points(&mut rng, INITIAL_POINTS);
+    let frames: Vec<Vec<[f32; 3]>> = (0..FRAMES)
+        .map(|_| random_points(&mut rng, POINTS_PER_FRAME))
+        .collect();
+
+    c.bench_function("rebuild_kdtree_per_frame", |b| {
+        b.iter(|| {
+            let mut points = initial.clone();
+            for frame in &frames {
+                points.extend(frame.iter().copied());
+                let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+                for (i, p) in points.iter().enumerate() {
+                    tree.add(p, i).unwrap();
+                }
+                black_box(&tree);
+            }
+        })
+    });
+
+    c.bench_function("incremental_kdtree", |b| {
+        b.iter(|| {
+            let mut tree = IncrementalKdTree::from_points(initial.clone(), 4 * POINTS_PER_FRAME);
+            for frame in &frames {
+                tree.extend(frame.iter().copied());
+                black_box(&tree);
+            }
+        })
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_streaming_construction
+}
+criterion_main!(benches);
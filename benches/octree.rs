@@ -0,0 +1,72 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use kiddo::{distance::squared_euclidean, KdTree};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+use vivotk::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use vivotk::structures::neighbours::select_neighboring_points;
+
+/// Expected number of neighbours of every point, the radius shrinks as the cloud grows.
+const NEIGHBOURS: f32 = 20.0;
+
+fn uniform_cloud(n: usize) -> PointCloud<PointXyzRgba> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let points = (0..n)
+        .map(|_| {
+            PointXyzRgba::from_xyz_rgba(
+                [
+                    rng.gen_range(0.0..1.0),
+                    rng.gen_range(0.0..1.0),
+                    rng.gen_range(0.0..1.0),
+                ],
+                [0, 0, 0, 255],
+            )
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// The radius neighbourhoods of [`select_neighboring_points`] searched with a kd tree instead of
+/// the octree, the baseline it is measured against.
+fn select_neighboring_points_kdtree(pc: &PointCloud<PointXyzRgba>, radius: f32) -> Vec<Vec<usize>> {
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in pc.points.iter().enumerate() {
+        tree.add(&p.xyz(), i).expect("Failed to add to tree");
+    }
+    pc.points
+        .par_iter()
+        .map(|p| {
+            let mut neighbours: Vec<usize> = tree
+                .within(&p.xyz(), radius * radius, &squared_euclidean)
+                .expect("Failed to query kd tree")
+                .into_iter()
+                .map(|(_, &i)| i)
+                .collect();
+            neighbours.sort_unstable();
+            neighbours
+        })
+        .collect()
+}
+
+/// Compares the radius neighbourhoods of every point of uniform clouds in the unit cube, searched
+/// with the kd tree and the octree, tree building included.
+fn bench_select_neighboring_points(c: &mut Criterion) {
+    let mut group = c.benchmark_group("select_neighboring_points");
+    for n in [1_000, 10_000, 100_000, 1_000_000] {
+        let pc = uniform_cloud(n);
+        let radius = (3.0 * NEIGHBOURS / (4.0 * std::f32::consts::PI * n as f32)).cbrt();
+        group.bench_with_input(BenchmarkId::new("kdtree", n), &pc, |b, pc| {
+            b.iter(|| black_box(select_neighboring_points_kdtree(pc, radius)))
+        });
+        group.bench_with_input(BenchmarkId::new("octree", n), &pc, |b, pc| {
+            b.iter(|| black_box(select_neighboring_points(pc, radius)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_select_neighboring_points
+}
+criterion_main!(benches);
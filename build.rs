@@ -0,0 +1,6 @@
+fn main() {
+    // the grpc service is generated from its protobuf definition, which needs protoc
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/pointcloud.proto")
+        .expect("Failed to compile protobuf definitions");
+}
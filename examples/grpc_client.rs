@@ -0,0 +1,27 @@
+//! Prints the frames streamed by `grpc-serve`, e.g.
+//!
+//! ```shell
+//! vv read ./ply +output=plys grpc-serve --port 50051 --clients 1 +input=plys
+//! cargo run --example grpc_client --features grpc -- http://localhost:50051
+//! ```
+use vivotk::grpc::from_frame;
+use vivotk::grpc::proto::{
+    point_cloud_service_client::PointCloudServiceClient, StreamCloudsRequest,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let url = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "http://localhost:50051".to_string());
+    let mut client = PointCloudServiceClient::connect(url).await?;
+    let mut frames = client
+        .stream_clouds(StreamCloudsRequest {})
+        .await?
+        .into_inner();
+    while let Some(frame) = frames.message().await? {
+        let cloud = from_frame(&frame);
+        println!("frame {}: {} points", frame.index, cloud.len());
+    }
+    Ok(())
+}
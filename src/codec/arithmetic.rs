@@ -0,0 +1,116 @@
+//! Adaptive model of bytes for the arithmetic coder of the `arithmetic-coding` crate.
+
+use arithmetic_coding::Model;
+use std::convert::Infallible;
+use std::ops::Range;
+
+/// Frequencies are halved once their total exceeds this, keeping the model adaptive and the
+/// denominator within the 17 bits left by the 47 bits of precision of a u64 coder.
+const MAX_TOTAL: u64 = 1 << 16;
+const INCREMENT: u64 = 32;
+
+/// Adaptive frequencies of the 256 byte values, every value starting at 1, after the end of the
+/// stream, which keeps a frequency of 1.
+#[derive(Clone)]
+pub(crate) struct ByteModel {
+    frequencies: [u64; 256],
+    total: u64,
+}
+
+impl ByteModel {
+    pub(crate) fn new() -> Self {
+        Self {
+            frequencies: [1; 256],
+            total: 256,
+        }
+    }
+}
+
+impl Model for ByteModel {
+    type Symbol = u8;
+    type ValueError = Infallible;
+    type B = u64;
+
+    fn probability(&self, symbol: Option<&u8>) -> Result<Range<u64>, Infallible> {
+        Ok(match symbol {
+            None => 0..1,
+            Some(&symbol) => {
+                let low = 1 + self.frequencies[..symbol as usize].iter().sum::<u64>();
+                low..low + self.frequencies[symbol as usize]
+            }
+        })
+    }
+
+    fn symbol(&self, value: u64) -> Option<u8> {
+        let mut low = 1;
+        if value < low {
+            return None;
+        }
+        for (symbol, &frequency) in self.frequencies.iter().enumerate() {
+            if value < low + frequency {
+                return Some(symbol as u8);
+            }
+            low += frequency;
+        }
+        unreachable!("Value beyond the total frequency");
+    }
+
+    fn denominator(&self) -> u64 {
+        self.total + 1
+    }
+
+    fn max_denominator(&self) -> u64 {
+        MAX_TOTAL + 1
+    }
+
+    fn update(&mut self, symbol: Option<&u8>) {
+        if let Some(&symbol) = symbol {
+            self.frequencies[symbol as usize] += INCREMENT;
+            self.total += INCREMENT;
+            if self.total > MAX_TOTAL {
+                self.total = 0;
+                for frequency in self.frequencies.iter_mut() {
+                    *frequency = frequency.div_ceil(2);
+                    self.total += *frequency;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use arithmetic_coding::{Decoder, Encoder};
+    use bitstream_io::{BigEndian, BitReader, BitWrite, BitWriter};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_round_trip() {
+        // skewed bytes, as occupancy codes are
+        let mut rng = StdRng::seed_from_u64(2);
+        let symbols: Vec<u8> = (0..20_000)
+            .map(|_| match rng.gen_range(0..10) {
+                0..=5 => 1 << rng.gen_range(0..8),
+                6..=8 => 0xff,
+                _ => rng.gen(),
+            })
+            .collect();
+
+        let mut writer = BitWriter::endian(vec![], BigEndian);
+        Encoder::new(ByteModel::new(), &mut writer)
+            .encode_all(symbols.iter().copied())
+            .unwrap();
+        writer.byte_align().unwrap();
+        let bytes = writer.into_writer();
+        // about 3.9 bits of entropy per symbol
+        assert!(bytes.len() < symbols.len() * 6 / 10);
+
+        let reader = BitReader::endian(bytes.as_slice(), BigEndian);
+        let decoded: Vec<u8> = Decoder::new(ByteModel::new(), reader)
+            .decode_all()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(decoded, symbols);
+    }
+}
@@ -0,0 +1,467 @@
+//! Lossless inter-frame coding of point cloud sequences: the first frame is stored as is, and
+//! every following frame as the zigzag and Golomb-Rice coded differences of its points with the
+//! points at the same index in the previous frame. The same pairing of points by index gives the
+//! motion vectors predicting a frame from the previous ones.
+
+use anyhow::{bail, Result};
+use cgmath::{Point3, Vector3};
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+const KEY_FRAME: u8 = 0;
+const DELTA_FRAME: u8 = 1;
+
+/// Frame kind and number of points.
+const HEADER_LEN: usize = 5;
+const KEY_POINT_LEN: usize = 16;
+
+/// Quotients from this one on are written as this many ones followed by the raw value, keeping
+/// the unary part short when a value is far larger than the others of its channel.
+const ESCAPE: u64 = 48;
+
+/// x, y, z, r, g, b, a, each with its own Rice parameter.
+const CHANNELS: usize = 7;
+
+/// Differences of the points of `current` with the points at the same index in `previous`, the
+/// points beyond the end of `previous` being compared to a zero point. Coordinates are compared
+/// on their bit patterns, which makes the differences exact whatever the values.
+pub fn frame_delta(
+    previous: &PointCloud<PointXyzRgba>,
+    current: &PointCloud<PointXyzRgba>,
+) -> Vec<([i64; 3], [i16; 4])> {
+    let zero = PointXyzRgba::from_xyz_rgba([0.0; 3], [0; 4]);
+    current
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let q = previous.points.get(i).unwrap_or(&zero);
+            let bits = |v: f32| v.to_bits() as i64;
+            (
+                [
+                    bits(p.x) - bits(q.x),
+                    bits(p.y) - bits(q.y),
+                    bits(p.z) - bits(q.z),
+                ],
+                [
+                    p.r as i16 - q.r as i16,
+                    p.g as i16 - q.g as i16,
+                    p.b as i16 - q.b as i16,
+                    p.a as i16 - q.a as i16,
+                ],
+            )
+        })
+        .collect()
+}
+
+/// Encodes a sequence of frames, each one against the previous one given to it.
+#[derive(Default)]
+pub struct DeltaEncoder {
+    previous: Option<PointCloud<PointXyzRgba>>,
+}
+
+impl DeltaEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn encode(&mut self, cloud: &PointCloud<PointXyzRgba>) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN);
+        bytes.push(if self.previous.is_some() {
+            DELTA_FRAME
+        } else {
+            KEY_FRAME
+        });
+        bytes.extend((cloud.points.len() as u32).to_le_bytes());
+        match &self.previous {
+            None => {
+                for p in &cloud.points {
+                    for v in p.xyz() {
+                        bytes.extend(v.to_le_bytes());
+                    }
+                    bytes.extend(p.rgba());
+                }
+            }
+            Some(previous) => {
+                let channels: Vec<Vec<u64>> = {
+                    let deltas = frame_delta(previous, cloud);
+                    (0..CHANNELS)
+                        .map(|c| {
+                            deltas
+                                .iter()
+                                .map(|(xyz, rgba)| {
+                                    zigzag(if c < 3 { xyz[c] } else { rgba[c - 3] as i64 })
+                                })
+                                .collect()
+                        })
+                        .collect()
+                };
+                let parameters: Vec<u8> = channels.iter().map(|c| rice_parameter(c)).collect();
+                bytes.extend(&parameters);
+                let mut writer = BitWriter::new(bytes);
+                for (values, &k) in channels.iter().zip(&parameters) {
+                    for &v in values {
+                        writer.write_rice(v, k);
+                    }
+                }
+                bytes = writer.finish();
+            }
+        }
+        self.previous = Some(cloud.clone());
+        bytes
+    }
+}
+
+/// Decodes the frames written by [`DeltaEncoder`], given in the same order.
+#[derive(Default)]
+pub struct DeltaDecoder {
+    previous: Option<PointCloud<PointXyzRgba>>,
+}
+
+impl DeltaDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<PointCloud<PointXyzRgba>> {
+        if bytes.len() < HEADER_LEN {
+            bail!("Delta frame is shorter than its header");
+        }
+        let num_points = u32::from_le_bytes(bytes[1..HEADER_LEN].try_into().unwrap()) as usize;
+        let body = &bytes[HEADER_LEN..];
+        let points = match (bytes[0], &self.previous) {
+            (KEY_FRAME, _) => {
+                if body.len() != num_points * KEY_POINT_LEN {
+                    bail!(
+                        "Key frame of {} points has {} bytes",
+                        num_points,
+                        body.len()
+                    );
+                }
+                body.chunks_exact(KEY_POINT_LEN)
+                    .map(|chunk| {
+                        let f = |i: usize| f32::from_le_bytes(chunk[i..i + 4].try_into().unwrap());
+                        PointXyzRgba::from_xyz_rgba(
+                            [f(0), f(4), f(8)],
+                            chunk[12..].try_into().unwrap(),
+                        )
+                    })
+                    .collect()
+            }
+            (DELTA_FRAME, None) => bail!("Delta frame without a previous frame"),
+            (DELTA_FRAME, Some(previous)) => {
+                if body.len() < CHANNELS {
+                    bail!("Delta frame is shorter than its Rice parameters");
+                }
+                let mut reader = BitReader::new(&body[CHANNELS..]);
+                let mut channels = Vec::with_capacity(CHANNELS);
+                for &k in &body[..CHANNELS] {
+                    let values = (0..num_points)
+                        .map(|_| reader.read_rice(k))
+                        .collect::<Option<Vec<u64>>>();
+                    match values {
+                        Some(values) => channels.push(values),
+                        None => bail!("Delta frame ends before its last point"),
+                    }
+                }
+                let zero = PointXyzRgba::from_xyz_rgba([0.0; 3], [0; 4]);
+                (0..num_points)
+                    .map(|i| {
+                        let q = previous.points.get(i).unwrap_or(&zero);
+                        let delta = |c: usize| unzigzag(channels[c][i]);
+                        let coordinate = |v: f32, c: usize| {
+                            f32::from_bits((v.to_bits() as i64 + delta(c)) as u32)
+                        };
+                        let color = |v: u8, c: usize| (v as i64 + delta(c)) as u8;
+                        PointXyzRgba::from_xyz_rgba(
+                            [coordinate(q.x, 0), coordinate(q.y, 1), coordinate(q.z, 2)],
+                            [color(q.r, 3), color(q.g, 4), color(q.b, 5), color(q.a, 6)],
+                        )
+                    })
+                    .collect()
+            }
+            (kind, _) => bail!("Unknown delta frame kind {}", kind),
+        };
+        let cloud = PointCloud::from_points(points);
+        self.previous = Some(cloud.clone());
+        Ok(cloud)
+    }
+}
+
+/// Motion vector of each point of `current`: its displacement from the point at the same index in
+/// `previous`, zero for the points beyond the end of `previous`.
+pub fn motion_vectors(
+    previous: &PointCloud<PointXyzRgba>,
+    current: &PointCloud<PointXyzRgba>,
+) -> Vec<Vector3<f32>> {
+    current
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| match previous.points.get(i) {
+            Some(q) => Point3::from(p.xyz()) - Point3::from(q.xyz()),
+            None => Vector3::new(0.0, 0.0, 0.0),
+        })
+        .collect()
+}
+
+/// Predicts the next frame by moving every point by its motion vector, as if it kept its
+/// velocity. Points without a motion vector stay in place.
+pub fn predict_frame(
+    current: &PointCloud<PointXyzRgba>,
+    motion: &[Vector3<f32>],
+) -> PointCloud<PointXyzRgba> {
+    let points = current
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let moved = Point3::from(p.xyz())
+                + motion
+                    .get(i)
+                    .copied()
+                    .unwrap_or(Vector3::new(0.0, 0.0, 0.0));
+            PointXyzRgba::from_xyz_rgba(moved.into(), p.rgba())
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// Differences of the positions of the points of `predicted` with the points at the same index in
+/// `actual`, a zero point standing for the points missing from `predicted`. The residual points
+/// keep the colors of `actual`.
+pub fn prediction_residual(
+    predicted: &PointCloud<PointXyzRgba>,
+    actual: &PointCloud<PointXyzRgba>,
+) -> PointCloud<PointXyzRgba> {
+    let points = actual
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let prediction = predicted.points.get(i).map_or([0.0; 3], |q| q.xyz());
+            let residual = Point3::from(prediction) - Point3::from(p.xyz());
+            PointXyzRgba::from_xyz_rgba(residual.into(), p.rgba())
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// Maps 0, -1, 1, -2, 2... to 0, 1, 2, 3, 4...
+fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+fn unzigzag(u: u64) -> i64 {
+    (u >> 1) as i64 ^ -((u & 1) as i64)
+}
+
+/// The Rice parameter coding the values in the fewest bits. A parameter derived from the mean
+/// would be thrown off by the few huge differences of coordinates crossing zero.
+fn rice_parameter(values: &[u64]) -> u8 {
+    let coded_bits = |k: u8| -> u64 {
+        values
+            .iter()
+            .map(|&v| match v >> k {
+                quotient if quotient >= ESCAPE => ESCAPE + 64,
+                quotient => quotient + 1 + k as u64,
+            })
+            .sum()
+    };
+    (0..64).min_by_key(|&k| coded_bits(k)).unwrap()
+}
+
+/// Packs bits most significant first after the bytes it is given.
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_count: u8,
+}
+
+impl BitWriter {
+    fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            bit_count: 8,
+        }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        if self.bit_count == 8 {
+            self.bytes.push(0);
+            self.bit_count = 0;
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 0x80 >> self.bit_count;
+        }
+        self.bit_count += 1;
+    }
+
+    fn write_bits(&mut self, value: u64, count: u8) {
+        for i in (0..count).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    /// The quotient by `2^k` in unary, ones ended by a zero, then the remainder in `k` bits.
+    fn write_rice(&mut self, value: u64, k: u8) {
+        let quotient = value >> k;
+        if quotient >= ESCAPE {
+            for _ in 0..ESCAPE {
+                self.write_bit(true);
+            }
+            self.write_bits(value, 64);
+            return;
+        }
+        for _ in 0..quotient {
+            self.write_bit(true);
+        }
+        self.write_bit(false);
+        self.write_bits(value, k);
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.position / 8)?;
+        let bit = (byte >> (7 - self.position % 8)) & 1 == 1;
+        self.position += 1;
+        Some(bit)
+    }
+
+    fn read_bits(&mut self, count: u8) -> Option<u64> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = value << 1 | self.read_bit()? as u64;
+        }
+        Some(value)
+    }
+
+    fn read_rice(&mut self, k: u8) -> Option<u64> {
+        let mut quotient = 0;
+        while self.read_bit()? {
+            quotient += 1;
+            if quotient == ESCAPE {
+                return self.read_bits(64);
+            }
+        }
+        Some(quotient << k | self.read_bits(k)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_rice_round_trip() {
+        let values = [0, 1, 2, 7, 8, 1000, u32::MAX as u64, u64::MAX];
+        for k in [0, 3, 20, 63] {
+            let mut writer = BitWriter::new(vec![]);
+            for &v in &values {
+                writer.write_rice(v, k);
+            }
+            let bytes = writer.finish();
+            let mut reader = BitReader::new(&bytes);
+            for &v in &values {
+                assert_eq!(reader.read_rice(k), Some(v));
+            }
+        }
+        for v in [0, 1, -1, i64::MAX, i64::MIN] {
+            assert_eq!(unzigzag(zigzag(v)), v);
+        }
+    }
+
+    #[test]
+    fn test_sequence_round_trip() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut frame: Vec<PointXyzRgba> = (0..2000)
+            .map(|_| {
+                PointXyzRgba::from_xyz_rgba(
+                    [rng.gen_range(-1.0..1.0), rng.gen(), rng.gen_range(0.0..5.0)],
+                    [rng.gen(), rng.gen(), rng.gen(), 255],
+                )
+            })
+            .collect();
+        let mut frames = vec![];
+        for _ in 0..5 {
+            frames.push(PointCloud::from_points(frame.clone()));
+            // small motions and color changes, points crossing zero, and points coming and going
+            for p in frame.iter_mut() {
+                p.x += rng.gen_range(-0.001..0.001);
+                p.z += 0.01;
+                p.g = p.g.wrapping_add(rng.gen_range(0..3));
+            }
+            frame.truncate(frame.len() - rng.gen_range(0..50));
+            frame.push(PointXyzRgba::from_xyz_rgba([f32::MAX, -0.0, 1e-40], [0; 4]));
+        }
+
+        let mut encoder = DeltaEncoder::new();
+        let encoded: Vec<Vec<u8>> = frames.iter().map(|f| encoder.encode(f)).collect();
+        let mut decoder = DeltaDecoder::new();
+        for (frame, bytes) in frames.iter().zip(&encoded) {
+            let decoded = decoder.decode(bytes).unwrap();
+            assert_eq!(decoded.points.len(), frame.points.len());
+            for (p, q) in decoded.points.iter().zip(&frame.points) {
+                assert_eq!(p.xyz().map(f32::to_bits), q.xyz().map(f32::to_bits));
+                assert_eq!(p.rgba(), q.rgba());
+            }
+        }
+        // the deltas take fewer bytes than the key frame
+        assert_eq!(encoded[0].len(), HEADER_LEN + 2000 * KEY_POINT_LEN);
+        assert!(encoded[1..]
+            .iter()
+            .all(|bytes| bytes.len() < encoded[0].len()));
+    }
+
+    #[test]
+    fn test_constant_velocity_prediction() {
+        // every point moves at its own constant velocity, and a point appears in the third frame
+        let frame = |t: f32, extra: bool| {
+            let mut points: Vec<_> = (0..10)
+                .map(|i| {
+                    let i = i as f32;
+                    PointXyzRgba::from_xyz_rgba([i + t * 0.5, -i * t, 2.0], [i as u8; 4])
+                })
+                .collect();
+            if extra {
+                points.push(PointXyzRgba::from_xyz_rgba([1.0, 2.0, 3.0], [7; 4]));
+            }
+            PointCloud::from_points(points)
+        };
+        let (first, second, third) = (frame(0.0, false), frame(1.0, false), frame(2.0, true));
+
+        let motion = motion_vectors(&first, &second);
+        assert_eq!(motion[3], Vector3::new(0.5, -3.0, 0.0));
+        let residual = prediction_residual(&predict_frame(&second, &motion), &third);
+        assert_eq!(residual.points.len(), 11);
+        for p in &residual.points[..10] {
+            assert!(p.xyz().iter().all(|v| v.abs() < 1e-5));
+        }
+        assert_eq!(residual.points[10].xyz(), [-1.0, -2.0, -3.0]);
+        assert_eq!(residual.points[10].rgba(), [7; 4]);
+    }
+
+    #[test]
+    fn test_delta_frame_needs_previous() {
+        let cloud = PointCloud::from_points(vec![PointXyzRgba::from_xyz_rgba([1.0; 3], [9; 4])]);
+        let mut encoder = DeltaEncoder::new();
+        encoder.encode(&cloud);
+        let delta = encoder.encode(&cloud);
+        assert!(DeltaDecoder::new().decode(&delta).is_err());
+        assert!(DeltaDecoder::new().decode(&delta[..3]).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+
+use super::run_tool;
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::ply::{read_ply, write_ply_with_alpha};
+
+/// Compresses point clouds with the `draco_encoder` and `draco_decoder` binaries of Google's
+/// Draco, which read and write ply files.
+pub struct DracoCodec {
+    encoder: PathBuf,
+    decoder: PathBuf,
+}
+
+impl DracoCodec {
+    pub fn new<P: Into<PathBuf>>(encoder: P, decoder: P) -> Self {
+        DracoCodec {
+            encoder: encoder.into(),
+            decoder: decoder.into(),
+        }
+    }
+
+    /// Compresses the cloud into `output`, quantizing the positions to `qp` bits per axis over
+    /// the bounding box.
+    pub fn encode(&self, cloud: &PointCloud<PointXyzRgba>, output: &Path, qp: u8) -> Result<()> {
+        let input = temp_ply(output, "input");
+        write_ply_with_alpha(cloud, &input).map_err(|e| anyhow!("{}", e))?;
+        let result = run_tool(
+            &self.encoder,
+            &[
+                "-point_cloud".to_string(),
+                "-i".to_string(),
+                input.display().to_string(),
+                "-o".to_string(),
+                output.display().to_string(),
+                "-qp".to_string(),
+                qp.to_string(),
+            ],
+        );
+        let _ = std::fs::remove_file(&input);
+        result
+    }
+
+    /// Reconstructs the cloud from `input`.
+    pub fn decode(&self, input: &Path) -> Result<PointCloud<PointXyzRgba>> {
+        let output = temp_ply(input, "decoded");
+        run_tool(
+            &self.decoder,
+            &[
+                "-i".to_string(),
+                input.display().to_string(),
+                "-o".to_string(),
+                output.display().to_string(),
+            ],
+        )?;
+        let cloud = read_ply(&output);
+        let _ = std::fs::remove_file(&output);
+        cloud.with_context(|| format!("Failed to read the decoded {}", input.display()))
+    }
+}
+
+/// Ply file in the temporary directory exchanged with Draco for the compressed file.
+fn temp_ply(compressed: &Path, suffix: &str) -> PathBuf {
+    let stem = compressed.file_stem().unwrap_or_default().to_string_lossy();
+    std::env::temp_dir().join(format!(
+        "vivotk_draco_{}_{}_{}.ply",
+        std::process::id(),
+        stem,
+        suffix
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::metrics::d1_errors;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::process::Command;
+
+    #[test]
+    fn test_round_trip() {
+        if Command::new("draco_encoder").arg("-h").output().is_err() {
+            println!("Skipping, draco_encoder is not installed");
+            return;
+        }
+        let mut rng = StdRng::seed_from_u64(5);
+        let points = (0..1000)
+            .map(|_| {
+                PointXyzRgba::from_xyz_rgba(
+                    [rng.gen(), rng.gen(), rng.gen()],
+                    [rng.gen(), rng.gen(), rng.gen(), 255],
+                )
+            })
+            .collect();
+        let cloud = PointCloud::from_points(points);
+        let codec = DracoCodec::new("draco_encoder", "draco_decoder");
+        let compressed = std::env::temp_dir().join("vivotk_test_draco_round_trip.drc");
+
+        codec.encode(&cloud, &compressed, 11).unwrap();
+        let decoded = codec.decode(&compressed).unwrap();
+        std::fs::remove_file(&compressed).unwrap();
+
+        assert_eq!(decoded.points.len(), cloud.points.len());
+        // every axis of the unit cube is quantized to 2^11 - 1 steps, each point moving by at
+        // most half a step along each axis
+        let max_error = 0.5 / 2047.0 * 3f32.sqrt() * 1.01;
+        assert!(d1_errors(&cloud, &decoded).iter().all(|&e| e <= max_error));
+        assert!(d1_errors(&decoded, &cloud).iter().all(|&e| e <= max_error));
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+
+use super::run_tool;
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::ply::{read_ply, write_ply_with_alpha};
+
+/// Compresses point clouds with `tmc3`, the reference software of MPEG G-PCC (TMC13), which
+/// reads and writes ply files.
+pub struct GpccCodec {
+    path: PathBuf,
+    /// encoder configuration, e.g. one of the `cfg` files of TMC13, without which only the
+    /// geometry is coded
+    config: Option<PathBuf>,
+}
+
+impl GpccCodec {
+    pub fn new<P: Into<PathBuf>>(path: P, config: Option<PathBuf>) -> Self {
+        GpccCodec {
+            path: path.into(),
+            config,
+        }
+    }
+
+    /// Compresses the cloud into the bitstream `output`.
+    pub fn encode(&self, cloud: &PointCloud<PointXyzRgba>, output: &Path) -> Result<()> {
+        let input = temp_ply(output);
+        write_ply_with_alpha(cloud, &input).map_err(|e| anyhow!("{}", e))?;
+        let mut args = vec![
+            "--mode=0".to_string(),
+            format!("--uncompressedDataPath={}", input.display()),
+            format!("--compressedStreamPath={}", output.display()),
+        ];
+        if let Some(config) = &self.config {
+            args.push(format!("--config={}", config.display()));
+        }
+        let result = run_tool(&self.path, &args);
+        let _ = std::fs::remove_file(&input);
+        result
+    }
+
+    /// Reconstructs the cloud from the bitstream `input`.
+    pub fn decode(&self, input: &Path) -> Result<PointCloud<PointXyzRgba>> {
+        let output = temp_ply(input);
+        run_tool(
+            &self.path,
+            &[
+                "--mode=1".to_string(),
+                format!("--compressedStreamPath={}", input.display()),
+                format!("--reconstructedDataPath={}", output.display()),
+                "--outputBinaryPly=1".to_string(),
+            ],
+        )?;
+        let cloud = read_ply(&output);
+        let _ = std::fs::remove_file(&output);
+        cloud.with_context(|| format!("Failed to read the decoded {}", input.display()))
+    }
+}
+
+/// Ply file in the temporary directory exchanged with `tmc3` for the bitstream.
+fn temp_ply(bitstream: &Path) -> PathBuf {
+    let stem = bitstream.file_stem().unwrap_or_default().to_string_lossy();
+    std::env::temp_dir().join(format!("vivotk_gpcc_{}_{}.ply", std::process::id(), stem))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_missing_tmc3() {
+        let codec = GpccCodec::new("vivotk_test_missing_tmc3", None);
+        let cloud = PointCloud::from_points(vec![PointXyzRgba::from_xyz_rgba(
+            [1.0, 2.0, 3.0],
+            [10, 20, 30, 255],
+        )]);
+        let bitstream = std::env::temp_dir().join("vivotk_test_missing_tmc3.bin");
+
+        assert!(codec.encode(&cloud, &bitstream).is_err());
+        // the ply handed to tmc3 is removed even if it failed
+        assert!(!temp_ply(&bitstream).exists());
+        assert!(codec.decode(&bitstream).is_err());
+    }
+}
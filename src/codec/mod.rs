@@ -1,10 +1,30 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
+pub mod arithmetic;
 pub mod decoder;
+pub mod delta;
+pub mod draco;
+pub mod gpcc;
+pub mod octree;
+pub mod vpcc;
 
 pub trait Decoder {
     fn decode(&self, filename: &OsStr) -> Vec<PathBuf>;
     fn decode_folder(&self, foldername: &Path) -> Result<()>;
 }
+
+/// Runs an external codec, failing if it cannot be started or exits with an error.
+fn run_tool(program: &Path, args: &[String]) -> Result<()> {
+    let status = Command::new(program)
+        .args(args)
+        .stdout(Stdio::null())
+        .status()
+        .with_context(|| format!("Failed to run {}", program.display()))?;
+    if !status.success() {
+        bail!("{} exited with {}", program.display(), status);
+    }
+    Ok(())
+}
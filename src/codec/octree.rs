@@ -0,0 +1,491 @@
+use anyhow::{anyhow, bail, Result};
+use arithmetic_coding::{Decoder, Encoder};
+use bitstream_io::{BigEndian, BitReader, BitWrite, BitWriter};
+use cgmath::Point3;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use std::convert::Infallible;
+use std::io::{self, Read, Write};
+use std::thread::JoinHandle;
+
+use super::arithmetic::ByteModel;
+use crate::formats::{grid::bounding_box, pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Morton codes of up to 21 levels fit in a u64.
+pub const MAX_DEPTH: u8 = 21;
+
+/// First byte of the versioned bitstreams, never a valid depth, unlike the first byte of the
+/// breadth first bitstreams written before the format had a version.
+const VERSIONED: u8 = 0;
+
+/// Format of the bitstreams written: nodes after their subtrees, ending with the end of stream
+/// symbol. Version 1 is the unversioned breadth first format, which is still decoded.
+const VERSION: u8 = 2;
+
+/// versioned marker (u8), version (u8), depth (u8), origin (3 x f32) and voxel size (f32),
+/// little endian
+const HEADER_LEN: usize = 19;
+
+/// depth (u8), empty flag (u8), origin (3 x f32) and voxel size (f32), little endian
+const BREADTH_FIRST_HEADER_LEN: usize = 18;
+
+/// Zero bytes the decoder may read past the end of the bitstream, covering the 47 bits it reads
+/// ahead of the encoder.
+const READ_AHEAD: usize = 8;
+
+/// Lattice the points are quantized to: `2^depth` voxels along each axis from the minimum
+/// corner of the bounding box, the largest extent spanning `2^depth - 1` voxels so that both
+/// ends of it are exact.
+struct Quantizer {
+    origin: [f32; 3],
+    voxel_size: f32,
+}
+
+impl Quantizer {
+    fn new((min, max): (Point3<f32>, Point3<f32>), depth: u8) -> Self {
+        let extent = (max - min).x.max((max - min).y).max((max - min).z);
+        let steps = ((1u64 << depth) - 1) as f32;
+        Self {
+            origin: [min.x, min.y, min.z],
+            // a single point, or points at the same position, still need a nonzero size
+            voxel_size: if extent > 0.0 { extent / steps } else { 1.0 },
+        }
+    }
+
+    fn morton_code(&self, p: &PointXyzRgba, depth: u8) -> u64 {
+        let max = (1u64 << depth) - 1;
+        let mut cell = [0u64; 3];
+        for ((c, v), o) in cell.iter_mut().zip(p.xyz()).zip(self.origin) {
+            *c = (((v - o) / self.voxel_size).round().max(0.0) as u64).min(max);
+        }
+        interleave(cell, depth)
+    }
+
+    fn position(&self, code: u64, depth: u8) -> [f32; 3] {
+        let cell = deinterleave(code, depth);
+        let mut xyz = [0.0; 3];
+        for ((v, c), o) in xyz.iter_mut().zip(cell).zip(self.origin) {
+            *v = o + c as f32 * self.voxel_size;
+        }
+        xyz
+    }
+}
+
+/// Morton code of the cell, three bits per level from the root, x being the most significant
+/// bit of every child index.
+fn interleave(cell: [u64; 3], depth: u8) -> u64 {
+    (0..depth).rev().fold(0, |code, bit| {
+        let child = cell
+            .iter()
+            .fold(0, |child, axis| (child << 1) | ((axis >> bit) & 1));
+        (code << 3) | child
+    })
+}
+
+fn deinterleave(code: u64, depth: u8) -> [u64; 3] {
+    let mut cell = [0; 3];
+    for level in 0..depth {
+        let child = (code >> (3 * (depth - 1 - level))) & 7;
+        for (axis, c) in cell.iter_mut().enumerate() {
+            *c = (*c << 1) | ((child >> (2 - axis)) & 1);
+        }
+    }
+    cell
+}
+
+/// Encodes the geometry of the cloud in an octree of `depth` levels.
+///
+/// The points are quantized to the `2^depth` voxels along each axis of the bounding cube, and
+/// the octree is serialized depth first, each occupied node as a byte with one bit per occupied
+/// child written after the subtrees of its children, the bytes compressed with adaptive
+/// arithmetic coding ending with the end of stream symbol, after a header of format version 2.
+/// Points in the same voxel are merged and colors are dropped.
+pub fn encode_octree(cloud: &PointCloud<PointXyzRgba>, depth: u8) -> Vec<u8> {
+    let origin = Point3::new(0.0, 0.0, 0.0);
+    let bounds =
+        bounding_box(cloud.points.iter().map(|p| p.xyz().into())).unwrap_or((origin, origin));
+    let mut encoder =
+        OctreeStreamEncoder::new(depth, bounds, vec![]).expect("Header written to memory");
+    let mut codes: Vec<u64> = cloud
+        .points
+        .iter()
+        .map(|p| encoder.morton_code(p))
+        .collect();
+    codes.sort_unstable();
+    for code in codes {
+        encoder
+            .push_code(code)
+            .expect("Sorted codes written to memory");
+    }
+    encoder.finish().expect("Bitstream written to memory")
+}
+
+/// Encodes points given in morton order into the same bitstream as [`encode_octree`], without
+/// holding the cloud: the bytes of every subtree are arithmetic coded, on a thread of their
+/// own, as soon as a point beyond it is pushed.
+pub struct OctreeStreamEncoder<W: Write + Send + 'static> {
+    depth: u8,
+    quantizer: Quantizer,
+    nodes: Sender<Vec<u8>>,
+    coder: JoinHandle<Result<W>>,
+    /// occupancy of the nodes containing the last voxel, from the root
+    occupancies: Vec<u8>,
+    last: Option<u64>,
+}
+
+impl<W: Write + Send + 'static> OctreeStreamEncoder<W> {
+    /// Writes the header to `output`. The points are quantized over `bounds`, the minimum and
+    /// maximum corners of their bounding box, which has to be known beforehand.
+    pub fn new(depth: u8, bounds: (Point3<f32>, Point3<f32>), mut output: W) -> Result<Self> {
+        assert!(
+            (1..=MAX_DEPTH).contains(&depth),
+            "Octree depth must be between 1 and {}",
+            MAX_DEPTH
+        );
+        let quantizer = Quantizer::new(bounds, depth);
+        output.write_all(&[VERSIONED, VERSION, depth])?;
+        for v in quantizer.origin.iter().chain([quantizer.voxel_size].iter()) {
+            output.write_all(&v.to_le_bytes())?;
+        }
+        let (nodes, received) = unbounded();
+        Ok(Self {
+            depth,
+            quantizer,
+            nodes,
+            coder: std::thread::spawn(move || arithmetic_code(received, output)),
+            occupancies: vec![0; depth as usize],
+            last: None,
+        })
+    }
+
+    /// Morton code of the voxel of the point, the order in which points have to be pushed.
+    pub fn morton_code(&self, point: &PointXyzRgba) -> u64 {
+        self.quantizer.morton_code(point, self.depth)
+    }
+
+    /// Adds the point, which must not precede the last one in morton order.
+    pub fn push(&mut self, point: &PointXyzRgba) -> Result<()> {
+        self.push_code(self.morton_code(point))
+    }
+
+    fn push_code(&mut self, code: u64) -> Result<()> {
+        if let Some(last) = self.last {
+            if code < last {
+                bail!("Points are not in morton order");
+            }
+            if code == last {
+                return Ok(());
+            }
+            // the nodes containing the last voxel but not this one are complete
+            let depth = self.depth as u32;
+            let common = (0..depth)
+                .take_while(|level| last >> (3 * (depth - level)) == code >> (3 * (depth - level)))
+                .count();
+            let completed = (common..self.depth as usize)
+                .rev()
+                .map(|level| std::mem::take(&mut self.occupancies[level]))
+                .collect();
+            if self.nodes.send(completed).is_err() {
+                bail!("Octree arithmetic coder stopped, finish returns its error");
+            }
+        }
+        for (level, occupancy) in self.occupancies.iter_mut().enumerate() {
+            *occupancy |= 1 << ((code >> (3 * (self.depth as usize - 1 - level))) & 7);
+        }
+        self.last = Some(code);
+        Ok(())
+    }
+
+    /// Writes the nodes still open and the end of the bitstream, returning the output.
+    pub fn finish(mut self) -> Result<W> {
+        if self.last.is_some() {
+            self.occupancies.reverse();
+            // an error of the coder is returned below
+            let _ = self.nodes.send(self.occupancies);
+        }
+        drop(self.nodes);
+        self.coder
+            .join()
+            .unwrap_or_else(|_| Err(anyhow!("Octree arithmetic coder panicked")))
+    }
+}
+
+/// Arithmetic codes the occupancy bytes received until the encoder is done with them.
+fn arithmetic_code<W: Write>(nodes: Receiver<Vec<u8>>, output: W) -> Result<W> {
+    let mut writer = BitWriter::endian(output, BigEndian);
+    let mut encoder = Encoder::new(ByteModel::new(), &mut writer);
+    for occupancy in nodes.iter().flatten() {
+        encoder.encode(Some(&occupancy)).map_err(io_error)?;
+    }
+    encoder.encode(None).map_err(io_error)?;
+    encoder.flush()?;
+    writer.byte_align()?;
+    let mut output = writer.into_writer();
+    output.flush()?;
+    Ok(output)
+}
+
+/// The error of writing the bitstream, as every byte is a valid symbol.
+fn io_error(error: arithmetic_coding::Error<Infallible>) -> io::Error {
+    match error {
+        arithmetic_coding::Error::Io(error) => error,
+        arithmetic_coding::Error::ValueError(never) => match never {},
+    }
+}
+
+/// Reads the bytes, then the zero bits the decoder reads ahead past their end, and fails beyond,
+/// as the bitstream then ends before its last node.
+struct ReadAhead<'a> {
+    bytes: &'a [u8],
+    padding: usize,
+}
+
+impl Read for ReadAhead<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.bytes.is_empty() {
+            return self.bytes.read(buf);
+        }
+        if self.padding == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Octree bitstream ends before its last node",
+            ));
+        }
+        let n = buf.len().min(self.padding);
+        buf[..n].fill(0);
+        self.padding -= n;
+        Ok(n)
+    }
+}
+
+/// Depth and lattice of the header from `depth_at`, followed by the origin at `origin_at`.
+fn read_header(bytes: &[u8], depth_at: usize, origin_at: usize) -> Result<(u8, Quantizer)> {
+    let depth = bytes[depth_at];
+    if !(1..=MAX_DEPTH).contains(&depth) {
+        bail!("Invalid octree depth {}", depth);
+    }
+    let float = |i: usize| {
+        let i = origin_at + 4 * i;
+        f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+    };
+    let quantizer = Quantizer {
+        origin: [float(0), float(1), float(2)],
+        voxel_size: float(3),
+    };
+    Ok((depth, quantizer))
+}
+
+fn byte_decoder(bytes: &[u8]) -> Decoder<ByteModel, BitReader<ReadAhead<'_>, BigEndian>> {
+    let input = ReadAhead {
+        bytes,
+        padding: READ_AHEAD,
+    };
+    Decoder::new(ByteModel::new(), BitReader::endian(input, BigEndian))
+}
+
+fn white_points(codes: Vec<u64>, quantizer: &Quantizer, depth: u8) -> PointCloud<PointXyzRgba> {
+    let points = codes
+        .into_iter()
+        .map(|code| {
+            PointXyzRgba::from_xyz_rgba(quantizer.position(code, depth), [255, 255, 255, 255])
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// Decodes the points encoded by [`encode_octree`] or [`OctreeStreamEncoder`], one white point
+/// per occupied voxel in morton order. The breadth first bitstreams of version 1 are decoded as
+/// well.
+pub fn decode_octree(bytes: &[u8]) -> Result<PointCloud<PointXyzRgba>> {
+    if bytes.first() != Some(&VERSIONED) {
+        return decode_breadth_first(bytes);
+    }
+    if bytes.len() < HEADER_LEN {
+        bail!("Octree bitstream is shorter than its header");
+    }
+    if bytes[1] != VERSION {
+        bail!("Unsupported octree bitstream version {}", bytes[1]);
+    }
+    let (depth, quantizer) = read_header(bytes, 2, 3)?;
+    let mut occupancies = byte_decoder(&bytes[HEADER_LEN..])
+        .decode_all()
+        .collect::<io::Result<Vec<u8>>>()?;
+
+    // every node follows its subtree, the root coming last
+    let mut codes = vec![];
+    if !occupancies.is_empty() {
+        pop_node(&mut occupancies, 0, 0, depth, &mut codes)?;
+    }
+    if !occupancies.is_empty() {
+        bail!("Corrupt octree bitstream: nodes outside the octree");
+    }
+    codes.reverse();
+    Ok(white_points(codes, &quantizer, depth))
+}
+
+/// Decodes a bitstream of version 1, every level of the octree written after the one above it.
+fn decode_breadth_first(bytes: &[u8]) -> Result<PointCloud<PointXyzRgba>> {
+    if bytes.len() < BREADTH_FIRST_HEADER_LEN {
+        bail!("Octree bitstream is shorter than its header");
+    }
+    let (depth, quantizer) = read_header(bytes, 0, 2)?;
+    if bytes[1] != 0 {
+        return Ok(PointCloud::from_points(vec![]));
+    }
+
+    let mut decoder = byte_decoder(&bytes[BREADTH_FIRST_HEADER_LEN..]);
+    let mut nodes = vec![0u64];
+    for _ in 0..depth {
+        let mut children = Vec::with_capacity(nodes.len() * 2);
+        for node in nodes {
+            let occupancy = match decoder.decode()? {
+                Some(occupancy) if occupancy != 0 => occupancy,
+                _ => bail!("Corrupt octree bitstream: node without children"),
+            };
+            children.extend(
+                (0..8)
+                    .filter(|child| occupancy & (1 << child) != 0)
+                    .map(|child| (node << 3) | child as u64),
+            );
+        }
+        nodes = children;
+    }
+    Ok(white_points(nodes, &quantizer, depth))
+}
+
+/// Pops the node `code` at `level` and its subtree from the end of `occupancies`, pushing the
+/// codes of its voxels in reverse morton order.
+fn pop_node(
+    occupancies: &mut Vec<u8>,
+    code: u64,
+    level: u8,
+    depth: u8,
+    codes: &mut Vec<u64>,
+) -> Result<()> {
+    let occupancy = match occupancies.pop() {
+        Some(occupancy) => occupancy,
+        None => bail!("Corrupt octree bitstream: missing nodes"),
+    };
+    for child in (0..8).rev().filter(|child| occupancy & (1 << child) != 0) {
+        let child_code = (code << 3) | child as u64;
+        if level + 1 == depth {
+            codes.push(child_code);
+        } else {
+            pop_node(occupancies, child_code, level + 1, depth, codes)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::metrics::d1_errors;
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+    /// Random points on the integer lattice of [0, 255], spanning all of it along x.
+    fn lattice_cloud() -> PointCloud<PointXyzRgba> {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut xyz: Vec<[f32; 3]> = (0..5000)
+            .map(|_| {
+                [
+                    rng.gen_range(0..256) as f32,
+                    rng.gen_range(0..256) as f32,
+                    rng.gen_range(0..256) as f32,
+                ]
+            })
+            .collect();
+        xyz.push([0.0, 0.0, 0.0]);
+        xyz.push([255.0, 0.0, 0.0]);
+        let points = xyz
+            .into_iter()
+            .map(|xyz| PointXyzRgba::from_xyz_rgba(xyz, [255, 255, 255, 255]))
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    fn sorted_positions(cloud: &PointCloud<PointXyzRgba>) -> Vec<[u32; 3]> {
+        let mut positions: Vec<[u32; 3]> = cloud
+            .points
+            .iter()
+            .map(|p| p.xyz().map(|v| v as u32))
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+    }
+
+    #[test]
+    fn test_lossless_round_trip() {
+        let cloud = lattice_cloud();
+        let bytes = encode_octree(&cloud, 8);
+        let decoded = decode_octree(&bytes).unwrap();
+        assert_eq!(sorted_positions(&decoded), sorted_positions(&cloud));
+        assert!(decoded
+            .points
+            .iter()
+            .all(|p| p.xyz() == p.xyz().map(f32::round)));
+        // fewer bits than the 24 of a lattice position
+        assert!(bytes.len() * 8 < 24 * cloud.points.len());
+        assert!(decode_octree(&bytes[..bytes.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn test_lossy_round_trip() {
+        let cloud = lattice_cloud();
+        let decoded = decode_octree(&encode_octree(&cloud, 5)).unwrap();
+        // voxels of 255 / 31 along each axis, every point rounded to the nearest corner
+        let max_error = 0.5 * 255.0 / 31.0 * 3f32.sqrt() * 1.001;
+        assert!(d1_errors(&decoded, &cloud).iter().all(|&e| e <= max_error));
+        assert!(d1_errors(&cloud, &decoded).iter().all(|&e| e <= max_error));
+    }
+
+    #[test]
+    fn test_empty_and_single_point() {
+        let empty = PointCloud::from_points(vec![]);
+        assert!(decode_octree(&encode_octree(&empty, 4))
+            .unwrap()
+            .points
+            .is_empty());
+
+        let single = PointCloud::from_points(vec![PointXyzRgba::from_xyz_rgba(
+            [1.5, -2.0, 3.0],
+            [0, 0, 0, 255],
+        )]);
+        let decoded = decode_octree(&encode_octree(&single, 4)).unwrap();
+        assert_eq!(decoded.points.len(), 1);
+        assert_eq!(decoded.points[0].xyz(), [1.5, -2.0, 3.0]);
+        assert!(decode_octree(&[4]).is_err());
+        assert!(decode_octree(&[VERSIONED, VERSION, 4]).is_err());
+        assert!(decode_octree(&[VERSIONED, VERSION + 1]).is_err());
+    }
+
+    #[test]
+    fn test_stream_matches_batch() {
+        let cloud = lattice_cloud();
+        let bounds = bounding_box(cloud.points.iter().map(|p| p.xyz().into())).unwrap();
+        let mut encoder = OctreeStreamEncoder::new(7, bounds, vec![]).unwrap();
+        let mut points = cloud.points.clone();
+        points.shuffle(&mut StdRng::seed_from_u64(6));
+        points.sort_by_key(|p| encoder.morton_code(p));
+        for p in &points {
+            encoder.push(p).unwrap();
+        }
+        assert_eq!(encoder.finish().unwrap(), encode_octree(&cloud, 7));
+
+        let mut encoder = OctreeStreamEncoder::new(7, bounds, vec![]).unwrap();
+        encoder.push(&points[points.len() - 1]).unwrap();
+        assert!(encoder.push(&points[0]).is_err());
+    }
+
+    #[test]
+    fn test_decode_breadth_first() {
+        // written by the octree-encode of version 1, at a depth of 8 keeping the lattice
+        let bytes = std::fs::read("test_files/octree/lattice_breadth_first.oct").unwrap();
+        assert_eq!(bytes[0], 8);
+        let decoded = decode_octree(&bytes).unwrap();
+        let cloud = lattice_cloud();
+        assert_eq!(sorted_positions(&decoded), sorted_positions(&cloud));
+        assert_eq!(decoded.points.len(), sorted_positions(&cloud).len());
+        assert!(decode_octree(&bytes[..bytes.len() / 2]).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+
+use super::run_tool;
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::ply::{read_ply, write_ply_with_alpha};
+
+/// Compresses sequences of point clouds with `PccAppEncoder`, the encoder of TMC2, the
+/// reference software of MPEG V-PCC, which reads numbered ply files.
+pub struct Tmc2Encoder {
+    path: PathBuf,
+    /// encoder configurations, e.g. the common, condition, sequence and rate `cfg` files of TMC2
+    configs: Vec<PathBuf>,
+}
+
+impl Tmc2Encoder {
+    pub fn new<P: Into<PathBuf>>(path: P, configs: Vec<PathBuf>) -> Self {
+        Tmc2Encoder {
+            path: path.into(),
+            configs,
+        }
+    }
+
+    /// Compresses the frames as one sequence into the bitstream `output`, returning its size in
+    /// bytes.
+    pub fn encode(&self, frames: &[PointCloud<PointXyzRgba>], output: &Path) -> Result<u64> {
+        let dir = temp_dir(output);
+        let mut args = vec![
+            format!(
+                "--uncompressedDataPath={}",
+                dir.join("frame_%04d.ply").display()
+            ),
+            "--startFrameNumber=0".to_string(),
+            format!("--frameCount={}", frames.len()),
+            format!("--compressedStreamPath={}", output.display()),
+        ];
+        args.extend(
+            self.configs
+                .iter()
+                .map(|config| format!("--config={}", config.display())),
+        );
+        let result = write_frames(frames, &dir).and_then(|_| run_tool(&self.path, &args));
+        let _ = std::fs::remove_dir_all(&dir);
+        result?;
+        Ok(std::fs::metadata(output)?.len())
+    }
+}
+
+/// Decompresses V-PCC bitstreams with `PccAppDecoder`, the decoder of TMC2, which writes
+/// numbered ply files.
+pub struct Tmc2Decoder {
+    path: PathBuf,
+}
+
+impl Tmc2Decoder {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Tmc2Decoder { path: path.into() }
+    }
+
+    /// Reconstructs the frames of the bitstream `input`, in order.
+    pub fn decode(&self, input: &Path) -> Result<Vec<PointCloud<PointXyzRgba>>> {
+        let dir = temp_dir(input);
+        std::fs::create_dir_all(&dir)?;
+        let result = run_tool(
+            &self.path,
+            &[
+                format!("--compressedStreamPath={}", input.display()),
+                format!(
+                    "--reconstructedDataPath={}",
+                    dir.join("frame_%04d.ply").display()
+                ),
+                "--startFrameNumber=0".to_string(),
+            ],
+        )
+        .and_then(|_| read_frames(&dir));
+        let _ = std::fs::remove_dir_all(&dir);
+        result.with_context(|| format!("Failed to decode {}", input.display()))
+    }
+}
+
+fn write_frames(frames: &[PointCloud<PointXyzRgba>], dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for (i, frame) in frames.iter().enumerate() {
+        write_ply_with_alpha(frame, dir.join(format!("frame_{:04}.ply", i)))
+            .map_err(|e| anyhow!("{}", e))?;
+    }
+    Ok(())
+}
+
+/// Reads the ply files of the directory in the order of their names.
+fn read_frames(dir: &Path) -> Result<Vec<PointCloud<PointXyzRgba>>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map_or(false, |ext| ext == "ply"))
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| read_ply(path).with_context(|| format!("Failed to read {}", path.display())))
+        .collect()
+}
+
+/// Directory in the temporary directory exchanged with TMC2 for the bitstream.
+fn temp_dir(bitstream: &Path) -> PathBuf {
+    let stem = bitstream.file_stem().unwrap_or_default().to_string_lossy();
+    std::env::temp_dir().join(format!("vivotk_vpcc_{}_{}", std::process::id(), stem))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_missing_tmc2() {
+        let encoder = Tmc2Encoder::new("vivotk_test_missing_encoder", vec![]);
+        let decoder = Tmc2Decoder::new("vivotk_test_missing_decoder");
+        let frame = PointCloud::from_points(vec![PointXyzRgba::from_xyz_rgba(
+            [1.0, 2.0, 3.0],
+            [10, 20, 30, 255],
+        )]);
+        let bitstream = std::env::temp_dir().join("vivotk_test_missing_tmc2.bin");
+
+        assert!(encoder.encode(&[frame.clone(), frame], &bitstream).is_err());
+        // the frames handed to the encoder are removed even if it failed
+        assert!(!temp_dir(&bitstream).exists());
+        assert!(decoder.decode(&bitstream).is_err());
+        assert!(!temp_dir(&bitstream).exists());
+    }
+}
@@ -1,17 +1,52 @@
-use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::formats::{
+    pointxyzrgba::PointXyzRgba, pointxyzrgbanormal::PointXyzRgbaNormal, HasCoordinate, PointCloud,
+};
 
 use std::iter::zip;
 
 const DELTA: f32 = 1e-4;
 
-pub fn downsample(
-    points: PointCloud<PointXyzRgba>,
-    points_per_voxel: usize,
-) -> PointCloud<PointXyzRgba> {
+/// A point type whose points can be merged into a single point per voxel.
+pub trait Voxel: HasCoordinate + Clone {
+    fn centroid(points: Vec<Self>) -> Self;
+}
+
+impl Voxel for PointXyzRgba {
+    fn centroid(points: Vec<Self>) -> Self {
+        centroid(points)
+    }
+}
+
+impl Voxel for PointXyzRgbaNormal {
+    /// The centroid of the positions and colors, with the normalized mean of the normals.
+    fn centroid(points: Vec<Self>) -> Self {
+        let (mut nx, mut ny, mut nz) = (0f32, 0f32, 0f32);
+        for point in &points {
+            nx += point.nx;
+            ny += point.ny;
+            nz += point.nz;
+        }
+        let norm = (nx * nx + ny * ny + nz * nz).sqrt();
+        let normal = if norm > 0.0 {
+            [nx / norm, ny / norm, nz / norm]
+        } else {
+            [0.0, 0.0, 0.0]
+        };
+        let point = centroid(
+            points
+                .iter()
+                .map(|p| PointXyzRgba::from_xyz_rgba(p.xyz(), p.rgba()))
+                .collect(),
+        );
+        PointXyzRgbaNormal::from_xyz_rgba_normal(point.xyz(), point.rgba(), normal)
+    }
+}
+
+pub fn downsample<T: Voxel>(points: PointCloud<T>, points_per_voxel: usize) -> PointCloud<T> {
     if points.points.is_empty() {
         points
     } else {
-        let first_point = points.points[0];
+        let first_point = points.points[0].coordinate();
         let mut min_x = first_point.x;
         let mut max_x = first_point.x;
         let mut min_y = first_point.y;
@@ -19,7 +54,7 @@ pub fn downsample(
         let mut min_z = first_point.z;
         let mut max_z = first_point.z;
 
-        for &point in &points.points {
+        for point in points.points.iter().map(|p| p.coordinate()) {
             min_x = min_x.min(point.x);
             max_x = max_x.max(point.x);
             min_y = min_y.min(point.y);
@@ -41,10 +76,7 @@ pub fn downsample(
             points_per_voxel,
         );
 
-        PointCloud {
-            number_of_points: points.len(),
-            points,
-        }
+        PointCloud::from_points(points)
     }
 }
 
@@ -123,7 +155,8 @@ impl Bounds {
         ]
     }
 
-    fn contains(&self, point: &PointXyzRgba) -> bool {
+    fn contains<T: HasCoordinate>(&self, point: &T) -> bool {
+        let point = point.coordinate();
         point.x >= self.min_x
             && point.x <= self.max_x
             && point.y >= self.min_y
@@ -133,17 +166,13 @@ impl Bounds {
     }
 }
 
-fn octree_downsample(
-    points: Vec<PointXyzRgba>,
-    bounds: Bounds,
-    points_per_voxel: usize,
-) -> Vec<PointXyzRgba> {
+fn octree_downsample<T: Voxel>(points: Vec<T>, bounds: Bounds, points_per_voxel: usize) -> Vec<T> {
     if points.is_empty() {
         return vec![];
     }
 
     if points.len() <= points_per_voxel {
-        return vec![centroid(points)];
+        return vec![T::centroid(points)];
     }
 
     let mut voxels = vec![vec![]; 8];
@@ -215,4 +244,25 @@ mod test {
             PathBuf::from("./test_files/pcd_ascii/longdress_vox10_1213_short_up_downsampled.pcd");
         write_pcd_file(&pcd, crate::pcd::PCDDataType::Ascii, &outpath).unwrap();
     }
+
+    #[test]
+    fn test_downsample_keeps_normals() {
+        let point = |x: f32, normal: [f32; 3]| {
+            PointXyzRgbaNormal::from_xyz_rgba_normal([x, 0.0, 0.0], [10, 20, 30, 255], normal)
+        };
+        let cloud = PointCloud::from_points(vec![
+            point(0.0, [0.0, 0.0, 2.0]),
+            point(0.1, [0.0, 0.0, 1.0]),
+            point(10.0, [1.0, 0.0, 0.0]),
+            point(10.1, [0.0, 1.0, 0.0]),
+        ]);
+        let downsampled = downsample(cloud, 2);
+        assert_eq!(downsampled.len(), 2);
+        assert_eq!(downsampled.points[0].normal(), [0.0, 0.0, 1.0]);
+        assert!((downsampled.points[0].x - 0.05).abs() < 1e-6);
+        let [nx, ny, nz] = downsampled.points[1].normal();
+        let s = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((nx - s).abs() < 1e-6 && (ny - s).abs() < 1e-6 && nz == 0.0);
+        assert_eq!(downsampled.points[1].rgba(), [10, 20, 30, 255]);
+    }
 }
@@ -0,0 +1,168 @@
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use cgmath::Point3;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Header of a dense cubic voxel grid, stored as json next to the flat binary payload.
+///
+/// Voxels are stored in row-major order, the voxel at `(x, y, z)` has index `(x * res + y) * res + z`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GridHeader {
+    pub resolution: usize,
+    pub origin: [f32; 3],
+    pub voxel_size: f32,
+}
+
+impl GridHeader {
+    /// Creates a grid of `resolution` cubic voxels along each axis covering the bounding box.
+    pub fn new(min: Point3<f32>, max: Point3<f32>, resolution: usize) -> Self {
+        let extent = (max.x - min.x).max(max.y - min.y).max(max.z - min.z);
+        let voxel_size = if extent > 0.0 {
+            extent / resolution as f32
+        } else {
+            1.0
+        };
+        Self {
+            resolution,
+            origin: [min.x, min.y, min.z],
+            voxel_size,
+        }
+    }
+
+    pub fn num_voxels(&self) -> usize {
+        self.resolution * self.resolution * self.resolution
+    }
+
+    pub fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (x * self.resolution + y) * self.resolution + z
+    }
+
+    pub fn voxel_center(&self, index: usize) -> Point3<f32> {
+        let res = self.resolution;
+        let (x, y, z) = (index / (res * res), (index / res) % res, index % res);
+        Point3::new(
+            self.origin[0] + (x as f32 + 0.5) * self.voxel_size,
+            self.origin[1] + (y as f32 + 0.5) * self.voxel_size,
+            self.origin[2] + (z as f32 + 0.5) * self.voxel_size,
+        )
+    }
+
+    /// Returns the index of the voxel containing the point, or `None` if it lies outside the grid.
+    /// Points on the far faces of the grid are assigned to the last voxel.
+    pub fn voxel_of(&self, point: Point3<f32>) -> Option<usize> {
+        let to_cell = |v: f32, origin: f32| {
+            let cell = ((v - origin) / self.voxel_size).floor();
+            if cell < 0.0 || cell > self.resolution as f32 {
+                None
+            } else {
+                Some((cell as usize).min(self.resolution - 1))
+            }
+        };
+        Some(self.index(
+            to_cell(point.x, self.origin[0])?,
+            to_cell(point.y, self.origin[1])?,
+            to_cell(point.z, self.origin[2])?,
+        ))
+    }
+
+    pub fn read_from<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(serde_json::to_writer_pretty(writer, self)?)
+    }
+}
+
+/// Returns the minimum and maximum corners of the points, or `None` if there are no points.
+pub fn bounding_box<I>(points: I) -> Option<(Point3<f32>, Point3<f32>)>
+where
+    I: IntoIterator<Item = Point3<f32>>,
+{
+    let mut points = points.into_iter();
+    let first = points.next()?;
+    let (mut min, mut max) = (first, first);
+    for p in points {
+        min = Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+        max = Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+    }
+    Some((min, max))
+}
+
+/// Writes one little endian f32 per voxel.
+pub fn write_f32_grid<P: AsRef<Path>>(path: P, values: &[f32]) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for &v in values {
+        writer.write_f32::<LittleEndian>(v)?;
+    }
+    writer.flush()
+}
+
+pub fn read_f32_grid<P: AsRef<Path>>(path: P, header: &GridHeader) -> std::io::Result<Vec<f32>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    (0..header.num_voxels())
+        .map(|_| reader.read_f32::<LittleEndian>())
+        .collect()
+}
+
+/// Writes one bit per voxel, packed into bytes with the lowest voxel index in the least significant bit.
+pub fn write_bit_grid<P: AsRef<Path>>(path: P, occupied: &[bool]) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for chunk in occupied.chunks(8) {
+        let byte = chunk
+            .iter()
+            .enumerate()
+            .fold(0u8, |byte, (bit, &o)| byte | ((o as u8) << bit));
+        writer.write_u8(byte)?;
+    }
+    writer.flush()
+}
+
+pub fn read_bit_grid<P: AsRef<Path>>(path: P, header: &GridHeader) -> std::io::Result<Vec<bool>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let num_voxels = header.num_voxels();
+    let mut occupied = Vec::with_capacity(num_voxels);
+    while occupied.len() < num_voxels {
+        let byte = reader.read_u8()?;
+        for bit in 0..8.min(num_voxels - occupied.len()) {
+            occupied.push(byte & (1 << bit) != 0);
+        }
+    }
+    Ok(occupied)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_voxel_center_round_trip() {
+        let header = GridHeader::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0), 4);
+        assert_eq!(header.voxel_size, 0.5);
+        for idx in 0..header.num_voxels() {
+            assert_eq!(header.voxel_of(header.voxel_center(idx)), Some(idx));
+        }
+        assert_eq!(
+            header.voxel_of(Point3::new(2.0, 2.0, 2.0)),
+            Some(header.num_voxels() - 1)
+        );
+        assert_eq!(header.voxel_of(Point3::new(-0.1, 0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_bit_grid_round_trip() {
+        let header = GridHeader::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0), 3);
+        let occupied: Vec<bool> = (0..header.num_voxels()).map(|i| i % 3 == 0).collect();
+        let dir = std::env::temp_dir().join("vvtk_test_bit_grid_round_trip");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("grid.occ");
+        write_bit_grid(&path, &occupied).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 4);
+        assert_eq!(read_bit_grid(&path, &header).unwrap(), occupied);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
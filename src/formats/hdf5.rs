@@ -0,0 +1,125 @@
+use ::hdf5::{File, Group, Result};
+use ndarray::Array2;
+use std::path::Path;
+
+use super::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Writes a sequence of point clouds to an HDF5 file, each frame as the datasets
+/// `/clouds/{frame_idx}/xyz` (f32, Nx3) and `/clouds/{frame_idx}/rgb` (u8, Nx3), with a
+/// `timestamp` attribute on the frame group and a `frame_count` attribute on the file.
+pub struct Hdf5Writer {
+    file: File,
+    clouds: Group,
+    frame_count: u64,
+}
+
+impl Hdf5Writer {
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::create(path)?;
+        let clouds = file.create_group("clouds")?;
+        Ok(Self {
+            file,
+            clouds,
+            frame_count: 0,
+        })
+    }
+
+    pub fn write_frame(
+        &mut self,
+        frame_idx: u32,
+        timestamp: f64,
+        cloud: &PointCloud<PointXyzRgba>,
+    ) -> Result<()> {
+        let n = cloud.points.len();
+        let xyz: Vec<f32> = cloud.points.iter().flat_map(|p| p.xyz()).collect();
+        let rgb: Vec<u8> = cloud.points.iter().flat_map(|p| [p.r, p.g, p.b]).collect();
+        let xyz = Array2::from_shape_vec((n, 3), xyz).expect("Expecting 3 values per point");
+        let rgb = Array2::from_shape_vec((n, 3), rgb).expect("Expecting 3 values per point");
+
+        let frame = self.clouds.create_group(&frame_idx.to_string())?;
+        frame.new_dataset_builder().with_data(&xyz).create("xyz")?;
+        frame.new_dataset_builder().with_data(&rgb).create("rgb")?;
+        frame
+            .new_attr::<f64>()
+            .shape(())
+            .create("timestamp")?
+            .write_scalar(&timestamp)?;
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    /// Writes the `frame_count` attribute and closes the file.
+    pub fn finish(self) -> Result<()> {
+        self.file
+            .new_attr::<u64>()
+            .shape(())
+            .create("frame_count")?
+            .write_scalar(&self.frame_count)?;
+        self.file.close()
+    }
+}
+
+/// Reads the frame `frame_idx` of a file written by [`Hdf5Writer`], the points are opaque.
+pub fn read_hdf5_frame<P: AsRef<Path>>(
+    path: P,
+    frame_idx: u32,
+) -> Result<PointCloud<PointXyzRgba>> {
+    let file = File::open(path)?;
+    let frame = file.group(&format!("clouds/{}", frame_idx))?;
+    let xyz = frame.dataset("xyz")?.read_2d::<f32>()?;
+    let rgb = frame.dataset("rgb")?.read_2d::<u8>()?;
+    let points = xyz
+        .rows()
+        .into_iter()
+        .zip(rgb.rows())
+        .map(|(p, c)| PointXyzRgba::from_xyz_rgba([p[0], p[1], p[2]], [c[0], c[1], c[2], 255]))
+        .collect();
+    Ok(PointCloud::from_points(points))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_write_ten_frames() {
+        let frame = |i: u32| {
+            PointCloud::from_points(
+                (0..=i)
+                    .map(|j| {
+                        PointXyzRgba::from_xyz_rgba(
+                            [i as f32, j as f32, 0.5],
+                            [i as u8, j as u8, 7, 255],
+                        )
+                    })
+                    .collect(),
+            )
+        };
+        let path = std::env::temp_dir().join("vivotk_test_write_ten_frames.h5");
+        let mut writer = Hdf5Writer::create(&path).unwrap();
+        for i in 0..10 {
+            writer.write_frame(i, i as f64 / 30.0, &frame(i)).unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_eq!(read_hdf5_frame(&path, 0).unwrap().points, frame(0).points);
+        let file = File::open(&path).unwrap();
+        assert_eq!(
+            file.attr("frame_count")
+                .unwrap()
+                .read_scalar::<u64>()
+                .unwrap(),
+            10
+        );
+        let timestamp: f64 = file
+            .group("clouds/3")
+            .unwrap()
+            .attr("timestamp")
+            .unwrap()
+            .read_scalar()
+            .unwrap();
+        assert!((timestamp - 0.1).abs() < 1e-12);
+        drop(file);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,22 +1,126 @@
+use cgmath::Point3;
+use kiddo::{distance::squared_euclidean, KdTree};
+use rand::Rng;
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Index, IndexMut};
 
 use crate::pcd::PointCloudData;
 use crate::velodyne::{VelodynPoint, VelodyneBinData};
 
+pub mod grid;
+#[cfg(feature = "hdf5")]
+pub mod hdf5;
+#[cfg(feature = "npz")]
+pub mod npz;
 pub mod pointxyzrgba;
+pub mod pointxyzrgbacurvature;
+pub mod pointxyzrgbaf64;
+pub mod pointxyzrgbanormal;
+pub mod pointxyzrgbat;
+#[cfg(feature = "ros2")]
+pub mod ros2;
+#[cfg(feature = "rosbag")]
+pub mod rosbag;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod udp;
 
+/// A point with a position in space.
+pub trait HasCoordinate {
+    fn coordinate(&self) -> Point3<f32>;
+}
+
+impl HasCoordinate for pointxyzrgba::PointXyzRgba {
+    fn coordinate(&self) -> Point3<f32> {
+        Point3::new(self.x, self.y, self.z)
+    }
+}
+
+impl HasCoordinate for pointxyzrgbanormal::PointXyzRgbaNormal {
+    fn coordinate(&self) -> Point3<f32> {
+        Point3::new(self.x, self.y, self.z)
+    }
+}
+
+impl HasCoordinate for pointxyzrgbacurvature::PointXyzRgbaCurvature {
+    fn coordinate(&self) -> Point3<f32> {
+        Point3::new(self.x, self.y, self.z)
+    }
+}
+
+/// Construct with [`PointCloud::from_points`] and read the number of points with
+/// [`PointCloud::len`], which is always the length of `points`.
 #[derive(Clone)]
 pub struct PointCloud<T> {
-    pub number_of_points: usize,
     pub points: Vec<T>,
 }
 
+impl<T> PointCloud<T> {
+    pub fn from_points(points: Vec<T>) -> Self {
+        Self { points }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.points.get(i)
+    }
+
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        self.points.get_mut(i)
+    }
+}
+
+impl<T> Index<usize> for PointCloud<T> {
+    type Output = T;
+
+    fn index(&self, i: usize) -> &T {
+        let len = self.len();
+        self.get(i).unwrap_or_else(|| {
+            panic!(
+                "point index {} is out of bounds for a point cloud of {} points",
+                i, len
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<usize> for PointCloud<T> {
+    fn index_mut(&mut self, i: usize) -> &mut T {
+        let len = self.len();
+        self.get_mut(i).unwrap_or_else(|| {
+            panic!(
+                "point index {} is out of bounds for a point cloud of {} points",
+                i, len
+            )
+        })
+    }
+}
+
 impl Debug for PointCloud<pointxyzrgba::PointXyzRgba> {
     // first print the number of points in one line
     // then for each T in the Vec, print in a new line
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "PointCloud<PointXyzRgba> {{")?;
-        writeln!(f, "   number_of_points: {}", self.number_of_points)?;
+        writeln!(f, "   number_of_points: {}", self.len())?;
+        for point in &self.points {
+            writeln!(f, "   {:?}", point)?;
+        }
+        writeln!(f, "}}")?;
+        Ok(())
+    }
+}
+
+impl Debug for PointCloud<pointxyzrgbanormal::PointXyzRgbaNormal> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "PointCloud<PointXyzRgbaNormal> {{")?;
+        writeln!(f, "   number_of_points: {}", self.len())?;
         for point in &self.points {
             writeln!(f, "   {:?}", point)?;
         }
@@ -25,6 +129,140 @@ impl Debug for PointCloud<pointxyzrgba::PointXyzRgba> {
     }
 }
 
+impl<T: HasCoordinate + Clone> PointCloud<T> {
+    /// Returns the points within `radius` of `center`, in their original order.
+    pub fn crop_sphere(&self, center: Point3<f32>, radius: f32) -> PointCloud<T> {
+        let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+        for (i, p) in self.points.iter().enumerate() {
+            let c = p.coordinate();
+            tree.add(&[c.x, c.y, c.z], i)
+                .expect("Failed to add point to kd tree");
+        }
+
+        let mut indices: Vec<usize> = if self.points.is_empty() {
+            vec![]
+        } else {
+            tree.within(
+                &[center.x, center.y, center.z],
+                radius * radius,
+                &squared_euclidean,
+            )
+            .expect("Failed to query kd tree")
+            .into_iter()
+            .map(|(_, &i)| i)
+            .collect()
+        };
+        indices.sort_unstable();
+
+        let points: Vec<T> = indices
+            .into_iter()
+            .map(|i| self.points[i].clone())
+            .collect();
+        PointCloud::from_points(points)
+    }
+}
+
+impl<T: HasCoordinate + Clone> PointCloud<T> {
+    /// Returns the points inside the axis aligned box spanned by `min` and `max`, bounds included.
+    pub fn crop_aabb(&self, min: Point3<f32>, max: Point3<f32>) -> PointCloud<T> {
+        let points: Vec<T> = self
+            .points
+            .iter()
+            .filter(|p| {
+                let c = p.coordinate();
+                (min.x..=max.x).contains(&c.x)
+                    && (min.y..=max.y).contains(&c.y)
+                    && (min.z..=max.z).contains(&c.z)
+            })
+            .cloned()
+            .collect();
+        PointCloud::from_points(points)
+    }
+}
+
+impl<T: HasCoordinate + Clone> PointCloud<T> {
+    /// Appends the points of `other` to this cloud, skipping the points of `other` within `epsilon`
+    /// of a point of this cloud. The points of `other` are not deduplicated against each other.
+    pub fn merge_dedup(&self, other: &PointCloud<T>, epsilon: f32) -> PointCloud<T> {
+        let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+        for (i, p) in self.points.iter().enumerate() {
+            let c = p.coordinate();
+            tree.add(&[c.x, c.y, c.z], i)
+                .expect("Failed to add point to kd tree");
+        }
+
+        let mut points = self.points.clone();
+        points.extend(
+            other
+                .points
+                .iter()
+                .filter(|p| {
+                    if self.points.is_empty() {
+                        return true;
+                    }
+                    let c = p.coordinate();
+                    tree.nearest(&[c.x, c.y, c.z], 1, &squared_euclidean)
+                        .expect("Failed to query kd tree")[0]
+                        .0
+                        > epsilon * epsilon
+                })
+                .cloned(),
+        );
+        PointCloud::from_points(points)
+    }
+}
+
+impl<T: HasCoordinate + Clone> PointCloud<T> {
+    /// Divides the bounding box into `grid_resolution` cubic cells along its largest extent and
+    /// keeps one random point of each non-empty cell, in their original order. Unlike sampling
+    /// the points uniformly, dense regions do not take most of the sample.
+    pub fn sample_stratified(&self, grid_resolution: usize) -> PointCloud<T> {
+        let (min, max) = match grid::bounding_box(self.points.iter().map(|p| p.coordinate())) {
+            Some(bounds) => bounds,
+            None => return PointCloud::from_points(vec![]),
+        };
+        let header = grid::GridHeader::new(min, max, grid_resolution.max(1));
+
+        // reservoir sampling of one point per cell: the k-th point of a cell replaces the kept
+        // one with probability 1 / k
+        let mut rng = rand::thread_rng();
+        let mut cells: HashMap<usize, (usize, usize)> = HashMap::new();
+        for (i, p) in self.points.iter().enumerate() {
+            if let Some(cell) = header.voxel_of(p.coordinate()) {
+                let (count, kept) = cells.entry(cell).or_insert((0, i));
+                *count += 1;
+                if rng.gen_range(0..*count) == 0 {
+                    *kept = i;
+                }
+            }
+        }
+
+        let mut indices: Vec<usize> = cells.into_values().map(|(_, i)| i).collect();
+        indices.sort_unstable();
+        let points: Vec<T> = indices
+            .into_iter()
+            .map(|i| self.points[i].clone())
+            .collect();
+        PointCloud::from_points(points)
+    }
+}
+
+impl<T: Clone> Add for PointCloud<T> {
+    type Output = PointCloud<T>;
+
+    /// Concatenates the points of both clouds, the points of `self` first.
+    fn add(mut self, rhs: PointCloud<T>) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl<T: Clone> AddAssign for PointCloud<T> {
+    fn add_assign(&mut self, rhs: PointCloud<T>) {
+        self.points.extend(rhs.points);
+    }
+}
+
 impl<T> From<PointCloudData> for PointCloud<T> {
     fn from(pcd: PointCloudData) -> Self {
         let number_of_points = pcd.header.points() as usize;
@@ -35,22 +273,14 @@ impl<T> From<PointCloudData> for PointCloud<T> {
             let capacity = v_clone.capacity() / factor;
             Vec::from_raw_parts(v_clone.as_mut_ptr() as *mut T, number_of_points, capacity)
         };
-        Self {
-            number_of_points,
-            points,
-        }
+        Self::from_points(points)
     }
 }
 
 impl From<VelodyneBinData> for PointCloud<pointxyzrgba::PointXyzRgba> {
     // type T: pointxyzrgba::PointXyzRgba;
     fn from(value: VelodyneBinData) -> Self {
-        let number_of_points = value.data.len();
-        let points = value.data.into_iter().map(|point| point.into()).collect();
-        Self {
-            number_of_points,
-            points,
-        }
+        Self::from_points(value.data.into_iter().map(|point| point.into()).collect())
     }
 }
 
@@ -67,3 +297,205 @@ impl From<VelodynPoint> for pointxyzrgba::PointXyzRgba {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cgmath::MetricSpace;
+    use pointxyzrgba::PointXyzRgba;
+
+    fn grid_cloud() -> PointCloud<PointXyzRgba> {
+        let mut points = vec![];
+        for x in -5..=5 {
+            for y in -5..=5 {
+                for z in -5..=5 {
+                    points.push(PointXyzRgba {
+                        x: x as f32,
+                        y: y as f32,
+                        z: z as f32,
+                        r: 0,
+                        g: 0,
+                        b: 0,
+                        a: 255,
+                    });
+                }
+            }
+        }
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_crop_sphere() {
+        let cloud = grid_cloud();
+        let center = Point3::new(1.0, 0.5, -1.0);
+        let cropped = cloud.crop_sphere(center, 2.6);
+
+        assert!(cropped
+            .points
+            .iter()
+            .all(|p| p.coordinate().distance(center) <= 2.6));
+        let expected = cloud
+            .points
+            .iter()
+            .filter(|p| p.coordinate().distance(center) <= 2.6)
+            .count();
+        assert_eq!(cropped.len(), expected);
+    }
+
+    #[test]
+    fn test_crop_aabb() {
+        let cloud = grid_cloud();
+        // keeps the planes x = 1 to x = 5
+        let min = Point3::new(0.5, -5.0, -5.0);
+        let max = Point3::new(5.0, 5.0, 5.0);
+        let cropped = cloud.crop_aabb(min, max);
+        assert_eq!(cropped.len(), 5 * 11 * 11);
+        assert!(cropped.points.iter().all(|p| p.x >= 0.5));
+
+        // half of the points lie outside
+        let points: Vec<PointXyzRgba> = (0..10)
+            .map(|i| PointXyzRgba {
+                x: i as f32,
+                y: 0.0,
+                z: 0.0,
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            })
+            .collect();
+        let line = PointCloud::from_points(points);
+        let cropped = line.crop_aabb(Point3::new(-1.0, -1.0, -1.0), Point3::new(4.0, 1.0, 1.0));
+        assert_eq!(cropped.len(), 5);
+        assert_eq!(cropped.points, line.points[..5].to_vec());
+    }
+
+    #[test]
+    fn test_merge_dedup() {
+        let cloud = grid_cloud();
+        let merged = cloud.merge_dedup(&cloud, 0.0);
+        assert_eq!(merged.len(), cloud.len());
+        assert_eq!(merged.points, cloud.points);
+
+        // shifted by 1.1 along x, only the plane x = 6.1 is new
+        let mut shifted = cloud.clone();
+        for p in shifted.points.iter_mut() {
+            p.x += 1.1;
+        }
+        let merged = cloud.merge_dedup(&shifted, 0.2);
+        assert_eq!(merged.len(), 11 * 11 * 12);
+        assert_eq!(merged.points[..cloud.len()], cloud.points[..]);
+
+        let merged = cloud.merge_dedup(&shifted, 0.05);
+        assert_eq!(merged.len(), 2 * cloud.len());
+
+        let empty = PointCloud::from_points(vec![]);
+        let merged = empty.merge_dedup(&cloud, 0.1);
+        assert_eq!(merged.points, cloud.points);
+    }
+
+    #[test]
+    fn test_sample_stratified() {
+        use rand::{rngs::StdRng, seq::index::sample, SeedableRng};
+
+        // a dense cluster in a corner of a sparse unit cube
+        let mut rng = StdRng::seed_from_u64(8);
+        let point = |x, y, z| PointXyzRgba {
+            x,
+            y,
+            z,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+        let mut points: Vec<PointXyzRgba> = (0..9000)
+            .map(|_| {
+                point(
+                    rng.gen::<f32>() * 0.1,
+                    rng.gen::<f32>() * 0.1,
+                    rng.gen::<f32>() * 0.1,
+                )
+            })
+            .collect();
+        points.extend((0..1000).map(|_| point(rng.gen(), rng.gen(), rng.gen())));
+        points.push(point(0.0, 0.0, 0.0));
+        points.push(point(1.0, 1.0, 1.0));
+        let cloud = PointCloud::from_points(points);
+        let in_cluster = |p: &PointXyzRgba| p.x < 0.1 && p.y < 0.1 && p.z < 0.1;
+
+        let stratified = cloud.sample_stratified(10);
+        let header =
+            grid::GridHeader::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0), 10);
+        let mut cells: Vec<usize> = stratified
+            .points
+            .iter()
+            .map(|p| header.voxel_of(p.coordinate()).unwrap())
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        assert_eq!(cells.len(), stratified.len());
+
+        let random: Vec<PointXyzRgba> =
+            sample(&mut rng, cloud.points.len(), stratified.points.len())
+                .into_iter()
+                .map(|i| cloud.points[i])
+                .collect();
+        let stratified_in_cluster = stratified.points.iter().filter(|p| in_cluster(p)).count();
+        let random_in_cluster = random.iter().filter(|p| in_cluster(p)).count();
+        assert!(stratified_in_cluster * 10 < random_in_cluster);
+    }
+
+    #[test]
+    fn test_add() {
+        let a = grid_cloud().crop_aabb(Point3::new(-5.0, -5.0, -5.0), Point3::new(0.0, 5.0, 5.0));
+        let b = grid_cloud().crop_aabb(Point3::new(1.0, -5.0, -5.0), Point3::new(5.0, 5.0, 5.0));
+
+        let sum = a.clone() + b.clone();
+        assert_eq!(sum.len(), a.len() + b.len());
+        assert_eq!(sum.points[..a.len()], a.points[..]);
+        assert_eq!(sum.points[a.len()..], b.points[..]);
+        assert_eq!(sum.points, grid_cloud().points);
+
+        let mut c = b.clone();
+        c += a;
+        assert_eq!(c.len(), sum.len());
+        assert_eq!(c.points[..b.len()], b.points[..]);
+    }
+
+    #[test]
+    fn test_from_points() {
+        let cloud = PointCloud::from_points(grid_cloud().points);
+        assert_eq!(cloud.len(), 11 * 11 * 11);
+        assert!(!cloud.is_empty());
+
+        // len follows the points
+        let mut cloud = cloud;
+        cloud.points.truncate(10);
+        assert_eq!(cloud.len(), 10);
+        assert!(PointCloud::<PointXyzRgba>::from_points(vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_index() {
+        let mut cloud = grid_cloud();
+        assert_eq!(cloud[0], cloud.points[0]);
+        assert_eq!(cloud.get(1), Some(&cloud.points[1]));
+        assert_eq!(cloud.get(cloud.len()), None);
+
+        cloud[2].r = 100;
+        assert_eq!(cloud.points[2].r, 100);
+        if let Some(p) = cloud.get_mut(3) {
+            p.g = 50;
+        }
+        assert_eq!(cloud.points[3].g, 50);
+        assert!(cloud.get_mut(cloud.len()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "point index 1331 is out of bounds for a point cloud of 1331 points")]
+    fn test_index_out_of_bounds() {
+        let cloud = grid_cloud();
+        let _ = cloud[1331];
+    }
+}
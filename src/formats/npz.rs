@@ -0,0 +1,170 @@
+use ndarray::Array2;
+use ndarray_npy::{NpzReader, NpzWriter, ReadNpzError, WriteNpzError};
+use std::fs::File;
+use std::path::Path;
+use thiserror::Error;
+
+use super::{pointxyzrgba::PointXyzRgba, pointxyzrgbanormal::PointXyzRgbaNormal, PointCloud};
+
+type Result<T> = std::result::Result<T, NpzError>;
+
+#[derive(Error, Debug)]
+pub enum NpzError {
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    ReadError(#[from] ReadNpzError),
+
+    #[error(transparent)]
+    WriteError(#[from] WriteNpzError),
+
+    /// Represents arrays of unexpected shapes.
+    #[error("Invalid data: {0}")]
+    InvalidData(String),
+}
+
+/// A point cloud read from a `.npz` file, with normals if it has a `normals` array.
+pub enum NpzPointCloud {
+    Plain(PointCloud<PointXyzRgba>),
+    Normal(PointCloud<PointXyzRgbaNormal>),
+}
+
+fn rows_of_3<T>(values: Vec<T>) -> Array2<T> {
+    let n = values.len() / 3;
+    Array2::from_shape_vec((n, 3), values).expect("Expecting 3 values per point")
+}
+
+fn write_arrays(path: &Path, xyz: Vec<f32>, rgb: Vec<u8>, normals: Option<Vec<f32>>) -> Result<()> {
+    let mut npz = NpzWriter::new(File::create(path)?);
+    npz.add_array("xyz.npy", &rows_of_3(xyz))?;
+    npz.add_array("rgb.npy", &rows_of_3(rgb))?;
+    if let Some(normals) = normals {
+        npz.add_array("normals.npy", &rows_of_3(normals))?;
+    }
+    npz.finish()?;
+    Ok(())
+}
+
+/// Writes the `xyz` (f32, Nx3) and `rgb` (u8, Nx3) arrays of the cloud, loadable with `np.load`.
+pub fn write_npz(cloud: &PointCloud<PointXyzRgba>, path: &Path) -> Result<()> {
+    write_arrays(
+        path,
+        cloud.points.iter().flat_map(|p| p.xyz()).collect(),
+        cloud.points.iter().flat_map(|p| [p.r, p.g, p.b]).collect(),
+        None,
+    )
+}
+
+/// Writes the `xyz` (f32, Nx3), `rgb` (u8, Nx3) and `normals` (f32, Nx3) arrays of the cloud.
+pub fn write_npz_normal(cloud: &PointCloud<PointXyzRgbaNormal>, path: &Path) -> Result<()> {
+    write_arrays(
+        path,
+        cloud.points.iter().flat_map(|p| p.xyz()).collect(),
+        cloud.points.iter().flat_map(|p| [p.r, p.g, p.b]).collect(),
+        Some(cloud.points.iter().flat_map(|p| p.normal()).collect()),
+    )
+}
+
+fn check_shape<T>(name: &str, array: &Array2<T>, n: usize) -> Result<()> {
+    if array.dim() != (n, 3) {
+        return Err(NpzError::InvalidData(format!(
+            "Expecting {} to be {}x3, got {:?}",
+            name,
+            n,
+            array.dim()
+        )));
+    }
+    Ok(())
+}
+
+/// Reads a `.npz` file with an `xyz` (f32, Nx3) array, an optional `rgb` (u8, Nx3) array, black
+/// points without it, and an optional `normals` (f32, Nx3) array. Points are opaque.
+pub fn read_npz(path: &Path) -> Result<NpzPointCloud> {
+    let mut npz = NpzReader::new(File::open(path)?)?;
+    let names = npz.names()?;
+    // numpy names the arrays `<name>.npy`, other writers may leave out the extension
+    let entry = |name: &str| {
+        names
+            .iter()
+            .find(|n| *n == name || **n == format!("{}.npy", name))
+            .cloned()
+    };
+
+    let xyz: Array2<f32> = npz.by_name(&entry("xyz").unwrap_or_else(|| "xyz.npy".to_string()))?;
+    let n = xyz.nrows();
+    check_shape("xyz", &xyz, n)?;
+    let rgb: Array2<u8> = if let Some(rgb) = entry("rgb") {
+        npz.by_name(&rgb)?
+    } else {
+        Array2::zeros((n, 3))
+    };
+    check_shape("rgb", &rgb, n)?;
+
+    let point = |i: usize| {
+        PointXyzRgba::from_xyz_rgba(
+            [xyz[[i, 0]], xyz[[i, 1]], xyz[[i, 2]]],
+            [rgb[[i, 0]], rgb[[i, 1]], rgb[[i, 2]], 255],
+        )
+    };
+    let Some(normals) = entry("normals") else {
+        return Ok(NpzPointCloud::Plain(PointCloud::from_points(
+            (0..n).map(point).collect(),
+        )));
+    };
+
+    let normals: Array2<f32> = npz.by_name(&normals)?;
+    check_shape("normals", &normals, n)?;
+    let points = (0..n)
+        .map(|i| {
+            let p = point(i);
+            PointXyzRgbaNormal::from_xyz_rgba_normal(
+                p.xyz(),
+                p.rgba(),
+                [normals[[i, 0]], normals[[i, 1]], normals[[i, 2]]],
+            )
+        })
+        .collect();
+    Ok(NpzPointCloud::Normal(PointCloud::from_points(points)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_npz_round_trip() {
+        let cloud = PointCloud::from_points(vec![
+            PointXyzRgbaNormal::from_xyz_rgba_normal(
+                [1.0, 2.0, 3.0],
+                [10, 20, 30, 255],
+                [0.0, 0.0, 1.0],
+            ),
+            PointXyzRgbaNormal::from_xyz_rgba_normal(
+                [-1.0, 0.5, 0.0],
+                [40, 50, 60, 255],
+                [1.0, 0.0, 0.0],
+            ),
+        ]);
+        let path = std::env::temp_dir().join("vivotk_test_npz_round_trip.npz");
+        write_npz_normal(&cloud, &path).unwrap();
+        match read_npz(&path).unwrap() {
+            NpzPointCloud::Normal(read) => assert_eq!(read.points, cloud.points),
+            NpzPointCloud::Plain(_) => panic!("Expecting the normals to be read"),
+        }
+
+        let plain = PointCloud::from_points(
+            cloud
+                .points
+                .iter()
+                .map(|p| PointXyzRgba::from_xyz_rgba(p.xyz(), p.rgba()))
+                .collect(),
+        );
+        write_npz(&plain, &path).unwrap();
+        match read_npz(&path).unwrap() {
+            NpzPointCloud::Plain(read) => assert_eq!(read.points, plain.points),
+            NpzPointCloud::Normal(_) => panic!("Expecting no normals"),
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -9,3 +9,25 @@ pub struct PointXyzRgba {
     pub b: u8,
     pub a: u8,
 }
+
+impl PointXyzRgba {
+    pub fn xyz(&self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    pub fn rgba(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    pub fn from_xyz_rgba([x, y, z]: [f32; 3], [r, g, b, a]: [u8; 4]) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            r,
+            g,
+            b,
+            a,
+        }
+    }
+}
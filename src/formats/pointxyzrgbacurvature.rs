@@ -0,0 +1,17 @@
+/// A point with its principal curvatures `k1 >= k2`, and the mean and gaussian curvatures
+/// derived from them.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PointXyzRgbaCurvature {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+    pub k1: f32,
+    pub k2: f32,
+    pub mean_curvature: f32,
+    pub gaussian_curvature: f32,
+}
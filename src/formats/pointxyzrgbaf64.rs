@@ -0,0 +1,12 @@
+/// A point with double precision coordinates, for scanners exporting `x/y/z` as 64-bit doubles
+/// where large coordinates would be truncated by [`super::pointxyzrgba::PointXyzRgba`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PointXyzRgbaF64 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
@@ -0,0 +1,47 @@
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PointXyzRgbaNormal {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+    pub nx: f32,
+    pub ny: f32,
+    pub nz: f32,
+}
+
+impl PointXyzRgbaNormal {
+    pub fn xyz(&self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    pub fn rgba(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    pub fn normal(&self) -> [f32; 3] {
+        [self.nx, self.ny, self.nz]
+    }
+
+    pub fn from_xyz_rgba_normal(
+        [x, y, z]: [f32; 3],
+        [r, g, b, a]: [u8; 4],
+        [nx, ny, nz]: [f32; 3],
+    ) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            r,
+            g,
+            b,
+            a,
+            nx,
+            ny,
+            nz,
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use super::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// A point with the time it was measured at, e.g. the firing time of a LiDAR return within its
+/// sweep, in seconds.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PointXyzRgbaT {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+    pub timestamp: f64,
+}
+
+impl PointXyzRgbaT {
+    /// The point without its timestamp.
+    pub fn point(&self) -> PointXyzRgba {
+        PointXyzRgba::from_xyz_rgba([self.x, self.y, self.z], [self.r, self.g, self.b, self.a])
+    }
+}
+
+/// Keeps the points measured within `[min, max]` seconds, in their order.
+pub fn filter_by_timestamp(
+    cloud: &PointCloud<PointXyzRgbaT>,
+    min: f64,
+    max: f64,
+) -> PointCloud<PointXyzRgbaT> {
+    let points = cloud
+        .points
+        .iter()
+        .filter(|p| (min..=max).contains(&p.timestamp))
+        .copied()
+        .collect();
+    PointCloud::from_points(points)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_filter_by_timestamp() {
+        // a sweep of 0.1 seconds with a point every millisecond
+        let points = (0..=100)
+            .map(|i| PointXyzRgbaT {
+                x: i as f32,
+                y: 0.0,
+                z: 0.0,
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+                timestamp: i as f64 / 1000.0,
+            })
+            .collect();
+        let cloud = PointCloud::from_points(points);
+
+        let filtered = filter_by_timestamp(&cloud, 0.03, 0.07);
+        assert_eq!(filtered.len(), 41);
+        assert_eq!(filtered.points[0].x, 30.0);
+        assert_eq!(filtered.points[40].x, 70.0);
+        assert!(filtered
+            .points
+            .iter()
+            .all(|p| (0.03..=0.07).contains(&p.timestamp)));
+
+        assert_eq!(filter_by_timestamp(&cloud, 0.2, 0.3).len(), 0);
+    }
+}
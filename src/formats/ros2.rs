@@ -0,0 +1,222 @@
+use ros2_client::{
+    Context, MessageTypeName, Name, Node, NodeName, NodeOptions, Publisher, DEFAULT_PUBLISHER_QOS,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+use super::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+type Result<T> = std::result::Result<T, Ros2Error>;
+
+#[derive(Error, Debug)]
+pub enum Ros2Error {
+    /// Represents an error creating the node, topic or publisher.
+    #[error("Failed to create publisher: {0}")]
+    Create(String),
+
+    /// Represents an error publishing a message.
+    #[error("Failed to publish: {0}")]
+    Publish(String),
+}
+
+/// `sensor_msgs/msg/PointField` datatype of the coordinates and the packed color
+const FLOAT32: u8 = 7;
+
+/// `builtin_interfaces/msg/Time`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Time {
+    pub sec: i32,
+    pub nanosec: u32,
+}
+
+impl Time {
+    pub fn now() -> Self {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            sec: since_epoch.as_secs() as i32,
+            nanosec: since_epoch.subsec_nanos(),
+        }
+    }
+}
+
+/// `std_msgs/msg/Header`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Header {
+    pub stamp: Time,
+    pub frame_id: String,
+}
+
+/// `sensor_msgs/msg/PointField`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PointField {
+    pub name: String,
+    pub offset: u32,
+    pub datatype: u8,
+    pub count: u32,
+}
+
+/// `sensor_msgs/msg/PointCloud2`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PointCloud2 {
+    pub header: Header,
+    pub height: u32,
+    pub width: u32,
+    pub fields: Vec<PointField>,
+    pub is_bigendian: bool,
+    pub point_step: u32,
+    pub row_step: u32,
+    pub data: Vec<u8>,
+    pub is_dense: bool,
+}
+
+impl ros2_client::Message for PointCloud2 {}
+
+impl PointCloud2 {
+    /// An unorganized little endian cloud with the float32 fields `x`, `y`, `z` and `rgb`, the
+    /// color packed as `0xAARRGGBB` like PCL does.
+    pub fn from_point_cloud(cloud: &PointCloud<PointXyzRgba>, frame_id: &str, stamp: Time) -> Self {
+        let field = |name: &str, offset: u32| PointField {
+            name: name.to_string(),
+            offset,
+            datatype: FLOAT32,
+            count: 1,
+        };
+        let point_step = 16;
+        let data = cloud
+            .points
+            .iter()
+            .flat_map(|p| {
+                let rgba = u32::from_be_bytes([p.a, p.r, p.g, p.b]);
+                [
+                    p.x.to_le_bytes(),
+                    p.y.to_le_bytes(),
+                    p.z.to_le_bytes(),
+                    rgba.to_le_bytes(),
+                ]
+            })
+            .flatten()
+            .collect();
+        Self {
+            header: Header {
+                stamp,
+                frame_id: frame_id.to_string(),
+            },
+            height: 1,
+            width: cloud.points.len() as u32,
+            fields: vec![
+                field("x", 0),
+                field("y", 4),
+                field("z", 8),
+                field("rgb", 12),
+            ],
+            is_bigendian: false,
+            point_step,
+            row_step: point_step * cloud.points.len() as u32,
+            data,
+            is_dense: true,
+        }
+    }
+}
+
+/// Destination of the published messages, a ROS 2 topic or a mock in tests.
+pub trait PointCloud2Publisher {
+    fn publish(&mut self, message: PointCloud2) -> Result<()>;
+
+    /// Publishes the cloud stamped with the current time.
+    fn publish_cloud(&mut self, cloud: &PointCloud<PointXyzRgba>, frame_id: &str) -> Result<()> {
+        self.publish(PointCloud2::from_point_cloud(cloud, frame_id, Time::now()))
+    }
+}
+
+fn create_error<E: Debug>(error: E) -> Ros2Error {
+    Ros2Error::Create(format!("{:?}", error))
+}
+
+/// Publishes on a ROS 2 topic through DDS, discoverable by ROS 2 nodes in the same domain.
+pub struct TopicPublisher {
+    // the publisher is only discoverable while its node is alive
+    _node: Node,
+    publisher: Publisher<PointCloud2>,
+}
+
+impl TopicPublisher {
+    /// Creates the node `node_name` publishing on the absolute `topic`, e.g. `/vvtk/points`.
+    pub fn new(node_name: &str, topic: &str) -> Result<Self> {
+        let context = Context::new().map_err(create_error)?;
+        let mut node = context
+            .new_node(
+                NodeName::new("/", node_name).map_err(create_error)?,
+                NodeOptions::new(),
+            )
+            .map_err(create_error)?;
+        let topic = node
+            .create_topic(
+                &Name::parse(topic).map_err(create_error)?,
+                MessageTypeName::new("sensor_msgs", "PointCloud2"),
+                &DEFAULT_PUBLISHER_QOS,
+            )
+            .map_err(create_error)?;
+        let publisher = node
+            .create_publisher::<PointCloud2>(&topic, None)
+            .map_err(create_error)?;
+        Ok(Self {
+            _node: node,
+            publisher,
+        })
+    }
+}
+
+impl PointCloud2Publisher for TopicPublisher {
+    fn publish(&mut self, message: PointCloud2) -> Result<()> {
+        self.publisher
+            .publish(message)
+            .map_err(|e| Ros2Error::Publish(format!("{:?}", e)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct MockPublisher {
+        messages: Vec<PointCloud2>,
+    }
+
+    impl PointCloud2Publisher for MockPublisher {
+        fn publish(&mut self, message: PointCloud2) -> Result<()> {
+            self.messages.push(message);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_publish_cloud() {
+        let cloud = PointCloud::from_points(vec![
+            PointXyzRgba::from_xyz_rgba([1.0, 2.0, 3.0], [10, 20, 30, 255]),
+            PointXyzRgba::from_xyz_rgba([-1.0, 0.5, 0.0], [40, 50, 60, 255]),
+        ]);
+        let mut publisher = MockPublisher::default();
+        publisher.publish_cloud(&cloud, "camera").unwrap();
+        publisher.publish_cloud(&cloud, "camera").unwrap();
+
+        assert_eq!(publisher.messages.len(), 2);
+        let message = &publisher.messages[0];
+        assert_eq!(message.header.frame_id, "camera");
+        assert!(message.header.stamp.sec > 0);
+        assert_eq!((message.height, message.width), (1, 2));
+        assert_eq!((message.point_step, message.row_step), (16, 32));
+        let names: Vec<&str> = message.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["x", "y", "z", "rgb"]);
+
+        let second = &message.data[16..32];
+        let f =
+            |i: usize| f32::from_le_bytes([second[i], second[i + 1], second[i + 2], second[i + 3]]);
+        assert_eq!((f(0), f(4), f(8)), (-1.0, 0.5, 0.0));
+        assert_eq!(&second[12..16], &[60, 50, 40, 255]);
+    }
+}
@@ -0,0 +1,341 @@
+use ::rosbag::{ChunkRecord, IndexRecord, MessageRecord, RosBag};
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::path::Path;
+use thiserror::Error;
+
+use super::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+type Result<T> = std::result::Result<T, RosbagReadError>;
+
+#[derive(Error, Debug)]
+pub enum RosbagReadError {
+    /// Represents an error with the records of the bag file.
+    #[error("Invalid bag: {0}")]
+    InvalidBag(String),
+
+    /// Represents an error with the data of a `sensor_msgs/PointCloud2` message.
+    #[error("Invalid data: {0}")]
+    InvalidData(String),
+}
+
+fn invalid_bag<E: Debug>(error: E) -> RosbagReadError {
+    RosbagReadError::InvalidBag(format!("{:?}", error))
+}
+
+/// `sensor_msgs/PointField` datatypes
+const INT8: u8 = 1;
+const UINT8: u8 = 2;
+const INT16: u8 = 3;
+const UINT16: u8 = 4;
+const INT32: u8 = 5;
+const UINT32: u8 = 6;
+const FLOAT32: u8 = 7;
+const FLOAT64: u8 = 8;
+
+/// Reads the `sensor_msgs/PointCloud2` messages of `topic`, in the order they are stored, keeping
+/// the messages within `[start, end]` seconds of the first message of the topic.
+pub fn read_rosbag_point_clouds<P: AsRef<Path>>(
+    path: P,
+    topic: &str,
+    start: Option<f64>,
+    end: Option<f64>,
+) -> Result<Vec<PointCloud<PointXyzRgba>>> {
+    let bag = RosBag::new(path.as_ref()).map_err(invalid_bag)?;
+    let mut connections = HashSet::new();
+    for record in bag.index_records() {
+        if let IndexRecord::Connection(connection) = record.map_err(invalid_bag)? {
+            if connection.topic == topic {
+                connections.insert(connection.id);
+            }
+        }
+    }
+    if connections.is_empty() {
+        return Err(RosbagReadError::InvalidBag(format!(
+            "No topic {} in the bag",
+            topic
+        )));
+    }
+
+    // the first pass only finds the time of the first message, which the bounds are relative to
+    let mut first_time = None;
+    for_each_message(&bag, &connections, |time, _| {
+        first_time = Some(first_time.map_or(time, |t: u64| t.min(time)));
+        Ok(())
+    })?;
+    let first_time = match first_time {
+        Some(time) => time,
+        None => return Ok(vec![]),
+    };
+
+    let mut clouds = vec![];
+    for_each_message(&bag, &connections, |time, data| {
+        let seconds = (time - first_time) as f64 * 1e-9;
+        if start.map_or(true, |s| seconds >= s) && end.map_or(true, |e| seconds <= e) {
+            clouds.push(parse_point_cloud2(data)?);
+        }
+        Ok(())
+    })?;
+    Ok(clouds)
+}
+
+/// Calls `f` with the time in nanoseconds and the data of every message of the connections.
+fn for_each_message<F>(bag: &RosBag, connections: &HashSet<u32>, mut f: F) -> Result<()>
+where
+    F: FnMut(u64, &[u8]) -> Result<()>,
+{
+    for record in bag.chunk_records() {
+        if let ChunkRecord::Chunk(chunk) = record.map_err(invalid_bag)? {
+            for message in chunk.messages() {
+                if let MessageRecord::MessageData(message) = message.map_err(invalid_bag)? {
+                    if connections.contains(&message.conn_id) {
+                        f(message.time, message.data)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+struct PointField {
+    name: String,
+    offset: usize,
+    datatype: u8,
+}
+
+impl PointField {
+    fn size(&self) -> usize {
+        match self.datatype {
+            INT8 | UINT8 => 1,
+            INT16 | UINT16 => 2,
+            INT32 | UINT32 | FLOAT32 => 4,
+            _ => 8,
+        }
+    }
+
+    fn read(&self, point: &[u8]) -> f64 {
+        let b = &point[self.offset..self.offset + self.size()];
+        match self.datatype {
+            INT8 => b[0] as i8 as f64,
+            UINT8 => b[0] as f64,
+            INT16 => i16::from_le_bytes([b[0], b[1]]) as f64,
+            UINT16 => u16::from_le_bytes([b[0], b[1]]) as f64,
+            INT32 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            UINT32 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            FLOAT32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            _ => f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]),
+        }
+    }
+
+    /// The packed `0xAARRGGBB` color of the field, the bytes of a float32 `rgb` field.
+    fn read_packed(&self, point: &[u8]) -> u32 {
+        let b = &point[self.offset..self.offset + 4];
+        u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+    }
+}
+
+/// Reads the little-endian fields of a serialized message.
+struct MessageReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> MessageReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.position + n > self.data.len() {
+            return Err(RosbagReadError::InvalidData(
+                "PointCloud2 message is truncated".to_string(),
+            ));
+        }
+        let bytes = &self.data[self.position..self.position + n];
+        self.position += n;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+}
+
+/// Decodes a serialized `sensor_msgs/PointCloud2` message, reading the `x`, `y` and `z` fields
+/// and the packed `rgb` or `rgba` field if any. Points with a non finite coordinate are skipped,
+/// and points without color are black.
+pub fn parse_point_cloud2(data: &[u8]) -> Result<PointCloud<PointXyzRgba>> {
+    let mut reader = MessageReader { data, position: 0 };
+    // std_msgs/Header: seq, stamp and frame_id
+    reader.take(12)?;
+    reader.string()?;
+
+    let height = reader.u32()? as usize;
+    let width = reader.u32()? as usize;
+    let mut fields = vec![];
+    for _ in 0..reader.u32()? {
+        let name = reader.string()?;
+        let offset = reader.u32()? as usize;
+        let datatype = reader.u8()?;
+        let _count = reader.u32()?;
+        if !(INT8..=FLOAT64).contains(&datatype) {
+            return Err(RosbagReadError::InvalidData(format!(
+                "Unknown datatype {} of field {}",
+                datatype, name
+            )));
+        }
+        fields.push(PointField {
+            name,
+            offset,
+            datatype,
+        });
+    }
+    if reader.u8()? != 0 {
+        return Err(RosbagReadError::InvalidData(
+            "Big endian point clouds are not supported".to_string(),
+        ));
+    }
+    let point_step = reader.u32()? as usize;
+    let row_step = reader.u32()? as usize;
+    let data_len = reader.u32()? as usize;
+    let data = reader.take(data_len)?;
+
+    if let Some(field) = fields.iter().find(|f| f.offset + f.size() > point_step) {
+        return Err(RosbagReadError::InvalidData(format!(
+            "Field {} does not fit in a point of {} bytes",
+            field.name, point_step
+        )));
+    }
+    let field = |name: &str| fields.iter().find(|f| f.name == name);
+    let (x, y, z) = match (field("x"), field("y"), field("z")) {
+        (Some(x), Some(y), Some(z)) => (x, y, z),
+        _ => {
+            return Err(RosbagReadError::InvalidData(
+                "PointCloud2 message has no x, y and z fields".to_string(),
+            ))
+        }
+    };
+    let rgb = field("rgb").map(|f| (f, false));
+    let color = rgb.or_else(|| field("rgba").map(|f| (f, true)));
+    if color.map_or(false, |(f, _)| f.size() != 4) {
+        return Err(RosbagReadError::InvalidData(
+            "Expecting a packed color of 4 bytes".to_string(),
+        ));
+    }
+    if height * width > 0 && (height - 1) * row_step + width * point_step > data.len() {
+        return Err(RosbagReadError::InvalidData(format!(
+            "Expecting {}x{} points, got {} bytes",
+            height,
+            width,
+            data.len()
+        )));
+    }
+
+    let mut points = Vec::with_capacity(height * width);
+    for row in 0..height {
+        for col in 0..width {
+            let start = row * row_step + col * point_step;
+            let point = &data[start..start + point_step];
+            let (px, py, pz) = (x.read(point), y.read(point), z.read(point));
+            if !(px.is_finite() && py.is_finite() && pz.is_finite()) {
+                continue;
+            }
+            let rgba = match color {
+                Some((field, has_alpha)) => {
+                    let packed = field.read_packed(point);
+                    let a = if has_alpha { (packed >> 24) as u8 } else { 255 };
+                    [(packed >> 16) as u8, (packed >> 8) as u8, packed as u8, a]
+                }
+                None => [0, 0, 0, 255],
+            };
+            points.push(PointXyzRgba::from_xyz_rgba(
+                [px as f32, py as f32, pz as f32],
+                rgba,
+            ));
+        }
+    }
+    Ok(PointCloud::from_points(points))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn string(bytes: &mut Vec<u8>, s: &str) {
+        bytes.extend((s.len() as u32).to_le_bytes());
+        bytes.extend(s.as_bytes());
+    }
+
+    /// A 1xN PointCloud2 message of float32 x, y, z and packed rgb fields.
+    fn point_cloud2(points: &[([f32; 3], [u8; 3])], is_bigendian: u8) -> Vec<u8> {
+        let mut bytes = vec![0; 12];
+        string(&mut bytes, "velodyne");
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend((points.len() as u32).to_le_bytes());
+        bytes.extend(4u32.to_le_bytes());
+        for (i, name) in ["x", "y", "z", "rgb"].iter().enumerate() {
+            string(&mut bytes, name);
+            bytes.extend((4 * i as u32).to_le_bytes());
+            bytes.push(FLOAT32);
+            bytes.extend(1u32.to_le_bytes());
+        }
+        bytes.push(is_bigendian);
+        bytes.extend(16u32.to_le_bytes());
+        bytes.extend((16 * points.len() as u32).to_le_bytes());
+        bytes.extend((16 * points.len() as u32).to_le_bytes());
+        for ([x, y, z], [r, g, b]) in points {
+            for v in [x, y, z] {
+                bytes.extend(v.to_le_bytes());
+            }
+            bytes.extend([*b, *g, *r, 0]);
+        }
+        bytes.push(1);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_point_cloud2() {
+        let data = point_cloud2(
+            &[
+                ([1.0, 2.0, 3.0], [255, 0, 10]),
+                ([f32::NAN, 0.0, 0.0], [0, 0, 0]),
+                ([-1.5, 0.5, 0.0], [1, 2, 3]),
+            ],
+            0,
+        );
+        let cloud = parse_point_cloud2(&data).unwrap();
+        assert_eq!(
+            cloud.points,
+            vec![
+                PointXyzRgba {
+                    x: 1.0,
+                    y: 2.0,
+                    z: 3.0,
+                    r: 255,
+                    g: 0,
+                    b: 10,
+                    a: 255
+                },
+                PointXyzRgba {
+                    x: -1.5,
+                    y: 0.5,
+                    z: 0.0,
+                    r: 1,
+                    g: 2,
+                    b: 3,
+                    a: 255
+                }
+            ]
+        );
+
+        assert!(parse_point_cloud2(&point_cloud2(&[([0.0; 3], [0; 3])], 1)).is_err());
+        assert!(parse_point_cloud2(&data[..data.len() - 10]).is_err());
+    }
+}
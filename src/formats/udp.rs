@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use super::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::ply::read_ply_bytes;
+
+/// Every datagram starts with the little endian frame id (u32), the index of the fragment (u16)
+/// and the number of fragments of the frame (u16).
+pub const HEADER_LEN: usize = 8;
+
+/// Payload bytes per datagram, keeping datagrams below the usual ethernet MTU.
+pub const MAX_PAYLOAD_LEN: usize = 1400;
+
+/// Encoding of the reassembled payload of a frame.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UdpFormat {
+    /// A binary or ascii ply file
+    Ply,
+    /// Six little endian f32 per point: x, y, z, r, g, b with colors in [0, 255]
+    #[value(name = "raw_f32")]
+    RawF32,
+}
+
+impl UdpFormat {
+    /// Decodes the payload of a frame, returning `None` if it is malformed.
+    pub fn decode(&self, payload: &[u8]) -> Option<PointCloud<PointXyzRgba>> {
+        match self {
+            UdpFormat::Ply => read_ply_bytes(payload),
+            UdpFormat::RawF32 => decode_raw_f32(payload),
+        }
+    }
+}
+
+pub fn encode_raw_f32(cloud: &PointCloud<PointXyzRgba>) -> Vec<u8> {
+    cloud
+        .points
+        .iter()
+        .flat_map(|p| [p.x, p.y, p.z, p.r as f32, p.g as f32, p.b as f32])
+        .flat_map(f32::to_le_bytes)
+        .collect()
+}
+
+pub fn decode_raw_f32(payload: &[u8]) -> Option<PointCloud<PointXyzRgba>> {
+    if payload.len() % 24 != 0 {
+        return None;
+    }
+    let points = payload
+        .chunks_exact(24)
+        .map(|point| {
+            let mut values = point
+                .chunks_exact(4)
+                .map(|v| f32::from_le_bytes([v[0], v[1], v[2], v[3]]));
+            let mut next = || values.next().unwrap();
+            PointXyzRgba {
+                x: next(),
+                y: next(),
+                z: next(),
+                r: next() as u8,
+                g: next() as u8,
+                b: next() as u8,
+                a: 255,
+            }
+        })
+        .collect();
+    Some(PointCloud::from_points(points))
+}
+
+/// Splits the payload of a frame into datagrams of at most `HEADER_LEN + MAX_PAYLOAD_LEN` bytes.
+pub fn fragment(frame_id: u32, payload: &[u8]) -> Vec<Vec<u8>> {
+    let chunks: Vec<&[u8]> = if payload.is_empty() {
+        vec![payload]
+    } else {
+        payload.chunks(MAX_PAYLOAD_LEN).collect()
+    };
+    assert!(
+        chunks.len() <= u16::MAX as usize,
+        "Frame is too large to be sent over udp"
+    );
+    let fragment_count = chunks.len() as u16;
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut datagram = Vec::with_capacity(HEADER_LEN + chunk.len());
+            datagram.extend_from_slice(&frame_id.to_le_bytes());
+            datagram.extend_from_slice(&(i as u16).to_le_bytes());
+            datagram.extend_from_slice(&fragment_count.to_le_bytes());
+            datagram.extend_from_slice(chunk);
+            datagram
+        })
+        .collect()
+}
+
+struct PartialFrame {
+    fragments: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+/// Reassembles frames from datagrams arriving in any order.
+/// Once a frame is complete, the incomplete frames with a lower id are dropped and their late
+/// datagrams are ignored.
+#[derive(Default)]
+pub struct FrameAssembler {
+    frames: HashMap<u32, PartialFrame>,
+    last_completed: Option<u32>,
+}
+
+impl FrameAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a datagram, returning the frame id and payload if it completes a frame.
+    /// Malformed datagrams are ignored.
+    pub fn push(&mut self, datagram: &[u8]) -> Option<(u32, Vec<u8>)> {
+        if datagram.len() < HEADER_LEN {
+            return None;
+        }
+        let frame_id = u32::from_le_bytes([datagram[0], datagram[1], datagram[2], datagram[3]]);
+        let index = u16::from_le_bytes([datagram[4], datagram[5]]) as usize;
+        let count = u16::from_le_bytes([datagram[6], datagram[7]]) as usize;
+        if index >= count || self.last_completed.map_or(false, |last| frame_id <= last) {
+            return None;
+        }
+
+        let frame = self.frames.entry(frame_id).or_insert_with(|| PartialFrame {
+            fragments: vec![None; count],
+            received: 0,
+        });
+        if frame.fragments.len() != count {
+            return None;
+        }
+        if frame.fragments[index].is_none() {
+            frame.fragments[index] = Some(datagram[HEADER_LEN..].to_vec());
+            frame.received += 1;
+        }
+        if frame.received < count {
+            return None;
+        }
+
+        let frame = self.frames.remove(&frame_id)?;
+        self.frames.retain(|&id, _| id > frame_id);
+        self.last_completed = Some(frame_id);
+        Some((
+            frame_id,
+            frame.fragments.into_iter().flatten().flatten().collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ply::write_ply_with_alpha;
+    use std::net::UdpSocket;
+    use std::time::Duration;
+
+    fn cloud(n: usize) -> PointCloud<PointXyzRgba> {
+        let points = (0..n)
+            .map(|i| PointXyzRgba {
+                x: i as f32,
+                y: 0.5 * i as f32,
+                z: -(i as f32),
+                r: (i % 256) as u8,
+                g: 20,
+                b: 30,
+                a: 255,
+            })
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_loopback() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let address = receiver.local_addr().unwrap();
+
+        // 200 raw points span several datagrams, which are sent in reverse order
+        let raw = cloud(200);
+        let ply_path = std::env::temp_dir().join("vivotk_test_udp_loopback.ply");
+        write_ply_with_alpha(&cloud(50), &ply_path).unwrap();
+        let ply = std::fs::read(&ply_path).unwrap();
+        std::fs::remove_file(&ply_path).unwrap();
+
+        let mut datagrams = fragment(0, &encode_raw_f32(&raw));
+        assert!(datagrams.len() > 1);
+        datagrams.reverse();
+        datagrams.extend(fragment(1, &ply));
+        let sender = std::thread::spawn(move || {
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            for datagram in datagrams {
+                socket.send_to(&datagram, address).unwrap();
+            }
+        });
+
+        let mut assembler = FrameAssembler::new();
+        let mut frames = vec![];
+        let mut buf = [0u8; HEADER_LEN + MAX_PAYLOAD_LEN];
+        while frames.len() < 2 {
+            let (len, _) = receiver.recv_from(&mut buf).unwrap();
+            frames.extend(assembler.push(&buf[..len]));
+        }
+        sender.join().unwrap();
+
+        assert_eq!(frames[0].0, 0);
+        assert_eq!(
+            UdpFormat::RawF32.decode(&frames[0].1).unwrap().points,
+            raw.points
+        );
+        assert_eq!(frames[1].0, 1);
+        assert_eq!(
+            UdpFormat::Ply.decode(&frames[1].1).unwrap().points,
+            cloud(50).points
+        );
+    }
+
+    #[test]
+    fn test_drops_stale_frames() {
+        let payload = vec![7u8; MAX_PAYLOAD_LEN + 1];
+        let first = fragment(1, &payload);
+        let second = fragment(2, &payload);
+
+        let mut assembler = FrameAssembler::new();
+        assert_eq!(assembler.push(&first[0]), None);
+        assert_eq!(assembler.push(&second[0]), None);
+        assert_eq!(assembler.push(&second[1]), Some((2, payload)));
+        // frame 1 is older than the last complete frame
+        assert_eq!(assembler.push(&first[1]), None);
+        assert!(assembler.frames.is_empty());
+        assert_eq!(assembler.push(&[0u8; 4]), None);
+    }
+}
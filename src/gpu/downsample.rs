@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+
+use wgpu::util::DeviceExt;
+
+use super::{workgroups, GpuContext, WORKGROUP_SIZE};
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::methods::sampling::VoxelGrid;
+
+const ENTRY_POINTS: [&str; 8] = [
+    "voxel_keys",
+    "split_flags",
+    "scan_blocks",
+    "add_block_sums",
+    "split_scatter",
+    "segment_heads",
+    "segment_starts",
+    "voxel_centroids",
+];
+
+/// The kernels of `downsample.wgsl`, each dispatched in its own compute pass so that every
+/// dispatch sees the writes of the previous ones.
+struct Kernels<'a> {
+    context: &'a GpuContext,
+    pipelines: HashMap<&'static str, wgpu::ComputePipeline>,
+    /// block sums of every level of a scan of `n` elements, from the finest
+    scan_levels: Vec<(wgpu::Buffer, u32)>,
+}
+
+impl<'a> Kernels<'a> {
+    fn new(context: &'a GpuContext, n: u32) -> Self {
+        let device = &context.device;
+        let shader = device.create_shader_module(wgpu::include_wgsl!("downsample.wgsl"));
+        let pipelines = ENTRY_POINTS
+            .iter()
+            .map(|&entry_point| {
+                let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some(entry_point),
+                    layout: None,
+                    module: &shader,
+                    entry_point,
+                });
+                (entry_point, pipeline)
+            })
+            .collect();
+
+        let mut scan_levels = vec![];
+        let mut count = n;
+        loop {
+            let blocks = (count + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+            scan_levels.push((storage_buffer(context, "Block Sums Buffer", blocks), blocks));
+            if blocks == 1 {
+                break;
+            }
+            count = blocks;
+        }
+        Self {
+            context,
+            pipelines,
+            scan_levels,
+        }
+    }
+
+    /// Runs `n` invocations of the entry point, with the parameters at binding 0 and the buffers
+    /// at the given bindings.
+    fn dispatch(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        entry_point: &str,
+        n: u32,
+        bit: u32,
+        buffers: &[(u32, &wgpu::Buffer)],
+    ) {
+        let device = &self.context.device;
+        let (groups_x, groups_y) = workgroups(n);
+        let params = [n, bit, groups_x * WORKGROUP_SIZE, 0];
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Params Buffer"),
+            contents: bytemuck::cast_slice(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let entries: Vec<wgpu::BindGroupEntry> = std::iter::once((0, &params_buffer))
+            .chain(buffers.iter().copied())
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        let pipeline = &self.pipelines[entry_point];
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(entry_point),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &entries,
+        });
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some(entry_point),
+        });
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(groups_x, groups_y, 1);
+    }
+
+    /// Replaces the `n` values of the buffer by their exclusive prefix sums: every block of
+    /// `WORKGROUP_SIZE` values is scanned on its own, the block totals are scanned the same way
+    /// one level up, and the scanned totals are added back to the blocks.
+    fn scan(&self, encoder: &mut wgpu::CommandEncoder, data: &wgpu::Buffer, n: u32) {
+        let mut level_data = data;
+        let mut count = n;
+        let mut levels = vec![];
+        for (sums, blocks) in &self.scan_levels {
+            self.dispatch(
+                encoder,
+                "scan_blocks",
+                count,
+                0,
+                &[(7, level_data), (8, sums)],
+            );
+            levels.push((level_data, sums, count));
+            if *blocks == 1 {
+                break;
+            }
+            level_data = sums;
+            count = *blocks;
+        }
+        // the block sums of the coarsest level are a single block, already complete
+        for (data, sums, count) in levels.into_iter().rev().skip(1) {
+            self.dispatch(encoder, "add_block_sums", count, 0, &[(7, data), (8, sums)]);
+        }
+    }
+}
+
+fn storage_buffer(context: &GpuContext, label: &str, len: u32) -> wgpu::Buffer {
+    context.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: len as u64 * 4,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    })
+}
+
+/// Voxel grid downsampling on the GPU, the same as
+/// [`voxel_centroid_sample`](crate::methods::sampling::voxel_centroid_sample): the points of
+/// every voxel of side `voxel_size` holding points are replaced by their mean position and mean
+/// color, ordered by voxel index.
+///
+/// Every point computes the index of its voxel, the points are sorted by voxel index with a radix
+/// sort of one bit per pass, each pass a stable split built on a parallel prefix sum, and a prefix
+/// sum over the first point of every run of equal indices gives the start of every voxel. One
+/// invocation per voxel then averages its points.
+pub fn voxel_downsample_gpu(
+    context: &GpuContext,
+    cloud: &PointCloud<PointXyzRgba>,
+    voxel_size: f32,
+) -> PointCloud<PointXyzRgba> {
+    if voxel_size <= 0.0 || cloud.points.is_empty() {
+        return cloud.clone();
+    }
+    let grid = VoxelGrid::covering(cloud, voxel_size)
+        .expect("Voxel size is too small for the extent of the point cloud");
+    let device = &context.device;
+    let n = cloud.points.len() as u32;
+    // bits of the largest voxel index
+    let bits = 64 - (grid.num_voxels() - 1).leading_zeros();
+
+    let grid_params = [
+        grid.origin[0].to_bits(),
+        grid.origin[1].to_bits(),
+        grid.origin[2].to_bits(),
+        grid.size.to_bits(),
+        grid.dims[0],
+        grid.dims[1],
+        grid.dims[2],
+        0,
+    ];
+    let grid_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Grid Buffer"),
+        contents: bytemuck::cast_slice(&grid_params),
+        usage: wgpu::BufferUsages::UNIFORM,
+    });
+    let points: Vec<[f32; 4]> = cloud.points.iter().map(|p| [p.x, p.y, p.z, 0.0]).collect();
+    let colors: Vec<u32> = cloud
+        .points
+        .iter()
+        .map(|p| u32::from_le_bytes(p.rgba()))
+        .collect();
+    let input = |label: &str, contents: &[u8]| {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents,
+            usage: wgpu::BufferUsages::STORAGE,
+        })
+    };
+    let points_buffer = input("Points Buffer", bytemuck::cast_slice(&points));
+    let colors_buffer = input("Colors Buffer", bytemuck::cast_slice(&colors));
+    let mut keys = storage_buffer(context, "Keys Buffer", n);
+    let mut values = storage_buffer(context, "Values Buffer", n);
+    let mut keys_out = storage_buffer(context, "Keys Buffer", n);
+    let mut values_out = storage_buffer(context, "Values Buffer", n);
+    let scanned = storage_buffer(context, "Scan Buffer", n);
+    let starts = storage_buffer(context, "Starts Buffer", n + 1);
+    let voxel_count = storage_buffer(context, "Voxel Count Buffer", 1);
+    let centroids = storage_buffer(context, "Centroids Buffer", 4 * n);
+    let centroid_colors = storage_buffer(context, "Centroid Colors Buffer", 4 * n);
+
+    let kernels = Kernels::new(context, n);
+    let mut encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    kernels.dispatch(
+        &mut encoder,
+        "voxel_keys",
+        n,
+        0,
+        &[
+            (1, &grid_buffer),
+            (2, &points_buffer),
+            (3, &keys),
+            (4, &values),
+        ],
+    );
+    for bit in 0..bits {
+        kernels.dispatch(
+            &mut encoder,
+            "split_flags",
+            n,
+            bit,
+            &[(3, &keys), (7, &scanned)],
+        );
+        kernels.scan(&mut encoder, &scanned, n);
+        kernels.dispatch(
+            &mut encoder,
+            "split_scatter",
+            n,
+            bit,
+            &[
+                (3, &keys),
+                (4, &values),
+                (5, &keys_out),
+                (6, &values_out),
+                (7, &scanned),
+            ],
+        );
+        std::mem::swap(&mut keys, &mut keys_out);
+        std::mem::swap(&mut values, &mut values_out);
+    }
+    kernels.dispatch(
+        &mut encoder,
+        "segment_heads",
+        n,
+        0,
+        &[(3, &keys), (7, &scanned)],
+    );
+    kernels.scan(&mut encoder, &scanned, n);
+    kernels.dispatch(
+        &mut encoder,
+        "segment_starts",
+        n,
+        0,
+        &[(3, &keys), (7, &scanned), (10, &starts), (11, &voxel_count)],
+    );
+    kernels.dispatch(
+        &mut encoder,
+        "voxel_centroids",
+        n,
+        0,
+        &[
+            (2, &points_buffer),
+            (4, &values),
+            (9, &colors_buffer),
+            (10, &starts),
+            (11, &voxel_count),
+            (12, &centroids),
+            (13, &centroid_colors),
+        ],
+    );
+    context.queue.submit(Some(encoder.finish()));
+
+    let count = context.read_buffer::<u32>(&voxel_count, 4)[0] as u64;
+    let size = count * std::mem::size_of::<[f32; 4]>() as u64;
+    let positions = context.read_buffer::<[f32; 4]>(&centroids, size);
+    let colors = context.read_buffer::<[f32; 4]>(&centroid_colors, size);
+    let points = positions
+        .into_iter()
+        .zip(colors)
+        .map(|([x, y, z, _], rgba)| {
+            PointXyzRgba::from_xyz_rgba([x, y, z], rgba.map(|c| c.round() as u8))
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::methods::sampling::voxel_centroid_sample;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_voxel_downsample_gpu() {
+        let context = match GpuContext::new() {
+            Some(context) => context,
+            None => {
+                println!("Skipping, no GPU adapter");
+                return;
+            }
+        };
+        // enough points for several levels of block sums, away from the faces of the voxels so
+        // that the CPU and the GPU agree on the voxel of every point
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut coordinate = || (rng.gen_range(0..20) as f32 + rng.gen_range(0.1..0.9)) * 0.05;
+        let xyz: Vec<[f32; 3]> = (0..300_000)
+            .map(|_| [coordinate(), coordinate(), coordinate()])
+            .collect();
+        let points = xyz
+            .into_iter()
+            .enumerate()
+            .map(|(i, xyz)| PointXyzRgba::from_xyz_rgba(xyz, [(i % 256) as u8, 20, 30, 255]))
+            .collect();
+        let cloud = PointCloud::from_points(points);
+
+        let expected = voxel_centroid_sample(&cloud, 0.05);
+        let downsampled = voxel_downsample_gpu(&context, &cloud, 0.05);
+        assert_eq!(downsampled.len(), expected.len());
+        for (p, q) in downsampled.points.iter().zip(&expected.points) {
+            for (a, b) in p.xyz().iter().zip(q.xyz()) {
+                assert!((a - b).abs() < 1e-4, "{:?} {:?}", p, q);
+            }
+            for (a, b) in p.rgba().iter().zip(q.rgba()) {
+                assert!((*a as i32 - b as i32).abs() <= 1, "{:?} {:?}", p, q);
+            }
+        }
+    }
+}
@@ -0,0 +1,189 @@
+// Voxel grid downsampling: the points are sorted by the index of their voxel with a radix sort of
+// one bit per pass, both the sort and the segmentation into voxels built on parallel prefix sums,
+// then every voxel averages its run of sorted points.
+
+struct Params {
+    n: u32,
+    // bit of the keys the radix pass splits on
+    bit: u32,
+    // invocations along x, see `workgroups`
+    stride: u32,
+    _padding: u32,
+}
+
+struct Grid {
+    // xyz of the corner of the grid, w the side of a voxel
+    origin: vec4<f32>,
+    // voxels along x, y and z, w unused
+    dims: vec4<u32>,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<uniform> grid: Grid;
+// xyz of every point, w unused
+@group(0) @binding(2) var<storage, read> points: array<vec4<f32>>;
+// voxel index and point index of every point, in sorted order once the sort is done
+@group(0) @binding(3) var<storage, read_write> keys: array<u32>;
+@group(0) @binding(4) var<storage, read_write> values: array<u32>;
+// destination of a radix pass, swapped with keys and values after every pass
+@group(0) @binding(5) var<storage, read_write> keys_out: array<u32>;
+@group(0) @binding(6) var<storage, read_write> values_out: array<u32>;
+// flags scanned in place into their exclusive prefix sums
+@group(0) @binding(7) var<storage, read_write> scanned: array<u32>;
+// total of every block of the scan
+@group(0) @binding(8) var<storage, read_write> block_sums: array<u32>;
+// rgba of every point packed as 0xAABBGGRR
+@group(0) @binding(9) var<storage, read> colors: array<u32>;
+// first sorted point of every voxel, followed by the number of points
+@group(0) @binding(10) var<storage, read_write> starts: array<u32>;
+@group(0) @binding(11) var<storage, read_write> voxel_count: array<u32>;
+@group(0) @binding(12) var<storage, read_write> centroids: array<vec4<f32>>;
+// mean rgba of every voxel in [0, 255]
+@group(0) @binding(13) var<storage, read_write> centroid_colors: array<vec4<f32>>;
+
+// elements scanned by a workgroup, the workgroup size
+let BLOCK: u32 = 64u;
+
+var<workgroup> partial: array<u32, 64>;
+
+fn invocation(id: vec3<u32>) -> u32 {
+    return id.x + id.y * params.stride;
+}
+
+fn key_bit(key: u32) -> u32 {
+    return (key >> params.bit) & 1u;
+}
+
+@compute @workgroup_size(64)
+fn voxel_keys(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = invocation(id);
+    if (i >= params.n) {
+        return;
+    }
+    let offset = max((points[i].xyz - grid.origin.xyz) / grid.origin.w, vec3<f32>(0.0, 0.0, 0.0));
+    let cell = min(vec3<u32>(floor(offset)), grid.dims.xyz - vec3<u32>(1u, 1u, 1u));
+    keys[i] = cell.x + grid.dims.x * (cell.y + grid.dims.y * cell.z);
+    values[i] = i;
+}
+
+// flags the keys whose bit is 0, which go first
+@compute @workgroup_size(64)
+fn split_flags(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = invocation(id);
+    if (i >= params.n) {
+        return;
+    }
+    scanned[i] = 1u - key_bit(keys[i]);
+}
+
+// exclusive scan of every block of BLOCK elements, Hillis-Steele in workgroup memory
+@compute @workgroup_size(64)
+fn scan_blocks(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(local_invocation_index) lane: u32,
+) {
+    let i = invocation(id);
+    var value = 0u;
+    if (i < params.n) {
+        value = scanned[i];
+    }
+    partial[lane] = value;
+    workgroupBarrier();
+    for (var offset = 1u; offset < BLOCK; offset = offset * 2u) {
+        var previous = 0u;
+        if (lane >= offset) {
+            previous = partial[lane - offset];
+        }
+        workgroupBarrier();
+        partial[lane] = partial[lane] + previous;
+        workgroupBarrier();
+    }
+    if (i < params.n) {
+        scanned[i] = partial[lane] - value;
+    }
+    // the padding workgroups of the dispatch have no block
+    if (lane == BLOCK - 1u && i - lane < params.n) {
+        block_sums[i / BLOCK] = partial[lane];
+    }
+}
+
+// adds the scanned total of the previous blocks to every element
+@compute @workgroup_size(64)
+fn add_block_sums(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = invocation(id);
+    if (i >= params.n) {
+        return;
+    }
+    scanned[i] = scanned[i] + block_sums[i / BLOCK];
+}
+
+// stable split on the bit: the keys with a 0 keep their order at the front, the others after them
+@compute @workgroup_size(64)
+fn split_scatter(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = invocation(id);
+    if (i >= params.n) {
+        return;
+    }
+    let last = params.n - 1u;
+    let zeros = scanned[last] + 1u - key_bit(keys[last]);
+    let key = keys[i];
+    var destination = scanned[i];
+    if (key_bit(key) == 1u) {
+        destination = zeros + i - scanned[i];
+    }
+    keys_out[destination] = key;
+    values_out[destination] = values[i];
+}
+
+fn is_head(i: u32) -> bool {
+    return i == 0u || keys[i] != keys[i - 1u];
+}
+
+// flags the first point of every voxel
+@compute @workgroup_size(64)
+fn segment_heads(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = invocation(id);
+    if (i >= params.n) {
+        return;
+    }
+    scanned[i] = select(0u, 1u, is_head(i));
+}
+
+// the scanned heads are the voxel of every sorted point
+@compute @workgroup_size(64)
+fn segment_starts(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = invocation(id);
+    if (i >= params.n) {
+        return;
+    }
+    let head = is_head(i);
+    if (head) {
+        starts[scanned[i]] = i;
+    }
+    if (i == params.n - 1u) {
+        let voxels = scanned[i] + select(0u, 1u, head);
+        starts[voxels] = params.n;
+        voxel_count[0] = voxels;
+    }
+}
+
+// one invocation per voxel
+@compute @workgroup_size(64)
+fn voxel_centroids(@builtin(global_invocation_id) id: vec3<u32>) {
+    let v = invocation(id);
+    if (v >= voxel_count[0]) {
+        return;
+    }
+    let start = starts[v];
+    let end = starts[v + 1u];
+    var position = vec3<f32>(0.0, 0.0, 0.0);
+    var color = vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    for (var j = start; j < end; j = j + 1u) {
+        let p = values[j];
+        position = position + points[p].xyz;
+        color = color + unpack4x8unorm(colors[p]);
+    }
+    let count = f32(end - start);
+    centroids[v] = vec4<f32>(position / count, 0.0);
+    centroid_colors[v] = color * 255.0 / count;
+}
@@ -0,0 +1,60 @@
+//! Compute shaders run on the GPU with wgpu, built with the `gpu` feature.
+
+pub mod downsample;
+pub mod normals;
+
+/// A headless GPU device and its queue for compute shaders, created once and shared by the
+/// kernels.
+pub struct GpuContext {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+}
+
+impl GpuContext {
+    /// Requests the default adapter, returning `None` if there is no GPU.
+    pub fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }))?;
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&Default::default(), None)).ok()?;
+        Some(Self { device, queue })
+    }
+
+    /// Copies the storage buffer to a mappable buffer and reads it back once the queue is done.
+    pub fn read_buffer<T: bytemuck::Pod>(&self, buffer: &wgpu::Buffer, size: u64) -> Vec<T> {
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Staging Buffer"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::Maintain::Wait);
+        let values = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        staging.unmap();
+        values
+    }
+}
+
+/// Number of invocations of every workgroup, the `@workgroup_size` of the shaders.
+pub(crate) const WORKGROUP_SIZE: u32 = 64;
+
+/// Workgroups covering `n` invocations, spread over y when there are more than the 65535
+/// workgroups allowed along x. The shaders recover the invocation index as
+/// `id.x + id.y * groups_x * WORKGROUP_SIZE`.
+pub(crate) fn workgroups(n: u32) -> (u32, u32) {
+    let groups = (n + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+    let groups_x = groups.clamp(1, 65535);
+    (groups_x, (groups + groups_x - 1) / groups_x)
+}
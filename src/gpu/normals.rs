@@ -0,0 +1,146 @@
+use kiddo::{distance::squared_euclidean, KdTree};
+use wgpu::util::DeviceExt;
+
+use super::{workgroups, GpuContext, WORKGROUP_SIZE};
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Indices of the `k` nearest neighbours of every point, the point itself included, flattened.
+fn nearest_neighbour_indices(cloud: &PointCloud<PointXyzRgba>, k: usize) -> Vec<u32> {
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        tree.add(&p.xyz(), i)
+            .expect("Should be able to add point to kd tree");
+    }
+    cloud
+        .points
+        .iter()
+        .flat_map(|p| {
+            tree.nearest(&p.xyz(), k, &squared_euclidean)
+                .expect("Failed to query tree")
+                .into_iter()
+                .map(|(_, &i)| i as u32)
+        })
+        .collect()
+}
+
+/// Estimates the normal of every point on the GPU from its `k` nearest neighbours, one
+/// invocation per point. The neighbours are found on the CPU with a kd tree, and every
+/// invocation fits the covariance matrix of its neighbours and takes the eigenvector of the
+/// smallest eigenvalue. The normals are unit length with an arbitrary sign.
+pub fn compute_normals_gpu(
+    context: &GpuContext,
+    cloud: &PointCloud<PointXyzRgba>,
+    k: usize,
+) -> Vec<[f32; 3]> {
+    let n = cloud.points.len();
+    if n == 0 {
+        return vec![];
+    }
+    let k = k.clamp(1, n);
+    let device = &context.device;
+
+    let points: Vec<[f32; 4]> = cloud.points.iter().map(|p| [p.x, p.y, p.z, 0.0]).collect();
+    let neighbours = nearest_neighbour_indices(cloud, k);
+    let (groups_x, groups_y) = workgroups(n as u32);
+    let params = [n as u32, k as u32, groups_x * WORKGROUP_SIZE, 0];
+
+    let storage = |label: &str, contents: &[u8]| {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents,
+            usage: wgpu::BufferUsages::STORAGE,
+        })
+    };
+    let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Params Buffer"),
+        contents: bytemuck::cast_slice(&params),
+        usage: wgpu::BufferUsages::UNIFORM,
+    });
+    let points_buffer = storage("Points Buffer", bytemuck::cast_slice(&points));
+    let neighbours_buffer = storage("Neighbours Buffer", bytemuck::cast_slice(&neighbours));
+    let normals_size = (n * std::mem::size_of::<[f32; 4]>()) as u64;
+    let normals_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Normals Buffer"),
+        size: normals_size,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+
+    let shader = device.create_shader_module(wgpu::include_wgsl!("normals.wgsl"));
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Normals Pipeline"),
+        layout: None,
+        module: &shader,
+        entry_point: "main",
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Normals Bind Group"),
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: points_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: neighbours_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: normals_buffer.as_entire_binding(),
+            },
+        ],
+    });
+
+    let mut encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
+        pass.set_pipeline(&pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(groups_x, groups_y, 1);
+    }
+    context.queue.submit(Some(encoder.finish()));
+
+    context
+        .read_buffer::<[f32; 4]>(&normals_buffer, normals_size)
+        .into_iter()
+        .map(|[x, y, z, _]| [x, y, z])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compute_normals_gpu() {
+        let context = match GpuContext::new() {
+            Some(context) => context,
+            None => {
+                println!("Skipping, no GPU adapter");
+                return;
+            }
+        };
+        // 20x20 grid on the plane x + y + z = 1, seen along the normal (1, 1, 1) / sqrt(3)
+        let points = (0..400)
+            .map(|i| {
+                let (u, v) = ((i / 20) as f32 * 0.05, (i % 20) as f32 * 0.05);
+                PointXyzRgba::from_xyz_rgba([u, v, 1.0 - u - v], [0, 0, 0, 255])
+            })
+            .collect();
+        let cloud = PointCloud::from_points(points);
+
+        let normals = compute_normals_gpu(&context, &cloud, 8);
+        assert_eq!(normals.len(), 400);
+        let expected = 1.0 / 3f32.sqrt();
+        for n in normals {
+            let along = (n[0] + n[1] + n[2]) * expected;
+            assert!((along.abs() - 1.0).abs() < 1e-3, "{:?}", n);
+        }
+    }
+}
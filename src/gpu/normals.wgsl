@@ -0,0 +1,97 @@
+// Normal of every point from the principal component analysis of its neighbours: the
+// eigenvector of the smallest eigenvalue of their covariance, found with Jacobi rotations.
+
+struct Params {
+    num_points: u32,
+    k: u32,
+    // invocations along x, see `workgroups`
+    stride: u32,
+    _padding: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+// xyz of every point, w unused
+@group(0) @binding(1) var<storage, read> points: array<vec4<f32>>;
+// the k nearest neighbours of every point, point i at [i * k, (i + 1) * k)
+@group(0) @binding(2) var<storage, read> neighbours: array<u32>;
+@group(0) @binding(3) var<storage, read_write> normals: array<vec4<f32>>;
+
+// cyclic sweeps over the off diagonal entries, enough to converge for 3x3 matrices
+let SWEEPS: u32 = 4u;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x + id.y * params.stride;
+    if (i >= params.num_points) {
+        return;
+    }
+
+    let first = i * params.k;
+    var mean = vec3<f32>(0.0, 0.0, 0.0);
+    for (var n = 0u; n < params.k; n = n + 1u) {
+        mean = mean + points[neighbours[first + n]].xyz;
+    }
+    mean = mean / f32(params.k);
+
+    // zero initialized
+    var a: array<array<f32, 3>, 3>;
+    for (var n = 0u; n < params.k; n = n + 1u) {
+        let d = points[neighbours[first + n]].xyz - mean;
+        for (var r = 0u; r < 3u; r = r + 1u) {
+            for (var c = 0u; c < 3u; c = c + 1u) {
+                a[r][c] = a[r][c] + d[r] * d[c];
+            }
+        }
+    }
+
+    // the columns of v converge to the eigenvectors
+    var v: array<array<f32, 3>, 3>;
+    for (var r = 0u; r < 3u; r = r + 1u) {
+        for (var c = 0u; c < 3u; c = c + 1u) {
+            v[r][c] = select(0.0, 1.0, r == c);
+        }
+    }
+    var ps = array<u32, 3>(0u, 0u, 1u);
+    var qs = array<u32, 3>(1u, 2u, 2u);
+    for (var sweep = 0u; sweep < SWEEPS; sweep = sweep + 1u) {
+        for (var pair = 0u; pair < 3u; pair = pair + 1u) {
+            let p = ps[pair];
+            let q = qs[pair];
+            let apq = a[p][q];
+            if (abs(apq) < 1e-12) {
+                continue;
+            }
+            // rotation zeroing a[p][q]
+            let theta = (a[q][q] - a[p][p]) / (2.0 * apq);
+            let t = select(-1.0, 1.0, theta >= 0.0) / (abs(theta) + sqrt(theta * theta + 1.0));
+            let c = 1.0 / sqrt(t * t + 1.0);
+            let s = t * c;
+            for (var k = 0u; k < 3u; k = k + 1u) {
+                let akp = a[k][p];
+                let akq = a[k][q];
+                a[k][p] = c * akp - s * akq;
+                a[k][q] = s * akp + c * akq;
+            }
+            for (var k = 0u; k < 3u; k = k + 1u) {
+                let apk = a[p][k];
+                let aqk = a[q][k];
+                a[p][k] = c * apk - s * aqk;
+                a[q][k] = s * apk + c * aqk;
+            }
+            for (var k = 0u; k < 3u; k = k + 1u) {
+                let vkp = v[k][p];
+                let vkq = v[k][q];
+                v[k][p] = c * vkp - s * vkq;
+                v[k][q] = s * vkp + c * vkq;
+            }
+        }
+    }
+
+    var smallest = 0u;
+    for (var e = 1u; e < 3u; e = e + 1u) {
+        if (a[e][e] < a[smallest][smallest]) {
+            smallest = e;
+        }
+    }
+    normals[i] = vec4<f32>(v[0][smallest], v[1][smallest], v[2][smallest], 0.0);
+}
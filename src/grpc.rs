@@ -0,0 +1,158 @@
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::pin::Pin;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::{BroadcastStream, TcpListenerStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+pub mod proto {
+    tonic::include_proto!("vivotk");
+}
+
+use proto::point_cloud_service_server::{PointCloudService, PointCloudServiceServer};
+use proto::{PointCloudFrame, StreamCloudsRequest};
+
+/// Frames buffered for each client, a client lagging further behind skips frames.
+const FRAME_BUFFER: usize = 30;
+
+pub fn to_frame(cloud: &PointCloud<PointXyzRgba>, index: u32) -> PointCloudFrame {
+    PointCloudFrame {
+        index,
+        xyz: cloud.points.iter().flat_map(|p| p.xyz()).collect(),
+        rgba: cloud.points.iter().flat_map(|p| p.rgba()).collect(),
+    }
+}
+
+pub fn from_frame(frame: &PointCloudFrame) -> PointCloud<PointXyzRgba> {
+    let points = frame
+        .xyz
+        .chunks_exact(3)
+        .zip(frame.rgba.chunks_exact(4))
+        .map(|(xyz, rgba)| {
+            PointXyzRgba::from_xyz_rgba(
+                [xyz[0], xyz[1], xyz[2]],
+                [rgba[0], rgba[1], rgba[2], rgba[3]],
+            )
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// `None` ends the streams of the clients.
+type FrameSender = broadcast::Sender<Option<PointCloudFrame>>;
+
+struct FrameBroadcaster {
+    sender: FrameSender,
+}
+
+#[tonic::async_trait]
+impl PointCloudService for FrameBroadcaster {
+    type StreamCloudsStream =
+        Pin<Box<dyn Stream<Item = Result<PointCloudFrame, Status>> + Send + 'static>>;
+
+    async fn stream_clouds(
+        &self,
+        _request: Request<StreamCloudsRequest>,
+    ) -> Result<Response<Self::StreamCloudsStream>, Status> {
+        let frames = BroadcastStream::new(self.sender.subscribe())
+            .filter_map(|frame| frame.ok())
+            .map_while(|frame| frame.map(Ok));
+        Ok(Response::new(Box::pin(frames)))
+    }
+}
+
+/// Serves `PointCloudService` from a background thread, streaming the frames it is sent to every
+/// connected client.
+pub struct FrameServer {
+    sender: FrameSender,
+    local_addr: SocketAddr,
+}
+
+impl FrameServer {
+    pub fn spawn<A: ToSocketAddrs>(addr: A) -> std::io::Result<Self> {
+        let listener = std::net::TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let (sender, _) = broadcast::channel(FRAME_BUFFER);
+        let service = PointCloudServiceServer::new(FrameBroadcaster {
+            sender: sender.clone(),
+        });
+
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+            let served = runtime.block_on(async move {
+                let listener = tokio::net::TcpListener::from_std(listener)?;
+                Server::builder()
+                    .add_service(service)
+                    .serve_with_incoming(TcpListenerStream::new(listener))
+                    .await
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+            });
+            if let Err(e) = served {
+                eprintln!("gRPC server stopped: {}", e);
+            }
+        });
+
+        Ok(Self { sender, local_addr })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Streams the frame to the connected clients, it is dropped if there are none.
+    pub fn send(&self, frame: PointCloudFrame) {
+        self.sender.send(Some(frame)).ok();
+    }
+
+    /// Ends the streams of the connected clients once they have received the previous frames.
+    pub fn end(&self) {
+        self.sender.send(None).ok();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::proto::point_cloud_service_client::PointCloudServiceClient;
+    use super::*;
+
+    #[test]
+    fn test_stream_clouds() {
+        let cloud = PointCloud::from_points(vec![
+            PointXyzRgba::from_xyz_rgba([1.0, 2.0, 3.0], [10, 20, 30, 255]),
+            PointXyzRgba::from_xyz_rgba([-1.0, 0.5, 0.0], [40, 50, 60, 128]),
+        ]);
+        let server = FrameServer::spawn("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.local_addr());
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let received = runtime.block_on(async {
+            let mut client = PointCloudServiceClient::connect(url).await.unwrap();
+            let mut frames = client
+                .stream_clouds(StreamCloudsRequest {})
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(server.client_count(), 1);
+            server.send(to_frame(&cloud, 3));
+            server.send(to_frame(&cloud, 4));
+            server.end();
+
+            let mut received = vec![];
+            while let Some(frame) = frames.message().await.unwrap() {
+                received.push(frame);
+            }
+            received
+        });
+
+        assert_eq!(received.len(), 2);
+        assert_eq!((received[0].index, received[1].index), (3, 4));
+        assert_eq!(from_frame(&received[1]).points, cloud.points);
+    }
+}
@@ -7,11 +7,21 @@ pub mod codec;
 pub mod dash;
 pub mod downsample;
 pub mod formats;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod methods;
 pub mod metrics;
 pub mod pcd;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod pipeline;
 pub mod ply;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod render;
+pub mod structures;
 pub mod upsample;
 pub mod utils;
 pub mod velodyne;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,102 @@
+use cgmath::{InnerSpace, Point3};
+use std::collections::HashMap;
+
+use super::triangulation::{circumsphere, delaunay_3d};
+
+/// Boundary faces of the alpha shape of the points: the union of the Delaunay tetrahedra with a
+/// circumradius of at most `1 / alpha`. As alpha goes to 0 every tetrahedron is kept and the
+/// alpha shape is the convex hull, larger values carve out concavities.
+///
+/// Returns the faces of exactly one kept tetrahedron, ordered counter-clockwise seen from outside.
+pub fn alpha_shape(points: &[Point3<f32>], alpha: f32) -> Vec<[usize; 3]> {
+    let max_radius_squared = if alpha > 0.0 {
+        (1.0 / alpha as f64).powi(2)
+    } else {
+        f64::INFINITY
+    };
+    let xyz = |i: usize| [points[i].x as f64, points[i].y as f64, points[i].z as f64];
+
+    // each face of a kept tetrahedron with the opposite vertex, and the number of kept
+    // tetrahedra it belongs to
+    let mut faces: HashMap<[usize; 3], (usize, usize)> = HashMap::new();
+    for [a, b, c, d] in delaunay_3d(points) {
+        let kept = circumsphere([a, b, c, d].map(xyz)).map_or(false, |(_, radius_squared)| {
+            radius_squared <= max_radius_squared
+        });
+        if !kept {
+            continue;
+        }
+        for (face, opposite) in [
+            ([a, b, c], d),
+            ([a, b, d], c),
+            ([a, c, d], b),
+            ([b, c, d], a),
+        ] {
+            faces.entry(face).or_insert((opposite, 0)).1 += 1;
+        }
+    }
+
+    faces
+        .into_iter()
+        .filter(|(_, (_, count))| *count == 1)
+        .map(|([a, b, c], (opposite, _))| {
+            let normal = (points[b] - points[a]).cross(points[c] - points[a]);
+            if normal.dot(points[opposite] - points[a]) > 0.0 {
+                [a, c, b]
+            } else {
+                [a, b, c]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn random_points() -> Vec<Point3<f32>> {
+        let mut rng = StdRng::seed_from_u64(13);
+        (0..100)
+            .map(|_| {
+                Point3::new(
+                    rng.gen_range(0.0..1.0),
+                    rng.gen_range(0.0..1.0),
+                    rng.gen_range(0.0..1.0),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_small_alpha_is_convex_hull() {
+        let points = random_points();
+        let faces = alpha_shape(&points, 1e-6);
+        assert!(!faces.is_empty());
+
+        // every point is behind the outward facing plane of every face
+        for &[a, b, c] in &faces {
+            let normal = (points[b] - points[a]).cross(points[c] - points[a]);
+            for p in &points {
+                assert!(normal.dot(*p - points[a]) <= 1e-6);
+            }
+        }
+
+        // the hull is closed, every edge is shared by two faces in opposite directions
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        for &[a, b, c] in &faces {
+            for edge in [(a, b), (b, c), (c, a)] {
+                *edges.entry(edge).or_default() += 1;
+            }
+        }
+        for (&(a, b), &count) in &edges {
+            assert_eq!(count, 1);
+            assert_eq!(edges.get(&(b, a)), Some(&1));
+        }
+    }
+
+    #[test]
+    fn test_large_alpha_is_empty() {
+        assert!(alpha_shape(&random_points(), 1e3).is_empty());
+    }
+}
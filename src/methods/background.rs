@@ -0,0 +1,111 @@
+use kiddo::{distance::squared_euclidean, KdTree};
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Splits the scene into foreground and background points. A scene point is background if its
+/// nearest background point is within `threshold`.
+pub struct BackgroundSubtractor {
+    tree: KdTree<f32, usize, 3>,
+    is_empty: bool,
+    threshold: f32,
+}
+
+impl BackgroundSubtractor {
+    pub fn new(background: &PointCloud<PointXyzRgba>, threshold: f32) -> Self {
+        let mut tree = KdTree::new();
+        for (i, p) in background.points.iter().enumerate() {
+            tree.add(&p.xyz(), i)
+                .expect("Failed to add point to kd tree");
+        }
+        Self {
+            tree,
+            is_empty: background.points.is_empty(),
+            threshold,
+        }
+    }
+
+    /// Returns the foreground and the background points of the scene, in their original order.
+    pub fn split(
+        &self,
+        scene: &PointCloud<PointXyzRgba>,
+    ) -> (PointCloud<PointXyzRgba>, PointCloud<PointXyzRgba>) {
+        let (background, foreground): (Vec<PointXyzRgba>, Vec<PointXyzRgba>) =
+            scene.points.iter().copied().partition(|p| {
+                !self.is_empty
+                    && self
+                        .tree
+                        .nearest(&p.xyz(), 1, &squared_euclidean)
+                        .expect("Failed to query kd tree")[0]
+                        .0
+                        <= self.threshold * self.threshold
+            });
+        (
+            PointCloud::from_points(foreground),
+            PointCloud::from_points(background),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(x: f32, y: f32, z: f32) -> PointXyzRgba {
+        PointXyzRgba {
+            x,
+            y,
+            z,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        }
+    }
+
+    /// Surface of the box [-5, 5]^3 sampled every 0.5.
+    fn box_points() -> Vec<PointXyzRgba> {
+        let mut points = vec![];
+        for i in 0..=20 {
+            for j in 0..=20 {
+                let (u, v) = (-5.0 + i as f32 * 0.5, -5.0 + j as f32 * 0.5);
+                for w in [-5.0, 5.0] {
+                    points.push(point(w, u, v));
+                    points.push(point(u, w, v));
+                    points.push(point(u, v, w));
+                }
+            }
+        }
+        points
+    }
+
+    fn sphere_points(cx: f32) -> Vec<PointXyzRgba> {
+        let mut points = vec![];
+        for i in 0..10 {
+            let theta = std::f32::consts::PI * (i as f32 + 0.5) / 10.0;
+            for j in 0..20 {
+                let phi = 2.0 * std::f32::consts::PI * j as f32 / 20.0;
+                points.push(point(
+                    cx + theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                ));
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn test_split_moving_sphere() {
+        let background = PointCloud::from_points(box_points());
+        let subtractor = BackgroundSubtractor::new(&background, 0.1);
+
+        for cx in [-2.0, 0.0, 2.0] {
+            let mut points = box_points();
+            points.extend(sphere_points(cx));
+            let scene = PointCloud::from_points(points);
+            let (foreground, background) = subtractor.split(&scene);
+            assert_eq!(foreground.points, sphere_points(cx));
+            assert_eq!(background.len(), box_points().len());
+        }
+    }
+}
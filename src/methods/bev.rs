@@ -0,0 +1,75 @@
+use cgmath::Point3;
+use image::{Rgb, RgbImage};
+
+use crate::formats::{grid::bounding_box, pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Renders a bird's eye view of the point cloud by projecting it onto the XY plane.
+///
+/// The image is centered on the XY bounding box of the cloud, with `resolution` meters per pixel
+/// and +y pointing up. Each pixel takes the color of its highest point, empty pixels stay black.
+pub fn bev_image(
+    cloud: &PointCloud<PointXyzRgba>,
+    width: u32,
+    height: u32,
+    resolution: f32,
+) -> RgbImage {
+    let mut image = RgbImage::new(width, height);
+    let (min, max) = match bounding_box(cloud.points.iter().map(|p| Point3::new(p.x, p.y, p.z))) {
+        Some(bounds) => bounds,
+        None => return image,
+    };
+    let (cx, cy) = ((min.x + max.x) / 2.0, (min.y + max.y) / 2.0);
+
+    let mut highest = vec![f32::NEG_INFINITY; (width * height) as usize];
+    for p in &cloud.points {
+        let col = ((p.x - cx) / resolution + width as f32 / 2.0).floor();
+        let row = (height as f32 / 2.0 - (p.y - cy) / resolution).floor();
+        if col < 0.0 || row < 0.0 || col >= width as f32 || row >= height as f32 {
+            continue;
+        }
+        let (col, row) = (col as u32, row as u32);
+        let idx = (row * width + col) as usize;
+        if p.z > highest[idx] {
+            highest[idx] = p.z;
+            image.put_pixel(col, row, Rgb([p.r, p.g, p.b]));
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(x: f32, y: f32, z: f32, rgb: u8) -> PointXyzRgba {
+        PointXyzRgba {
+            x,
+            y,
+            z,
+            r: rgb,
+            g: rgb,
+            b: rgb,
+            a: 255,
+        }
+    }
+
+    #[test]
+    fn test_bev_image_floor() {
+        let mut points = vec![];
+        for i in 0..40 {
+            for j in 0..40 {
+                let x = (i as f32 + 0.5) * 0.05 - 1.0;
+                let y = (j as f32 + 0.5) * 0.05 - 1.0;
+                points.push(point(x, y, 0.0, 128));
+            }
+        }
+        points.push(point(0.01, 0.01, 1.0, 255));
+        let cloud = PointCloud::from_points(points);
+
+        let image = bev_image(&cloud, 20, 20, 0.1);
+        assert!(image.pixels().all(|p| p.0 != [0, 0, 0]));
+        // the highest point wins
+        assert_eq!(image.get_pixel(10, 9).0, [255, 255, 255]);
+        assert_eq!(image.get_pixel(9, 10).0, [128, 128, 128]);
+    }
+}
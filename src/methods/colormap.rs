@@ -0,0 +1,176 @@
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    Jet,
+    Viridis,
+    Rainbow,
+}
+
+const JET: [(f32, [u8; 3]); 6] = [
+    (0.0, [0, 0, 128]),
+    (0.125, [0, 0, 255]),
+    (0.375, [0, 255, 255]),
+    (0.625, [255, 255, 0]),
+    (0.875, [255, 0, 0]),
+    (1.0, [128, 0, 0]),
+];
+
+const VIRIDIS: [(f32, [u8; 3]); 9] = [
+    (0.0, [68, 1, 84]),
+    (0.125, [71, 44, 122]),
+    (0.25, [59, 81, 139]),
+    (0.375, [44, 113, 142]),
+    (0.5, [33, 144, 141]),
+    (0.625, [39, 173, 129]),
+    (0.75, [92, 200, 99]),
+    (0.875, [170, 220, 50]),
+    (1.0, [253, 231, 37]),
+];
+
+const RAINBOW: [(f32, [u8; 3]); 6] = [
+    (0.0, [255, 0, 0]),
+    (0.2, [255, 255, 0]),
+    (0.4, [0, 255, 0]),
+    (0.6, [0, 255, 255]),
+    (0.8, [0, 0, 255]),
+    (1.0, [255, 0, 255]),
+];
+
+impl Colormap {
+    fn table(&self) -> &'static [(f32, [u8; 3])] {
+        match self {
+            Colormap::Jet => &JET,
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Rainbow => &RAINBOW,
+        }
+    }
+
+    /// Maps `t` in [0, 1] to a color by linearly interpolating the colormap table.
+    /// Values outside the range are clamped.
+    pub fn color(&self, t: f32) -> [u8; 3] {
+        let table = self.table();
+        let t = t.clamp(0.0, 1.0);
+        let upper = table
+            .iter()
+            .position(|&(pos, _)| pos >= t)
+            .unwrap_or(table.len() - 1)
+            .max(1);
+        let (p0, c0) = table[upper - 1];
+        let (p1, c1) = table[upper];
+        let w = (t - p0) / (p1 - p0);
+        let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * w).round() as u8;
+        [lerp(c0[0], c1[0]), lerp(c0[1], c1[1]), lerp(c0[2], c1[2])]
+    }
+}
+
+/// Colors each point by its z value, scaled from [min_z, max_z] to [0, 1].
+///
+/// Values outside the range are clipped to the end colors, or wrapped around if `wrap` is set.
+/// Overwrites the red, green and blue channels and keeps alpha.
+pub fn colorize_height(
+    cloud: &mut PointCloud<PointXyzRgba>,
+    min_z: f32,
+    max_z: f32,
+    colormap: Colormap,
+    wrap: bool,
+) {
+    let range = max_z - min_z;
+    for p in cloud.points.iter_mut() {
+        let mut t = if range > 0.0 {
+            (p.z - min_z) / range
+        } else {
+            0.0
+        };
+        // the range itself is kept, so that max_z gets the top color instead of wrapping to 0
+        if wrap && !(0.0..=1.0).contains(&t) {
+            t = t.rem_euclid(1.0);
+        }
+        let [r, g, b] = colormap.color(t);
+        p.r = r;
+        p.g = g;
+        p.b = b;
+    }
+}
+
+/// Colors each point by its value, e.g. an error, scaled from [0, max] to [0, 1] and clipped.
+/// Overwrites the red, green and blue channels and keeps alpha.
+pub fn colorize_values(
+    cloud: &mut PointCloud<PointXyzRgba>,
+    values: &[f32],
+    max: f32,
+    colormap: Colormap,
+) {
+    assert_eq!(
+        cloud.points.len(),
+        values.len(),
+        "Expecting one value per point"
+    );
+    for (p, value) in cloud.points.iter_mut().zip(values) {
+        let t = if max > 0.0 { value / max } else { 0.0 };
+        let [r, g, b] = colormap.color(t);
+        p.r = r;
+        p.g = g;
+        p.b = b;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn column() -> PointCloud<PointXyzRgba> {
+        let points: Vec<PointXyzRgba> = (0..11)
+            .map(|i| PointXyzRgba {
+                x: 0.0,
+                y: 0.0,
+                z: i as f32,
+                r: 10,
+                g: 20,
+                b: 30,
+                a: 40,
+            })
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    fn rgb(p: &PointXyzRgba) -> [u8; 3] {
+        [p.r, p.g, p.b]
+    }
+
+    #[test]
+    fn test_colorize_height_jet() {
+        let mut cloud = column();
+        colorize_height(&mut cloud, 0.0, 10.0, Colormap::Jet, false);
+        assert_eq!(rgb(&cloud.points[0]), [0, 0, 128]);
+        assert_eq!(rgb(&cloud.points[10]), [128, 0, 0]);
+        assert!(cloud.points.iter().all(|p| p.a == 40));
+    }
+
+    #[test]
+    fn test_colorize_height_clip_and_wrap() {
+        let mut clipped = column();
+        colorize_height(&mut clipped, 2.0, 6.0, Colormap::Viridis, false);
+        assert_eq!(rgb(&clipped.points[0]), Colormap::Viridis.color(0.0));
+        assert_eq!(rgb(&clipped.points[8]), Colormap::Viridis.color(1.0));
+
+        let mut wrapped = column();
+        colorize_height(&mut wrapped, 2.0, 6.0, Colormap::Viridis, true);
+        // z = 7 wraps around to z = 3
+        assert_eq!(rgb(&wrapped.points[7]), rgb(&wrapped.points[3]));
+        assert_eq!(rgb(&wrapped.points[2]), Colormap::Viridis.color(0.0));
+        assert_eq!(rgb(&wrapped.points[6]), Colormap::Viridis.color(1.0));
+        assert_eq!(rgb(&wrapped.points[1]), rgb(&wrapped.points[5]));
+    }
+
+    #[test]
+    fn test_colorize_values() {
+        let mut cloud = column();
+        let values: Vec<f32> = (0..11).map(|i| i as f32 * 0.1).collect();
+        colorize_values(&mut cloud, &values, 0.5, Colormap::Jet);
+        assert_eq!(rgb(&cloud.points[0]), [0, 0, 128]);
+        assert_eq!(rgb(&cloud.points[5]), [128, 0, 0]);
+        // errors above the maximum are clipped
+        assert_eq!(rgb(&cloud.points[10]), [128, 0, 0]);
+    }
+}
@@ -0,0 +1,172 @@
+use cgmath::{InnerSpace, Vector3};
+use kiddo::{distance::squared_euclidean, KdTree};
+
+use super::plane::Plane;
+use crate::formats::{
+    pointxyzrgbacurvature::PointXyzRgbaCurvature, pointxyzrgbanormal::PointXyzRgbaNormal,
+    PointCloud,
+};
+
+const EPSILON: f64 = 1e-12;
+
+/// Estimates the principal curvatures of every point from the normals of its neighbours within
+/// `radius`.
+///
+/// The offsets to the neighbours and the differences of their normals are projected onto the
+/// tangent plane of the point, and the shape operator is the 2x2 map from offsets to normal
+/// differences fitted by least squares. Its eigenvalues are the principal curvatures, positive
+/// where the surface bends away from the normal, as on a sphere with outward normals.
+/// Points with a zero normal or too few neighbours get zero curvatures.
+pub fn principal_curvatures(
+    cloud: &PointCloud<PointXyzRgbaNormal>,
+    radius: f64,
+) -> PointCloud<PointXyzRgbaCurvature> {
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        tree.add(&p.xyz(), i)
+            .expect("Should be able to add point to kd tree");
+    }
+
+    let r2 = (radius * radius) as f32;
+    let points = cloud
+        .points
+        .iter()
+        .map(|p| {
+            let neighbours = tree
+                .within(&p.xyz(), r2, &squared_euclidean)
+                .expect("Failed to query tree");
+            let (k1, k2) =
+                shape_operator(cloud, p, neighbours.iter().map(|&(_, &j)| j)).unwrap_or((0.0, 0.0));
+            PointXyzRgbaCurvature {
+                x: p.x,
+                y: p.y,
+                z: p.z,
+                r: p.r,
+                g: p.g,
+                b: p.b,
+                a: p.a,
+                k1,
+                k2,
+                mean_curvature: (k1 + k2) / 2.0,
+                gaussian_curvature: k1 * k2,
+            }
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// Eigenvalues of the fitted shape operator of `p`, largest first.
+fn shape_operator(
+    cloud: &PointCloud<PointXyzRgbaNormal>,
+    p: &PointXyzRgbaNormal,
+    neighbours: impl Iterator<Item = usize>,
+) -> Option<(f32, f32)> {
+    let normal = Plane::new(Vector3::new(p.nx, p.ny, p.nz), 0.0)?.normal;
+    let (u, v) = Plane::new(normal, 0.0)?.basis();
+
+    // a = sum of d d^T and b = sum of dn d^T over the tangent offsets d and normal differences dn
+    let (mut a11, mut a12, mut a22) = (0f64, 0f64, 0f64);
+    let (mut b11, mut b12, mut b21, mut b22) = (0f64, 0f64, 0f64, 0f64);
+    for j in neighbours {
+        let q = &cloud.points[j];
+        let mut n = Vector3::new(q.nx, q.ny, q.nz);
+        if n.magnitude2() == 0.0 {
+            continue;
+        }
+        n = n.normalize();
+        // the normals may not be consistently oriented
+        if n.dot(normal) < 0.0 {
+            n = -n;
+        }
+        let d = Vector3::new(q.x - p.x, q.y - p.y, q.z - p.z);
+        let dn = n - normal;
+        let (du, dv) = (d.dot(u) as f64, d.dot(v) as f64);
+        let (dnu, dnv) = (dn.dot(u) as f64, dn.dot(v) as f64);
+        a11 += du * du;
+        a12 += du * dv;
+        a22 += dv * dv;
+        b11 += dnu * du;
+        b12 += dnu * dv;
+        b21 += dnv * du;
+        b22 += dnv * dv;
+    }
+
+    let det = a11 * a22 - a12 * a12;
+    if det <= EPSILON {
+        return None;
+    }
+    // s = b a^-1, symmetrized since the shape operator is self-adjoint
+    let s11 = (b11 * a22 - b12 * a12) / det;
+    let s12 = (b12 * a11 - b11 * a12) / det;
+    let s21 = (b21 * a22 - b22 * a12) / det;
+    let s22 = (b22 * a11 - b21 * a12) / det;
+    let off_diagonal = (s12 + s21) / 2.0;
+
+    let mean = (s11 + s22) / 2.0;
+    let spread = (((s11 - s22) / 2.0).powi(2) + off_diagonal.powi(2)).sqrt();
+    Some(((mean + spread) as f32, (mean - spread) as f32))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(position: Vector3<f32>, normal: Vector3<f32>) -> PointXyzRgbaNormal {
+        PointXyzRgbaNormal {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+            nx: normal.x,
+            ny: normal.y,
+            nz: normal.z,
+        }
+    }
+
+    #[test]
+    fn test_sphere_curvature() {
+        // fibonacci sphere of radius 2 with normals alternating between outward and inward
+        let n = 2000;
+        let golden_angle = std::f32::consts::PI * (3.0 - 5f32.sqrt());
+        let points = (0..n)
+            .map(|i| {
+                let y = 1.0 - 2.0 * (i as f32 + 0.5) / n as f32;
+                let r = (1.0 - y * y).sqrt();
+                let theta = golden_angle * i as f32;
+                let normal = Vector3::new(r * theta.cos(), y, r * theta.sin());
+                let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+                point(normal * 2.0, normal * sign)
+            })
+            .collect();
+
+        let curvatures = principal_curvatures(&PointCloud::from_points(points), 0.4);
+        for p in &curvatures.points {
+            // the sign of the curvatures follows the orientation of the normal
+            assert!((p.k1.abs() - 0.5).abs() < 0.01, "{:?}", p);
+            assert!((p.k2.abs() - 0.5).abs() < 0.01, "{:?}", p);
+            assert!((p.gaussian_curvature - 0.25).abs() < 0.01, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn test_cylinder_curvature() {
+        // unit cylinder along z with outward normals
+        let points = (0..2000)
+            .map(|i| {
+                let theta = 2.0 * std::f32::consts::PI * (i % 50) as f32 / 50.0;
+                let normal = Vector3::new(theta.cos(), theta.sin(), 0.0);
+                point(normal + Vector3::unit_z() * (i / 50) as f32 * 0.1, normal)
+            })
+            .collect();
+
+        let curvatures = principal_curvatures(&PointCloud::from_points(points), 0.3);
+        for p in &curvatures.points {
+            assert!((p.k1 - 1.0).abs() < 0.01, "{:?}", p);
+            assert!(p.k2.abs() < 0.01, "{:?}", p);
+            assert!((p.mean_curvature - 0.5).abs() < 0.01, "{:?}", p);
+        }
+    }
+}
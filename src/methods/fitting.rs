@@ -0,0 +1,168 @@
+use cgmath::{InnerSpace, Point3, Vector3};
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+const MAX_ITERATIONS: usize = 100;
+const EPSILON: f32 = 1e-6;
+
+/// Fits a cylinder with a known axis direction to the point cloud.
+///
+/// The points are projected onto the plane perpendicular to the axis and a circle is fitted to the
+/// projection, first algebraically and then refined with iterative geometric least squares.
+/// Returns the point on the axis at the centroid's height and the radius of the cylinder.
+pub fn fit_cylinder(
+    cloud: &PointCloud<PointXyzRgba>,
+    axis_direction: Vector3<f32>,
+) -> Option<(Point3<f32>, f32)> {
+    if cloud.points.len() < 3 || axis_direction.magnitude2() < EPSILON {
+        return None;
+    }
+
+    let axis = axis_direction.normalize();
+    let helper = if axis.x.abs() < 0.9 {
+        Vector3::unit_x()
+    } else {
+        Vector3::unit_y()
+    };
+    let u = axis.cross(helper).normalize();
+    let v = axis.cross(u);
+
+    let mut height = 0f64;
+    let projected: Vec<(f64, f64)> = cloud
+        .points
+        .iter()
+        .map(|p| {
+            let p = Vector3::new(p.x, p.y, p.z);
+            height += p.dot(axis) as f64;
+            (p.dot(u) as f64, p.dot(v) as f64)
+        })
+        .collect();
+    let height = (height / projected.len() as f64) as f32;
+
+    let (mut cx, mut cy, mut radius) = fit_circle_algebraic(&projected)?;
+
+    // refine with the geometric fit: r = mean(d_i), c = mean(p_i) + r * mean((c - p_i) / d_i)
+    for _ in 0..MAX_ITERATIONS {
+        let n = projected.len() as f64;
+        let (mut mean_x, mut mean_y) = (0f64, 0f64);
+        let (mut mean_dx, mut mean_dy) = (0f64, 0f64);
+        let mut mean_dist = 0f64;
+        for &(x, y) in &projected {
+            let dist = ((cx - x).powi(2) + (cy - y).powi(2)).sqrt();
+            mean_x += x;
+            mean_y += y;
+            mean_dist += dist;
+            if dist > EPSILON as f64 {
+                mean_dx += (cx - x) / dist;
+                mean_dy += (cy - y) / dist;
+            }
+        }
+        let new_radius = mean_dist / n;
+        let new_cx = mean_x / n + new_radius * mean_dx / n;
+        let new_cy = mean_y / n + new_radius * mean_dy / n;
+
+        let change = (new_cx - cx).abs() + (new_cy - cy).abs() + (new_radius - radius).abs();
+        cx = new_cx;
+        cy = new_cy;
+        radius = new_radius;
+        if change < EPSILON as f64 {
+            break;
+        }
+    }
+
+    let center = axis * height + u * cx as f32 + v * cy as f32;
+    Some((Point3::new(center.x, center.y, center.z), radius as f32))
+}
+
+/// Kasa fit: minimizes sum((x^2 + y^2 + D * x + E * y + F)^2) over D, E, F.
+fn fit_circle_algebraic(points: &[(f64, f64)]) -> Option<(f64, f64, f64)> {
+    let (mut sxx, mut sxy, mut syy, mut sx, mut sy) = (0f64, 0f64, 0f64, 0f64, 0f64);
+    let (mut sxz, mut syz, mut sz) = (0f64, 0f64, 0f64);
+    for &(x, y) in points {
+        let z = x * x + y * y;
+        sxx += x * x;
+        sxy += x * y;
+        syy += y * y;
+        sx += x;
+        sy += y;
+        sxz += x * z;
+        syz += y * z;
+        sz += z;
+    }
+    let n = points.len() as f64;
+
+    let a = [[sxx, sxy, sx], [sxy, syy, sy], [sx, sy, n]];
+    let b = [-sxz, -syz, -sz];
+    let [d, e, f] = solve_3x3(a, b)?;
+
+    let cx = -d / 2.0;
+    let cy = -e / 2.0;
+    let r2 = cx * cx + cy * cy - f;
+    if r2 <= 0.0 {
+        return None;
+    }
+    Some((cx, cy, r2.sqrt()))
+}
+
+/// Solves `a * x = b` with Cramer's rule, returns `None` if `a` is singular.
+pub(crate) fn solve_3x3(a: [[f64; 3]; 3], b: [f64; 3]) -> Option<[f64; 3]> {
+    let det = |m: &[[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let d = det(&a);
+    if d.abs() < 1e-12 {
+        return None;
+    }
+    let mut x = [0f64; 3];
+    for (col, x) in x.iter_mut().enumerate() {
+        let mut m = a;
+        for row in 0..3 {
+            m[row][col] = b[row];
+        }
+        *x = det(&m) / d;
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cylinder_cloud(center_x: f32, center_z: f32, radius: f32) -> PointCloud<PointXyzRgba> {
+        let mut points = vec![];
+        for i in 0..36 {
+            let theta = (i as f32 * 10.0).to_radians();
+            for j in 0..10 {
+                points.push(PointXyzRgba {
+                    x: center_x + radius * theta.cos(),
+                    y: j as f32,
+                    z: center_z + radius * theta.sin(),
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                });
+            }
+        }
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_fit_cylinder_y_axis() {
+        let cloud = cylinder_cloud(1.0, -3.0, 2.0);
+        let (center, radius) = fit_cylinder(&cloud, Vector3::new(0.0, 1.0, 0.0)).unwrap();
+        assert!((center.x - 1.0).abs() < 1e-3);
+        assert!((center.y - 4.5).abs() < 1e-3);
+        assert!((center.z + 3.0).abs() < 1e-3);
+        assert!((radius - 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_fit_cylinder_too_few_points() {
+        let mut cloud = cylinder_cloud(0.0, 0.0, 1.0);
+        cloud.points.truncate(2);
+        assert!(fit_cylinder(&cloud, Vector3::new(0.0, 1.0, 0.0)).is_none());
+    }
+}
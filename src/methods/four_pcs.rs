@@ -0,0 +1,284 @@
+use cgmath::{InnerSpace, Matrix3, Vector3};
+use kiddo::{distance::squared_euclidean, KdTree};
+use rand::{seq::index::sample, Rng};
+
+use super::fusion::{rigid_extrinsic, Extrinsic};
+use super::icp::best_rigid_transform;
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Number of bases tried, the best transform over all of them is kept.
+const BASE_TRIALS: usize = 20;
+/// Number of random triplets the widest one is picked from for a base.
+const TRIPLET_TRIALS: usize = 50;
+
+/// Four approximately coplanar points `[a, b, c, d]` of the reference, where the segments ab and
+/// cd cross at `a + r1 (b - a)` and `c + r2 (d - c)`, which an affine map preserves.
+struct Base {
+    points: [Vector3<f64>; 4],
+    r1: f64,
+    r2: f64,
+}
+
+/// 4-Points Congruent Sets: finds a coarse rigid transform aligning the source cloud to the
+/// reference cloud without an initial estimate.
+///
+/// `num_samples` points of each cloud are sampled. For each base sampled from the reference, the
+/// sets of four source points whose pair distances and crossing ratios match the base within
+/// `delta` are found through the crossing points of the pairs, and each set gives a candidate
+/// transform, scored by the fraction of sampled source points within `delta` of a reference
+/// point. Returns the best transform with its score, or `None` if no candidate is found.
+pub fn four_pcs<R: Rng>(
+    reference: &PointCloud<PointXyzRgba>,
+    source: &PointCloud<PointXyzRgba>,
+    delta: f32,
+    num_samples: usize,
+    rng: &mut R,
+) -> Option<(Extrinsic, f32)> {
+    let reference_points = sample_points(reference, num_samples, rng);
+    let source_points = sample_points(source, num_samples, rng);
+    if reference_points.len() < 4 || source_points.len() < 4 {
+        return None;
+    }
+
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in reference.points.iter().enumerate() {
+        tree.add(&p.xyz(), i)
+            .expect("Should be able to add point to kd tree");
+    }
+    let delta = delta as f64;
+    let score = |rotation: Matrix3<f64>, translation: Vector3<f64>| {
+        let inliers = source_points
+            .iter()
+            .filter(|&&p| {
+                let q = rotation * p + translation;
+                tree.nearest(&[q.x as f32, q.y as f32, q.z as f32], 1, &squared_euclidean)
+                    .expect("Failed to query tree")[0]
+                    .0
+                    <= (delta * delta) as f32
+            })
+            .count();
+        inliers as f32 / source_points.len() as f32
+    };
+
+    let mut best: Option<(Extrinsic, f32)> = None;
+    for _ in 0..BASE_TRIALS {
+        let base = match select_base(&reference_points, delta, rng) {
+            Some(base) => base,
+            None => continue,
+        };
+        for set in congruent_sets(&source_points, &base, delta) {
+            let from = set.map(|i| source_points[i]);
+            let (rotation, translation) = best_rigid_transform(&from, &base.points);
+            // the crossing ratios are affine invariant, only keep the sets matched rigidly
+            let is_rigid = from
+                .iter()
+                .zip(&base.points)
+                .all(|(&p, &q)| (rotation * p + translation - q).magnitude() <= delta);
+            if !is_rigid {
+                continue;
+            }
+            let candidate_score = score(rotation, translation);
+            if best.map_or(true, |(_, s)| candidate_score > s) {
+                best = Some((rigid_extrinsic(&rotation, &translation), candidate_score));
+            }
+        }
+        if best.map_or(false, |(_, s)| s >= 1.0) {
+            break;
+        }
+    }
+    best
+}
+
+fn sample_points<R: Rng>(
+    cloud: &PointCloud<PointXyzRgba>,
+    num_samples: usize,
+    rng: &mut R,
+) -> Vec<Vector3<f64>> {
+    let n = cloud.points.len();
+    let to_vector = |p: &PointXyzRgba| Vector3::new(p.x as f64, p.y as f64, p.z as f64);
+    if num_samples >= n {
+        return cloud.points.iter().map(to_vector).collect();
+    }
+    sample(rng, n, num_samples)
+        .into_iter()
+        .map(|i| to_vector(&cloud.points[i]))
+        .collect()
+}
+
+/// Picks the widest of random triplets, then the fourth point within `delta` of their plane
+/// that best spreads the base, paired so that the two segments cross.
+fn select_base<R: Rng>(points: &[Vector3<f64>], delta: f64, rng: &mut R) -> Option<Base> {
+    let (a, b, c) = (0..TRIPLET_TRIALS)
+        .map(|_| {
+            let indices = sample(rng, points.len(), 3);
+            (
+                points[indices.index(0)],
+                points[indices.index(1)],
+                points[indices.index(2)],
+            )
+        })
+        .max_by(|x, y| triangle_area(x).total_cmp(&triangle_area(y)))?;
+    let normal = (b - a).cross(c - a);
+    if normal.magnitude() <= f64::EPSILON {
+        return None;
+    }
+    let normal = normal.normalize();
+
+    let mut best: Option<(f64, Base)> = None;
+    for &d in points {
+        if normal.dot(d - a).abs() > delta {
+            continue;
+        }
+        let spread = [a, b, c]
+            .iter()
+            .map(|&p| (d - p).magnitude())
+            .fold(f64::INFINITY, f64::min);
+        if spread <= delta || best.as_ref().map_or(false, |(s, _)| spread <= *s) {
+            continue;
+        }
+        // the three ways to split the four points into two pairs
+        for [p, q, r, s] in [[a, b, c, d], [a, c, b, d], [a, d, b, c]] {
+            if let Some((r1, r2)) = crossing(p, q, r, s, delta) {
+                best = Some((
+                    spread,
+                    Base {
+                        points: [p, q, r, s],
+                        r1,
+                        r2,
+                    },
+                ));
+                break;
+            }
+        }
+    }
+    best.map(|(_, base)| base)
+}
+
+fn triangle_area((a, b, c): &(Vector3<f64>, Vector3<f64>, Vector3<f64>)) -> f64 {
+    (b - a).cross(c - a).magnitude() / 2.0
+}
+
+/// Ratios along the segments pq and rs of their closest points, if the segments cross within
+/// `delta` of each other.
+fn crossing(
+    p: Vector3<f64>,
+    q: Vector3<f64>,
+    r: Vector3<f64>,
+    s: Vector3<f64>,
+    delta: f64,
+) -> Option<(f64, f64)> {
+    let (u, v, w) = (q - p, s - r, p - r);
+    let (uu, uv, vv, uw, vw) = (u.dot(u), u.dot(v), v.dot(v), u.dot(w), v.dot(w));
+    let denominator = uu * vv - uv * uv;
+    if denominator <= f64::EPSILON {
+        return None;
+    }
+    let r1 = (uv * vw - vv * uw) / denominator;
+    let r2 = (uu * vw - uv * uw) / denominator;
+    let gap = (p + u * r1 - (r + v * r2)).magnitude();
+    ((0.0..=1.0).contains(&r1) && (0.0..=1.0).contains(&r2) && gap <= delta).then_some((r1, r2))
+}
+
+/// Ordered pairs of points whose distance is within `delta` of `distance`.
+fn pairs_at_distance(points: &[Vector3<f64>], distance: f64, delta: f64) -> Vec<(usize, usize)> {
+    let mut pairs = vec![];
+    for (i, &p) in points.iter().enumerate() {
+        for (j, &q) in points.iter().enumerate().skip(i + 1) {
+            if ((p - q).magnitude() - distance).abs() <= delta {
+                pairs.push((i, j));
+                pairs.push((j, i));
+            }
+        }
+    }
+    pairs
+}
+
+/// Sets of four points, in the order of the base, whose pairs have the lengths of the pairs of
+/// the base and cross at the same point, all within `delta`.
+fn congruent_sets(points: &[Vector3<f64>], base: &Base, delta: f64) -> Vec<[usize; 4]> {
+    let [a, b, c, d] = base.points;
+    let first = pairs_at_distance(points, (b - a).magnitude(), delta);
+    let second = pairs_at_distance(points, (d - c).magnitude(), delta);
+
+    let mut crossings: KdTree<f32, usize, 3> = KdTree::new();
+    for (k, &(i, j)) in first.iter().enumerate() {
+        let e = points[i] + (points[j] - points[i]) * base.r1;
+        crossings
+            .add(&[e.x as f32, e.y as f32, e.z as f32], k)
+            .expect("Should be able to add point to kd tree");
+    }
+    if crossings.size() == 0 {
+        return vec![];
+    }
+
+    let mut sets = vec![];
+    for &(k, l) in &second {
+        let e = points[k] + (points[l] - points[k]) * base.r2;
+        let matches = crossings
+            .within(
+                &[e.x as f32, e.y as f32, e.z as f32],
+                (delta * delta) as f32,
+                &squared_euclidean,
+            )
+            .expect("Failed to query tree");
+        for (_, &m) in matches {
+            let (i, j) = first[m];
+            if i != k && i != l && j != k && j != l {
+                sets.push([i, j, k, l]);
+            }
+        }
+    }
+    sets
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::methods::fusion::transform_cloud;
+    use cgmath::Rad;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_four_pcs_recovers_large_rotation() {
+        let mut rng = StdRng::seed_from_u64(5);
+        // random points on the surface of the box [0, 1] x [0, 0.8] x [0, 0.6]
+        let reference = PointCloud::from_points(
+            (0..300)
+                .map(|i| {
+                    let (s, t) = (rng.gen::<f32>(), rng.gen::<f32>());
+                    let [x, y, z] = match i % 6 {
+                        0 => [s, t * 0.8, 0.0],
+                        1 => [s, t * 0.8, 0.6],
+                        2 => [0.0, s * 0.8, t * 0.6],
+                        3 => [1.0, s * 0.8, t * 0.6],
+                        4 => [s, 0.0, t * 0.6],
+                        _ => [s, 0.8, t * 0.6],
+                    };
+                    PointXyzRgba {
+                        x,
+                        y,
+                        z,
+                        r: 0,
+                        g: 0,
+                        b: 0,
+                        a: 255,
+                    }
+                })
+                .collect(),
+        );
+        let rotation = Matrix3::from_axis_angle(
+            Vector3::new(1.0, 2.0, 3.0).normalize(),
+            Rad(60f64.to_radians()),
+        );
+        let moved = rigid_extrinsic(&rotation, &Vector3::new(3.0, -2.0, 1.0));
+        let source = transform_cloud(&reference, &moved);
+
+        let (extrinsic, score) = four_pcs(&reference, &source, 0.02, 300, &mut rng).unwrap();
+        assert_eq!(score, 1.0);
+        let aligned = transform_cloud(&source, &extrinsic);
+        for (a, r) in aligned.points.iter().zip(&reference.points) {
+            let error = Vector3::new(a.x - r.x, a.y - r.y, a.z - r.z).magnitude();
+            assert!(error < 0.02, "{}", error);
+        }
+    }
+}
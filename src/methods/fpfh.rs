@@ -0,0 +1,198 @@
+use cgmath::{InnerSpace, MetricSpace, Point3, Vector3};
+use kiddo::{distance::squared_euclidean, KdTree};
+use rayon::prelude::*;
+
+use crate::formats::{pointxyzrgbanormal::PointXyzRgbaNormal, PointCloud};
+
+pub const FPFH_BINS: usize = 11;
+pub const FPFH_SIZE: usize = 3 * FPFH_BINS;
+
+pub type Fpfh = [f32; FPFH_SIZE];
+
+/// Computes the angular features (alpha, phi, theta) of a point pair in the Darboux frame of the
+/// source point, as in Rusu et al. "Fast Point Feature Histograms (FPFH) for 3D registration".
+/// Returns `None` for coincident points or when the frame is degenerate.
+fn pair_features(
+    p1: Point3<f32>,
+    n1: Vector3<f32>,
+    p2: Point3<f32>,
+    n2: Vector3<f32>,
+) -> Option<(f32, f32, f32)> {
+    let mut dp = p2 - p1;
+    let dist = dp.magnitude();
+    if dist == 0.0 {
+        return None;
+    }
+    let (mut n1, mut n2) = (n1, n2);
+    let angle1 = n1.dot(dp) / dist;
+    let angle2 = n2.dot(dp) / dist;
+    // use the point whose normal is closer to the connecting line as the source
+    let theta = if angle1.abs().acos() > angle2.abs().acos() {
+        std::mem::swap(&mut n1, &mut n2);
+        dp = -dp;
+        -angle2
+    } else {
+        angle1
+    };
+
+    let v = dp.cross(n1);
+    let v_norm = v.magnitude();
+    if v_norm == 0.0 {
+        return None;
+    }
+    let v = v / v_norm;
+    let w = n1.cross(v);
+    let phi = v.dot(n2);
+    let alpha = w.dot(n2).atan2(n1.dot(n2));
+    Some((alpha, phi, theta))
+}
+
+fn bin(value: f32, min: f32, max: f32) -> usize {
+    let b = ((value - min) / (max - min) * FPFH_BINS as f32).floor();
+    (b.max(0.0) as usize).min(FPFH_BINS - 1)
+}
+
+/// Scales each of the three sub-histograms to sum to 100.
+fn normalize(hist: &mut Fpfh) {
+    for sub in hist.chunks_mut(FPFH_BINS) {
+        let sum: f32 = sub.iter().sum();
+        if sum > 0.0 {
+            sub.iter_mut().for_each(|h| *h *= 100.0 / sum);
+        }
+    }
+}
+
+/// Computes the Fast Point Feature Histogram of every point, using the neighbours within `radius`.
+///
+/// The simplified histogram (SPFH) of each point is built from the pair features with its
+/// neighbours, then the FPFH of a point is its SPFH plus the distance weighted mean of its
+/// neighbours' SPFH. Both passes run in parallel with rayon. Points without neighbours get an
+/// all zero descriptor.
+pub fn compute_fpfh(cloud: &PointCloud<PointXyzRgbaNormal>, radius: f32) -> Vec<Fpfh> {
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        tree.add(&p.xyz(), i)
+            .expect("Failed to add point to kd tree");
+    }
+    let position = |p: &PointXyzRgbaNormal| Point3::new(p.x, p.y, p.z);
+    let normal = |p: &PointXyzRgbaNormal| Vector3::new(p.nx, p.ny, p.nz);
+
+    let neighbours: Vec<Vec<usize>> = cloud
+        .points
+        .par_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            tree.within(&p.xyz(), radius * radius, &squared_euclidean)
+                .expect("Failed to query kd tree")
+                .into_iter()
+                .map(|(_, &j)| j)
+                .filter(|&j| j != i)
+                .collect()
+        })
+        .collect();
+
+    let spfh: Vec<Fpfh> = cloud
+        .points
+        .par_iter()
+        .zip(neighbours.par_iter())
+        .map(|(p, nbrs)| {
+            let mut hist = [0f32; FPFH_SIZE];
+            for &j in nbrs {
+                let q = &cloud.points[j];
+                if let Some((alpha, phi, theta)) =
+                    pair_features(position(p), normal(p), position(q), normal(q))
+                {
+                    let pi = std::f32::consts::PI;
+                    hist[bin(alpha, -pi, pi)] += 1.0;
+                    hist[FPFH_BINS + bin(phi, -1.0, 1.0)] += 1.0;
+                    hist[2 * FPFH_BINS + bin(theta, -1.0, 1.0)] += 1.0;
+                }
+            }
+            normalize(&mut hist);
+            hist
+        })
+        .collect();
+
+    cloud
+        .points
+        .par_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let mut hist = spfh[i];
+            let nbrs = &neighbours[i];
+            for &j in nbrs {
+                let dist = position(p).distance(position(&cloud.points[j]));
+                if dist == 0.0 {
+                    continue;
+                }
+                let weight = 1.0 / (dist * nbrs.len() as f32);
+                hist.iter_mut()
+                    .zip(spfh[j].iter())
+                    .for_each(|(h, s)| *h += weight * s);
+            }
+            normalize(&mut hist);
+            hist
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(x: f32, y: f32, z: f32, n: Vector3<f32>) -> PointXyzRgbaNormal {
+        PointXyzRgbaNormal {
+            x,
+            y,
+            z,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+            nx: n.x,
+            ny: n.y,
+            nz: n.z,
+        }
+    }
+
+    fn cloud(points: Vec<PointXyzRgbaNormal>) -> PointCloud<PointXyzRgbaNormal> {
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_compute_fpfh_plane() {
+        let mut points = vec![];
+        for i in 0..10 {
+            for j in 0..10 {
+                points.push(point(
+                    i as f32 * 0.1,
+                    j as f32 * 0.1,
+                    0.0,
+                    Vector3::unit_z(),
+                ));
+            }
+        }
+        let descriptors = compute_fpfh(&cloud(points), 0.25);
+        assert_eq!(descriptors.len(), 100);
+        // every pair on a plane has the same features, so all descriptors are identical
+        for d in &descriptors {
+            assert!(d
+                .iter()
+                .zip(descriptors[0].iter())
+                .all(|(a, b)| (a - b).abs() < 1e-3));
+            for sub in d.chunks(FPFH_BINS) {
+                assert!((sub.iter().sum::<f32>() - 100.0).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_fpfh_isolated_point() {
+        let points = vec![
+            point(0.0, 0.0, 0.0, Vector3::unit_z()),
+            point(5.0, 0.0, 0.0, Vector3::unit_z()),
+        ];
+        let descriptors = compute_fpfh(&cloud(points), 1.0);
+        assert_eq!(descriptors, vec![[0f32; FPFH_SIZE]; 2]);
+    }
+}
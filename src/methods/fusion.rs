@@ -0,0 +1,253 @@
+use cgmath::{Matrix3, Vector3};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use crate::formats::{
+    pointxyzrgba::PointXyzRgba, pointxyzrgbanormal::PointXyzRgbaNormal, PointCloud,
+};
+
+/// Row-major 4x4 rigid transform from a camera frame to the common frame.
+pub type Extrinsic = [[f32; 4]; 4];
+
+/// Reads the extrinsics of every source from a json array of row-major 4x4 matrices.
+pub fn read_extrinsics<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<Extrinsic>> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+/// Reads a single row-major 4x4 matrix from a json file, as written by [`write_extrinsic`].
+pub fn read_extrinsic<P: AsRef<Path>>(path: P) -> std::io::Result<Extrinsic> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+/// Writes the row-major 4x4 matrix as a json array of rows.
+pub fn write_extrinsic<P: AsRef<Path>>(extrinsic: &Extrinsic, path: P) -> std::io::Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    Ok(serde_json::to_writer_pretty(writer, extrinsic)?)
+}
+
+/// Extrinsic of the rotation followed by the translation.
+pub fn rigid_extrinsic(rotation: &Matrix3<f64>, translation: &Vector3<f64>) -> Extrinsic {
+    let mut extrinsic = [[0.0; 4]; 4];
+    for (row, values) in extrinsic.iter_mut().take(3).enumerate() {
+        // cgmath matrices are indexed by column first
+        for (col, value) in values.iter_mut().take(3).enumerate() {
+            *value = rotation[col][row] as f32;
+        }
+        values[3] = translation[row] as f32;
+    }
+    extrinsic[3][3] = 1.0;
+    extrinsic
+}
+
+/// Extrinsic applying `inner` then `outer`.
+pub fn compose_extrinsics(outer: &Extrinsic, inner: &Extrinsic) -> Extrinsic {
+    let mut composed = [[0.0; 4]; 4];
+    for (row, values) in composed.iter_mut().enumerate() {
+        for (col, value) in values.iter_mut().enumerate() {
+            *value = (0..4).map(|k| outer[row][k] * inner[k][col]).sum();
+        }
+    }
+    composed
+}
+
+/// Applies the extrinsic to every point of the cloud.
+pub fn transform_cloud(
+    cloud: &PointCloud<PointXyzRgba>,
+    extrinsic: &Extrinsic,
+) -> PointCloud<PointXyzRgba> {
+    let m = extrinsic;
+    let points: Vec<PointXyzRgba> = cloud
+        .points
+        .iter()
+        .map(|p| PointXyzRgba {
+            x: m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3],
+            y: m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3],
+            z: m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3],
+            ..*p
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// Applies the extrinsic to the position of every point and rotates its normal.
+pub fn transform_cloud_normal(
+    cloud: &PointCloud<PointXyzRgbaNormal>,
+    extrinsic: &Extrinsic,
+) -> PointCloud<PointXyzRgbaNormal> {
+    let m = extrinsic;
+    let points: Vec<PointXyzRgbaNormal> = cloud
+        .points
+        .iter()
+        .map(|p| PointXyzRgbaNormal {
+            x: m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3],
+            y: m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3],
+            z: m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3],
+            nx: m[0][0] * p.nx + m[0][1] * p.ny + m[0][2] * p.nz,
+            ny: m[1][0] * p.nx + m[1][1] * p.ny + m[1][2] * p.nz,
+            nz: m[2][0] * p.nx + m[2][1] * p.ny + m[2][2] * p.nz,
+            ..*p
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// Inverse of a rigid extrinsic: the transposed rotation and the translation moved back by it.
+pub fn invert_rigid_extrinsic(extrinsic: &Extrinsic) -> Extrinsic {
+    let mut inverse = [[0.0; 4]; 4];
+    for (row, values) in inverse.iter_mut().take(3).enumerate() {
+        for (col, value) in values.iter_mut().take(3).enumerate() {
+            *value = extrinsic[col][row];
+        }
+        values[3] = -(0..3)
+            .map(|k| extrinsic[k][row] * extrinsic[k][3])
+            .sum::<f32>();
+    }
+    inverse[3][3] = 1.0;
+    inverse
+}
+
+/// Transforms every view into the common frame and merges them in order, dropping the points
+/// within `epsilon` of a point from an earlier view.
+pub fn fuse_views(
+    views: &[PointCloud<PointXyzRgba>],
+    extrinsics: &[Extrinsic],
+    epsilon: f32,
+) -> PointCloud<PointXyzRgba> {
+    assert_eq!(
+        views.len(),
+        extrinsics.len(),
+        "Expecting one extrinsic per view"
+    );
+    views.iter().zip(extrinsics).fold(
+        PointCloud::from_points(vec![]),
+        |fused, (view, extrinsic)| fused.merge_dedup(&transform_cloud(view, extrinsic), epsilon),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(x: f32, y: f32, z: f32) -> PointXyzRgba {
+        PointXyzRgba {
+            x,
+            y,
+            z,
+            r: 10,
+            g: 20,
+            b: 30,
+            a: 255,
+        }
+    }
+
+    fn cloud(points: Vec<PointXyzRgba>) -> PointCloud<PointXyzRgba> {
+        PointCloud::from_points(points)
+    }
+
+    const IDENTITY: Extrinsic = [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+
+    #[test]
+    fn test_transform_cloud() {
+        // rotation of 90 degrees around z followed by a translation of (1, 2, 3)
+        let extrinsic = [
+            [0.0, -1.0, 0.0, 1.0],
+            [1.0, 0.0, 0.0, 2.0],
+            [0.0, 0.0, 1.0, 3.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        let transformed = transform_cloud(&cloud(vec![point(1.0, 0.0, 0.0)]), &extrinsic);
+        assert_eq!(transformed.points, vec![point(1.0, 3.0, 3.0)]);
+    }
+
+    #[test]
+    fn test_fuse_views() {
+        // both cameras see the point (1, 0, 0), the second camera sits at x = 1
+        let first = cloud(vec![point(1.0, 0.0, 0.0), point(0.0, 1.0, 0.0)]);
+        let second = cloud(vec![point(0.0, 0.0, 0.0), point(1.0, 0.0, 0.0)]);
+        let mut shift = IDENTITY;
+        shift[0][3] = 1.0;
+
+        let fused = fuse_views(&[first, second], &[IDENTITY, shift], 0.01);
+        assert_eq!(
+            fused.points,
+            vec![
+                point(1.0, 0.0, 0.0),
+                point(0.0, 1.0, 0.0),
+                point(2.0, 0.0, 0.0)
+            ]
+        );
+        assert_eq!(fused.len(), 3);
+    }
+
+    #[test]
+    fn test_compose_extrinsics() {
+        // rotation of 90 degrees around z, then a translation of (1, 0, 0)
+        let rotation = [
+            [0.0, -1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        let mut shift = IDENTITY;
+        shift[0][3] = 1.0;
+
+        let composed = compose_extrinsics(&shift, &rotation);
+        let transformed = transform_cloud(&cloud(vec![point(1.0, 0.0, 0.0)]), &composed);
+        assert_eq!(transformed.points, vec![point(1.0, 1.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_invert_rigid_extrinsic() {
+        // rotation of 90 degrees around z followed by a translation of (1, 2, 3)
+        let extrinsic = [
+            [0.0, -1.0, 0.0, 1.0],
+            [1.0, 0.0, 0.0, 2.0],
+            [0.0, 0.0, 1.0, 3.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        let points = vec![
+            PointXyzRgbaNormal {
+                x: 1.0,
+                y: -2.0,
+                z: 0.5,
+                r: 10,
+                g: 20,
+                b: 30,
+                a: 255,
+                nx: 1.0,
+                ny: 0.0,
+                nz: 0.0,
+            },
+            PointXyzRgbaNormal {
+                x: 0.0,
+                y: 4.0,
+                z: -1.0,
+                r: 40,
+                g: 50,
+                b: 60,
+                a: 255,
+                nx: 0.0,
+                ny: 0.6,
+                nz: 0.8,
+            },
+        ];
+        let cloud = PointCloud::from_points(points);
+
+        let moved = transform_cloud_normal(&cloud, &extrinsic);
+        assert_eq!((moved.points[0].nx, moved.points[0].ny), (0.0, 1.0));
+        let restored = transform_cloud_normal(&moved, &invert_rigid_extrinsic(&extrinsic));
+        assert_eq!(restored.points, cloud.points);
+        assert_eq!(
+            compose_extrinsics(&invert_rigid_extrinsic(&extrinsic), &extrinsic),
+            IDENTITY
+        );
+    }
+}
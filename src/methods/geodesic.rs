@@ -0,0 +1,107 @@
+use float_ord::FloatOrd;
+use kiddo::{distance::squared_euclidean, KdTree};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Shortest path distance from the source point to every point, along the graph connecting each
+/// point to the points within `radius`. Points which can't be reached get an infinite distance.
+pub fn geodesic_distances(
+    cloud: &PointCloud<PointXyzRgba>,
+    source: usize,
+    radius: f64,
+) -> Vec<f32> {
+    assert!(
+        source < cloud.points.len(),
+        "source index {} is out of bounds for a point cloud of {} points",
+        source,
+        cloud.points.len()
+    );
+
+    let mut tree = KdTree::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        tree.add(&p.xyz(), i).expect("Failed to add to tree");
+    }
+    let radius_squared = (radius * radius) as f32;
+
+    let mut distances = vec![f32::INFINITY; cloud.points.len()];
+    let mut heap = BinaryHeap::new();
+    distances[source] = 0.0;
+    heap.push(Reverse((FloatOrd(0.0f32), source)));
+
+    while let Some(Reverse((FloatOrd(distance), i))) = heap.pop() {
+        if distance > distances[i] {
+            // a shorter path to i was already expanded
+            continue;
+        }
+        let p = &cloud.points[i];
+        let neighbours = tree
+            .within(&p.xyz(), radius_squared, &squared_euclidean)
+            .expect("Failed to query tree");
+        for (edge_squared, &j) in neighbours {
+            let candidate = distance + edge_squared.sqrt();
+            if candidate < distances[j] {
+                distances[j] = candidate;
+                heap.push(Reverse((FloatOrd(candidate), j)));
+            }
+        }
+    }
+    distances
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(x: f32, y: f32, z: f32) -> PointXyzRgba {
+        PointXyzRgba {
+            x,
+            y,
+            z,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        }
+    }
+
+    fn distance(a: &PointXyzRgba, b: &PointXyzRgba) -> f32 {
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_flat_plane_is_close_to_euclidean() {
+        // 40x40 grid of spacing 0.1 on the z = 1 plane
+        let cloud = PointCloud::from_points(
+            (0..1600)
+                .map(|i| point((i / 40) as f32 * 0.1, (i % 40) as f32 * 0.1, 1.0))
+                .collect(),
+        );
+        let distances = geodesic_distances(&cloud, 0, 0.35);
+        assert_eq!(distances[0], 0.0);
+        for (p, &geodesic) in cloud.points.iter().zip(&distances) {
+            let euclidean = distance(&cloud.points[0], p);
+            assert!(geodesic >= euclidean - 1e-4);
+            assert!(
+                geodesic <= euclidean * 1.05 + 1e-4,
+                "{} {}",
+                geodesic,
+                euclidean
+            );
+        }
+    }
+
+    #[test]
+    fn test_disconnected_points_are_infinite() {
+        let cloud = PointCloud::from_points(vec![
+            point(0.0, 0.0, 0.0),
+            point(0.5, 0.0, 0.0),
+            point(1.0, 0.0, 0.0),
+            point(5.0, 0.0, 0.0),
+        ]);
+        let distances = geodesic_distances(&cloud, 2, 0.6);
+        assert_eq!(distances[..3], [1.0, 0.5, 0.0]);
+        assert!(distances[3].is_infinite());
+    }
+}
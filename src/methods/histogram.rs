@@ -0,0 +1,165 @@
+use plotters::prelude::*;
+use std::io::Write;
+use std::path::Path;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, pointxyzrgbanormal::PointXyzRgbaNormal};
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    X,
+    Y,
+    Z,
+    R,
+    G,
+    B,
+    A,
+    Nx,
+    Ny,
+    Nz,
+}
+
+impl Attribute {
+    pub fn is_normal(&self) -> bool {
+        matches!(self, Attribute::Nx | Attribute::Ny | Attribute::Nz)
+    }
+
+    /// Returns the attribute of the point, or `None` for a normal attribute.
+    pub fn of(&self, p: &PointXyzRgba) -> Option<f32> {
+        match self {
+            Attribute::X => Some(p.x),
+            Attribute::Y => Some(p.y),
+            Attribute::Z => Some(p.z),
+            Attribute::R => Some(p.r as f32),
+            Attribute::G => Some(p.g as f32),
+            Attribute::B => Some(p.b as f32),
+            Attribute::A => Some(p.a as f32),
+            Attribute::Nx | Attribute::Ny | Attribute::Nz => None,
+        }
+    }
+
+    pub fn of_normal(&self, p: &PointXyzRgbaNormal) -> f32 {
+        match self {
+            Attribute::X => p.x,
+            Attribute::Y => p.y,
+            Attribute::Z => p.z,
+            Attribute::R => p.r as f32,
+            Attribute::G => p.g as f32,
+            Attribute::B => p.b as f32,
+            Attribute::A => p.a as f32,
+            Attribute::Nx => p.nx,
+            Attribute::Ny => p.ny,
+            Attribute::Nz => p.nz,
+        }
+    }
+}
+
+/// Histogram with `counts.len()` bins of equal width starting at `min`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub min: f32,
+    pub bin_width: f32,
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Bins the values over their range, the maximum falls in the last bin.
+    /// Returns `None` if there are no values.
+    pub fn from_values(values: &[f32], bins: usize) -> Option<Self> {
+        assert!(bins > 0, "Expecting at least one bin");
+        let min = values.iter().copied().reduce(f32::min)?;
+        let max = values.iter().copied().reduce(f32::max)?;
+        // a constant attribute falls in the first bin
+        let range = if max > min { max - min } else { 1.0 };
+        let bin_width = range / bins as f32;
+
+        let mut counts = vec![0; bins];
+        for &v in values {
+            let bin = (((v - min) / bin_width) as usize).min(bins - 1);
+            counts[bin] += 1;
+        }
+        Some(Self {
+            min,
+            bin_width,
+            counts,
+        })
+    }
+
+    pub fn bin_centers(&self) -> impl Iterator<Item = f32> + '_ {
+        (0..self.counts.len()).map(|i| self.min + (i as f32 + 0.5) * self.bin_width)
+    }
+
+    /// Writes one `bin_center,count` row per bin.
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writeln!(writer, "bin_center,count")?;
+        for (center, count) in self.bin_centers().zip(&self.counts) {
+            writeln!(writer, "{},{}", center, count)?;
+        }
+        Ok(())
+    }
+
+    /// Draws the histogram as a bar chart, the highest bar spans the height of the image.
+    pub fn plot_png<P: AsRef<Path>>(
+        &self,
+        path: P,
+        width: u32,
+        height: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let root = BitMapBackend::new(path.as_ref(), (width, height)).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        let max_count = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        let bar_width = width as f32 / self.counts.len() as f32;
+        for (i, &count) in self.counts.iter().enumerate() {
+            let x0 = (i as f32 * bar_width) as i32;
+            let x1 = ((i + 1) as f32 * bar_width) as i32;
+            let y0 = height as i32 - (count as f32 / max_count as f32 * height as f32) as i32;
+            root.draw(&Rectangle::new(
+                [(x0, y0), (x1, height as i32)],
+                BLUE.filled(),
+            ))?;
+        }
+        root.present()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_uniform_histogram() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let points: Vec<PointXyzRgba> = (0..100_000)
+            .map(|_| PointXyzRgba {
+                x: rng.gen_range(-1.0..1.0),
+                y: rng.gen_range(-1.0..1.0),
+                z: rng.gen_range(-1.0..1.0),
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            })
+            .collect();
+        let values: Vec<f32> = points.iter().filter_map(|p| Attribute::Y.of(p)).collect();
+
+        assert_eq!(Attribute::Nx.of(&points[0]), None);
+
+        let histogram = Histogram::from_values(&values, 10).unwrap();
+        assert_eq!(histogram.counts.iter().sum::<usize>(), 100_000);
+        for &count in &histogram.counts {
+            assert!((9_000..=11_000).contains(&count), "{}", count);
+        }
+        let centers: Vec<f32> = histogram.bin_centers().collect();
+        assert!((centers[0] + 0.9).abs() < 1e-3);
+        assert!((centers[9] - 0.9).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_constant_values() {
+        let histogram = Histogram::from_values(&[3.0; 5], 4).unwrap();
+        assert_eq!(histogram.counts, vec![5, 0, 0, 0]);
+        assert!(Histogram::from_values(&[], 4).is_none());
+    }
+}
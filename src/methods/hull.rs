@@ -0,0 +1,77 @@
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+fn cross(o: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+/// Convex hull of the points projected onto the XY plane, with Andrew's monotone chain algorithm.
+/// Returns the indices of the hull vertices in counter-clockwise order, starting from the vertex
+/// with the lowest x (then y). Points on the edges of the hull are not vertices.
+pub fn convex_hull_xy(cloud: &PointCloud<PointXyzRgba>) -> Vec<usize> {
+    let xy = |i: usize| (cloud.points[i].x, cloud.points[i].y);
+    let mut order: Vec<usize> = (0..cloud.points.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (xy(a), xy(b));
+        a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1))
+    });
+    order.dedup_by(|a, b| xy(*a) == xy(*b));
+    if order.len() < 3 {
+        return order;
+    }
+
+    let mut hull: Vec<usize> = Vec::with_capacity(2 * order.len());
+    // lower hull, then upper hull, each ending at the first point of the other
+    for pass in [order.clone(), order.iter().rev().copied().collect()] {
+        let start = hull.len();
+        for i in pass {
+            while hull.len() >= start + 2
+                && cross(xy(hull[hull.len() - 2]), xy(hull[hull.len() - 1]), xy(i)) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(i);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(x: f32, y: f32, z: f32) -> PointXyzRgba {
+        PointXyzRgba {
+            x,
+            y,
+            z,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        }
+    }
+
+    #[test]
+    fn test_square_has_four_vertices() {
+        // 5x5 grid at two heights, the hull has the corners only
+        let points: Vec<PointXyzRgba> = (0..50)
+            .map(|i| point((i % 5) as f32, (i % 25 / 5) as f32, (i / 25) as f32))
+            .collect();
+        let cloud = PointCloud::from_points(points);
+        let hull: Vec<(f32, f32)> = convex_hull_xy(&cloud)
+            .into_iter()
+            .map(|i| (cloud.points[i].x, cloud.points[i].y))
+            .collect();
+        assert_eq!(hull, vec![(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+    }
+
+    #[test]
+    fn test_degenerate_clouds() {
+        let collinear =
+            PointCloud::from_points((0..5).map(|i| point(i as f32, i as f32, 0.0)).collect());
+        assert_eq!(convex_hull_xy(&collinear), vec![0, 4]);
+        let single = PointCloud::from_points(vec![point(1.0, 1.0, 0.0), point(1.0, 1.0, 2.0)]);
+        assert_eq!(convex_hull_xy(&single).len(), 1);
+    }
+}
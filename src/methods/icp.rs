@@ -0,0 +1,596 @@
+use cgmath::{InnerSpace, Matrix3, Quaternion, SquareMatrix, Vector3, Zero};
+use kiddo::KdTree;
+
+use super::fusion::{compose_extrinsics, rigid_extrinsic, transform_cloud, Extrinsic};
+use super::sampling::voxel_grid_sample;
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Iterations stop once the rotation angle and the translation of a step fall below this.
+const CONVERGENCE: f64 = 1e-6;
+/// Leaf sizes of the coarse levels of multi-scale registration, in units of the leaf size.
+const COARSE_LEVELS: [f32; 2] = [8.0, 4.0];
+
+/// Weighting of the correspondences by the distance between their points, which downweights
+/// the far ones, likely outliers.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weighting {
+    /// every correspondence weighs 1
+    None,
+    /// min(1, delta / distance), quadratic cost up to delta and linear beyond
+    Huber,
+    /// (delta^2 / (delta^2 + distance^2))^2, the far correspondences weighing next to nothing
+    #[value(name = "geman_mcclure")]
+    GemanMcclure,
+}
+
+impl Weighting {
+    /// Weight of a correspondence between points `distance` apart, 1 at distance 0.
+    pub fn weight(&self, distance: f64, delta: f64) -> f64 {
+        match self {
+            Weighting::None => 1.0,
+            Weighting::Huber if distance > delta => delta / distance,
+            Weighting::Huber => 1.0,
+            Weighting::GemanMcclure => {
+                let ratio = delta * delta / (delta * delta + distance * distance);
+                ratio * ratio
+            }
+        }
+    }
+}
+
+/// Point-to-point iterative closest point registration.
+pub struct Icp {
+    pub max_iterations: usize,
+    /// fraction of the correspondences, the closest ones, used at every iteration, which trims
+    /// the points outside the overlap of partially overlapping clouds. 1 for standard ICP.
+    pub overlap_ratio: f32,
+    /// weight of the color distance, with colors in [0, 1], added to the geometric distance of
+    /// a correspondence. 0 for geometry only.
+    pub color_weight: f32,
+    /// weighting of the kept correspondences in the rigid transform of every iteration
+    pub weighting: Weighting,
+    /// distance from which `weighting` downweights a correspondence
+    pub weighting_delta: f32,
+}
+
+impl Icp {
+    /// Finds the rigid transform aligning the source cloud to the reference cloud.
+    ///
+    /// Every iteration pairs each source point with the reference point minimizing the geometric
+    /// distance plus `color_weight` times the color distance, keeps the closest `overlap_ratio` of
+    /// the pairs, and moves the source by the rigid transform best aligning the kept pairs, each
+    /// weighted by `weighting` of its distance.
+    pub fn register(
+        &self,
+        reference: &PointCloud<PointXyzRgba>,
+        source: &PointCloud<PointXyzRgba>,
+    ) -> Extrinsic {
+        assert!(
+            self.overlap_ratio > 0.0 && self.overlap_ratio <= 1.0,
+            "Expecting an overlap ratio in (0, 1]"
+        );
+        let mut rotation = Matrix3::identity();
+        let mut translation = Vector3::zero();
+        if reference.points.is_empty() || source.points.is_empty() {
+            return rigid_extrinsic(&rotation, &translation);
+        }
+
+        let color = |p: &PointXyzRgba| {
+            let scale = self.color_weight / 255.0;
+            [p.r as f32 * scale, p.g as f32 * scale, p.b as f32 * scale]
+        };
+        let mut tree: KdTree<f32, usize, 6> = KdTree::new();
+        for (i, p) in reference.points.iter().enumerate() {
+            let [r, g, b] = color(p);
+            tree.add(&[p.x, p.y, p.z, r, g, b], i)
+                .expect("Should be able to add point to kd tree");
+        }
+        let colors: Vec<[f32; 3]> = source.points.iter().map(color).collect();
+        let targets: Vec<Vector3<f64>> = reference.points.iter().map(to_vector).collect();
+        let mut points: Vec<Vector3<f64>> = source.points.iter().map(to_vector).collect();
+        let keep = ((self.overlap_ratio * points.len() as f32).ceil() as usize)
+            .clamp(3.min(points.len()), points.len());
+
+        for _ in 0..self.max_iterations {
+            let mut correspondences: Vec<(f32, usize, usize)> = points
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let [r, g, b] = colors[i];
+                    let query = [p.x as f32, p.y as f32, p.z as f32, r, g, b];
+                    let nearest = tree
+                        .nearest(&query, 1, &geometry_color_distance)
+                        .expect("Failed to query tree");
+                    (nearest[0].0, i, *nearest[0].1)
+                })
+                .collect();
+            correspondences.sort_by(|a, b| a.0.total_cmp(&b.0));
+            correspondences.truncate(keep);
+
+            let (from, to): (Vec<_>, Vec<_>) = correspondences
+                .iter()
+                .map(|&(_, i, j)| (points[i], targets[j]))
+                .unzip();
+            let weights: Vec<f64> = from
+                .iter()
+                .zip(&to)
+                .map(|(p, q)| {
+                    self.weighting
+                        .weight((p - q).magnitude(), self.weighting_delta as f64)
+                })
+                .collect();
+            let (step_rotation, step_translation) =
+                weighted_best_rigid_transform(&from, &to, &weights);
+            for p in points.iter_mut() {
+                *p = step_rotation * *p + step_translation;
+            }
+            rotation = step_rotation * rotation;
+            translation = step_rotation * translation + step_translation;
+
+            let angle = ((step_rotation.trace() - 1.0) / 2.0)
+                .clamp(-1.0, 1.0)
+                .acos();
+            if angle < CONVERGENCE && step_translation.magnitude() < CONVERGENCE {
+                break;
+            }
+        }
+        rigid_extrinsic(&rotation, &translation)
+    }
+
+    /// Registers voxel grid samples of the clouds with leaf sizes of 8 and 4 times `leaf_size`,
+    /// then the full clouds, each level starting from the transform of the coarser one.
+    /// The coarse levels have fewer points and a wider basin of convergence.
+    pub fn register_multi_scale(
+        &self,
+        reference: &PointCloud<PointXyzRgba>,
+        source: &PointCloud<PointXyzRgba>,
+        leaf_size: f32,
+    ) -> Extrinsic {
+        let mut extrinsic = rigid_extrinsic(&Matrix3::identity(), &Vector3::zero());
+        for scale in COARSE_LEVELS {
+            let coarse_reference = voxel_grid_sample(reference, leaf_size * scale);
+            let coarse_source =
+                transform_cloud(&voxel_grid_sample(source, leaf_size * scale), &extrinsic);
+            let step = self.register(&coarse_reference, &coarse_source);
+            extrinsic = compose_extrinsics(&step, &extrinsic);
+        }
+        let step = self.register(reference, &transform_cloud(source, &extrinsic));
+        compose_extrinsics(&step, &extrinsic)
+    }
+}
+
+/// Euclidean distance of the positions plus Euclidean distance of the scaled colors.
+///
+/// It is never more than the distance to a point of a bounding box from the point clamped into
+/// it, so the kd tree can prune with it.
+fn geometry_color_distance(a: &[f32; 6], b: &[f32; 6]) -> f32 {
+    let norm = |range: std::ops::Range<usize>| {
+        range
+            .map(|i| (a[i] - b[i]) * (a[i] - b[i]))
+            .sum::<f32>()
+            .sqrt()
+    };
+    norm(0..3) + norm(3..6)
+}
+
+fn to_vector(p: &PointXyzRgba) -> Vector3<f64> {
+    Vector3::new(p.x as f64, p.y as f64, p.z as f64)
+}
+
+/// Rotation and translation minimizing the squared distances from the moved `from` points to the
+/// `to` points, by Horn's closed form: the rotation is the unit quaternion of the largest
+/// eigenvalue of a 4x4 matrix built from the cross covariance of the points.
+pub fn best_rigid_transform(
+    from: &[Vector3<f64>],
+    to: &[Vector3<f64>],
+) -> (Matrix3<f64>, Vector3<f64>) {
+    weighted_best_rigid_transform(from, to, &vec![1.0; from.len()])
+}
+
+/// Rotation and translation minimizing the sum of the squared distances from the moved `from`
+/// points to the `to` points, each multiplied by its weight. The centroids are weighted, and so
+/// is the cross covariance of [`best_rigid_transform`].
+pub fn weighted_best_rigid_transform(
+    from: &[Vector3<f64>],
+    to: &[Vector3<f64>],
+    weights: &[f64],
+) -> (Matrix3<f64>, Vector3<f64>) {
+    assert_eq!(from.len(), to.len(), "Expecting pairs of points");
+    assert_eq!(from.len(), weights.len(), "Expecting a weight per pair");
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return (Matrix3::identity(), Vector3::zero());
+    }
+    let from_centroid = from
+        .iter()
+        .zip(weights)
+        .fold(Vector3::zero(), |sum, (&p, &w)| sum + p * w)
+        / total;
+    let to_centroid = to
+        .iter()
+        .zip(weights)
+        .fold(Vector3::zero(), |sum, (&q, &w)| sum + q * w)
+        / total;
+
+    // s[a][b] is the weighted sum of the products of coordinate a of `from` and coordinate b of
+    // `to`
+    let mut s = [[0.0; 3]; 3];
+    for ((p, q), &w) in from.iter().zip(to).zip(weights) {
+        let (p, q) = (*p - from_centroid, *q - to_centroid);
+        for (a, row) in s.iter_mut().enumerate() {
+            for (b, value) in row.iter_mut().enumerate() {
+                *value += w * p[a] * q[b];
+            }
+        }
+    }
+    let [[xx, xy, xz], [yx, yy, yz], [zx, zy, zz]] = s;
+    let m = [
+        [xx + yy + zz, yz - zy, zx - xz, xy - yx],
+        [yz - zy, xx - yy - zz, xy + yx, zx + xz],
+        [zx - xz, xy + yx, -xx + yy - zz, yz + zy],
+        [xy - yx, zx + xz, yz + zy, -xx - yy + zz],
+    ];
+    let [w, x, y, z] = largest_eigenvector(m);
+    let rotation = Matrix3::from(Quaternion::new(w, x, y, z).normalize());
+    (rotation, to_centroid - rotation * from_centroid)
+}
+
+/// Rigid transform best aligning each point of `from` to the point of `to` at the same index,
+/// e.g. matched keypoints.
+pub fn matched_points_transform(
+    from: &PointCloud<PointXyzRgba>,
+    to: &PointCloud<PointXyzRgba>,
+) -> Extrinsic {
+    assert_eq!(
+        from.points.len(),
+        to.points.len(),
+        "Expecting the same number of points in both clouds"
+    );
+    let from: Vec<Vector3<f64>> = from.points.iter().map(to_vector).collect();
+    let to: Vec<Vector3<f64>> = to.points.iter().map(to_vector).collect();
+    let (rotation, translation) = best_rigid_transform(&from, &to);
+    rigid_extrinsic(&rotation, &translation)
+}
+
+/// Eigenvector of the largest eigenvalue of a symmetric matrix, by cyclic Jacobi rotations.
+fn largest_eigenvector(mut a: [[f64; 4]; 4]) -> [f64; 4] {
+    let mut v = [[0.0; 4]; 4];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+    for _ in 0..50 {
+        let off_diagonal: f64 = (0..4)
+            .flat_map(|i| (0..4).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        if off_diagonal < 1e-24 {
+            break;
+        }
+        for p in 0..4 {
+            for q in p + 1..4 {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                // rotation zeroing a[p][q]
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+                for k in 0..4 {
+                    let (pk, qk) = (a[p][k], a[q][k]);
+                    a[p][k] = c * pk - s * qk;
+                    a[q][k] = s * pk + c * qk;
+                }
+                for row in v.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+            }
+        }
+    }
+    let largest = (0..4)
+        .max_by(|&i, &j| a[i][i].total_cmp(&a[j][j]))
+        .unwrap_or(0);
+    [v[0][largest], v[1][largest], v[2][largest], v[3][largest]]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cgmath::Rad;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Evenly spread points on the unit sphere, `offset` shifts the samples.
+    fn sphere(n: usize, offset: f32) -> Vec<PointXyzRgba> {
+        let golden_angle = std::f32::consts::PI * (3.0 - 5f32.sqrt());
+        (0..n)
+            .map(|i| {
+                let y = 1.0 - 2.0 * (i as f32 + offset) / n as f32;
+                let r = (1.0 - y * y).sqrt();
+                let theta = golden_angle * i as f32;
+                PointXyzRgba {
+                    x: r * theta.cos(),
+                    y,
+                    z: r * theta.sin(),
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_best_rigid_transform() {
+        let from: Vec<Vector3<f64>> = (0..20)
+            .map(|i| Vector3::new((i % 3) as f64, (i % 5) as f64, (i % 7) as f64))
+            .collect();
+        let rotation = Matrix3::from_axis_angle(Vector3::new(1.0, -2.0, 0.5).normalize(), Rad(0.7));
+        let translation = Vector3::new(0.1, 0.2, -0.3);
+        let to: Vec<Vector3<f64>> = from.iter().map(|&p| rotation * p + translation).collect();
+
+        let (r, t) = best_rigid_transform(&from, &to);
+        for col in 0..3 {
+            assert!((r[col] - rotation[col]).magnitude() < 1e-9);
+        }
+        assert!((t - translation).magnitude() < 1e-9);
+    }
+
+    #[test]
+    fn test_matched_points_transform() {
+        let from = PointCloud::from_points(sphere(100, 0.5));
+        // rotation of 90 degrees around z followed by a translation of (1, 2, 3)
+        let extrinsic = [
+            [0.0, -1.0, 0.0, 1.0],
+            [1.0, 0.0, 0.0, 2.0],
+            [0.0, 0.0, 1.0, 3.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        let to = transform_cloud(&from, &extrinsic);
+
+        let estimated = matched_points_transform(&from, &to);
+        for (row, values) in estimated.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                assert!(
+                    (value - extrinsic[row][col]).abs() < 1e-5,
+                    "{:?}",
+                    estimated
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_trimmed_icp_half_spheres() {
+        // each cloud covers two thirds of the sphere, half of each cloud is in the overlap
+        let reference = PointCloud::from_points(
+            sphere(3000, 0.5)
+                .into_iter()
+                .filter(|p| p.y >= -1.0 / 3.0)
+                .collect(),
+        );
+        let source = PointCloud::from_points(
+            sphere(600, 0.25)
+                .into_iter()
+                .filter(|p| p.y <= 1.0 / 3.0)
+                .map(|p| PointXyzRgba {
+                    x: p.x + 0.02,
+                    y: p.y + 0.02,
+                    z: p.z + 0.02,
+                    ..p
+                })
+                .collect(),
+        );
+        // a sphere only constrains the translation, so the aligned points must lie on it
+        let radial_error = |icp: Icp| {
+            let aligned = transform_cloud(&source, &icp.register(&reference, &source));
+            aligned
+                .points
+                .iter()
+                .map(|p| (Vector3::new(p.x, p.y, p.z).magnitude() - 1.0).abs())
+                .fold(0.0, f32::max)
+        };
+
+        let trimmed = radial_error(Icp {
+            max_iterations: 100,
+            overlap_ratio: 0.4,
+            color_weight: 0.0,
+            weighting: Weighting::None,
+            weighting_delta: 0.0,
+        });
+        assert!(trimmed < 0.015, "{}", trimmed);
+
+        // the points outside the overlap drag standard ICP off
+        let standard = radial_error(Icp {
+            max_iterations: 100,
+            overlap_ratio: 1.0,
+            color_weight: 0.0,
+            weighting: Weighting::None,
+            weighting_delta: 0.0,
+        });
+        assert!(standard > 0.1, "{}", standard);
+    }
+
+    #[test]
+    fn test_color_icp_flat_texture() {
+        // flat grid with red increasing along x and green along y
+        let reference = PointCloud::from_points(
+            (0..41 * 41)
+                .map(|i| {
+                    let (x, y) = ((i / 41) as f32 / 40.0, (i % 41) as f32 / 40.0);
+                    PointXyzRgba {
+                        x,
+                        y,
+                        z: 0.0,
+                        r: (x * 255.0) as u8,
+                        g: (y * 255.0) as u8,
+                        b: 0,
+                        a: 255,
+                    }
+                })
+                .collect(),
+        );
+        // slid within the plane, which the geometry alone cannot tell
+        let source = PointCloud::from_points(
+            reference
+                .points
+                .iter()
+                .map(|p| PointXyzRgba {
+                    x: p.x + 0.1,
+                    y: p.y + 0.05,
+                    ..*p
+                })
+                .collect(),
+        );
+        let error = |color_weight: f32| {
+            let icp = Icp {
+                max_iterations: 10,
+                overlap_ratio: 1.0,
+                color_weight,
+                weighting: Weighting::None,
+                weighting_delta: 0.0,
+            };
+            let aligned = transform_cloud(&source, &icp.register(&reference, &source));
+            aligned
+                .points
+                .iter()
+                .zip(&reference.points)
+                .map(|(a, r)| Vector3::new(a.x - r.x, a.y - r.y, a.z - r.z).magnitude())
+                .fold(0.0, f32::max)
+        };
+
+        let color = error(2.0);
+        assert!(color < 1e-3, "{}", color);
+        let geometry = error(0.0);
+        assert!(geometry > 0.05, "{}", geometry);
+    }
+
+    /// Random points on the surface of the box [0, 1] x [0, 0.8] x [0, 0.6].
+    fn box_surface(n: usize, rng: &mut StdRng) -> PointCloud<PointXyzRgba> {
+        let areas = [0.8, 0.8, 0.48, 0.48, 0.6, 0.6];
+        let points = (0..n)
+            .map(|_| {
+                let mut u = rng.gen_range(0.0..areas.iter().sum::<f32>());
+                let mut face = 0;
+                while face < 5 && u > areas[face] {
+                    u -= areas[face];
+                    face += 1;
+                }
+                let (s, t) = (rng.gen::<f32>(), rng.gen::<f32>());
+                let [x, y, z] = match face {
+                    0 => [s, t * 0.8, 0.0],
+                    1 => [s, t * 0.8, 0.6],
+                    2 => [0.0, s * 0.8, t * 0.6],
+                    3 => [1.0, s * 0.8, t * 0.6],
+                    4 => [s, 0.0, t * 0.6],
+                    _ => [s, 0.8, t * 0.6],
+                };
+                PointXyzRgba {
+                    x,
+                    y,
+                    z,
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                }
+            })
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_multi_scale_icp_far_apart() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let reference = box_surface(1500, &mut rng);
+        let original = box_surface(1500, &mut rng);
+        // farther than the diagonal of the box, about 1.41
+        let source = PointCloud::from_points(
+            original
+                .points
+                .iter()
+                .map(|p| PointXyzRgba {
+                    x: p.x + 2.03,
+                    y: p.y + 0.51,
+                    z: p.z + 0.27,
+                    ..*p
+                })
+                .collect(),
+        );
+
+        let icp = Icp {
+            max_iterations: 50,
+            overlap_ratio: 1.0,
+            color_weight: 0.0,
+            weighting: Weighting::None,
+            weighting_delta: 0.0,
+        };
+        let aligned = transform_cloud(
+            &source,
+            &icp.register_multi_scale(&reference, &source, 0.025),
+        );
+        for (a, p) in aligned.points.iter().zip(&original.points) {
+            let error = Vector3::new(a.x - p.x, a.y - p.y, a.z - p.z).magnitude();
+            assert!(error < 0.02, "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_huber_icp_outliers() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let reference = box_surface(1500, &mut rng);
+        // the reference turned by 0.1 radians around z and moved, with a tenth of its points
+        // pushed 0.2 to 0.4 off the surface, whose correspondences are outliers
+        let extrinsic = rigid_extrinsic(
+            &Matrix3::from_angle_z(Rad(0.1)),
+            &Vector3::new(0.05, -0.03, 0.02),
+        );
+        let moved = transform_cloud(&reference, &extrinsic);
+        let outliers = moved.points.len() / 10;
+        let inliers = moved.points.len() - outliers;
+        let mut points = moved.points.clone();
+        for p in points[inliers..].iter_mut() {
+            let direction = Vector3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0f32),
+            )
+            .normalize();
+            let offset = direction * rng.gen_range(0.2..0.4);
+            (p.x, p.y, p.z) = (p.x + offset.x, p.y + offset.y, p.z + offset.z);
+        }
+        let source = PointCloud::from_points(points);
+
+        let error = |weighting: Weighting, max_iterations: usize| {
+            let icp = Icp {
+                max_iterations,
+                overlap_ratio: 1.0,
+                color_weight: 0.0,
+                weighting,
+                weighting_delta: 0.02,
+            };
+            let aligned = transform_cloud(&source, &icp.register(&reference, &source));
+            aligned.points[..inliers]
+                .iter()
+                .zip(&reference.points)
+                .map(|(a, r)| Vector3::new(a.x - r.x, a.y - r.y, a.z - r.z).magnitude())
+                .fold(0.0, f32::max)
+        };
+        let iterations_to_converge =
+            |weighting: Weighting| (1..=30).find(|&n| error(weighting, n) < 1e-3);
+
+        // the outliers keep pulling unweighted icp off the alignment
+        let huber = iterations_to_converge(Weighting::Huber);
+        let unweighted = iterations_to_converge(Weighting::None);
+        assert!(huber.is_some(), "{}", error(Weighting::Huber, 30));
+        assert!(unweighted.map_or(true, |n| n > huber.unwrap()));
+        assert!(error(Weighting::GemanMcclure, 30) < 1e-3);
+    }
+}
@@ -0,0 +1,248 @@
+use std::fmt;
+
+use crate::formats::{pointxyzrgbanormal::PointXyzRgbaNormal, HasCoordinate, PointCloud};
+
+/// Normals with a magnitude outside `1 ± NORMAL_TOLERANCE` are denormalized.
+pub const NORMAL_TOLERANCE: f32 = 0.01;
+
+/// An integrity issue of a point cloud. The colors are stored as `u8` and are always in range.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    /// the point has a NaN or infinite coordinate
+    NonFiniteCoordinate(usize),
+    /// the normal of the point is not of unit length
+    DenormalizedNormal { index: usize, magnitude: f32 },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::NonFiniteCoordinate(index) => {
+                write!(f, "point {} has a non finite coordinate", index)
+            }
+            Issue::DenormalizedNormal { index, magnitude } => {
+                write!(f, "point {} has a normal of magnitude {}", index, magnitude)
+            }
+        }
+    }
+}
+
+/// Checks the coordinates of the cloud.
+pub fn validate<T: HasCoordinate>(cloud: &PointCloud<T>) -> Vec<Issue> {
+    let mut issues = vec![];
+    for (i, p) in cloud.points.iter().enumerate() {
+        let c = p.coordinate();
+        if !(c.x.is_finite() && c.y.is_finite() && c.z.is_finite()) {
+            issues.push(Issue::NonFiniteCoordinate(i));
+        }
+    }
+    issues
+}
+
+fn normal_magnitude(p: &PointXyzRgbaNormal) -> f32 {
+    (p.nx * p.nx + p.ny * p.ny + p.nz * p.nz).sqrt()
+}
+
+/// Checks the coordinates and the normals of the cloud.
+pub fn validate_normals(cloud: &PointCloud<PointXyzRgbaNormal>) -> Vec<Issue> {
+    let mut issues = validate(cloud);
+    for (i, p) in cloud.points.iter().enumerate() {
+        let magnitude = normal_magnitude(p);
+        // also catches NaN magnitudes
+        if !((1.0 - NORMAL_TOLERANCE)..=(1.0 + NORMAL_TOLERANCE)).contains(&magnitude) {
+            issues.push(Issue::DenormalizedNormal {
+                index: i,
+                magnitude,
+            });
+        }
+    }
+    issues
+}
+
+/// A fix applied by [`repair`] or [`repair_normals`], with the index of the point in the
+/// cloud before the repair.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Repair {
+    /// the point had a NaN or infinite coordinate and was removed
+    RemovedNonFinite(usize),
+    /// the normal of the point was scaled to unit length
+    NormalizedNormal(usize),
+    /// the normal of the point had no direction and the point was removed
+    RemovedZeroNormal(usize),
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Repair::RemovedNonFinite(index) => {
+                write!(f, "removed point {} with a non finite coordinate", index)
+            }
+            Repair::NormalizedNormal(index) => {
+                write!(f, "normalized the normal of point {}", index)
+            }
+            Repair::RemovedZeroNormal(index) => {
+                write!(f, "removed point {} with a zero length normal", index)
+            }
+        }
+    }
+}
+
+fn is_finite<T: HasCoordinate>(p: &T) -> bool {
+    let c = p.coordinate();
+    c.x.is_finite() && c.y.is_finite() && c.z.is_finite()
+}
+
+/// Removes the points for which `keep` returns false. `keep` records its repairs.
+fn retain_points<T>(
+    cloud: &mut PointCloud<T>,
+    mut keep: impl FnMut(usize, &mut T, &mut Vec<Repair>) -> bool,
+) -> Vec<Repair> {
+    let mut repairs = vec![];
+    let mut index = 0;
+    cloud.points.retain_mut(|p| {
+        index += 1;
+        keep(index - 1, p, &mut repairs)
+    });
+    repairs
+}
+
+/// Fixes the issues found by [`validate`] and returns the repairs in order. The points with a non
+/// finite coordinate are removed. The colors are stored as `u8` and need no clamping.
+pub fn repair<T: HasCoordinate>(cloud: &mut PointCloud<T>) -> Vec<Repair> {
+    retain_points(cloud, |i, p, repairs| {
+        if !is_finite(p) {
+            repairs.push(Repair::RemovedNonFinite(i));
+            return false;
+        }
+        true
+    })
+}
+
+/// Fixes the issues found by [`validate_normals`] and returns the repairs in order. Denormalized
+/// normals are normalized, the points with a non finite coordinate or with a zero length or non
+/// finite normal are removed.
+pub fn repair_normals(cloud: &mut PointCloud<PointXyzRgbaNormal>) -> Vec<Repair> {
+    retain_points(cloud, |i, p, repairs| {
+        if !is_finite(p) {
+            repairs.push(Repair::RemovedNonFinite(i));
+            return false;
+        }
+        let magnitude = normal_magnitude(p);
+        if !magnitude.is_finite() || magnitude <= f32::EPSILON {
+            repairs.push(Repair::RemovedZeroNormal(i));
+            return false;
+        }
+        if (magnitude - 1.0).abs() > NORMAL_TOLERANCE {
+            p.nx /= magnitude;
+            p.ny /= magnitude;
+            p.nz /= magnitude;
+            repairs.push(Repair::NormalizedNormal(i));
+        }
+        true
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::formats::pointxyzrgba::PointXyzRgba;
+
+    fn point(x: f32, nx: f32) -> PointXyzRgbaNormal {
+        PointXyzRgbaNormal {
+            x,
+            y: 0.0,
+            z: 0.0,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+            nx,
+            ny: 0.0,
+            nz: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_valid_cloud() {
+        let cloud = PointCloud::from_points(vec![point(0.0, 1.0), point(1.0, -0.995)]);
+        assert!(validate_normals(&cloud).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_cloud() {
+        let cloud = PointCloud::from_points(vec![
+            point(0.0, 1.0),
+            point(f32::NAN, 1.0),
+            point(2.0, 0.5),
+            point(f32::INFINITY, f32::NAN),
+        ]);
+        let issues = validate_normals(&cloud);
+        assert_eq!(issues.len(), 4);
+        assert_eq!(issues[0], Issue::NonFiniteCoordinate(1));
+        assert_eq!(issues[1], Issue::NonFiniteCoordinate(3));
+        assert_eq!(
+            issues[2],
+            Issue::DenormalizedNormal {
+                index: 2,
+                magnitude: 0.5
+            }
+        );
+        assert!(matches!(
+            issues[3],
+            Issue::DenormalizedNormal { index: 3, .. }
+        ));
+
+        let points = vec![PointXyzRgba {
+            x: 0.0,
+            y: f32::NEG_INFINITY,
+            z: 0.0,
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        }];
+        let cloud = PointCloud::from_points(points);
+        assert_eq!(validate(&cloud), vec![Issue::NonFiniteCoordinate(0)]);
+    }
+
+    #[test]
+    fn test_repair_normals() {
+        let mut cloud = PointCloud::from_points(vec![
+            point(0.0, 1.0),
+            point(f32::NAN, 1.0),
+            point(2.0, 0.0),
+            point(3.0, -2.0),
+        ]);
+        let repairs = repair_normals(&mut cloud);
+        assert_eq!(
+            repairs,
+            vec![
+                Repair::RemovedNonFinite(1),
+                Repair::RemovedZeroNormal(2),
+                Repair::NormalizedNormal(3)
+            ]
+        );
+        assert_eq!(cloud.len(), 2);
+        assert_eq!(cloud.points, vec![point(0.0, 1.0), point(3.0, -1.0)]);
+        assert!(validate_normals(&cloud).is_empty());
+        assert!(repair_normals(&mut cloud).is_empty());
+    }
+
+    #[test]
+    fn test_repair() {
+        let p = |x: f32| PointXyzRgba {
+            x,
+            y: 0.0,
+            z: 0.0,
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+        let mut cloud = PointCloud::from_points(vec![p(0.0), p(f32::INFINITY), p(2.0)]);
+        let repairs = repair(&mut cloud);
+        assert_eq!(repairs, vec![Repair::RemovedNonFinite(1)]);
+        assert_eq!(cloud.len(), 2);
+        assert_eq!(cloud.points, vec![p(0.0), p(2.0)]);
+    }
+}
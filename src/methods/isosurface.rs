@@ -0,0 +1,129 @@
+use cgmath::{InnerSpace, Point3};
+use isosurface::marching_cubes::MarchingCubes;
+use isosurface::source::Source;
+use rand::Rng;
+
+use crate::formats::grid::GridHeader;
+
+/// The grid as an `isosurface` source over the unit cube, each axis spanning the voxel centers.
+struct GridSource<'a> {
+    header: &'a GridHeader,
+    values: &'a [f32],
+}
+
+impl Source for GridSource<'_> {
+    fn sample(&self, x: f32, y: f32, z: f32) -> f32 {
+        let last = self.header.resolution - 1;
+        let [x, y, z] = [x, y, z].map(|v| (v * last as f32).round() as usize);
+        // marching cubes also samples one layer past the last one in z, which is outside
+        if z > last {
+            return f32::MAX;
+        }
+        self.values[self.header.index(x.min(last), y.min(last), z)]
+    }
+}
+
+/// Extracts the zero level set of a grid of values sampled at the voxel centers, e.g. a signed
+/// distance field from sdf-sample, as unoriented triangles, with the marching cubes of the
+/// `isosurface` crate. Negative values are inside.
+pub fn marching_cubes(header: &GridHeader, values: &[f32]) -> Vec<[Point3<f32>; 3]> {
+    if header.resolution < 2 {
+        return vec![];
+    }
+    let (mut vertices, mut indices) = (vec![], vec![]);
+    MarchingCubes::new(header.resolution).extract(
+        &GridSource { header, values },
+        &mut vertices,
+        &mut indices,
+    );
+
+    let first = header.voxel_center(0);
+    let extent = (header.resolution - 1) as f32 * header.voxel_size;
+    let vertex = |i: u32| {
+        let v = &vertices[3 * i as usize..3 * i as usize + 3];
+        Point3::new(
+            first.x + v[0] * extent,
+            first.y + v[1] * extent,
+            first.z + v[2] * extent,
+        )
+    };
+    indices
+        .chunks_exact(3)
+        .map(|t| [vertex(t[0]), vertex(t[1]), vertex(t[2])])
+        .collect()
+}
+
+/// Samples points uniformly on the surface of the triangles: each point picks a triangle with a
+/// probability proportional to its area, then a uniform random barycentric position in it.
+pub fn sample_triangles<R: Rng>(
+    triangles: &[[Point3<f32>; 3]],
+    num_points: usize,
+    rng: &mut R,
+) -> Vec<Point3<f32>> {
+    let mut cumulative_areas = Vec::with_capacity(triangles.len());
+    let mut total = 0.0;
+    for &[a, b, c] in triangles {
+        total += 0.5 * (b - a).cross(c - a).magnitude();
+        cumulative_areas.push(total);
+    }
+    if total <= 0.0 {
+        return vec![];
+    }
+
+    (0..num_points)
+        .map(|_| {
+            let target = rng.gen_range(0.0..total);
+            let i = cumulative_areas
+                .partition_point(|&area| area <= target)
+                .min(triangles.len() - 1);
+            let [a, b, c] = triangles[i];
+            // folding the unit square onto the triangle keeps the density uniform
+            let (mut u, mut v) = (rng.gen::<f32>(), rng.gen::<f32>());
+            if u + v > 1.0 {
+                (u, v) = (1.0 - u, 1.0 - v);
+            }
+            a + (b - a) * u + (c - a) * v
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cgmath::EuclideanSpace;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_sphere_surface_samples() {
+        let header = GridHeader::new(
+            Point3::new(-1.5, -1.5, -1.5),
+            Point3::new(1.5, 1.5, 1.5),
+            32,
+        );
+        let sdf: Vec<f32> = (0..header.num_voxels())
+            .map(|i| header.voxel_center(i).to_vec().magnitude() - 1.0)
+            .collect();
+        let triangles = marching_cubes(&header, &sdf);
+        assert!(!triangles.is_empty());
+
+        let points = sample_triangles(&triangles, 2000, &mut StdRng::seed_from_u64(3));
+        assert_eq!(points.len(), 2000);
+        for p in &points {
+            let error = (p.to_vec().magnitude() - 1.0).abs();
+            assert!(error <= 2.0 * header.voxel_size);
+        }
+        // the samples cover the sphere rather than a part of it
+        let centroid = points
+            .iter()
+            .fold(Point3::origin(), |c, p| c + p.to_vec() / 2000.0);
+        assert!(centroid.to_vec().magnitude() < 0.1);
+    }
+
+    #[test]
+    fn test_no_crossing() {
+        let header = GridHeader::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0), 4);
+        let triangles = marching_cubes(&header, &[1.0; 64]);
+        assert!(triangles.is_empty());
+        assert!(sample_triangles(&triangles, 10, &mut rand::thread_rng()).is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashMap;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// The Tableau 20 palette.
+const TABLEAU_20: [[u8; 3]; 20] = [
+    [31, 119, 180],
+    [174, 199, 232],
+    [255, 127, 14],
+    [255, 187, 120],
+    [44, 160, 44],
+    [152, 223, 138],
+    [214, 39, 40],
+    [255, 152, 150],
+    [148, 103, 189],
+    [197, 176, 213],
+    [140, 86, 75],
+    [196, 156, 148],
+    [227, 119, 194],
+    [247, 182, 210],
+    [127, 127, 127],
+    [199, 199, 199],
+    [188, 189, 34],
+    [219, 219, 141],
+    [23, 190, 207],
+    [158, 218, 229],
+];
+
+/// Assigns a distinct color to each label in the order the labels are first seen.
+///
+/// The first 20 labels take the Tableau 20 palette, later labels take random colors drawn from a
+/// generator seeded with `seed`, so the mapping is deterministic for a given seed and input.
+pub struct LabelPalette {
+    colors: HashMap<u32, [u8; 3]>,
+    rng: StdRng,
+}
+
+impl LabelPalette {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            colors: HashMap::new(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn color_of(&mut self, label: u32) -> [u8; 3] {
+        if let Some(&color) = self.colors.get(&label) {
+            return color;
+        }
+        let color = match TABLEAU_20.get(self.colors.len()) {
+            Some(&color) => color,
+            None => self.rng.gen(),
+        };
+        self.colors.insert(label, color);
+        color
+    }
+
+    /// Overwrites the red, green and blue channels of each point with the color of its label.
+    pub fn colorize(&mut self, cloud: &mut PointCloud<PointXyzRgba>, labels: &[u32]) {
+        for (p, &label) in cloud.points.iter_mut().zip(labels) {
+            let [r, g, b] = self.color_of(label);
+            p.r = r;
+            p.g = g;
+            p.b = b;
+        }
+    }
+}
+
+/// KITTI semantic segmentation classes, numbered by their Cityscapes train id: road, sidewalk,
+/// building, wall, fence, pole, traffic light, traffic sign, vegetation, terrain, sky, person,
+/// rider, car, truck, bus, train, motorcycle, bicycle.
+const KITTI: [[u8; 3]; 19] = [
+    [128, 64, 128],
+    [244, 35, 232],
+    [70, 70, 70],
+    [102, 102, 156],
+    [190, 153, 153],
+    [153, 153, 153],
+    [250, 170, 30],
+    [220, 220, 0],
+    [107, 142, 35],
+    [152, 251, 152],
+    [70, 130, 180],
+    [220, 20, 60],
+    [255, 0, 0],
+    [0, 0, 142],
+    [0, 0, 70],
+    [0, 60, 100],
+    [0, 80, 100],
+    [0, 0, 230],
+    [119, 11, 32],
+];
+
+/// ScanNet classes, numbered by their NYU40 id from 0 (unannotated) to 40 (otherprop).
+const SCANNET: [[u8; 3]; 41] = [
+    [0, 0, 0],
+    [174, 199, 232],
+    [152, 223, 138],
+    [31, 119, 180],
+    [255, 187, 120],
+    [188, 189, 34],
+    [140, 86, 75],
+    [255, 152, 150],
+    [214, 39, 40],
+    [197, 176, 213],
+    [148, 103, 189],
+    [196, 156, 148],
+    [23, 190, 207],
+    [178, 76, 76],
+    [247, 182, 210],
+    [66, 188, 102],
+    [219, 219, 141],
+    [140, 57, 197],
+    [202, 185, 52],
+    [51, 176, 203],
+    [200, 54, 131],
+    [92, 193, 61],
+    [78, 71, 183],
+    [172, 114, 82],
+    [255, 127, 14],
+    [91, 163, 138],
+    [153, 98, 156],
+    [140, 153, 101],
+    [158, 218, 229],
+    [100, 125, 154],
+    [178, 127, 135],
+    [120, 185, 128],
+    [146, 111, 194],
+    [44, 160, 44],
+    [112, 128, 144],
+    [96, 207, 209],
+    [227, 119, 194],
+    [213, 92, 176],
+    [94, 106, 211],
+    [82, 84, 163],
+    [100, 85, 144],
+];
+
+/// nuScenes lidarseg classes, numbered by their index from 0 (noise) to 31 (vehicle.ego).
+const NUSCENES: [[u8; 3]; 32] = [
+    [0, 0, 0],
+    [70, 130, 180],
+    [0, 0, 230],
+    [135, 206, 235],
+    [100, 149, 237],
+    [219, 112, 147],
+    [0, 0, 128],
+    [240, 128, 128],
+    [138, 43, 226],
+    [112, 128, 144],
+    [210, 105, 30],
+    [105, 105, 105],
+    [47, 79, 79],
+    [188, 143, 143],
+    [220, 20, 60],
+    [255, 127, 80],
+    [255, 69, 0],
+    [255, 158, 0],
+    [233, 150, 70],
+    [255, 83, 0],
+    [255, 215, 0],
+    [255, 61, 99],
+    [255, 140, 0],
+    [255, 99, 71],
+    [0, 207, 191],
+    [175, 0, 75],
+    [75, 0, 75],
+    [112, 180, 60],
+    [222, 184, 135],
+    [255, 228, 196],
+    [0, 175, 0],
+    [255, 240, 245],
+];
+
+/// A dataset whose semantic classes have canonical colors.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticDataset {
+    Kitti,
+    Scannet,
+    Nuscenes,
+}
+
+impl SemanticDataset {
+    pub fn palette(&self) -> &'static [[u8; 3]] {
+        match self {
+            SemanticDataset::Kitti => &KITTI,
+            SemanticDataset::Scannet => &SCANNET,
+            SemanticDataset::Nuscenes => &NUSCENES,
+        }
+    }
+
+    /// The canonical color of the class, black for a class the dataset does not define.
+    pub fn color_of(&self, class: u32) -> [u8; 3] {
+        self.palette()
+            .get(class as usize)
+            .copied()
+            .unwrap_or([0, 0, 0])
+    }
+
+    /// Overwrites the red, green and blue channels of each point with the color of its class.
+    pub fn colorize(&self, cloud: &mut PointCloud<PointXyzRgba>, labels: &[u32]) {
+        for (p, &label) in cloud.points.iter_mut().zip(labels) {
+            let [r, g, b] = self.color_of(label);
+            p.r = r;
+            p.g = g;
+            p.b = b;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cloud(n: usize) -> PointCloud<PointXyzRgba> {
+        let points: Vec<PointXyzRgba> = (0..n)
+            .map(|i| PointXyzRgba {
+                x: i as f32,
+                y: 0.0,
+                z: 0.0,
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            })
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_same_label_same_color() {
+        let mut pc = cloud(4);
+        let mut palette = LabelPalette::new(0);
+        palette.colorize(&mut pc, &[7, 3, 7, 3]);
+        let rgb = |p: &PointXyzRgba| [p.r, p.g, p.b];
+        assert_eq!(rgb(&pc.points[0]), rgb(&pc.points[2]));
+        assert_eq!(rgb(&pc.points[1]), rgb(&pc.points[3]));
+        assert_ne!(rgb(&pc.points[0]), rgb(&pc.points[1]));
+        assert_eq!(rgb(&pc.points[0]), TABLEAU_20[0]);
+    }
+
+    #[test]
+    fn test_random_colors_are_seeded() {
+        let labels: Vec<u32> = (0..30).collect();
+        let (mut a, mut b) = (cloud(30), cloud(30));
+        LabelPalette::new(42).colorize(&mut a, &labels);
+        LabelPalette::new(42).colorize(&mut b, &labels);
+        assert_eq!(a.points, b.points);
+    }
+
+    #[test]
+    fn test_semantic_palettes() {
+        // road, pedestrian and car
+        assert_eq!(SemanticDataset::Kitti.color_of(0), [128, 64, 128]);
+        assert_eq!(SemanticDataset::Kitti.color_of(11), [220, 20, 60]);
+        assert_eq!(SemanticDataset::Kitti.color_of(13), [0, 0, 142]);
+        assert_eq!(SemanticDataset::Kitti.color_of(19), [0, 0, 0]);
+
+        let mut pc = cloud(2);
+        SemanticDataset::Scannet.colorize(&mut pc, &[1, 41]);
+        assert_eq!(
+            (pc.points[0].r, pc.points[0].g, pc.points[0].b),
+            (174, 199, 232)
+        );
+        assert_eq!((pc.points[1].r, pc.points[1].g, pc.points[1].b), (0, 0, 0));
+    }
+}
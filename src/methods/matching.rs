@@ -0,0 +1,79 @@
+use kiddo::{distance::squared_euclidean, KdTree};
+
+use crate::methods::fpfh::{Fpfh, FPFH_SIZE};
+
+fn build_tree(descriptors: &[Fpfh]) -> KdTree<f32, usize, FPFH_SIZE> {
+    let mut tree = KdTree::new();
+    for (i, d) in descriptors.iter().enumerate() {
+        tree.add(d, i).expect("Failed to add descriptor to kd tree");
+    }
+    tree
+}
+
+/// Finds putative correspondences between two sets of descriptors by mutual nearest neighbour
+/// matching: `(i, j)` is kept if `target[j]` is the nearest neighbour of `source[i]` and vice versa.
+///
+/// Ambiguous matches are rejected with Lowe's ratio test, the distance to the nearest neighbour
+/// must be less than `ratio_threshold` times the distance to the second nearest neighbour.
+pub fn match_descriptors(
+    source: &[Fpfh],
+    target: &[Fpfh],
+    ratio_threshold: f32,
+) -> Vec<(usize, usize)> {
+    if source.is_empty() || target.is_empty() {
+        return vec![];
+    }
+    let source_tree = build_tree(source);
+    let target_tree = build_tree(target);
+
+    let mut correspondences = vec![];
+    for (i, d) in source.iter().enumerate() {
+        let nearest = target_tree
+            .nearest(d, 2, &squared_euclidean)
+            .expect("Failed to query kd tree");
+        let (dist_sq, &j) = nearest[0];
+        if let Some(&(second_dist_sq, _)) = nearest.get(1) {
+            if dist_sq.sqrt() >= ratio_threshold * second_dist_sq.sqrt() {
+                continue;
+            }
+        }
+
+        let back = source_tree
+            .nearest(&target[j], 1, &squared_euclidean)
+            .expect("Failed to query kd tree");
+        if *back[0].1 == i {
+            correspondences.push((i, j));
+        }
+    }
+    correspondences
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn descriptor(seed: usize) -> Fpfh {
+        let mut d = [0f32; FPFH_SIZE];
+        d[seed % FPFH_SIZE] = 100.0;
+        d[(seed * 7 + 3) % FPFH_SIZE] += 50.0;
+        d
+    }
+
+    #[test]
+    fn test_match_descriptors_permutation() {
+        let source: Vec<Fpfh> = (0..20).map(descriptor).collect();
+        let target: Vec<Fpfh> = (0..20).rev().map(descriptor).collect();
+        let mut correspondences = match_descriptors(&source, &target, 0.8);
+        correspondences.sort();
+        let expected: Vec<(usize, usize)> = (0..20).map(|i| (i, 19 - i)).collect();
+        assert_eq!(correspondences, expected);
+    }
+
+    #[test]
+    fn test_match_descriptors_ratio_test() {
+        let source = vec![descriptor(0), descriptor(1)];
+        // descriptor(0) has two identical candidates and is rejected as ambiguous
+        let target = vec![descriptor(0), descriptor(0), descriptor(1)];
+        assert_eq!(match_descriptors(&source, &target, 0.8), vec![(1, 2)]);
+    }
+}
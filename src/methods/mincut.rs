@@ -0,0 +1,196 @@
+use kiddo::{distance::squared_euclidean, KdTree};
+use petgraph::algo::dinics;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Edge weights in `(0, 1]` are scaled by this and rounded to integer capacities.
+const CAPACITY_SCALE: f64 = (1 << 24) as f64;
+
+/// Reads seed positions from a json array of `[x, y, z]` coordinates.
+pub fn read_seeds<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<[f32; 3]>> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+fn build_tree(cloud: &PointCloud<PointXyzRgba>) -> KdTree<f32, usize, 3> {
+    let mut tree = KdTree::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        tree.add(&p.xyz(), i).expect("Failed to add to tree");
+    }
+    tree
+}
+
+/// Nodes on the source side of the minimum cut, those reachable from the source in the residual
+/// graph of a maximum flow.
+fn source_side(graph: &DiGraph<(), u64>, flows: &[u64], source: NodeIndex) -> Vec<bool> {
+    let mut reached = vec![false; graph.node_count()];
+    reached[source.index()] = true;
+    let mut queue = VecDeque::from([source]);
+    while let Some(u) = queue.pop_front() {
+        let forward = graph
+            .edges_directed(u, Direction::Outgoing)
+            .filter(|e| flows[e.id().index()] < *e.weight())
+            .map(|e| e.target());
+        let backward = graph
+            .edges_directed(u, Direction::Incoming)
+            .filter(|e| flows[e.id().index()] > 0)
+            .map(|e| e.source());
+        for v in forward.chain(backward).collect::<Vec<_>>() {
+            if !reached[v.index()] {
+                reached[v.index()] = true;
+                queue.push_back(v);
+            }
+        }
+    }
+    reached
+}
+
+/// Binary segmentation with a minimum cut of the k nearest neighbour graph of the cloud.
+///
+/// Each point is connected to its `k` nearest neighbours with a capacity of
+/// `exp(-d^2 / (2 sigma^2))`, the foreground seeds to the source and the background seeds to the
+/// sink with an infinite capacity. The maximum flow is computed with Dinic's algorithm of
+/// petgraph, on capacities scaled to integers by `CAPACITY_SCALE`, and the points on the source
+/// side of the minimum cut are the foreground. Returns whether each point is in the foreground.
+pub fn min_cut_segment(
+    cloud: &PointCloud<PointXyzRgba>,
+    foreground: &[usize],
+    background: &[usize],
+    k: usize,
+    sigma: f32,
+) -> Vec<bool> {
+    let n = cloud.points.len();
+    if n == 0 {
+        return vec![];
+    }
+    let mut graph = DiGraph::<(), u64>::new();
+    let nodes: Vec<NodeIndex> = (0..n).map(|_| graph.add_node(())).collect();
+    let (source, sink) = (graph.add_node(()), graph.add_node(()));
+
+    let tree = build_tree(cloud);
+    let two_sigma2 = 2.0 * (sigma as f64).powi(2);
+    let mut total = 0;
+    for (i, p) in cloud.points.iter().enumerate() {
+        let neighbours = tree
+            .nearest(&p.xyz(), k + 1, &squared_euclidean)
+            .expect("Failed to query tree");
+        for (d2, &j) in neighbours {
+            // each undirected edge is added once from each side, with half the weight
+            if j != i {
+                let weight = (-(d2 as f64) / two_sigma2).exp() / 2.0;
+                let capacity = (weight * CAPACITY_SCALE).round() as u64;
+                graph.add_edge(nodes[i], nodes[j], capacity);
+                graph.add_edge(nodes[j], nodes[i], capacity);
+                total += 2 * capacity;
+            }
+        }
+    }
+    // more than all the edges between points together, without overflowing the total flow
+    let infinite = total + 1;
+    for &i in foreground {
+        graph.add_edge(source, nodes[i], infinite);
+    }
+    for &i in background {
+        graph.add_edge(nodes[i], sink, infinite);
+    }
+
+    let (_, flows) = dinics(&graph, source, sink);
+    let mut is_foreground = source_side(&graph, &flows, source);
+    is_foreground.truncate(n);
+    is_foreground
+}
+
+/// Index of the point nearest to each seed.
+pub fn nearest_points(cloud: &PointCloud<PointXyzRgba>, seeds: &[[f32; 3]]) -> Vec<usize> {
+    if cloud.points.is_empty() {
+        return vec![];
+    }
+    let tree = build_tree(cloud);
+    seeds
+        .iter()
+        .map(|seed| {
+            let nearest = tree
+                .nearest(seed, 1, &squared_euclidean)
+                .expect("Failed to query tree");
+            *nearest[0].1
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Evenly spread points on the unit sphere with a Fibonacci lattice.
+    fn sphere(center_x: f32, n: usize) -> Vec<PointXyzRgba> {
+        let golden_angle = std::f32::consts::PI * (3.0 - 5f32.sqrt());
+        (0..n)
+            .map(|i| {
+                let y = 1.0 - 2.0 * (i as f32 + 0.5) / n as f32;
+                let r = (1.0 - y * y).sqrt();
+                let theta = golden_angle * i as f32;
+                PointXyzRgba {
+                    x: center_x + r * theta.cos(),
+                    y,
+                    z: r * theta.sin(),
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_touching_spheres() {
+        // the spheres almost touch around x = 1, closer than the spacing of the points
+        let mut points = sphere(0.0, 1000);
+        points.extend(sphere(2.1, 1000));
+        let cloud = PointCloud::from_points(points);
+
+        let foreground: Vec<usize> = (0..1000).filter(|&i| cloud[i].x < -0.5).collect();
+        let background: Vec<usize> = (1000..2000).filter(|&i| cloud[i].x > 2.6).collect();
+        let labels = min_cut_segment(&cloud, &foreground, &background, 8, 0.1);
+
+        let correct = labels
+            .iter()
+            .enumerate()
+            .filter(|&(i, &is_foreground)| is_foreground == (i < 1000))
+            .count();
+        assert!(correct >= 1950, "{}", correct);
+        assert!(foreground.iter().all(|&i| labels[i]));
+        assert!(background.iter().all(|&i| !labels[i]));
+    }
+
+    #[test]
+    fn test_nearest_points() {
+        let cloud = PointCloud::from_points(sphere(0.0, 100));
+        let p = cloud[42];
+        assert_eq!(nearest_points(&cloud, &[[p.x, p.y, p.z + 1e-4]]), vec![42]);
+    }
+
+    #[test]
+    fn test_source_side() {
+        // two paths from 0 to 3 with bottlenecks of 1 and 2
+        let mut graph = DiGraph::<(), u64>::new();
+        let nodes: Vec<NodeIndex> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.add_edge(nodes[0], nodes[1], 5);
+        graph.add_edge(nodes[1], nodes[3], 1);
+        graph.add_edge(nodes[0], nodes[2], 2);
+        graph.add_edge(nodes[2], nodes[3], 5);
+        let (flow, flows) = dinics(&graph, nodes[0], nodes[3]);
+        assert_eq!(flow, 3);
+        assert_eq!(
+            source_side(&graph, &flows, nodes[0]),
+            vec![true, true, false, false]
+        );
+    }
+}
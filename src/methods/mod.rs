@@ -0,0 +1,39 @@
+pub mod alpha_shape;
+pub mod background;
+#[cfg(feature = "io")]
+pub mod bev;
+pub mod colormap;
+pub mod curvature;
+pub mod fitting;
+pub mod four_pcs;
+pub mod fpfh;
+pub mod fusion;
+pub mod geodesic;
+#[cfg(feature = "io")]
+pub mod histogram;
+pub mod hull;
+pub mod icp;
+pub mod integrity;
+pub mod isosurface;
+pub mod labels;
+pub mod matching;
+pub mod mincut;
+pub mod ndt;
+pub mod noise;
+pub mod normals;
+pub mod occupancy;
+pub mod paint;
+pub mod plane;
+pub mod pose_graph;
+pub mod registration_chain;
+pub mod sampling;
+pub mod scan;
+pub mod sdf;
+pub mod segmentation;
+pub mod skeleton;
+pub mod surface_area;
+pub mod symmetry;
+pub mod tracking;
+pub mod triangulation;
+pub mod visibility;
+pub mod voronoi;
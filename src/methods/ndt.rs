@@ -0,0 +1,299 @@
+use cgmath::{InnerSpace, Matrix3, Rad, SquareMatrix, Vector3, Zero};
+use std::collections::HashMap;
+
+use super::fusion::{rigid_extrinsic, Extrinsic};
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Voxels with fewer points do not get a gaussian.
+const MIN_POINTS_PER_VOXEL: usize = 5;
+/// Added to the variances, relative to the squared voxel size, so that flat voxels stay invertible.
+const COVARIANCE_REGULARIZATION: f64 = 0.01;
+const CONVERGENCE_STEP: f64 = 1e-6;
+const MIN_STEP_LENGTH: f64 = 1e-3;
+
+type Vector6 = [f64; 6];
+type Matrix6 = [[f64; 6]; 6];
+
+/// The normal distributions transform of a reference cloud: a gaussian fitted to the points of
+/// every voxel, under which the likelihood of another cloud is its sum of densities.
+pub struct NdtGrid {
+    voxel_size: f64,
+    /// mean and inverse covariance of each voxel
+    cells: HashMap<[i64; 3], (Vector3<f64>, Matrix3<f64>)>,
+}
+
+impl NdtGrid {
+    pub fn new(reference: &PointCloud<PointXyzRgba>, voxel_size: f32) -> Self {
+        assert!(voxel_size > 0.0, "Expecting a positive voxel size");
+        let voxel_size = voxel_size as f64;
+        let mut voxels: HashMap<[i64; 3], Vec<Vector3<f64>>> = HashMap::new();
+        for p in &reference.points {
+            let p = Vector3::new(p.x as f64, p.y as f64, p.z as f64);
+            voxels.entry(voxel_of(p, voxel_size)).or_default().push(p);
+        }
+
+        let cells = voxels
+            .into_iter()
+            .filter(|(_, points)| points.len() >= MIN_POINTS_PER_VOXEL)
+            .filter_map(|(key, points)| {
+                let n = points.len() as f64;
+                let mean = points.iter().fold(Vector3::zero(), |sum, &p| sum + p) / n;
+                let mut covariance = points.iter().fold(Matrix3::zero(), |sum, &p| {
+                    let r = p - mean;
+                    sum + Matrix3::from_cols(r * r.x, r * r.y, r * r.z)
+                }) / (n - 1.0);
+                covariance += Matrix3::from_value(COVARIANCE_REGULARIZATION * voxel_size.powi(2));
+                Some((key, (mean, covariance.invert()?)))
+            })
+            .collect();
+        Self { voxel_size, cells }
+    }
+
+    /// Finds the rigid transform maximizing the likelihood of the source cloud, by Newton's method
+    /// on the rotation and translation with a backtracking line search.
+    pub fn register(&self, source: &PointCloud<PointXyzRgba>, max_iterations: usize) -> Extrinsic {
+        let mut points: Vec<Vector3<f64>> = source
+            .points
+            .iter()
+            .map(|p| Vector3::new(p.x as f64, p.y as f64, p.z as f64))
+            .collect();
+        let mut rotation = Matrix3::identity();
+        let mut translation = Vector3::zero();
+
+        for _ in 0..max_iterations {
+            let (score, gradient, hessian) = self.evaluate(&points);
+            let delta = match newton_step(hessian, gradient) {
+                Some(delta) => delta,
+                None => break,
+            };
+
+            let mut step = 1.0;
+            let (step_rotation, step_translation, moved) = loop {
+                let (r, t) = increment(&delta, step);
+                let moved: Vec<Vector3<f64>> = points.iter().map(|&p| r * p + t).collect();
+                if self.evaluate(&moved).0 >= score || step < MIN_STEP_LENGTH {
+                    break (r, t, moved);
+                }
+                step /= 2.0;
+            };
+            points = moved;
+            rotation = step_rotation * rotation;
+            translation = step_rotation * translation + step_translation;
+
+            if step * delta.iter().map(|d| d * d).sum::<f64>().sqrt() < CONVERGENCE_STEP {
+                break;
+            }
+        }
+
+        rigid_extrinsic(&rotation, &translation)
+    }
+
+    /// Score of the points, with its gradient and hessian with respect to a translation followed
+    /// by a small rotation (about x, y and z) of the points.
+    ///
+    /// Every point is scored under the gaussians of its voxel and of the 26 voxels around it,
+    /// which widens the basin of convergence.
+    fn evaluate(&self, points: &[Vector3<f64>]) -> (f64, Vector6, Matrix6) {
+        let axes = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+        let mut score = 0.0;
+        let mut gradient = [0.0; 6];
+        let mut hessian = [[0.0; 6]; 6];
+
+        for &p in points {
+            let voxel = voxel_of(p, self.voxel_size);
+            // derivatives of the point, the translations then the rotations
+            let jacobian = [
+                axes[0],
+                axes[1],
+                axes[2],
+                axes[0].cross(p),
+                axes[1].cross(p),
+                axes[2].cross(p),
+            ];
+            for neighbour in neighbour_voxels(voxel) {
+                let (mean, inverse_covariance) = match self.cells.get(&neighbour) {
+                    Some(&cell) => cell,
+                    None => continue,
+                };
+                let q = p - mean;
+                let cq = inverse_covariance * q;
+                let e = (-0.5 * q.dot(cq)).exp();
+                if e < f64::EPSILON {
+                    continue;
+                }
+                score += e;
+
+                let cq_j: Vec<f64> = jacobian.iter().map(|j| cq.dot(*j)).collect();
+                for i in 0..6 {
+                    gradient[i] -= e * cq_j[i];
+                    let c_ji = inverse_covariance * jacobian[i];
+                    for j in 0..6 {
+                        // second derivative of the point, only the rotations have one
+                        let second = if i >= 3 && j >= 3 {
+                            let (a, b) = (axes[i - 3], axes[j - 3]);
+                            cq.dot((a.cross(b.cross(p)) + b.cross(a.cross(p))) / 2.0)
+                        } else {
+                            0.0
+                        };
+                        hessian[i][j] += e * (cq_j[i] * cq_j[j] - jacobian[j].dot(c_ji) - second);
+                    }
+                }
+            }
+        }
+        (score, gradient, hessian)
+    }
+}
+
+fn voxel_of(p: Vector3<f64>, voxel_size: f64) -> [i64; 3] {
+    [
+        (p.x / voxel_size).floor() as i64,
+        (p.y / voxel_size).floor() as i64,
+        (p.z / voxel_size).floor() as i64,
+    ]
+}
+
+fn neighbour_voxels(voxel: [i64; 3]) -> impl Iterator<Item = [i64; 3]> {
+    (-1..=1).flat_map(move |dx| {
+        (-1..=1).flat_map(move |dy| {
+            (-1..=1).map(move |dz| [voxel[0] + dx, voxel[1] + dy, voxel[2] + dz])
+        })
+    })
+}
+
+/// Rotation and translation of a fraction `step` of the parameters.
+fn increment(delta: &Vector6, step: f64) -> (Matrix3<f64>, Vector3<f64>) {
+    let translation = Vector3::new(delta[0], delta[1], delta[2]) * step;
+    let omega = Vector3::new(delta[3], delta[4], delta[5]) * step;
+    let angle = omega.magnitude();
+    let rotation = if angle > 0.0 {
+        Matrix3::from_axis_angle(omega / angle, Rad(angle))
+    } else {
+        Matrix3::identity()
+    };
+    (rotation, translation)
+}
+
+/// Solves `-hessian * delta = gradient`, damping the hessian until it is negative definite so
+/// that the step goes uphill. Returns `None` if the gradient vanishes.
+fn newton_step(hessian: Matrix6, gradient: Vector6) -> Option<Vector6> {
+    if gradient.iter().all(|&g| g == 0.0) {
+        return None;
+    }
+    let scale = (0..6).map(|i| hessian[i][i].abs()).fold(0.0, f64::max);
+    let mut damping = 0.0;
+    loop {
+        let mut m = hessian.map(|row| row.map(|h| -h));
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] += damping;
+        }
+        if let Some(delta) = cholesky_solve(m, gradient) {
+            return Some(delta);
+        }
+        damping = if damping == 0.0 {
+            1e-3 * scale + f64::EPSILON
+        } else {
+            damping * 10.0
+        };
+    }
+}
+
+/// Returns `None` if the matrix is not positive definite.
+fn cholesky_solve(m: Matrix6, b: Vector6) -> Option<Vector6> {
+    let mut l = [[0.0; 6]; 6];
+    for i in 0..6 {
+        for j in 0..=i {
+            let s = m[i][j] - (0..j).map(|k| l[i][k] * l[j][k]).sum::<f64>();
+            if i == j {
+                if s <= 0.0 {
+                    return None;
+                }
+                l[i][i] = s.sqrt();
+            } else {
+                l[i][j] = s / l[j][j];
+            }
+        }
+    }
+    let mut y = [0.0; 6];
+    for i in 0..6 {
+        y[i] = (b[i] - (0..i).map(|k| l[i][k] * y[k]).sum::<f64>()) / l[i][i];
+    }
+    let mut x = [0.0; 6];
+    for i in (0..6).rev() {
+        x[i] = (y[i] - (i + 1..6).map(|k| l[k][i] * x[k]).sum::<f64>()) / l[i][i];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::methods::fusion::transform_cloud;
+
+    fn point(x: f32, y: f32, z: f32) -> PointXyzRgba {
+        PointXyzRgba {
+            x,
+            y,
+            z,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        }
+    }
+
+    /// Surface of the box [0, 1] x [0, 0.8] x [0, 0.6].
+    fn box_surface() -> PointCloud<PointXyzRgba> {
+        let n = 16;
+        let mut points = vec![];
+        for a in 0..=n {
+            for b in 0..=n {
+                let (s, t) = (a as f32 / n as f32, b as f32 / n as f32);
+                points.push(point(s, t * 0.8, 0.0));
+                points.push(point(s, t * 0.8, 0.6));
+                points.push(point(0.0, s * 0.8, t * 0.6));
+                points.push(point(1.0, s * 0.8, t * 0.6));
+                points.push(point(s, 0.0, t * 0.6));
+                points.push(point(s, 0.8, t * 0.6));
+            }
+        }
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_register_rotated_box() {
+        let reference = box_surface();
+        // 5 degrees around (1, 1, 1) followed by a small translation
+        let rotation = Matrix3::from_axis_angle(
+            Vector3::new(1.0f32, 1.0, 1.0).normalize(),
+            Rad(5f32.to_radians()),
+        );
+        let mut moved = [[0.0; 4]; 4];
+        for row in 0..3 {
+            for col in 0..3 {
+                moved[row][col] = rotation[col][row];
+            }
+        }
+        moved[0][3] = 0.03;
+        moved[1][3] = -0.02;
+        moved[2][3] = 0.01;
+        moved[3][3] = 1.0;
+        let source = transform_cloud(&reference, &moved);
+
+        let extrinsic = NdtGrid::new(&reference, 0.25).register(&source, 30);
+        let aligned = transform_cloud(&source, &extrinsic);
+        for (a, r) in aligned.points.iter().zip(&reference.points) {
+            let error = Vector3::new(a.x - r.x, a.y - r.y, a.z - r.z).magnitude();
+            assert!(error < 0.01, "{}", error);
+        }
+
+        // the recovered rotation undoes the applied one to within half a degree
+        let mut residual_trace = 0.0;
+        for i in 0..3 {
+            for k in 0..3 {
+                residual_trace += extrinsic[i][k] * moved[k][i];
+            }
+        }
+        let residual_angle = ((residual_trace - 1.0) / 2.0).clamp(-1.0, 1.0).acos();
+        assert!(residual_angle.to_degrees() < 0.5, "{}", residual_angle);
+    }
+}
@@ -0,0 +1,117 @@
+use rand::Rng;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Draws a sample from the standard normal distribution with the Box-Muller transform.
+fn standard_normal<R: Rng>(rng: &mut R) -> f32 {
+    let u1: f32 = 1.0 - rng.gen::<f32>();
+    let u2: f32 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+}
+
+/// LiDAR noise relative to a sensor at the origin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseModel {
+    /// standard deviation of the gaussian noise along the ray from the origin
+    pub range_std: f32,
+    /// standard deviation of the gaussian noise on the azimuth and elevation, in radians
+    pub angular_std: f32,
+    /// fraction of points removed at random
+    pub dropout_rate: f32,
+}
+
+impl NoiseModel {
+    /// Applies range noise, angular noise and dropout in sequence. Noise types with a zero
+    /// parameter are skipped, so the zero model returns the cloud unchanged.
+    pub fn apply<R: Rng>(
+        &self,
+        cloud: &PointCloud<PointXyzRgba>,
+        rng: &mut R,
+    ) -> PointCloud<PointXyzRgba> {
+        let mut points = cloud.points.clone();
+
+        if self.range_std > 0.0 {
+            for p in points.iter_mut() {
+                let r = (p.x * p.x + p.y * p.y + p.z * p.z).sqrt();
+                if r > 0.0 {
+                    let scale = (r + self.range_std * standard_normal(rng)).max(0.0) / r;
+                    p.x *= scale;
+                    p.y *= scale;
+                    p.z *= scale;
+                }
+            }
+        }
+
+        if self.angular_std > 0.0 {
+            for p in points.iter_mut() {
+                let r = (p.x * p.x + p.y * p.y + p.z * p.z).sqrt();
+                if r > 0.0 {
+                    let azimuth = p.y.atan2(p.x) + self.angular_std * standard_normal(rng);
+                    let elevation = (p.z / r).asin() + self.angular_std * standard_normal(rng);
+                    p.x = r * elevation.cos() * azimuth.cos();
+                    p.y = r * elevation.cos() * azimuth.sin();
+                    p.z = r * elevation.sin();
+                }
+            }
+        }
+
+        if self.dropout_rate > 0.0 {
+            points.retain(|_| rng.gen::<f32>() >= self.dropout_rate);
+        }
+
+        PointCloud::from_points(points)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn ring() -> PointCloud<PointXyzRgba> {
+        let points: Vec<PointXyzRgba> = (0..1000)
+            .map(|i| {
+                let angle = i as f32 * 0.01;
+                PointXyzRgba {
+                    x: 10.0 * angle.cos(),
+                    y: 10.0 * angle.sin(),
+                    z: 1.0,
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                }
+            })
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_zero_noise_is_identity() {
+        let cloud = ring();
+        let model = NoiseModel {
+            range_std: 0.0,
+            angular_std: 0.0,
+            dropout_rate: 0.0,
+        };
+        let noisy = model.apply(&cloud, &mut StdRng::seed_from_u64(0));
+        assert_eq!(noisy.len(), cloud.len());
+        assert_eq!(noisy.points, cloud.points);
+    }
+
+    #[test]
+    fn test_noise() {
+        let cloud = ring();
+        let model = NoiseModel {
+            range_std: 0.1,
+            angular_std: 0.01,
+            dropout_rate: 0.5,
+        };
+        let noisy = model.apply(&cloud, &mut StdRng::seed_from_u64(0));
+        assert!(noisy.len() > 400 && noisy.len() < 600);
+        assert_ne!(noisy.points[..10], cloud.points[..10]);
+
+        let same = model.apply(&cloud, &mut StdRng::seed_from_u64(0));
+        assert_eq!(noisy.points, same.points);
+    }
+}
@@ -0,0 +1,301 @@
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Vector3};
+use kiddo::{distance::squared_euclidean, KdTree};
+
+use super::plane::Plane;
+use crate::formats::{
+    pointxyzrgba::PointXyzRgba, pointxyzrgbanormal::PointXyzRgbaNormal, PointCloud,
+};
+
+/// Number of nearest neighbours whose mean distance measures the local density of a point.
+const DENSITY_NEIGHBOURS: usize = 5;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// away from the centroid
+    Outward,
+    /// towards the centroid
+    Inward,
+}
+
+fn flip_if(p: &mut PointXyzRgbaNormal, towards: Vector3<f32>) {
+    if Vector3::new(p.nx, p.ny, p.nz).dot(towards) < 0.0 {
+        p.nx = -p.nx;
+        p.ny = -p.ny;
+        p.nz = -p.nz;
+    }
+}
+
+/// Flips every normal that points away from the viewpoint, i.e. whose dot product with
+/// (viewpoint - point) is negative, so that all normals face the sensor.
+pub fn flip_normals_towards(cloud: &mut PointCloud<PointXyzRgbaNormal>, viewpoint: Point3<f32>) {
+    for p in cloud.points.iter_mut() {
+        let towards = viewpoint - Point3::new(p.x, p.y, p.z);
+        flip_if(p, towards);
+    }
+}
+
+/// Orients every normal outward from or inward to the centroid of the cloud, which is suited for
+/// closed surfaces.
+pub fn orient_normals(cloud: &mut PointCloud<PointXyzRgbaNormal>, orientation: Orientation) {
+    if cloud.points.is_empty() {
+        return;
+    }
+    let centroid = Point3::centroid(
+        &cloud
+            .points
+            .iter()
+            .map(|p| Point3::new(p.x, p.y, p.z))
+            .collect::<Vec<_>>(),
+    );
+    for p in cloud.points.iter_mut() {
+        let outward = Point3::new(p.x, p.y, p.z) - centroid;
+        match orientation {
+            Orientation::Outward => flip_if(p, outward),
+            Orientation::Inward => flip_if(p, -outward),
+        }
+    }
+}
+
+/// Absolute dot product between the unit normal of every point and the unit normals of its `k`
+/// nearest neighbours, so that opposite normals on the same surface agree.
+pub fn neighbour_normal_dots(cloud: &PointCloud<PointXyzRgbaNormal>, k: usize) -> Vec<Vec<f32>> {
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        tree.add(&p.xyz(), i)
+            .expect("Should be able to add point to kd tree");
+    }
+    let normals: Vec<Vector3<f32>> = cloud
+        .points
+        .iter()
+        .map(|p| {
+            let n = Vector3::new(p.nx, p.ny, p.nz);
+            if n.magnitude2() > 0.0 {
+                n.normalize()
+            } else {
+                n
+            }
+        })
+        .collect();
+
+    cloud
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            // the nearest point is the point itself
+            tree.nearest(&p.xyz(), k + 1, &squared_euclidean)
+                .expect("Failed to query tree")
+                .into_iter()
+                .filter(|&(_, &j)| j != i)
+                .take(k)
+                .map(|(_, &j)| normals[i].dot(normals[j]).abs())
+                .collect()
+        })
+        .collect()
+}
+
+/// Mean of the neighbour dot products of every point, 1 for a point without neighbours.
+pub fn normal_consistency(dots: &[Vec<f32>]) -> Vec<f32> {
+    dots.iter()
+        .map(|d| {
+            if d.is_empty() {
+                1.0
+            } else {
+                d.iter().sum::<f32>() / d.len() as f32
+            }
+        })
+        .collect()
+}
+
+fn build_tree(cloud: &PointCloud<PointXyzRgba>) -> KdTree<f32, usize, 3> {
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        tree.add(&p.xyz(), i)
+            .expect("Should be able to add point to kd tree");
+    }
+    tree
+}
+
+/// Search radius of every point for adaptive normal estimation, `k_scale` times the mean
+/// distance to its 5 nearest neighbours. Dense regions get a small radius and sparse regions a
+/// large one, so that every point is fitted from a similar number of neighbours.
+pub fn adaptive_radii(cloud: &PointCloud<PointXyzRgba>, k_scale: f32) -> Vec<f32> {
+    let tree = build_tree(cloud);
+    cloud
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            // the nearest point is the point itself
+            let distances: Vec<f32> = tree
+                .nearest(&p.xyz(), DENSITY_NEIGHBOURS + 1, &squared_euclidean)
+                .expect("Failed to query tree")
+                .into_iter()
+                .filter(|&(_, &j)| j != i)
+                .take(DENSITY_NEIGHBOURS)
+                .map(|(d, _)| d.sqrt())
+                .collect();
+            if distances.is_empty() {
+                0.0
+            } else {
+                k_scale * distances.iter().sum::<f32>() / distances.len() as f32
+            }
+        })
+        .collect()
+}
+
+/// Normal of every point from the plane fitted to its neighbours within its own radius. Points
+/// with fewer than three non collinear neighbours get a zero normal.
+pub fn estimate_normals(cloud: &PointCloud<PointXyzRgba>, radii: &[f32]) -> Vec<[f32; 3]> {
+    assert_eq!(
+        cloud.points.len(),
+        radii.len(),
+        "Expecting one radius per point"
+    );
+    let tree = build_tree(cloud);
+    cloud
+        .points
+        .iter()
+        .zip(radii)
+        .map(|(p, radius)| {
+            let neighbours = tree
+                .within(&p.xyz(), radius * radius, &squared_euclidean)
+                .expect("Failed to query tree");
+            let neighbours = PointCloud::from_points(
+                neighbours.iter().map(|&(_, &i)| cloud.points[i]).collect(),
+            );
+            match Plane::fit(&neighbours) {
+                Some(plane) => [plane.normal.x, plane.normal.y, plane.normal.z],
+                None => [0.0; 3],
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Unit sphere centered at (2, 0, 0) with every other normal pointing inward.
+    fn sphere() -> PointCloud<PointXyzRgbaNormal> {
+        let mut points = vec![];
+        for i in 0..10 {
+            let theta = std::f32::consts::PI * (i as f32 + 0.5) / 10.0;
+            for j in 0..20 {
+                let phi = 2.0 * std::f32::consts::PI * j as f32 / 20.0;
+                let n = Vector3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                let sign = if (i + j) % 2 == 0 { 1.0 } else { -1.0 };
+                points.push(PointXyzRgbaNormal {
+                    x: 2.0 + n.x,
+                    y: n.y,
+                    z: n.z,
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                    nx: sign * n.x,
+                    ny: sign * n.y,
+                    nz: sign * n.z,
+                });
+            }
+        }
+        PointCloud::from_points(points)
+    }
+
+    fn radial(p: &PointXyzRgbaNormal) -> f32 {
+        Vector3::new(p.nx, p.ny, p.nz).dot(Vector3::new(p.x - 2.0, p.y, p.z))
+    }
+
+    #[test]
+    fn test_orient_normals_sphere() {
+        let mut cloud = sphere();
+        orient_normals(&mut cloud, Orientation::Outward);
+        assert!(cloud.points.iter().all(|p| radial(p) > 0.0));
+
+        orient_normals(&mut cloud, Orientation::Inward);
+        assert!(cloud.points.iter().all(|p| radial(p) < 0.0));
+    }
+
+    #[test]
+    fn test_flip_normals_towards_viewpoint() {
+        let mut cloud = sphere();
+        let viewpoint = Point3::new(2.0, 0.0, 0.0);
+        flip_normals_towards(&mut cloud, viewpoint);
+        // seen from the center every normal points inward
+        assert!(cloud.points.iter().all(|p| radial(p) < 0.0));
+
+        let viewpoint = Point3::new(-10.0, 0.0, 0.0);
+        flip_normals_towards(&mut cloud, viewpoint);
+        assert!(cloud.points.iter().all(|p| {
+            Vector3::new(p.nx, p.ny, p.nz).dot(viewpoint - Point3::new(p.x, p.y, p.z)) >= 0.0
+        }));
+    }
+
+    #[test]
+    fn test_normal_consistency_plane() {
+        // 10x10 grid in the xy plane with alternating normals along z
+        let points = (0..100)
+            .map(|i| PointXyzRgbaNormal {
+                x: (i / 10) as f32,
+                y: (i % 10) as f32,
+                z: 0.0,
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+                nx: 0.0,
+                ny: 0.0,
+                nz: if i % 2 == 0 { 2.0 } else { -1.0 },
+            })
+            .collect();
+        let dots = neighbour_normal_dots(&PointCloud::from_points(points), 4);
+        assert!(dots.iter().all(|d| d.len() == 4));
+        assert!(normal_consistency(&dots)
+            .iter()
+            .all(|&c| (c - 1.0).abs() < 1e-6));
+    }
+
+    /// A dense 20x20 grid of spacing 0.01 next to a sparse 10x10 grid of spacing 0.05, on z = 0.
+    fn two_densities() -> PointCloud<PointXyzRgba> {
+        let grid = |n: usize, spacing: f32, x0: f32| {
+            (0..n * n).map(move |i| {
+                let (u, v) = ((i / n) as f32, (i % n) as f32);
+                PointXyzRgba::from_xyz_rgba([x0 + u * spacing, v * spacing, 0.0], [0, 0, 0, 255])
+            })
+        };
+        PointCloud::from_points(grid(20, 0.01, 0.0).chain(grid(10, 0.05, 1.0)).collect())
+    }
+
+    fn neighbour_count(cloud: &PointCloud<PointXyzRgba>, i: usize, radius: f32) -> usize {
+        let p = cloud.points[i];
+        cloud
+            .points
+            .iter()
+            .filter(|q| {
+                (p.x - q.x).powi(2) + (p.y - q.y).powi(2) + (p.z - q.z).powi(2) <= radius * radius
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_adaptive_radii() {
+        let cloud = two_densities();
+        // interior points of the dense and sparse grids
+        let (dense, sparse) = (10 * 20 + 10, 400 + 5 * 10 + 5);
+        assert!(neighbour_count(&cloud, dense, 0.06) > 4 * neighbour_count(&cloud, sparse, 0.06));
+
+        let radii = adaptive_radii(&cloud, 2.0);
+        assert!(radii[sparse] > 4.0 * radii[dense]);
+        assert_eq!(
+            neighbour_count(&cloud, dense, radii[dense]),
+            neighbour_count(&cloud, sparse, radii[sparse])
+        );
+
+        let normals = estimate_normals(&cloud, &radii);
+        assert!(normals.iter().all(|n| (n[2].abs() - 1.0).abs() < 1e-5));
+    }
+}
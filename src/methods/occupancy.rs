@@ -0,0 +1,122 @@
+use cgmath::Point3;
+
+use crate::formats::{
+    grid::{bounding_box, GridHeader},
+    pointxyzrgba::PointXyzRgba,
+    PointCloud,
+};
+
+/// Computes a binary occupancy grid of `resolution` voxels along each axis over the bounding box
+/// of the point cloud. A voxel is occupied if at least one point falls inside it.
+/// Returns `None` if the cloud is empty.
+pub fn occupancy_grid(
+    cloud: &PointCloud<PointXyzRgba>,
+    resolution: usize,
+) -> Option<(GridHeader, Vec<bool>)> {
+    let (min, max) = bounding_box(cloud.points.iter().map(|p| Point3::new(p.x, p.y, p.z)))?;
+    let header = GridHeader::new(min, max, resolution);
+    let mut occupied = vec![false; header.num_voxels()];
+    for p in &cloud.points {
+        if let Some(idx) = header.voxel_of(Point3::new(p.x, p.y, p.z)) {
+            occupied[idx] = true;
+        }
+    }
+    Some((header, occupied))
+}
+
+/// Emits one point at the center of each occupied voxel.
+///
+/// Points are colored by their voxel coordinates, scaled so that the full grid spans 0..=255 on
+/// each of the red, green and blue channels.
+pub fn occupancy_to_cloud(header: &GridHeader, occupied: &[bool]) -> PointCloud<PointXyzRgba> {
+    let res = header.resolution;
+    let scale = |c: usize| (c * 255 / (res - 1).max(1)) as u8;
+    let points: Vec<PointXyzRgba> = occupied
+        .iter()
+        .enumerate()
+        .filter(|&(_, &o)| o)
+        .map(|(idx, _)| {
+            let center = header.voxel_center(idx);
+            PointXyzRgba {
+                x: center.x,
+                y: center.y,
+                z: center.z,
+                r: scale(idx / (res * res)),
+                g: scale((idx / res) % res),
+                b: scale(idx % res),
+                a: 255,
+            }
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cgmath::MetricSpace;
+
+    fn sphere(radius: f32) -> PointCloud<PointXyzRgba> {
+        let mut points = vec![];
+        for i in 0..50 {
+            let theta = std::f32::consts::PI * i as f32 / 49.0;
+            for j in 0..100 {
+                let phi = 2.0 * std::f32::consts::PI * j as f32 / 100.0;
+                points.push(PointXyzRgba {
+                    x: radius * theta.sin() * phi.cos(),
+                    y: radius * theta.cos(),
+                    z: radius * theta.sin() * phi.sin(),
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                });
+            }
+        }
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_occupancy_grid_sphere() {
+        let (header, occupied) = occupancy_grid(&sphere(1.0), 16).unwrap();
+        assert_eq!(header.resolution, 16);
+        assert_eq!(occupied.len(), 16 * 16 * 16);
+
+        let center = Point3::new(0.0, 0.0, 0.0);
+        let half_diagonal = header.voxel_size * 3f32.sqrt() / 2.0;
+        // every occupied voxel lies on the shell, leaving the inside empty
+        for (idx, _) in occupied.iter().enumerate().filter(|&(_, &o)| o) {
+            let dist = header.voxel_center(idx).distance(center);
+            assert!((dist - 1.0).abs() <= half_diagonal + 1e-4);
+        }
+        assert!(!occupied[header.voxel_of(center).unwrap()]);
+        assert!(occupied[header.voxel_of(Point3::new(1.0, 0.0, 0.0)).unwrap()]);
+    }
+
+    #[test]
+    fn test_occupancy_to_cloud_recovers_points() {
+        let cloud = sphere(1.0);
+        let (header, occupied) = occupancy_grid(&cloud, 16).unwrap();
+        let recovered = occupancy_to_cloud(&header, &occupied);
+        assert_eq!(recovered.len(), occupied.iter().filter(|&&o| o).count());
+
+        let within = cloud
+            .points
+            .iter()
+            .filter(|p| {
+                let p = Point3::new(p.x, p.y, p.z);
+                recovered
+                    .points
+                    .iter()
+                    .any(|q| Point3::new(q.x, q.y, q.z).distance(p) <= header.voxel_size)
+            })
+            .count();
+        assert!(within as f32 >= 0.9 * cloud.len() as f32);
+    }
+
+    #[test]
+    fn test_occupancy_grid_empty_cloud() {
+        let cloud = PointCloud::from_points(vec![]);
+        assert!(occupancy_grid(&cloud, 8).is_none());
+    }
+}
@@ -0,0 +1,79 @@
+use kiddo::KdTree;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::structures::neighbours::NearestNeighbours;
+
+/// Colors each point of `geometry` with the color of its nearest `reference` point, or with
+/// `missing` when no reference point is within `search_radius`. The alpha of the points is kept.
+pub fn paint_cloud(
+    geometry: &PointCloud<PointXyzRgba>,
+    reference: &PointCloud<PointXyzRgba>,
+    search_radius: f64,
+    missing: [u8; 3],
+) -> PointCloud<PointXyzRgba> {
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in reference.points.iter().enumerate() {
+        tree.add(&p.xyz(), i)
+            .expect("Should be able to add point to kd tree");
+    }
+
+    let radius_squared = (search_radius * search_radius) as f32;
+    let points = geometry
+        .points
+        .iter()
+        .map(|p| {
+            let [r, g, b] = match tree.nearest_neighbours(&p.xyz(), 1).first() {
+                Some(&(d, i)) if d <= radius_squared => {
+                    let q = &reference.points[i];
+                    [q.r, q.g, q.b]
+                }
+                _ => missing,
+            };
+            PointXyzRgba { r, g, b, ..*p }
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(x: f32, y: f32, rgb: [u8; 3]) -> PointXyzRgba {
+        PointXyzRgba::from_xyz_rgba([x, y, 0.0], [rgb[0], rgb[1], rgb[2], 255])
+    }
+
+    #[test]
+    fn test_paint_identical_coordinates() {
+        let coordinates = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)];
+        let geometry = PointCloud::from_points(
+            coordinates
+                .iter()
+                .map(|&(x, y)| point(x, y, [0, 0, 0]))
+                .collect(),
+        );
+        let reference = PointCloud::from_points(
+            coordinates
+                .iter()
+                .enumerate()
+                .map(|(i, &(x, y))| point(x, y, [10 * i as u8, 20, 30]))
+                .collect(),
+        );
+
+        let painted = paint_cloud(&geometry, &reference, 0.1, [255, 0, 255]);
+        assert_eq!(painted.points, reference.points);
+
+        // no reference point within the radius of the shifted geometry
+        let shifted = PointCloud::from_points(
+            coordinates
+                .iter()
+                .map(|&(x, y)| point(x + 0.5, y, [0, 0, 0]))
+                .collect(),
+        );
+        let painted = paint_cloud(&shifted, &reference, 0.1, [255, 0, 255]);
+        assert!(painted
+            .points
+            .iter()
+            .all(|p| p.rgba() == [255, 0, 255, 255]));
+    }
+}
@@ -0,0 +1,203 @@
+use cgmath::{InnerSpace, Vector3};
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+const EPSILON: f64 = 1e-12;
+
+/// The plane of the points `p` with `normal . p = offset`, with a unit normal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3<f32>,
+    pub offset: f32,
+}
+
+impl Plane {
+    /// Returns `None` if the normal is zero.
+    pub fn new(normal: Vector3<f32>, offset: f32) -> Option<Self> {
+        let magnitude = normal.magnitude();
+        if magnitude < f32::EPSILON {
+            return None;
+        }
+        Some(Self {
+            normal: normal / magnitude,
+            offset: offset / magnitude,
+        })
+    }
+
+    /// Fits the plane minimizing the sum of squared orthogonal distances to the points.
+    ///
+    /// The plane passes through the centroid, and its normal is found from the covariance of the
+    /// points by solving along the axis with the best conditioned determinant.
+    /// Returns `None` for less than three points or collinear points.
+    pub fn fit(cloud: &PointCloud<PointXyzRgba>) -> Option<Self> {
+        if cloud.points.len() < 3 {
+            return None;
+        }
+        let n = cloud.points.len() as f64;
+        let centroid = cloud
+            .points
+            .iter()
+            .fold(Vector3::new(0f64, 0f64, 0f64), |sum, p| {
+                sum + Vector3::new(p.x as f64, p.y as f64, p.z as f64)
+            })
+            / n;
+
+        let (mut xx, mut xy, mut xz, mut yy, mut yz, mut zz) = (0f64, 0f64, 0f64, 0f64, 0f64, 0f64);
+        for p in &cloud.points {
+            let r = Vector3::new(p.x as f64, p.y as f64, p.z as f64) - centroid;
+            xx += r.x * r.x;
+            xy += r.x * r.y;
+            xz += r.x * r.z;
+            yy += r.y * r.y;
+            yz += r.y * r.z;
+            zz += r.z * r.z;
+        }
+
+        let det_x = yy * zz - yz * yz;
+        let det_y = xx * zz - xz * xz;
+        let det_z = xx * yy - xy * xy;
+        let det_max = det_x.max(det_y).max(det_z);
+        if det_max <= EPSILON {
+            return None;
+        }
+        let normal = if det_max == det_x {
+            Vector3::new(det_x, xz * yz - xy * zz, xy * yz - xz * yy)
+        } else if det_max == det_y {
+            Vector3::new(xz * yz - xy * zz, det_y, xy * xz - yz * xx)
+        } else {
+            Vector3::new(xy * yz - xz * yy, xy * xz - yz * xx, det_z)
+        }
+        .normalize();
+
+        Plane::new(normal.cast::<f32>()?, normal.dot(centroid) as f32)
+    }
+
+    /// Two unit vectors spanning the plane, orthogonal to each other and to the normal.
+    pub fn basis(&self) -> (Vector3<f32>, Vector3<f32>) {
+        let helper = if self.normal.x.abs() < 0.9 {
+            Vector3::unit_x()
+        } else {
+            Vector3::unit_y()
+        };
+        let u = self.normal.cross(helper).normalize();
+        let v = self.normal.cross(u);
+        (u, v)
+    }
+
+    /// Signed distance from the point to the plane, positive on the side of the normal.
+    pub fn distance(&self, p: &PointXyzRgba) -> f32 {
+        self.normal.dot(Vector3::new(p.x, p.y, p.z)) - self.offset
+    }
+
+    /// Moves every point orthogonally onto the plane, keeping its color.
+    pub fn project(&self, cloud: &PointCloud<PointXyzRgba>) -> PointCloud<PointXyzRgba> {
+        let points = cloud
+            .points
+            .iter()
+            .map(|p| {
+                let d = self.distance(p);
+                PointXyzRgba {
+                    x: p.x - d * self.normal.x,
+                    y: p.y - d * self.normal.y,
+                    z: p.z - d * self.normal.z,
+                    ..*p
+                }
+            })
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    /// Coordinates of the projection of every point along the vectors of [`Plane::basis`].
+    pub fn uv(&self, cloud: &PointCloud<PointXyzRgba>) -> Vec<(f32, f32)> {
+        let (u, v) = self.basis();
+        cloud
+            .points
+            .iter()
+            .map(|p| {
+                let p = Vector3::new(p.x, p.y, p.z);
+                (p.dot(u), p.dot(v))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn point(x: f32, y: f32, z: f32) -> PointXyzRgba {
+        PointXyzRgba {
+            x,
+            y,
+            z,
+            r: 10,
+            g: 20,
+            b: 30,
+            a: 255,
+        }
+    }
+
+    /// Random points on the plane x + 2y + 3z = 4 with random colors.
+    fn planar_cloud() -> PointCloud<PointXyzRgba> {
+        let mut rng = StdRng::seed_from_u64(2);
+        let points = (0..500)
+            .map(|_| {
+                let (x, y): (f32, f32) = (rng.gen_range(-5.0..5.0), rng.gen_range(-5.0..5.0));
+                PointXyzRgba {
+                    r: rng.gen(),
+                    g: rng.gen(),
+                    b: rng.gen(),
+                    ..point(x, y, (4.0 - x - 2.0 * y) / 3.0)
+                }
+            })
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_project_planar_cloud_onto_its_plane() {
+        let cloud = planar_cloud();
+        let plane = Plane::fit(&cloud).unwrap();
+        let expected = Vector3::new(1.0, 2.0, 3.0).normalize();
+        let sign = plane.normal.dot(expected).signum();
+        assert!((plane.normal * sign - expected).magnitude() < 1e-4);
+        assert!((plane.offset * sign - 4.0 / 14f32.sqrt()).abs() < 1e-4);
+
+        let projected = plane.project(&cloud);
+        assert_eq!(projected.len(), cloud.len());
+        for (p, q) in cloud.points.iter().zip(&projected.points) {
+            assert!((p.x - q.x).abs() < 1e-4);
+            assert!((p.y - q.y).abs() < 1e-4);
+            assert!((p.z - q.z).abs() < 1e-4);
+            assert_eq!((p.r, p.g, p.b, p.a), (q.r, q.g, q.b, q.a));
+        }
+    }
+
+    #[test]
+    fn test_project_onto_given_plane() {
+        let plane = Plane::new(Vector3::new(0.0, 0.0, 2.0), 2.0).unwrap();
+        assert_eq!(plane.offset, 1.0);
+        let cloud = PointCloud::from_points(vec![point(1.0, 2.0, 5.0), point(-1.0, 0.0, -3.0)]);
+        let projected = plane.project(&cloud);
+        assert_eq!(
+            projected.points,
+            vec![point(1.0, 2.0, 1.0), point(-1.0, 0.0, 1.0)]
+        );
+
+        let uv = plane.uv(&cloud);
+        let (u, v) = plane.basis();
+        assert!(u.dot(v).abs() < 1e-6 && u.dot(plane.normal).abs() < 1e-6);
+        // the distance between the projections is preserved in the plane coordinates
+        let du = uv[0].0 - uv[1].0;
+        let dv = uv[0].1 - uv[1].1;
+        assert!((du * du + dv * dv - 8.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_degenerate_planes() {
+        assert!(Plane::new(Vector3::new(0.0, 0.0, 0.0), 1.0).is_none());
+        let line = PointCloud::from_points((0..10).map(|i| point(i as f32, 0.0, 0.0)).collect());
+        assert!(Plane::fit(&line).is_none());
+    }
+}
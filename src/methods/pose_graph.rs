@@ -0,0 +1,262 @@
+use cgmath::{InnerSpace, Matrix, Matrix3, Rad, Vector3, Zero};
+
+use super::fusion::{compose_extrinsics, invert_rigid_extrinsic, rigid_extrinsic, Extrinsic};
+
+/// Gradient descent stops once the step size falls below this.
+const MIN_STEP: f64 = 1e-12;
+
+/// A measured relative pose: `measurement` maps the frame of node `to` into the frame of node
+/// `from`, as registering cloud `to` to cloud `from` does.
+pub struct PoseEdge {
+    pub from: usize,
+    pub to: usize,
+    pub measurement: Extrinsic,
+}
+
+/// Global poses of a sequence of frames constrained by the relative poses measured between them,
+/// e.g. by registering consecutive frames and revisited places.
+pub struct PoseGraph {
+    pub poses: Vec<Extrinsic>,
+    pub edges: Vec<PoseEdge>,
+}
+
+impl PoseGraph {
+    /// Chains the poses, each one constrained to its current pose relative to the previous one.
+    pub fn from_odometry(poses: Vec<Extrinsic>) -> Self {
+        let edges = poses
+            .windows(2)
+            .enumerate()
+            .map(|(i, pair)| PoseEdge {
+                from: i,
+                to: i + 1,
+                measurement: compose_extrinsics(&invert_rigid_extrinsic(&pair[0]), &pair[1]),
+            })
+            .collect();
+        Self { poses, edges }
+    }
+
+    /// Pairs of non consecutive frames, at least `min_gap` apart in the sequence, whose positions
+    /// are within `max_distance`: the places revisited where a loop can be closed.
+    pub fn loop_candidates(&self, max_distance: f32, min_gap: usize) -> Vec<(usize, usize)> {
+        let position = |pose: &Extrinsic| Vector3::new(pose[0][3], pose[1][3], pose[2][3]);
+        let mut candidates = vec![];
+        for (j, b) in self.poses.iter().enumerate() {
+            for (i, a) in self.poses[..j.saturating_sub(min_gap.max(2) - 1)]
+                .iter()
+                .enumerate()
+            {
+                if (position(a) - position(b)).magnitude() <= max_distance {
+                    candidates.push((i, j));
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Sum over the edges of the squared Frobenius norm of the rotation error and the squared
+    /// translation error, between the measured and the current relative poses.
+    pub fn error(&self) -> f64 {
+        let poses: Vec<_> = self.poses.iter().map(to_rigid).collect();
+        self.edges
+            .iter()
+            .map(|edge| edge_residual(&poses, edge))
+            .map(|(r, t)| frobenius2(&r) + t.magnitude2())
+            .sum()
+    }
+
+    /// Minimizes [`PoseGraph::error`] by gradient descent on the rotations and translations of
+    /// all poses but the first, which anchors the graph. Rotations are updated on the rotation
+    /// group, and the step grows after every improvement and halves after every failure.
+    pub fn optimize(&mut self, iterations: usize) {
+        let mut poses: Vec<_> = self.poses.iter().map(to_rigid).collect();
+        let cost = |poses: &[(Matrix3<f64>, Vector3<f64>)]| -> f64 {
+            self.edges
+                .iter()
+                .map(|edge| edge_residual(poses, edge))
+                .map(|(r, t)| frobenius2(&r) + t.magnitude2())
+                .sum()
+        };
+        let mut current = cost(&poses);
+        let mut step = 0.1;
+        for _ in 0..iterations {
+            let gradient = self.gradient(&poses);
+            let moved: Vec<_> = poses
+                .iter()
+                .zip(&gradient)
+                .enumerate()
+                .map(
+                    |(i, (&(rotation, translation), &(g_rotation, g_translation)))| {
+                        if i == 0 {
+                            (rotation, translation)
+                        } else {
+                            (
+                                exp_rotation(g_rotation * -step) * rotation,
+                                translation - g_translation * step,
+                            )
+                        }
+                    },
+                )
+                .collect();
+            let moved_cost = cost(&moved);
+            if moved_cost < current {
+                poses = moved;
+                current = moved_cost;
+                step *= 1.5;
+            } else {
+                step /= 2.0;
+                if step < MIN_STEP {
+                    break;
+                }
+            }
+        }
+        self.poses = poses
+            .iter()
+            .map(|(rotation, translation)| rigid_extrinsic(rotation, translation))
+            .collect();
+    }
+
+    /// Gradient of the error with respect to a rotation `w` applied before each rotation, as
+    /// `exp(w) R`, and to each translation.
+    fn gradient(
+        &self,
+        poses: &[(Matrix3<f64>, Vector3<f64>)],
+    ) -> Vec<(Vector3<f64>, Vector3<f64>)> {
+        let mut gradient = vec![(Vector3::zero(), Vector3::zero()); poses.len()];
+        for edge in &self.edges {
+            let (r, t) = edge_residual(poses, edge);
+            let (rotation_from, _) = poses[edge.from];
+            let (rotation_to, _) = poses[edge.to];
+            let (measured_rotation, measured_translation) = to_rigid(&edge.measurement);
+            let rotated = rotation_from * measured_translation;
+            gradient[edge.from].0 +=
+                skew_part(&(rotation_from * measured_rotation * r.transpose())) * 2.0
+                    + rotated.cross(t) * 2.0;
+            gradient[edge.from].1 += t * 2.0;
+            gradient[edge.to].0 -= skew_part(&(rotation_to * r.transpose())) * 2.0;
+            gradient[edge.to].1 -= t * 2.0;
+        }
+        gradient
+    }
+}
+
+/// Rotation and translation residuals of the pose of `to` predicted from `from` and the
+/// measurement.
+fn edge_residual(
+    poses: &[(Matrix3<f64>, Vector3<f64>)],
+    edge: &PoseEdge,
+) -> (Matrix3<f64>, Vector3<f64>) {
+    let (rotation_from, translation_from) = poses[edge.from];
+    let (rotation_to, translation_to) = poses[edge.to];
+    let (measured_rotation, measured_translation) = to_rigid(&edge.measurement);
+    (
+        rotation_from * measured_rotation - rotation_to,
+        rotation_from * measured_translation + translation_from - translation_to,
+    )
+}
+
+fn to_rigid(extrinsic: &Extrinsic) -> (Matrix3<f64>, Vector3<f64>) {
+    let e = |row: usize, col: usize| extrinsic[row][col] as f64;
+    (
+        Matrix3::new(
+            e(0, 0),
+            e(1, 0),
+            e(2, 0),
+            e(0, 1),
+            e(1, 1),
+            e(2, 1),
+            e(0, 2),
+            e(1, 2),
+            e(2, 2),
+        ),
+        Vector3::new(e(0, 3), e(1, 3), e(2, 3)),
+    )
+}
+
+fn frobenius2(m: &Matrix3<f64>) -> f64 {
+    m.x.magnitude2() + m.y.magnitude2() + m.z.magnitude2()
+}
+
+/// The vector `v` such that the trace of `m [w]x` is `w . v` for every `w`, `[w]x` being the
+/// cross product matrix of `w`.
+fn skew_part(m: &Matrix3<f64>) -> Vector3<f64> {
+    // cgmath matrices are indexed by column first
+    Vector3::new(m[2][1] - m[1][2], m[0][2] - m[2][0], m[1][0] - m[0][1])
+}
+
+/// Rotation of `w.magnitude()` radians around `w`.
+fn exp_rotation(w: Vector3<f64>) -> Matrix3<f64> {
+    let angle = w.magnitude();
+    if angle < 1e-15 {
+        return Matrix3::from_scale(1.0);
+    }
+    Matrix3::from_axis_angle(w / angle, Rad(angle))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cgmath::Deg;
+
+    /// Pose `k` of `n` evenly spaced around the unit circle, facing along it.
+    fn circle_pose(k: usize, n: usize) -> Extrinsic {
+        let angle = 2.0 * std::f64::consts::PI * k as f64 / n as f64;
+        rigid_extrinsic(
+            &Matrix3::from_angle_z(Rad(angle)),
+            &Vector3::new(angle.cos(), angle.sin(), 0.0),
+        )
+    }
+
+    fn distance(a: &Extrinsic, b: &Extrinsic) -> f32 {
+        (0..3)
+            .map(|i| (a[i][3] - b[i][3]).powi(2))
+            .sum::<f32>()
+            .sqrt()
+    }
+
+    #[test]
+    fn test_loop_closure_removes_drift() {
+        // every odometry step turns 1.5 degrees and moves 2 cm too far, and the last pose is
+        // back at the first
+        let n = 12;
+        let bias = rigid_extrinsic(
+            &Matrix3::from_angle_z(Deg(1.5)),
+            &Vector3::new(0.01, 0.02, 0.0),
+        );
+        let mut poses = vec![circle_pose(0, n)];
+        for k in 0..n {
+            let step = compose_extrinsics(
+                &invert_rigid_extrinsic(&circle_pose(k, n)),
+                &circle_pose(k + 1, n),
+            );
+            poses.push(compose_extrinsics(
+                poses.last().unwrap(),
+                &compose_extrinsics(&step, &bias),
+            ));
+        }
+        let mut graph = PoseGraph::from_odometry(poses);
+        assert_eq!(graph.edges.len(), n);
+        let drifted = graph.poses.clone();
+        let gap = distance(&drifted[0], &drifted[n]);
+        assert!(gap > 0.3);
+
+        assert!(graph.loop_candidates(0.5, 2).contains(&(0, n)));
+        assert_eq!(graph.loop_candidates(0.5, n), vec![(0, n)]);
+        graph.edges.push(PoseEdge {
+            from: 0,
+            to: n,
+            measurement: rigid_extrinsic(&Matrix3::from_scale(1.0), &Vector3::zero()),
+        });
+        let error = graph.error();
+        graph.optimize(300);
+
+        assert!(graph.error() < error / 10.0);
+        assert_eq!(graph.poses[0], drifted[0]);
+        assert!(distance(&graph.poses[0], &graph.poses[n]) < gap / 20.0);
+        let max_error = |poses: &[Extrinsic]| {
+            (0..=n)
+                .map(|k| distance(&poses[k], &circle_pose(k, n)))
+                .fold(0.0, f32::max)
+        };
+        assert!(max_error(&graph.poses) < max_error(&drifted) / 2.0);
+    }
+}
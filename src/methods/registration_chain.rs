@@ -0,0 +1,190 @@
+use cgmath::{InnerSpace, Matrix3, One, Quaternion, Vector3};
+
+use super::fusion::{compose_extrinsics, rigid_extrinsic, transform_cloud, Extrinsic};
+use super::icp::Icp;
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+const IDENTITY: Extrinsic = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Builds a map from a sequence of clouds by registering each cloud to the previous one.
+///
+/// The global pose of a cloud, mapping it into the frame of the first cloud, is the pose of the
+/// previous cloud composed with the incremental transform found by ICP.
+pub struct RegistrationChain {
+    icp: Icp,
+    clouds: Vec<PointCloud<PointXyzRgba>>,
+    poses: Vec<Extrinsic>,
+}
+
+impl RegistrationChain {
+    pub fn new(icp: Icp) -> Self {
+        Self {
+            icp,
+            clouds: vec![],
+            poses: vec![],
+        }
+    }
+
+    /// Registers the cloud to the previous one, returning its global pose.
+    pub fn push(&mut self, cloud: PointCloud<PointXyzRgba>) -> Extrinsic {
+        let pose = match (self.clouds.last(), self.poses.last()) {
+            (Some(previous), Some(previous_pose)) => {
+                compose_extrinsics(previous_pose, &self.icp.register(previous, &cloud))
+            }
+            _ => IDENTITY,
+        };
+        self.clouds.push(cloud);
+        self.poses.push(pose);
+        pose
+    }
+
+    pub fn poses(&self) -> &[Extrinsic] {
+        &self.poses
+    }
+
+    pub fn clouds(&self) -> &[PointCloud<PointXyzRgba>] {
+        &self.clouds
+    }
+
+    pub fn icp(&self) -> &Icp {
+        &self.icp
+    }
+
+    /// Corrects the drift accumulated along the chain if the global pose of the last cloud is
+    /// within `max_distance` of the first, i.e. the sensor came back to where it started.
+    ///
+    /// The last cloud is registered to the first one, and the correction is spread along the
+    /// chain: each pose takes the fraction of the rotation and translation of the correction
+    /// given by its position in the sequence. Returns whether a loop was closed.
+    pub fn close_loop(&mut self, max_distance: f32) -> bool {
+        let n = self.clouds.len();
+        if n < 3 {
+            return false;
+        }
+        let position = |pose: &Extrinsic| Vector3::new(pose[0][3], pose[1][3], pose[2][3]);
+        if (position(&self.poses[n - 1]) - position(&self.poses[0])).magnitude() > max_distance {
+            return false;
+        }
+
+        let last = transform_cloud(&self.clouds[n - 1], &self.poses[n - 1]);
+        let c = self.icp.register(&self.clouds[0], &last);
+        let rotation = Quaternion::from(Matrix3::new(
+            c[0][0] as f64,
+            c[1][0] as f64,
+            c[2][0] as f64,
+            c[0][1] as f64,
+            c[1][1] as f64,
+            c[2][1] as f64,
+            c[0][2] as f64,
+            c[1][2] as f64,
+            c[2][2] as f64,
+        ));
+        let translation = Vector3::new(c[0][3] as f64, c[1][3] as f64, c[2][3] as f64);
+        for (i, pose) in self.poses.iter_mut().enumerate() {
+            let amount = i as f64 / (n - 1) as f64;
+            let partial = rigid_extrinsic(
+                &Matrix3::from(Quaternion::one().slerp(rotation, amount)),
+                &(translation * amount),
+            );
+            *pose = compose_extrinsics(&partial, pose);
+        }
+        true
+    }
+
+    /// Moves every cloud to its global pose and concatenates them in order.
+    pub fn merged(&self) -> PointCloud<PointXyzRgba> {
+        let points = self
+            .clouds
+            .iter()
+            .zip(&self.poses)
+            .flat_map(|(cloud, pose)| transform_cloud(cloud, pose).points)
+            .collect();
+        PointCloud::from_points(points)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::methods::fusion::invert_rigid_extrinsic;
+    use crate::methods::icp::Weighting;
+    use cgmath::{Deg, Rad};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Random points on the surface of a box, each side of a different length.
+    fn box_surface(n: usize) -> PointCloud<PointXyzRgba> {
+        let mut rng = StdRng::seed_from_u64(3);
+        let size = [1.0, 0.6, 0.3];
+        let points = (0..n)
+            .map(|_| {
+                let mut xyz: [f32; 3] = [
+                    rng.gen_range(0.0..size[0]),
+                    rng.gen_range(0.0..size[1]),
+                    rng.gen_range(0.0..size[2]),
+                ];
+                let axis = rng.gen_range(0..3);
+                xyz[axis] = if rng.gen_bool(0.5) { 0.0 } else { size[axis] };
+                PointXyzRgba::from_xyz_rgba(xyz, [0, 0, 0, 255])
+            })
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    /// Pose of the sensor after `k` steps of 4 degrees around z and 2 cm along x.
+    fn pose(k: usize) -> Extrinsic {
+        let rotation = Matrix3::from_angle_z(Rad::from(Deg(4.0 * k as f64)));
+        rigid_extrinsic(&rotation, &Vector3::new(0.02 * k as f64, 0.0, 0.0))
+    }
+
+    fn icp() -> Icp {
+        Icp {
+            max_iterations: 100,
+            overlap_ratio: 1.0,
+            color_weight: 0.0,
+            weighting: Weighting::None,
+            weighting_delta: 0.0,
+        }
+    }
+
+    fn assert_close(a: &Extrinsic, b: &Extrinsic) {
+        for (row_a, row_b) in a.iter().zip(b).take(3) {
+            for (value_a, value_b) in row_a.iter().zip(row_b) {
+                assert!((value_a - value_b).abs() < 1e-3, "{:?} {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_chain_poses() {
+        let scene = box_surface(600);
+        let mut chain = RegistrationChain::new(icp());
+        for k in 0..4 {
+            // the sensor sees the scene in its own frame
+            chain.push(transform_cloud(&scene, &invert_rigid_extrinsic(&pose(k))));
+        }
+        for (k, estimated) in chain.poses().iter().enumerate() {
+            assert_close(estimated, &pose(k));
+        }
+        assert_eq!(chain.merged().len(), 4 * 600);
+        // the trajectory does not come back to the start
+        assert!(!chain.close_loop(0.01));
+    }
+
+    #[test]
+    fn test_close_loop() {
+        let scene = box_surface(600);
+        let mut chain = RegistrationChain::new(icp());
+        // out and back to the start
+        for k in [0, 1, 2, 1, 0] {
+            chain.push(transform_cloud(&scene, &invert_rigid_extrinsic(&pose(k))));
+        }
+        assert!(chain.close_loop(0.05));
+        assert_close(&chain.poses()[4], &IDENTITY);
+        assert_close(&chain.poses()[2], &pose(2));
+    }
+}
@@ -0,0 +1,487 @@
+use cgmath::MetricSpace;
+use rand::{
+    seq::{index::sample, SliceRandom},
+    Rng,
+};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::formats::{grid::bounding_box, pointxyzrgba::PointXyzRgba, HasCoordinate, PointCloud};
+
+/// Keeps a uniformly random `ratio` of the points, in their original order.
+pub fn uniform_sample<T: Clone>(cloud: &PointCloud<T>, ratio: f32) -> PointCloud<T> {
+    let n = cloud.points.len();
+    let amount = ((n as f32 * ratio.clamp(0.0, 1.0)).round() as usize).min(n);
+    let mut indices = sample(&mut rand::thread_rng(), n, amount).into_vec();
+    indices.sort_unstable();
+    let points: Vec<T> = indices
+        .into_iter()
+        .map(|i| cloud.points[i].clone())
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// Poisson-disk sampling: visits the points in random order and accepts a point only if it is at
+/// least `min_distance` away from every point accepted so far.
+///
+/// Accepted points are bucketed in a hash grid with cells of `min_distance`, so only the 27
+/// neighbouring cells have to be checked for each point.
+pub fn poisson_disk_sample<T: HasCoordinate + Clone>(
+    cloud: &PointCloud<T>,
+    min_distance: f32,
+) -> PointCloud<T> {
+    if min_distance <= 0.0 {
+        return cloud.clone();
+    }
+
+    let mut order: Vec<usize> = (0..cloud.points.len()).collect();
+    order.shuffle(&mut rand::thread_rng());
+
+    let cell_of = |v: f32| (v / min_distance).floor() as i64;
+    let mut grid: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+    let mut accepted = vec![];
+    for i in order {
+        let p = cloud.points[i].coordinate();
+        let cell = (cell_of(p.x), cell_of(p.y), cell_of(p.z));
+        let mut is_far = true;
+        'search: for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let neighbour = (cell.0 + dx, cell.1 + dy, cell.2 + dz);
+                    if let Some(indices) = grid.get(&neighbour) {
+                        if indices
+                            .iter()
+                            .any(|&j| cloud.points[j].coordinate().distance(p) < min_distance)
+                        {
+                            is_far = false;
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+        if is_far {
+            grid.entry(cell).or_default().push(i);
+            accepted.push(i);
+        }
+    }
+
+    accepted.sort_unstable();
+    let points: Vec<T> = accepted
+        .into_iter()
+        .map(|i| cloud.points[i].clone())
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// Voxel grid sampling: keeps the point nearest to the center of each voxel of side `leaf_size`
+/// holding points, in their original order.
+pub fn voxel_grid_sample<T: HasCoordinate + Clone>(
+    cloud: &PointCloud<T>,
+    leaf_size: f32,
+) -> PointCloud<T> {
+    if leaf_size <= 0.0 {
+        return cloud.clone();
+    }
+
+    // voxel of the point and squared distance to its center, in units of the leaf size
+    let locate = |p: &T| {
+        let p = p.coordinate() / leaf_size;
+        let voxel = (p.x.floor() as i64, p.y.floor() as i64, p.z.floor() as i64);
+        let offset = [p.x, p.y, p.z]
+            .iter()
+            .map(|v| (v - v.floor() - 0.5).powi(2))
+            .sum::<f32>();
+        (voxel, offset)
+    };
+    let mut kept: HashMap<(i64, i64, i64), (usize, f32)> = HashMap::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        let (voxel, offset) = locate(p);
+        let nearest = kept.entry(voxel).or_insert((i, offset));
+        if offset < nearest.1 {
+            *nearest = (i, offset);
+        }
+    }
+
+    let mut indices: Vec<usize> = kept.into_values().map(|(i, _)| i).collect();
+    indices.sort_unstable();
+    let points: Vec<T> = indices
+        .into_iter()
+        .map(|i| cloud.points[i].clone())
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// Grid of cubic voxels of side `size` covering the bounding box of a cloud, the voxel at
+/// `(x, y, z)` having index `x + dims[0] * (y + dims[1] * z)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoxelGrid {
+    pub origin: [f32; 3],
+    pub size: f32,
+    pub dims: [u32; 3],
+}
+
+impl VoxelGrid {
+    /// Returns `None` if the cloud is empty or the grid has more voxels than `u32` can index.
+    pub fn covering<T: HasCoordinate>(cloud: &PointCloud<T>, size: f32) -> Option<Self> {
+        let (min, max) = bounding_box(cloud.points.iter().map(|p| p.coordinate()))?;
+        let extent = max - min;
+        let dims = [extent.x, extent.y, extent.z].map(|e| (e / size).floor() as u64 + 1);
+        if dims.iter().product::<u64>() > 1 << 32 {
+            return None;
+        }
+        Some(Self {
+            origin: [min.x, min.y, min.z],
+            size,
+            dims: dims.map(|d| d as u32),
+        })
+    }
+
+    pub fn num_voxels(&self) -> u64 {
+        self.dims.iter().map(|&d| d as u64).product()
+    }
+
+    /// Index of the voxel holding the point, clamped to the grid.
+    pub fn index(&self, p: [f32; 3]) -> u32 {
+        let mut cell = [0; 3];
+        for (c, ((v, o), d)) in cell
+            .iter_mut()
+            .zip(p.iter().zip(self.origin).zip(self.dims))
+        {
+            *c = (((v - o) / self.size).floor() as u32).min(d - 1);
+        }
+        cell[0] + self.dims[0] * (cell[1] + self.dims[1] * cell[2])
+    }
+}
+
+/// Voxel grid downsampling to centroids: replaces the points of each voxel of side `voxel_size`
+/// holding points by their mean position and mean color, ordered by voxel index.
+pub fn voxel_centroid_sample(
+    cloud: &PointCloud<PointXyzRgba>,
+    voxel_size: f32,
+) -> PointCloud<PointXyzRgba> {
+    if voxel_size <= 0.0 || cloud.points.is_empty() {
+        return cloud.clone();
+    }
+    let grid = VoxelGrid::covering(cloud, voxel_size)
+        .expect("Voxel size is too small for the extent of the point cloud");
+
+    // sum of the coordinates, sum of the colors and number of points of every voxel
+    let mut voxels: BTreeMap<u32, ([f64; 3], [u64; 4], usize)> = BTreeMap::new();
+    for p in &cloud.points {
+        let (xyz, rgba, count) = voxels.entry(grid.index(p.xyz())).or_default();
+        for (sum, v) in xyz.iter_mut().zip(p.xyz()) {
+            *sum += v as f64;
+        }
+        for (sum, v) in rgba.iter_mut().zip(p.rgba()) {
+            *sum += v as u64;
+        }
+        *count += 1;
+    }
+
+    let points = voxels
+        .into_values()
+        .map(|(xyz, rgba, count)| {
+            PointXyzRgba::from_xyz_rgba(
+                xyz.map(|v| (v / count as f64) as f32),
+                rgba.map(|v| (v as f64 / count as f64).round() as u8),
+            )
+        })
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// Farthest point sampling: starts from a random point and repeatedly selects the point farthest
+/// from all selected points, in O(N * K) by caching the distance of each point to the selection.
+/// Returns the selected points in selection order, or the whole cloud if it has at most
+/// `num_points` points.
+pub fn farthest_point_sample<T: HasCoordinate + Clone>(
+    cloud: &PointCloud<T>,
+    num_points: usize,
+) -> PointCloud<T> {
+    if num_points >= cloud.points.len() {
+        return cloud.clone();
+    }
+
+    let points: Vec<T> = farthest_point_indices(cloud, num_points)
+        .into_iter()
+        .map(|i| cloud.points[i].clone())
+        .collect();
+    PointCloud::from_points(points)
+}
+
+/// Indices of the points selected by [`farthest_point_sample`], in selection order, or of all the
+/// points if the cloud has at most `num_points` points.
+pub fn farthest_point_indices<T: HasCoordinate>(
+    cloud: &PointCloud<T>,
+    num_points: usize,
+) -> Vec<usize> {
+    let n = cloud.points.len();
+    if num_points >= n {
+        return (0..n).collect();
+    }
+
+    let coordinates: Vec<_> = cloud.points.iter().map(|p| p.coordinate()).collect();
+    let mut min_distances = vec![f32::INFINITY; n];
+    let mut selected = Vec::with_capacity(num_points);
+    let mut current = rand::thread_rng().gen_range(0..n);
+    while selected.len() < num_points {
+        selected.push(current);
+        let c = coordinates[current];
+        let mut farthest = (0, f32::NEG_INFINITY);
+        for (i, p) in coordinates.iter().enumerate() {
+            let d = min_distances[i].min(p.distance2(c));
+            min_distances[i] = d;
+            if d > farthest.1 {
+                farthest = (i, d);
+            }
+        }
+        current = farthest.0;
+    }
+    selected
+}
+
+/// Indices selected by farthest point sampling starting from the point `start`, like
+/// [`farthest_point_indices`] but on flat arrays of the coordinates and of the distances to the
+/// selection, which the update of the distances and the search of the farthest point stream
+/// through. With the `simd` feature, both use AVX on the x86_64 CPUs supporting it.
+pub fn farthest_point_indices_flat<T: HasCoordinate>(
+    cloud: &PointCloud<T>,
+    num_points: usize,
+    start: usize,
+) -> Vec<usize> {
+    let n = cloud.points.len();
+    if num_points >= n {
+        return (0..n).collect();
+    }
+
+    let mut xyz = [
+        Vec::with_capacity(n),
+        Vec::with_capacity(n),
+        Vec::with_capacity(n),
+    ];
+    for p in &cloud.points {
+        let c = p.coordinate();
+        xyz[0].push(c.x);
+        xyz[1].push(c.y);
+        xyz[2].push(c.z);
+    }
+    let mut min_distances = vec![f32::INFINITY; n];
+    let mut selected = Vec::with_capacity(num_points);
+    let mut current = start;
+    while selected.len() < num_points {
+        selected.push(current);
+        let c = [xyz[0][current], xyz[1][current], xyz[2][current]];
+        current = update_farthest(c, &xyz, &mut min_distances);
+    }
+    selected
+}
+
+/// Lowers the distances to the selection to the distances to `c`, returning the index of the
+/// first farthest point.
+fn update_farthest(c: [f32; 3], xyz: &[Vec<f32>; 3], min_distances: &mut [f32]) -> usize {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if is_x86_feature_detected!("avx") {
+        // safe as the cpu supports avx
+        return unsafe { update_farthest_avx(c, xyz, min_distances) };
+    }
+    update_farthest_scalar(c, xyz, min_distances, 0, (0, f32::NEG_INFINITY)).0
+}
+
+/// Updates the distances from index `from` on, starting the search from `farthest`.
+fn update_farthest_scalar(
+    c: [f32; 3],
+    xyz: &[Vec<f32>; 3],
+    min_distances: &mut [f32],
+    from: usize,
+    mut farthest: (usize, f32),
+) -> (usize, f32) {
+    for i in from..min_distances.len() {
+        let (dx, dy, dz) = (xyz[0][i] - c[0], xyz[1][i] - c[1], xyz[2][i] - c[2]);
+        let d = min_distances[i].min(dx * dx + dy * dy + dz * dz);
+        min_distances[i] = d;
+        if d > farthest.1 {
+            farthest = (i, d);
+        }
+    }
+    farthest
+}
+
+/// Updates the distances 8 at a time, every lane keeping its farthest point, before the lanes
+/// and the remaining points are searched in index order.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[target_feature(enable = "avx")]
+unsafe fn update_farthest_avx(
+    c: [f32; 3],
+    xyz: &[Vec<f32>; 3],
+    min_distances: &mut [f32],
+) -> usize {
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 8;
+    let chunks = min_distances.len() / LANES;
+    let (cx, cy, cz) = (
+        _mm256_set1_ps(c[0]),
+        _mm256_set1_ps(c[1]),
+        _mm256_set1_ps(c[2]),
+    );
+    let mut max = _mm256_set1_ps(f32::NEG_INFINITY);
+    // chunks are counted in f32, exact up to 2^24 chunks of 8 points
+    let mut max_chunk = _mm256_setzero_ps();
+    for chunk in 0..chunks {
+        let i = chunk * LANES;
+        let dx = _mm256_sub_ps(_mm256_loadu_ps(xyz[0].as_ptr().add(i)), cx);
+        let dy = _mm256_sub_ps(_mm256_loadu_ps(xyz[1].as_ptr().add(i)), cy);
+        let dz = _mm256_sub_ps(_mm256_loadu_ps(xyz[2].as_ptr().add(i)), cz);
+        let d = _mm256_add_ps(
+            _mm256_add_ps(_mm256_mul_ps(dx, dx), _mm256_mul_ps(dy, dy)),
+            _mm256_mul_ps(dz, dz),
+        );
+        let d = _mm256_min_ps(_mm256_loadu_ps(min_distances.as_ptr().add(i)), d);
+        _mm256_storeu_ps(min_distances.as_mut_ptr().add(i), d);
+        let greater = _mm256_cmp_ps::<_CMP_GT_OQ>(d, max);
+        max = _mm256_blendv_ps(max, d, greater);
+        max_chunk = _mm256_blendv_ps(max_chunk, _mm256_set1_ps(chunk as f32), greater);
+    }
+
+    let (mut maxes, mut max_chunks) = ([0f32; LANES], [0f32; LANES]);
+    _mm256_storeu_ps(maxes.as_mut_ptr(), max);
+    _mm256_storeu_ps(max_chunks.as_mut_ptr(), max_chunk);
+    let mut farthest = (0, f32::NEG_INFINITY);
+    for lane in 0..LANES {
+        let i = max_chunks[lane] as usize * LANES + lane;
+        if maxes[lane] > farthest.1 || (maxes[lane] == farthest.1 && i < farthest.0) {
+            farthest = (i, maxes[lane]);
+        }
+    }
+    update_farthest_scalar(c, xyz, min_distances, chunks * LANES, farthest).0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cube(side: usize, spacing: f32) -> PointCloud<PointXyzRgba> {
+        let mut points = vec![];
+        for x in 0..side {
+            for y in 0..side {
+                for z in 0..side {
+                    points.push(PointXyzRgba {
+                        x: x as f32 * spacing,
+                        y: y as f32 * spacing,
+                        z: z as f32 * spacing,
+                        r: 0,
+                        g: 0,
+                        b: 0,
+                        a: 255,
+                    });
+                }
+            }
+        }
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_poisson_disk_sample_min_distance() {
+        let cloud = cube(10, 0.1);
+        let sampled = poisson_disk_sample(&cloud, 0.25);
+        assert!(sampled.len() > 0);
+        assert!(sampled.len() < cloud.len());
+        for (i, p) in sampled.points.iter().enumerate() {
+            for q in &sampled.points[i + 1..] {
+                assert!(p.coordinate().distance(q.coordinate()) >= 0.25);
+            }
+        }
+    }
+
+    #[test]
+    fn test_voxel_grid_sample_one_point_per_voxel() {
+        let cloud = cube(10, 0.1);
+        let sampled = voxel_grid_sample(&cloud, 0.2);
+        assert_eq!(sampled.len(), 125);
+        // the kept points are in their original order
+        let positions: Vec<usize> = sampled
+            .points
+            .iter()
+            .map(|p| cloud.points.iter().position(|q| q == p).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_voxel_centroid_sample() {
+        // 4x4x4 points in 2x2x2 voxels of 8 points each
+        let mut cloud = cube(4, 0.25);
+        for (i, p) in cloud.points.iter_mut().enumerate() {
+            p.r = (i % 2) as u8 * 100;
+        }
+        let sampled = voxel_centroid_sample(&cloud, 0.5);
+        assert_eq!(sampled.len(), 8);
+        assert_eq!(sampled.points[0].xyz(), [0.125, 0.125, 0.125]);
+        // x varies fastest
+        assert_eq!(sampled.points[1].xyz(), [0.625, 0.125, 0.125]);
+        assert_eq!(sampled.points[7].xyz(), [0.625, 0.625, 0.625]);
+        assert!(sampled.points.iter().all(|p| p.rgba() == [50, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_farthest_point_sample_count() {
+        let cloud = cube(10, 0.1);
+        let sampled = farthest_point_sample(&cloud, 64);
+        assert_eq!(sampled.len(), 64);
+        assert_eq!(sampled.points.len(), 64);
+        // every selected point is distinct
+        for (i, p) in sampled.points.iter().enumerate() {
+            assert!(!sampled.points[i + 1..].contains(p));
+        }
+
+        let all = farthest_point_sample(&cloud, 2000);
+        assert_eq!(all.len(), cloud.len());
+    }
+
+    #[test]
+    fn test_farthest_point_sample_picks_corners() {
+        let cloud = cube(5, 1.0);
+        let sampled = farthest_point_sample(&cloud, 2);
+        // the farthest point from any seed is a corner at least half a diagonal away
+        let d = sampled.points[0]
+            .coordinate()
+            .distance(sampled.points[1].coordinate());
+        assert!(d > 3.4);
+    }
+
+    #[test]
+    fn test_farthest_point_indices_flat() {
+        // not a multiple of 8 points, leaving a remainder to the scalar search
+        let mut rng = rand::thread_rng();
+        let points = (0..1003)
+            .map(|_| PointXyzRgba::from_xyz_rgba([rng.gen(), rng.gen(), rng.gen()], [0; 4]))
+            .collect();
+        let cloud = PointCloud::from_points(points);
+        let selected = farthest_point_indices_flat(&cloud, 50, 17);
+        assert_eq!(selected.len(), 50);
+        assert_eq!(selected[0], 17);
+
+        // every point selected is the farthest from the ones selected before
+        let coordinates: Vec<_> = cloud.points.iter().map(|p| p.coordinate()).collect();
+        let distance = |i: usize, k: usize| {
+            selected[..k]
+                .iter()
+                .map(|&j| coordinates[i].distance2(coordinates[j]))
+                .fold(f32::INFINITY, f32::min)
+        };
+        for k in 1..selected.len() {
+            let farthest = (0..coordinates.len())
+                .map(|i| distance(i, k))
+                .fold(0.0, f32::max);
+            assert_eq!(distance(selected[k], k), farthest);
+        }
+    }
+
+    #[test]
+    fn test_uniform_sample_ratio() {
+        let cloud = cube(10, 0.1);
+        let sampled = uniform_sample(&cloud, 0.3);
+        assert_eq!(sampled.len(), 300);
+        assert_eq!(sampled.points.len(), 300);
+    }
+}
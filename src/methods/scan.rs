@@ -0,0 +1,187 @@
+use cgmath::{InnerSpace, Point3};
+use std::collections::HashMap;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Beam layout of a spinning LiDAR.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScanPattern {
+    pub num_beams: usize,
+    /// angle between two consecutive firings of a beam, in degrees
+    pub horizontal_resolution: f32,
+    /// elevation of the highest beam, in degrees
+    pub fov_up: f32,
+    /// elevation of the lowest beam, in degrees
+    pub fov_down: f32,
+    pub max_range: f32,
+}
+
+/// Simulates a LiDAR scan of a dense point cloud from `origin`.
+///
+/// Each beam and firing angle defines a ray, which hits the closest point within `max_range` whose
+/// direction falls within the angular cell around the ray. Points behind it are occluded.
+pub fn simulate_scan(
+    cloud: &PointCloud<PointXyzRgba>,
+    origin: Point3<f32>,
+    pattern: &ScanPattern,
+) -> PointCloud<PointXyzRgba> {
+    // a single beam accepts points within half a firing angle of its elevation
+    let beam_spacing = if pattern.num_beams > 1 {
+        (pattern.fov_up - pattern.fov_down) / (pattern.num_beams - 1) as f32
+    } else {
+        pattern.horizontal_resolution
+    };
+
+    let mut hits: HashMap<(usize, i64), (f32, usize)> = HashMap::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        let ray = Point3::new(p.x, p.y, p.z) - origin;
+        let range = ray.magnitude();
+        if range == 0.0 || range > pattern.max_range {
+            continue;
+        }
+        let elevation = (ray.z / range).asin().to_degrees();
+        let beam = ((pattern.fov_up - elevation) / beam_spacing).round();
+        if beam < 0.0 || beam >= pattern.num_beams as f32 {
+            continue;
+        }
+        let azimuth = ray.y.atan2(ray.x).to_degrees();
+        let column = (azimuth / pattern.horizontal_resolution).round() as i64;
+
+        let hit = hits.entry((beam as usize, column)).or_insert((range, i));
+        if range < hit.0 {
+            *hit = (range, i);
+        }
+    }
+
+    let mut indices: Vec<usize> = hits.into_values().map(|(_, i)| i).collect();
+    indices.sort_unstable();
+    let points: Vec<PointXyzRgba> = indices.into_iter().map(|i| cloud.points[i]).collect();
+    PointCloud::from_points(points)
+}
+
+/// Converts a range image of a spinning LiDAR, one row per beam with `elevations[row]` in degrees
+/// and the columns spanning `hfov` degrees of azimuth from left to right, to a point cloud.
+///
+/// `ranges` is row-major with `elevations.len()` rows. The azimuth of the first column is
+/// `hfov / 2` and decreases towards the last column, with x forward and z up. Pixels with a zero
+/// or non finite range are no return and are skipped.
+pub fn deproject_range_image(
+    ranges: &[f32],
+    elevations: &[f32],
+    hfov: f32,
+) -> PointCloud<PointXyzRgba> {
+    assert!(!elevations.is_empty(), "Expecting at least one beam");
+    assert_eq!(
+        ranges.len() % elevations.len(),
+        0,
+        "Expecting the same number of columns for every beam"
+    );
+    let columns = ranges.len() / elevations.len();
+    let column_width = hfov / columns as f32;
+
+    let mut points = vec![];
+    for (row, elevation) in elevations.iter().enumerate() {
+        let (sin_el, cos_el) = elevation.to_radians().sin_cos();
+        for column in 0..columns {
+            let range = ranges[row * columns + column];
+            if !range.is_finite() || range <= 0.0 {
+                continue;
+            }
+            let azimuth = hfov / 2.0 - (column as f32 + 0.5) * column_width;
+            let (sin_az, cos_az) = azimuth.to_radians().sin_cos();
+            points.push(PointXyzRgba {
+                x: range * cos_el * cos_az,
+                y: range * cos_el * sin_az,
+                z: range * sin_el,
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            });
+        }
+    }
+    PointCloud::from_points(points)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Dense wall in the plane x = `distance`, spanning y and z in [-2, 2].
+    fn wall(distance: f32, color: u8) -> Vec<PointXyzRgba> {
+        let mut points = vec![];
+        for i in 0..200 {
+            for j in 0..200 {
+                points.push(PointXyzRgba {
+                    x: distance,
+                    y: -2.0 + i as f32 * 0.02,
+                    z: -2.0 + j as f32 * 0.02,
+                    r: color,
+                    g: color,
+                    b: color,
+                    a: 255,
+                });
+            }
+        }
+        points
+    }
+
+    fn pattern(max_range: f32) -> ScanPattern {
+        ScanPattern {
+            num_beams: 16,
+            horizontal_resolution: 2.0,
+            fov_up: 15.0,
+            fov_down: -15.0,
+            max_range,
+        }
+    }
+
+    #[test]
+    fn test_simulate_scan_occlusion() {
+        let mut points = wall(5.0, 255);
+        points.extend(wall(8.0, 0));
+        let cloud = PointCloud::from_points(points);
+
+        let scan = simulate_scan(&cloud, Point3::new(0.0, 0.0, 0.0), &pattern(100.0));
+        assert!(scan.len() > 0);
+        assert!(scan.len() < cloud.len());
+        // the back wall is hidden behind the front wall
+        assert!(scan.points.iter().all(|p| p.x == 5.0));
+        // at most one point per beam and column
+        assert!(scan.len() <= 16 * 180);
+    }
+
+    #[test]
+    fn test_simulate_scan_max_range() {
+        let cloud = PointCloud::from_points(wall(5.0, 255));
+        let scan = simulate_scan(&cloud, Point3::new(0.0, 0.0, 0.0), &pattern(4.0));
+        assert_eq!(scan.len(), 0);
+
+        // moving the sensor closer brings the wall within range
+        let scan = simulate_scan(&cloud, Point3::new(2.0, 0.0, 0.0), &pattern(4.0));
+        assert!(scan.len() > 0);
+    }
+
+    #[test]
+    fn test_deproject_range_image() {
+        // 4 beams of 4 columns over 90 degrees, the second beam has no return in its first column
+        let elevations = [15.0, 5.0, -5.0, -15.0];
+        let mut ranges = vec![2.0; 16];
+        ranges[4] = 0.0;
+
+        let cloud = deproject_range_image(&ranges, &elevations, 90.0);
+        assert_eq!(cloud.len(), 15);
+        for p in &cloud.points {
+            let range = (p.x * p.x + p.y * p.y + p.z * p.z).sqrt();
+            assert!((range - 2.0).abs() < 1e-5);
+        }
+
+        // the first point of the second beam is in its second column
+        let p = cloud.points[4];
+        assert!(((p.z / 2.0).asin().to_degrees() - 5.0).abs() < 1e-3);
+        assert!((p.y.atan2(p.x).to_degrees() - 11.25).abs() < 1e-3);
+        // the last point is on the right of the sensor
+        let p = cloud.points[14];
+        assert!((p.y.atan2(p.x).to_degrees() + 33.75).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,92 @@
+use cgmath::Point3;
+use kiddo::{distance::squared_euclidean, KdTree};
+use rayon::prelude::*;
+
+use crate::formats::{pointxyzrgbanormal::PointXyzRgbaNormal, PointCloud};
+
+/// Samples the signed distance from each query point to the surface represented by the point cloud.
+///
+/// The distance is taken to the nearest point and is negative when the query lies behind that
+/// point's normal, i.e. inside the surface. Returns infinity for every query if the cloud is empty.
+pub fn sample_sdf(
+    cloud: &PointCloud<PointXyzRgbaNormal>,
+    query_points: &[Point3<f32>],
+) -> Vec<f32> {
+    if cloud.points.is_empty() {
+        return vec![f32::INFINITY; query_points.len()];
+    }
+
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        tree.add(&p.xyz(), i)
+            .expect("Failed to add point to kd tree");
+    }
+
+    query_points
+        .par_iter()
+        .map(|q| {
+            let nearest = tree
+                .nearest(&[q.x, q.y, q.z], 1, &squared_euclidean)
+                .expect("Failed to query kd tree");
+            let (dist_sq, &idx) = nearest[0];
+            let p = &cloud.points[idx];
+            let side = (q.x - p.x) * p.nx + (q.y - p.y) * p.ny + (q.z - p.z) * p.nz;
+            if side < 0.0 {
+                -dist_sq.sqrt()
+            } else {
+                dist_sq.sqrt()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sphere(radius: f32) -> PointCloud<PointXyzRgbaNormal> {
+        let mut points = vec![];
+        for i in 0..20 {
+            let theta = std::f32::consts::PI * (i as f32 + 0.5) / 20.0;
+            for j in 0..40 {
+                let phi = 2.0 * std::f32::consts::PI * j as f32 / 40.0;
+                let (nx, ny, nz) = (
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                points.push(PointXyzRgbaNormal {
+                    x: radius * nx,
+                    y: radius * ny,
+                    z: radius * nz,
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                    nx,
+                    ny,
+                    nz,
+                });
+            }
+        }
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_sample_sdf_sphere() {
+        let cloud = sphere(1.0);
+        let sdf = sample_sdf(
+            &cloud,
+            &[Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 0.0, 0.0)],
+        );
+        assert!((sdf[0] + 1.0).abs() < 1e-3);
+        assert!(sdf[1] > 0.9 && sdf[1] < 1.1);
+    }
+
+    #[test]
+    fn test_sample_sdf_empty_cloud() {
+        let cloud = PointCloud::from_points(vec![]);
+        let sdf = sample_sdf(&cloud, &[Point3::new(0.0, 0.0, 0.0)]);
+        assert_eq!(sdf, vec![f32::INFINITY]);
+    }
+}
@@ -0,0 +1,160 @@
+use kiddo::{distance::squared_euclidean, KdTree};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Counts of (ground truth, predicted) label pairs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfusionMatrix {
+    counts: BTreeMap<(u32, u32), usize>,
+    classes: BTreeSet<u32>,
+}
+
+impl ConfusionMatrix {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the confusion matrix of a predicted labelling against the ground truth, pairing each
+    /// predicted point with the label of its nearest ground truth point.
+    pub fn from_clouds(
+        predicted: &PointCloud<PointXyzRgba>,
+        predicted_labels: &[u32],
+        ground_truth: &PointCloud<PointXyzRgba>,
+        ground_truth_labels: &[u32],
+    ) -> Self {
+        let mut matrix = Self::new();
+        if ground_truth.points.is_empty() {
+            return matrix;
+        }
+
+        let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+        for (i, p) in ground_truth.points.iter().enumerate() {
+            tree.add(&p.xyz(), i)
+                .expect("Failed to add point to kd tree");
+        }
+        for (p, &label) in predicted.points.iter().zip(predicted_labels) {
+            let nearest = tree
+                .nearest(&p.xyz(), 1, &squared_euclidean)
+                .expect("Failed to query kd tree");
+            matrix.add(ground_truth_labels[*nearest[0].1], label);
+        }
+        matrix
+    }
+
+    pub fn add(&mut self, ground_truth: u32, predicted: u32) {
+        *self.counts.entry((ground_truth, predicted)).or_insert(0) += 1;
+        self.classes.insert(ground_truth);
+        self.classes.insert(predicted);
+    }
+
+    pub fn merge(&mut self, other: &ConfusionMatrix) {
+        for (&key, &count) in &other.counts {
+            *self.counts.entry(key).or_insert(0) += count;
+        }
+        self.classes.extend(&other.classes);
+    }
+
+    pub fn count(&self, ground_truth: u32, predicted: u32) -> usize {
+        self.counts
+            .get(&(ground_truth, predicted))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Intersection over union of each class, true positives / (true positives + false positives + false negatives).
+    pub fn iou(&self) -> BTreeMap<u32, f64> {
+        let mut tp = BTreeMap::new();
+        let mut union = BTreeMap::new();
+        for (&(gt, pred), &count) in &self.counts {
+            if gt == pred {
+                *tp.entry(gt).or_insert(0) += count;
+                *union.entry(gt).or_insert(0) += count;
+            } else {
+                *union.entry(gt).or_insert(0) += count;
+                *union.entry(pred).or_insert(0) += count;
+            }
+        }
+        union
+            .into_iter()
+            .map(|(class, u)| (class, *tp.get(&class).unwrap_or(&0) as f64 / u as f64))
+            .collect()
+    }
+
+    /// Mean of the per class IoU, or `None` if the matrix is empty.
+    pub fn mean_iou(&self) -> Option<f64> {
+        let iou = self.iou();
+        if iou.is_empty() {
+            None
+        } else {
+            Some(iou.values().sum::<f64>() / iou.len() as f64)
+        }
+    }
+
+    /// Writes the matrix as csv with one row per ground truth class and one column per predicted class.
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        write!(writer, "ground_truth\\predicted")?;
+        for pred in &self.classes {
+            write!(writer, ",{}", pred)?;
+        }
+        writeln!(writer)?;
+        for &gt in &self.classes {
+            write!(writer, "{}", gt)?;
+            for &pred in &self.classes {
+                write!(writer, ",{}", self.count(gt, pred))?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn two_class_cloud() -> (PointCloud<PointXyzRgba>, Vec<u32>) {
+        let points: Vec<PointXyzRgba> = (0..10)
+            .map(|i| PointXyzRgba {
+                x: i as f32,
+                y: 0.0,
+                z: 0.0,
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            })
+            .collect();
+        let labels = (0..10).map(|i| if i < 5 { 1 } else { 2 }).collect();
+        (PointCloud::from_points(points), labels)
+    }
+
+    #[test]
+    fn test_identical_labels() {
+        let (pc, labels) = two_class_cloud();
+        let matrix = ConfusionMatrix::from_clouds(&pc, &labels, &pc, &labels);
+        assert_eq!(matrix.count(1, 1), 5);
+        assert_eq!(matrix.count(2, 2), 5);
+        assert_eq!(matrix.count(1, 2), 0);
+        assert_eq!(matrix.mean_iou(), Some(1.0));
+    }
+
+    #[test]
+    fn test_partial_overlap() {
+        let (pc, gt_labels) = two_class_cloud();
+        let mut predicted = gt_labels.clone();
+        predicted[4] = 2;
+        let matrix = ConfusionMatrix::from_clouds(&pc, &predicted, &pc, &gt_labels);
+        let iou = matrix.iou();
+        assert_eq!(iou[&1], 4.0 / 5.0);
+        assert_eq!(iou[&2], 5.0 / 6.0);
+
+        let mut csv = vec![];
+        matrix.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "ground_truth\\predicted,1,2\n1,4,1\n2,0,5\n"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::structures::neighbours::select_neighboring_points;
+
+use super::sampling::poisson_disk_sample;
+
+/// Contracts the cloud towards its medial axis: in each iteration, every point moves by
+/// `strength` of the way towards the centroid of its neighbours within `radius`, found in the
+/// positions of the previous iteration. A radius about the width of the thinnest part of the
+/// shape collapses each cross section onto its center in a few iterations.
+pub fn contract(
+    cloud: &PointCloud<PointXyzRgba>,
+    iterations: usize,
+    strength: f32,
+    radius: f32,
+) -> PointCloud<PointXyzRgba> {
+    let strength = strength.clamp(0.0, 1.0);
+    let mut points = cloud.clone();
+    for _ in 0..iterations {
+        // an octree, as contracted points end up too close together for the kd tree to split
+        let neighbourhoods = select_neighboring_points(&points, radius);
+        let contracted = points
+            .points
+            .iter()
+            .zip(&neighbourhoods)
+            .map(|(p, neighbours)| {
+                // the point is its own neighbour, so there is at least one
+                let n = neighbours.len() as f32;
+                let (mut cx, mut cy, mut cz) = (0.0, 0.0, 0.0);
+                for &j in neighbours {
+                    cx += points.points[j].x;
+                    cy += points.points[j].y;
+                    cz += points.points[j].z;
+                }
+                PointXyzRgba {
+                    x: p.x + strength * (cx / n - p.x),
+                    y: p.y + strength * (cy / n - p.y),
+                    z: p.z + strength * (cz / n - p.z),
+                    ..*p
+                }
+            })
+            .collect();
+        points = PointCloud::from_points(contracted);
+    }
+    points
+}
+
+/// Extracts a curve skeleton: contracts the cloud with [`contract`], then keeps the contracted
+/// points at least `sample_distance` apart as the nodes of the skeleton.
+pub fn extract_skeleton(
+    cloud: &PointCloud<PointXyzRgba>,
+    iterations: usize,
+    strength: f32,
+    radius: f32,
+    sample_distance: f32,
+) -> PointCloud<PointXyzRgba> {
+    poisson_disk_sample(
+        &contract(cloud, iterations, strength, radius),
+        sample_distance,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Random points on the surface of a pipe of radius 0.5 along the y axis, from y = 0 to 10.
+    fn pipe() -> PointCloud<PointXyzRgba> {
+        let mut rng = StdRng::seed_from_u64(8);
+        let points = (0..3000)
+            .map(|_| {
+                let angle: f32 = rng.gen_range(0.0..std::f32::consts::TAU);
+                PointXyzRgba {
+                    x: 0.5 * angle.cos(),
+                    y: rng.gen_range(0.0..10.0),
+                    z: 0.5 * angle.sin(),
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                }
+            })
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    fn mean_distance_to_axis(cloud: &PointCloud<PointXyzRgba>) -> f32 {
+        cloud
+            .points
+            .iter()
+            .map(|p| (p.x * p.x + p.z * p.z).sqrt())
+            .sum::<f32>()
+            / cloud.points.len() as f32
+    }
+
+    #[test]
+    fn test_pipe_contracts_to_axis() {
+        let cloud = pipe();
+        let contracted = contract(&cloud, 10, 0.8, 1.0);
+        assert_eq!(contracted.len(), cloud.len());
+        assert!(mean_distance_to_axis(&contracted) < 0.05);
+        assert!(contract(&cloud, 0, 0.8, 1.0).points == cloud.points);
+    }
+
+    #[test]
+    fn test_skeleton_is_sparse() {
+        let skeleton = extract_skeleton(&pipe(), 10, 0.8, 1.0, 0.5);
+        assert!(mean_distance_to_axis(&skeleton) < 0.05);
+        // nodes at least 0.5 apart along the axis, which shrinks from its ends while contracting
+        assert!((5..=21).contains(&skeleton.len()), "{}", skeleton.len());
+    }
+}
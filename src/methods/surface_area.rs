@@ -0,0 +1,150 @@
+use cgmath::{InnerSpace, Vector3};
+use kiddo::{distance::squared_euclidean, KdTree};
+
+use super::plane::Plane;
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Estimates the area of the surface around every point as the area of its Voronoi cell among its
+/// `k` nearest neighbours, in the plane fitted to the neighbourhood.
+///
+/// The cell is clipped to a square reaching the farthest neighbour, which bounds the cells of
+/// points on the border of the surface. Points with less than two neighbours or a degenerate
+/// neighbourhood get a zero area.
+pub fn point_areas(cloud: &PointCloud<PointXyzRgba>, k: usize) -> Vec<f32> {
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        tree.add(&p.xyz(), i)
+            .expect("Should be able to add point to kd tree");
+    }
+
+    cloud
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let neighbours: Vec<PointXyzRgba> = tree
+                .nearest(&p.xyz(), k + 1, &squared_euclidean)
+                .expect("Failed to query tree")
+                .into_iter()
+                .filter(|&(_, &j)| j != i)
+                .map(|(_, &j)| cloud.points[j])
+                .collect();
+            voronoi_cell_area(p, &neighbours)
+        })
+        .collect()
+}
+
+/// Total of [`point_areas`].
+pub fn surface_area(cloud: &PointCloud<PointXyzRgba>, k: usize) -> f32 {
+    point_areas(cloud, k).iter().sum()
+}
+
+fn voronoi_cell_area(p: &PointXyzRgba, neighbours: &[PointXyzRgba]) -> f32 {
+    if neighbours.len() < 2 {
+        return 0.0;
+    }
+    let mut neighbourhood = neighbours.to_vec();
+    neighbourhood.push(*p);
+    let (u, v) = match Plane::fit(&PointCloud::from_points(neighbourhood)) {
+        Some(plane) => plane.basis(),
+        None => return 0.0,
+    };
+
+    let offsets: Vec<(f32, f32)> = neighbours
+        .iter()
+        .map(|q| {
+            let d = Vector3::new(q.x - p.x, q.y - p.y, q.z - p.z);
+            (d.dot(u), d.dot(v))
+        })
+        .collect();
+    let extent = neighbours
+        .iter()
+        .map(|q| Vector3::new(q.x - p.x, q.y - p.y, q.z - p.z).magnitude())
+        .fold(0.0, f32::max);
+
+    let mut cell = vec![
+        (-extent, -extent),
+        (extent, -extent),
+        (extent, extent),
+        (-extent, extent),
+    ];
+    for &(mu, mv) in &offsets {
+        // the points closer to the origin than to the neighbour satisfy x . m <= |m|^2 / 2
+        let bound = (mu * mu + mv * mv) / 2.0;
+        if bound > 0.0 {
+            cell = clip(&cell, (mu, mv), bound);
+        }
+    }
+    polygon_area(&cell)
+}
+
+/// Clips the convex polygon to the half-plane `x . m <= bound`.
+fn clip(polygon: &[(f32, f32)], m: (f32, f32), bound: f32) -> Vec<(f32, f32)> {
+    let side = |p: (f32, f32)| p.0 * m.0 + p.1 * m.1 - bound;
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (i, &a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        let (sa, sb) = (side(a), side(b));
+        if sa <= 0.0 {
+            clipped.push(a);
+        }
+        if (sa < 0.0 && sb > 0.0) || (sa > 0.0 && sb < 0.0) {
+            let t = sa / (sa - sb);
+            clipped.push((a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1)));
+        }
+    }
+    clipped
+}
+
+/// Shoelace formula.
+fn polygon_area(polygon: &[(f32, f32)]) -> f32 {
+    let twice_area: f32 = polygon
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            let b = polygon[(i + 1) % polygon.len()];
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    twice_area.abs() / 2.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unit_sphere_area() {
+        // fibonacci sphere, the points cover the sphere evenly
+        let n = 4000;
+        let golden_angle = std::f32::consts::PI * (3.0 - 5f32.sqrt());
+        let points = (0..n)
+            .map(|i| {
+                let y = 1.0 - 2.0 * (i as f32 + 0.5) / n as f32;
+                let r = (1.0 - y * y).sqrt();
+                let theta = golden_angle * i as f32;
+                PointXyzRgba {
+                    x: r * theta.cos(),
+                    y,
+                    z: r * theta.sin(),
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                }
+            })
+            .collect();
+
+        let area = surface_area(&PointCloud::from_points(points), 12);
+        let expected = 4.0 * std::f32::consts::PI;
+        assert!((area - expected).abs() < 0.01 * expected, "{}", area);
+    }
+
+    #[test]
+    fn test_clip_square() {
+        let square = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+        assert_eq!(polygon_area(&square), 4.0);
+        let half = clip(&square, (1.0, 0.0), 1.0);
+        assert_eq!(polygon_area(&half), 2.0);
+    }
+}
@@ -0,0 +1,166 @@
+use cgmath::{InnerSpace, Vector3};
+use kiddo::{distance::squared_euclidean, KdTree};
+use rand::{seq::index::sample, Rng};
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+use super::plane::Plane;
+
+/// Number of points whose mirror images are checked to score a candidate plane.
+const MAX_SCORED_POINTS: usize = 1000;
+
+/// Mirror image of the point across the plane.
+fn reflect(plane: &Plane, p: &PointXyzRgba) -> [f32; 3] {
+    let d = 2.0 * plane.distance(p);
+    [
+        p.x - d * plane.normal.x,
+        p.y - d * plane.normal.y,
+        p.z - d * plane.normal.z,
+    ]
+}
+
+fn has_mirror(
+    tree: &KdTree<f32, usize, 3>,
+    plane: &Plane,
+    p: &PointXyzRgba,
+    tolerance: f32,
+) -> bool {
+    let nearest = tree
+        .nearest(&reflect(plane, p), 1, &squared_euclidean)
+        .expect("Failed to query tree");
+    nearest[0].0 <= tolerance * tolerance
+}
+
+/// Estimates the plane of reflective symmetry of the cloud with RANSAC.
+///
+/// Each iteration draws a triplet of points. The first two points are assumed to be mirror images
+/// of each other, which gives the candidate plane bisecting them, and the candidate is only scored
+/// if the third point also has a mirror image within `tolerance`. The score of a plane is the
+/// fraction of points, out of a random subset, with a mirror image within `tolerance`.
+///
+/// Returns the best plane, with the largest component of its normal positive, and its score.
+pub fn detect_symmetry<R: Rng>(
+    cloud: &PointCloud<PointXyzRgba>,
+    iterations: usize,
+    tolerance: f32,
+    rng: &mut R,
+) -> Option<(Plane, f32)> {
+    let n = cloud.points.len();
+    if n < 3 {
+        return None;
+    }
+
+    let mut tree = KdTree::new();
+    for (i, p) in cloud.points.iter().enumerate() {
+        tree.add(&p.xyz(), i).expect("Failed to add to tree");
+    }
+    let scored = sample(rng, n, n.min(MAX_SCORED_POINTS)).into_vec();
+
+    let mut best: Option<(Plane, f32)> = None;
+    for _ in 0..iterations {
+        let triplet = sample(rng, n, 3);
+        let (a, b, c) = (
+            &cloud.points[triplet.index(0)],
+            &cloud.points[triplet.index(1)],
+            &cloud.points[triplet.index(2)],
+        );
+        let normal = Vector3::new(a.x - b.x, a.y - b.y, a.z - b.z);
+        let midpoint = Vector3::new(a.x + b.x, a.y + b.y, a.z + b.z) / 2.0;
+        let plane = match Plane::new(normal, normal.dot(midpoint)) {
+            Some(plane) => plane,
+            None => continue,
+        };
+        if !has_mirror(&tree, &plane, c, tolerance) {
+            continue;
+        }
+
+        let inliers = scored
+            .iter()
+            .filter(|&&i| has_mirror(&tree, &plane, &cloud.points[i], tolerance))
+            .count();
+        let score = inliers as f32 / scored.len() as f32;
+        if best.map_or(true, |(_, best_score)| score > best_score) {
+            best = Some((plane, score));
+        }
+    }
+
+    best.map(|(plane, score)| {
+        let n = plane.normal;
+        let largest = if n.x.abs() >= n.y.abs() && n.x.abs() >= n.z.abs() {
+            n.x
+        } else if n.y.abs() >= n.z.abs() {
+            n.y
+        } else {
+            n.z
+        };
+        let sign = largest.signum();
+        (
+            Plane {
+                normal: n * sign,
+                offset: plane.offset * sign,
+            },
+            score,
+        )
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Random points mirrored across the plane x = 2, with a few points without a mirror image.
+    fn bilateral_cloud(rng: &mut StdRng) -> PointCloud<PointXyzRgba> {
+        let mut points = vec![];
+        for _ in 0..100 {
+            let p = PointXyzRgba {
+                x: rng.gen_range(2.1..4.0),
+                y: rng.gen_range(-1.0..1.0),
+                z: rng.gen_range(0.0..3.0),
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            };
+            points.push(p);
+            points.push(PointXyzRgba { x: 4.0 - p.x, ..p });
+        }
+        for _ in 0..10 {
+            points.push(PointXyzRgba {
+                x: rng.gen_range(2.1..4.0),
+                y: rng.gen_range(1.5..2.0),
+                z: rng.gen_range(0.0..3.0),
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            });
+        }
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_bilateral_symmetry() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let cloud = bilateral_cloud(&mut rng);
+        let (plane, score) = detect_symmetry(&cloud, 5000, 0.01, &mut rng).unwrap();
+        assert!((plane.normal.x - 1.0).abs() < 1e-3, "{:?}", plane);
+        assert!((plane.offset - 2.0).abs() < 1e-3, "{:?}", plane);
+        assert!((score - 200.0 / 210.0).abs() < 1e-6, "{}", score);
+    }
+
+    #[test]
+    fn test_reflect() {
+        let plane = Plane::new(Vector3::new(0.0, 2.0, 0.0), 2.0).unwrap();
+        let p = PointXyzRgba {
+            x: 1.0,
+            y: 3.0,
+            z: -1.0,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+        assert_eq!(reflect(&plane, &p), [1.0, -1.0, -1.0]);
+    }
+}
@@ -0,0 +1,286 @@
+use cgmath::{EuclideanSpace, MetricSpace, Point3, Vector3};
+use std::collections::BTreeMap;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Solves the rectangular assignment problem with the Hungarian algorithm in O(n^2 m).
+/// Returns the column assigned to each row, every row gets a column if there are at least as
+/// many columns as rows.
+pub fn hungarian(cost: &[Vec<f64>]) -> Vec<Option<usize>> {
+    let n = cost.len();
+    if n == 0 {
+        return vec![];
+    }
+    let m = cost[0].len();
+    if n > m {
+        let transposed: Vec<Vec<f64>> = (0..m)
+            .map(|j| (0..n).map(|i| cost[i][j]).collect())
+            .collect();
+        let mut assignment = vec![None; n];
+        for (j, i) in hungarian(&transposed).into_iter().enumerate() {
+            if let Some(i) = i {
+                assignment[i] = Some(j);
+            }
+        }
+        return assignment;
+    }
+
+    // potentials and matching are 1-indexed, p[j] is the row matched to column j
+    let mut u = vec![0f64; n + 1];
+    let mut v = vec![0f64; m + 1];
+    let mut p = vec![0usize; m + 1];
+    let mut way = vec![0usize; m + 1];
+    for i in 1..=n {
+        p[0] = i;
+        let mut j0 = 0;
+        let mut minv = vec![f64::INFINITY; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if !used[j] {
+                    let cur = cost[i0 - 1][j - 1] - u[i0] - v[j];
+                    if cur < minv[j] {
+                        minv[j] = cur;
+                        way[j] = j0;
+                    }
+                    if minv[j] < delta {
+                        delta = minv[j];
+                        j1 = j;
+                    }
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut assignment = vec![None; n];
+    for j in 1..=m {
+        if p[j] != 0 {
+            assignment[p[j] - 1] = Some(j - 1);
+        }
+    }
+    assignment
+}
+
+struct Track {
+    id: u32,
+    centroid: Point3<f32>,
+    missing: usize,
+}
+
+/// Tracks clusters across frames by assigning the cluster centroids of each frame to the tracks of
+/// the previous frames with the Hungarian algorithm.
+pub struct Tracker {
+    max_distance: f32,
+    max_missing_frames: usize,
+    tracks: Vec<Track>,
+    next_id: u32,
+}
+
+impl Tracker {
+    pub fn new(max_distance: f32, max_missing_frames: usize) -> Self {
+        Self {
+            max_distance,
+            max_missing_frames,
+            tracks: vec![],
+            next_id: 0,
+        }
+    }
+
+    /// Returns the track id of each centroid. Centroids further than `max_distance` from every
+    /// track start a new track, tracks not seen for more than `max_missing_frames` are deleted.
+    pub fn update(&mut self, centroids: &[Point3<f32>]) -> Vec<u32> {
+        // gated pairs get a cost above any valid assignment so they are only used when unavoidable
+        let gate =
+            self.max_distance as f64 * (centroids.len() + self.tracks.len() + 1) as f64 + 1.0;
+        let cost: Vec<Vec<f64>> = centroids
+            .iter()
+            .map(|c| {
+                self.tracks
+                    .iter()
+                    .map(|t| {
+                        let d = c.distance(t.centroid);
+                        if d <= self.max_distance {
+                            d as f64
+                        } else {
+                            gate
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let assignment = if self.tracks.is_empty() {
+            vec![None; centroids.len()]
+        } else {
+            hungarian(&cost)
+        };
+
+        let mut seen = vec![false; self.tracks.len()];
+        let mut ids = Vec::with_capacity(centroids.len());
+        for (c, assigned) in centroids.iter().zip(assignment) {
+            match assigned.filter(|&t| c.distance(self.tracks[t].centroid) <= self.max_distance) {
+                Some(t) => {
+                    seen[t] = true;
+                    self.tracks[t].centroid = *c;
+                    self.tracks[t].missing = 0;
+                    ids.push(self.tracks[t].id);
+                }
+                None => {
+                    self.tracks.push(Track {
+                        id: self.next_id,
+                        centroid: *c,
+                        missing: 0,
+                    });
+                    ids.push(self.next_id);
+                    self.next_id += 1;
+                }
+            }
+        }
+
+        let max_missing_frames = self.max_missing_frames;
+        let mut idx = 0;
+        self.tracks.retain_mut(|t| {
+            let matched = seen.get(idx).copied().unwrap_or(true);
+            idx += 1;
+            if !matched {
+                t.missing += 1;
+            }
+            t.missing <= max_missing_frames
+        });
+        ids
+    }
+
+    /// Replaces the cluster labels of a clustered point cloud with track ids.
+    pub fn relabel(&mut self, cloud: &PointCloud<PointXyzRgba>, labels: &[u32]) -> Vec<u32> {
+        let mut sums: BTreeMap<u32, (Vector3<f32>, usize)> = BTreeMap::new();
+        for (p, &label) in cloud.points.iter().zip(labels) {
+            let entry = sums
+                .entry(label)
+                .or_insert((Vector3::new(0.0, 0.0, 0.0), 0));
+            entry.0 += Vector3::new(p.x, p.y, p.z);
+            entry.1 += 1;
+        }
+        let clusters: Vec<u32> = sums.keys().copied().collect();
+        let centroids: Vec<Point3<f32>> = sums
+            .values()
+            .map(|&(sum, count)| Point3::from_vec(sum / count as f32))
+            .collect();
+        let ids: BTreeMap<u32, u32> = clusters.into_iter().zip(self.update(&centroids)).collect();
+        labels.iter().map(|label| ids[label]).collect()
+    }
+
+    /// Splits a clustered point cloud into one point cloud per cluster, paired with the track id of
+    /// the cluster and ordered by track id.
+    pub fn split(
+        &mut self,
+        cloud: &PointCloud<PointXyzRgba>,
+        labels: &[u32],
+    ) -> Vec<(u32, PointCloud<PointXyzRgba>)> {
+        split_by_label(cloud, &self.relabel(cloud, labels))
+    }
+}
+
+/// Splits a labelled point cloud into one point cloud per label, paired with the label and
+/// ordered by label.
+pub fn split_by_label(
+    cloud: &PointCloud<PointXyzRgba>,
+    labels: &[u32],
+) -> Vec<(u32, PointCloud<PointXyzRgba>)> {
+    let mut parts: BTreeMap<u32, Vec<PointXyzRgba>> = BTreeMap::new();
+    for (p, &label) in cloud.points.iter().zip(labels) {
+        parts.entry(label).or_default().push(*p);
+    }
+    parts
+        .into_iter()
+        .map(|(label, points)| (label, PointCloud::from_points(points)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hungarian() {
+        let cost = vec![
+            vec![4.0, 1.0, 3.0],
+            vec![2.0, 0.0, 5.0],
+            vec![3.0, 2.0, 2.0],
+        ];
+        assert_eq!(hungarian(&cost), vec![Some(1), Some(0), Some(2)]);
+
+        let cost = vec![vec![1.0], vec![0.0]];
+        assert_eq!(hungarian(&cost), vec![None, Some(0)]);
+    }
+
+    #[test]
+    fn test_tracker_stable_ids() {
+        let mut tracker = Tracker::new(1.0, 1);
+        let a = Point3::new(0.0, 0.0, 0.0);
+        let b = Point3::new(10.0, 0.0, 0.0);
+        assert_eq!(tracker.update(&[a, b]), vec![0, 1]);
+
+        // both objects move a little and are listed in the other order
+        let step = Vector3::new(0.5, 0.0, 0.0);
+        assert_eq!(tracker.update(&[b + step, a + step]), vec![1, 0]);
+
+        // an object far from every track starts a new track
+        let c = Point3::new(0.0, 20.0, 0.0);
+        assert_eq!(tracker.update(&[a + step * 2.0, c]), vec![0, 2]);
+
+        // track 1 was missing for two frames and is deleted
+        assert_eq!(tracker.update(&[a + step * 3.0]), vec![0]);
+        assert_eq!(tracker.update(&[b + step]), vec![3]);
+    }
+
+    #[test]
+    fn test_tracker_split() {
+        let point = |x: f32| PointXyzRgba {
+            x,
+            y: 0.0,
+            z: 0.0,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+        let mut tracker = Tracker::new(1.0, 1);
+        let cloud = PointCloud::from_points(vec![point(0.0), point(10.0), point(0.2)]);
+        let clusters = tracker.split(&cloud, &[7, 3, 7]);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].0, 0);
+        assert_eq!(clusters[0].1.points, vec![point(10.0)]);
+        assert_eq!(clusters[1].0, 1);
+        assert_eq!(clusters[1].1.points, vec![point(0.0), point(0.2)]);
+
+        // the clusters keep their track ids when their labels change
+        let cloud = PointCloud::from_points(vec![point(10.5), point(0.5)]);
+        let clusters = tracker.split(&cloud, &[0, 1]);
+        let ids: Vec<u32> = clusters.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![0, 1]);
+        assert_eq!(clusters[0].1.points, vec![point(10.5)]);
+    }
+}
@@ -0,0 +1,585 @@
+use cgmath::Point3;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+use super::fitting::solve_3x3;
+
+/// A triangle of the triangulation with its circumcircle, in counter-clockwise order starting
+/// from its smallest vertex index.
+struct Triangle {
+    vertices: [usize; 3],
+    center: (f64, f64),
+    radius_squared: f64,
+}
+
+impl Triangle {
+    fn new(vertices: [usize; 3], xy: &[(f64, f64)]) -> Self {
+        let [a, b, c] = vertices.map(|i| xy[i]);
+        let orientation = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+        let mut vertices = if orientation < 0.0 {
+            [vertices[0], vertices[2], vertices[1]]
+        } else {
+            vertices
+        };
+        // start from the smallest index, so that each triangle has a single representation
+        let first = (0..3).min_by_key(|&k| vertices[k]).unwrap();
+        vertices.rotate_left(first);
+
+        let d = 2.0 * (a.0 * (b.1 - c.1) + b.0 * (c.1 - a.1) + c.0 * (a.1 - b.1));
+        let (a2, b2, c2) = (
+            a.0 * a.0 + a.1 * a.1,
+            b.0 * b.0 + b.1 * b.1,
+            c.0 * c.0 + c.1 * c.1,
+        );
+        let center = (
+            (a2 * (b.1 - c.1) + b2 * (c.1 - a.1) + c2 * (a.1 - b.1)) / d,
+            (a2 * (c.0 - b.0) + b2 * (a.0 - c.0) + c2 * (b.0 - a.0)) / d,
+        );
+        let radius_squared = (a.0 - center.0).powi(2) + (a.1 - center.1).powi(2);
+        Self {
+            vertices,
+            center,
+            radius_squared,
+        }
+    }
+
+    fn circumcircle_contains(&self, p: (f64, f64)) -> bool {
+        (p.0 - self.center.0).powi(2) + (p.1 - self.center.1).powi(2) < self.radius_squared
+    }
+}
+
+/// Marks a missing neighbor, across the edges of the bounding triangle.
+const NONE: usize = usize::MAX;
+
+/// A triangle of the mesh built by `delaunay_2d`, `neighbors[k]` is the triangle across the edge
+/// opposite `triangle.vertices[k]`.
+struct Face {
+    triangle: Triangle,
+    neighbors: [usize; 3],
+    alive: bool,
+}
+
+impl Face {
+    fn set_neighbor(&mut self, opposite: usize, neighbor: usize) {
+        let k = self.triangle.vertices.iter().position(|&v| v == opposite);
+        self.neighbors[k.expect("vertex of the triangle")] = neighbor;
+    }
+}
+
+fn orientation(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+/// Indices of the points sorted along a Z-order curve over their bounding box, so that
+/// consecutive points are close to each other. Points with the same code keep their order.
+fn z_order<const D: usize>(points: &[[f64; D]]) -> Vec<usize> {
+    let bits = 63 / D as u32;
+    let mut min = [f64::INFINITY; D];
+    let mut max = [f64::NEG_INFINITY; D];
+    for p in points {
+        for k in 0..D {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+    let cells = ((1u64 << bits) - 1) as f64;
+    let code = |p: &[f64; D]| {
+        let cell: [u64; D] = std::array::from_fn(|k| {
+            ((p[k] - min[k]) / (max[k] - min[k]).max(1e-300) * cells) as u64
+        });
+        let mut code = 0u64;
+        for bit in (0..bits).rev() {
+            for c in cell {
+                code = (code << 1) | ((c >> bit) & 1);
+            }
+        }
+        code
+    };
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by_key(|&i| code(&points[i]));
+    order
+}
+
+/// Delaunay triangulation of the points projected onto the XY plane, with the Bowyer-Watson
+/// algorithm. Returns the indices of the vertices of each triangle in counter-clockwise order,
+/// starting from the smallest index.
+/// Points with the same XY coordinates as an earlier point are left out.
+///
+/// The points are inserted along a Z-order curve. Each one is located by walking across the
+/// triangles from the last one created, which stays close, and the triangles whose circumcircle
+/// holds it are found among the neighbors of the one containing it, so the expected cost of an
+/// insertion does not grow with the number of points.
+pub fn delaunay_2d(points: &[Point3<f32>]) -> Vec<[usize; 3]> {
+    let n = points.len();
+    if n < 3 {
+        return vec![];
+    }
+    let mut xy: Vec<(f64, f64)> = points.iter().map(|p| (p.x as f64, p.y as f64)).collect();
+    let order = z_order(&xy.iter().map(|&(x, y)| [x, y]).collect::<Vec<_>>());
+
+    // a triangle holding every point far inside its circumcircle, removed at the end
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (xy[0].0, xy[0].1, xy[0].0, xy[0].1);
+    for &(x, y) in &xy {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    let extent = (max_x - min_x).max(max_y - min_y).max(1.0);
+    let (mid_x, mid_y) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+    xy.push((mid_x - 20.0 * extent, mid_y - extent));
+    xy.push((mid_x, mid_y + 20.0 * extent));
+    xy.push((mid_x + 20.0 * extent, mid_y - extent));
+    let mut faces = vec![Face {
+        triangle: Triangle::new([n, n + 1, n + 2], &xy),
+        neighbors: [NONE; 3],
+        alive: true,
+    }];
+
+    // the point whose cavity holds each face, to tell the cavity from the faces around it
+    let mut cavity_of: Vec<usize> = vec![NONE];
+    let mut last = 0;
+    let mut seen = std::collections::HashSet::new();
+    for i in order {
+        let p = xy[i];
+        if !seen.insert((p.0.to_bits(), p.1.to_bits())) {
+            continue;
+        }
+
+        // walk towards the point, crossing an edge it is on the other side of
+        let mut current = last;
+        for step in 0..faces.len() {
+            let vertices = faces[current].triangle.vertices;
+            let crossed = (0..3).map(|k| (k + step) % 3).find(|&k| {
+                let (a, b) = (vertices[(k + 1) % 3], vertices[(k + 2) % 3]);
+                orientation(xy[a], xy[b], p) < 0.0
+            });
+            match crossed.map(|k| faces[current].neighbors[k]) {
+                Some(NONE) | None => break,
+                Some(next) => current = next,
+            }
+        }
+        // rounding can stop the walk short, then any triangle whose circumcircle holds it will do
+        let seed = if faces[current].triangle.circumcircle_contains(p) {
+            Some(current)
+        } else {
+            (0..faces.len()).find(|&t| faces[t].alive && faces[t].triangle.circumcircle_contains(p))
+        };
+        let seed = match seed {
+            Some(seed) => seed,
+            None => continue,
+        };
+
+        let mut cavity = vec![seed];
+        cavity_of[seed] = i;
+        let mut stack = vec![seed];
+        while let Some(t) = stack.pop() {
+            for next in faces[t].neighbors {
+                if next != NONE
+                    && cavity_of[next] != i
+                    && faces[next].triangle.circumcircle_contains(p)
+                {
+                    cavity_of[next] = i;
+                    cavity.push(next);
+                    stack.push(next);
+                }
+            }
+        }
+
+        // the boundary of the cavity is made of the edges to the faces outside of it
+        let mut created = vec![];
+        for &t in &cavity {
+            faces[t].alive = false;
+            let vertices = faces[t].triangle.vertices;
+            for k in 0..3 {
+                let outside = faces[t].neighbors[k];
+                if outside != NONE && cavity_of[outside] == i {
+                    continue;
+                }
+                let (a, b) = (vertices[(k + 1) % 3], vertices[(k + 2) % 3]);
+                let mut face = Face {
+                    triangle: Triangle::new([a, b, i], &xy),
+                    neighbors: [NONE; 3],
+                    alive: true,
+                };
+                face.set_neighbor(i, outside);
+                if outside != NONE {
+                    let k = faces[outside].neighbors.iter().position(|&f| f == t);
+                    faces[outside].neighbors[k.expect("neighbors link both ways")] = faces.len();
+                }
+                created.push((a, b, faces.len()));
+                faces.push(face);
+                cavity_of.push(NONE);
+            }
+        }
+        // the new faces around the point share the edges from their vertices to it
+        let starting_at: HashMap<usize, usize> = created.iter().map(|&(a, _, t)| (a, t)).collect();
+        let ending_at: HashMap<usize, usize> = created.iter().map(|&(_, b, t)| (b, t)).collect();
+        for &(a, b, t) in &created {
+            faces[t].set_neighbor(a, starting_at.get(&b).copied().unwrap_or(NONE));
+            faces[t].set_neighbor(b, ending_at.get(&a).copied().unwrap_or(NONE));
+        }
+        last = faces.len() - 1;
+    }
+
+    faces
+        .into_iter()
+        .filter(|f| f.alive && f.triangle.vertices.iter().all(|&v| v < n))
+        .map(|f| f.triangle.vertices)
+        .collect()
+}
+
+/// A tetrahedron of the tetrahedralization with its circumsphere, with sorted vertices.
+/// Degenerate tetrahedra have no circumsphere. `neighbors[k]` is the tetrahedron across the face
+/// opposite `vertices[k]`.
+struct Tetrahedron {
+    vertices: [usize; 4],
+    sphere: Option<([f64; 3], f64)>,
+    neighbors: [usize; 4],
+    alive: bool,
+}
+
+impl Tetrahedron {
+    fn new(mut vertices: [usize; 4], xyz: &[[f64; 3]]) -> Self {
+        vertices.sort_unstable();
+        Self {
+            vertices,
+            sphere: circumsphere(vertices.map(|i| xyz[i])),
+            neighbors: [NONE; 4],
+            alive: true,
+        }
+    }
+
+    fn set_neighbor(&mut self, opposite: usize, neighbor: usize) {
+        let k = self.vertices.iter().position(|&v| v == opposite);
+        self.neighbors[k.expect("vertex of the tetrahedron")] = neighbor;
+    }
+
+    fn circumsphere_contains(&self, p: [f64; 3]) -> bool {
+        self.sphere.map_or(false, |(center, radius_squared)| {
+            squared_distance(p, center) < radius_squared
+        })
+    }
+
+    /// The face opposite `vertices[k]`, with sorted vertices.
+    fn face(&self, k: usize) -> [usize; 3] {
+        let [a, b, c, d] = self.vertices;
+        [[b, c, d], [a, c, d], [a, b, d], [a, b, c]][k]
+    }
+}
+
+fn orientation_3d(a: [f64; 3], b: [f64; 3], c: [f64; 3], d: [f64; 3]) -> f64 {
+    let u = [0, 1, 2].map(|k| b[k] - a[k]);
+    let v = [0, 1, 2].map(|k| c[k] - a[k]);
+    let w = [0, 1, 2].map(|k| d[k] - a[k]);
+    u[0] * (v[1] * w[2] - v[2] * w[1]) - u[1] * (v[0] * w[2] - v[2] * w[0])
+        + u[2] * (v[0] * w[1] - v[1] * w[0])
+}
+
+fn squared_distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+/// Center and squared radius of the sphere through the four points, `None` if they are coplanar.
+pub(crate) fn circumsphere(p: [[f64; 3]; 4]) -> Option<([f64; 3], f64)> {
+    // |c - p_i|^2 = |c - p_0|^2  <=>  2 (p_i - p_0) . c = |p_i|^2 - |p_0|^2
+    let norm2 = |q: [f64; 3]| q[0] * q[0] + q[1] * q[1] + q[2] * q[2];
+    let mut a = [[0f64; 3]; 3];
+    let mut b = [0f64; 3];
+    for row in 0..3 {
+        for col in 0..3 {
+            a[row][col] = 2.0 * (p[row + 1][col] - p[0][col]);
+        }
+        b[row] = norm2(p[row + 1]) - norm2(p[0]);
+    }
+    let center = solve_3x3(a, b)?;
+    Some((center, squared_distance(center, p[0])))
+}
+
+/// Delaunay tetrahedralization of the points with the Bowyer-Watson algorithm. Returns the
+/// sorted indices of the vertices of each tetrahedron. Points at the same position as an earlier
+/// point are left out.
+///
+/// As in `delaunay_2d`, the points are inserted along a Z-order curve, located by walking from the
+/// last tetrahedron created and their cavity is grown from the tetrahedron containing them.
+pub fn delaunay_3d(points: &[Point3<f32>]) -> Vec<[usize; 4]> {
+    let n = points.len();
+    if n < 4 {
+        return vec![];
+    }
+    let mut xyz: Vec<[f64; 3]> = points
+        .iter()
+        .map(|p| [p.x as f64, p.y as f64, p.z as f64])
+        .collect();
+    let order = z_order(&xyz);
+
+    // a tetrahedron holding every point far inside its circumsphere, removed at the end
+    let mut min = xyz[0];
+    let mut max = xyz[0];
+    for p in &xyz {
+        for k in 0..3 {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+    let extent = (0..3).map(|k| max[k] - min[k]).fold(1.0, f64::max) * 1000.0;
+    let mid = [0, 1, 2].map(|k| (min[k] + max[k]) / 2.0);
+    xyz.push([mid[0] - extent, mid[1] - extent, mid[2] - extent]);
+    xyz.push([mid[0] + 3.0 * extent, mid[1] - extent, mid[2] - extent]);
+    xyz.push([mid[0] - extent, mid[1] + 3.0 * extent, mid[2] - extent]);
+    xyz.push([mid[0] - extent, mid[1] - extent, mid[2] + 3.0 * extent]);
+    let mut tetrahedra = vec![Tetrahedron::new([n, n + 1, n + 2, n + 3], &xyz)];
+
+    // the point whose cavity holds each tetrahedron
+    let mut cavity_of: Vec<usize> = vec![NONE];
+    let mut last = 0;
+    let mut seen = std::collections::HashSet::new();
+    for i in order {
+        let p = xyz[i];
+        if !seen.insert(p.map(f64::to_bits)) {
+            continue;
+        }
+
+        // walk towards the point, crossing a face it is on the other side of
+        let mut current = last;
+        for step in 0..tetrahedra.len() {
+            let t = &tetrahedra[current];
+            let crossed = (0..4).map(|k| (k + step) % 4).find(|&k| {
+                let [a, b, c] = t.face(k).map(|v| xyz[v]);
+                let inside = orientation_3d(a, b, c, xyz[t.vertices[k]]);
+                inside * orientation_3d(a, b, c, p) < 0.0
+            });
+            match crossed.map(|k| t.neighbors[k]) {
+                Some(NONE) | None => break,
+                Some(next) => current = next,
+            }
+        }
+        // rounding can stop the walk short, then any tetrahedron whose circumsphere holds it will do
+        let seed = if tetrahedra[current].circumsphere_contains(p) {
+            Some(current)
+        } else {
+            (0..tetrahedra.len())
+                .find(|&t| tetrahedra[t].alive && tetrahedra[t].circumsphere_contains(p))
+        };
+        let seed = match seed {
+            Some(seed) => seed,
+            None => continue,
+        };
+
+        let mut cavity = vec![seed];
+        cavity_of[seed] = i;
+        let mut stack = vec![seed];
+        while let Some(t) = stack.pop() {
+            for next in tetrahedra[t].neighbors {
+                if next != NONE && cavity_of[next] != i && tetrahedra[next].circumsphere_contains(p)
+                {
+                    cavity_of[next] = i;
+                    cavity.push(next);
+                    stack.push(next);
+                }
+            }
+        }
+
+        // the boundary of the cavity is made of the faces to the tetrahedra outside of it, the
+        // new tetrahedra around the point share the edges of these faces
+        let mut around_edge: HashMap<[usize; 2], (usize, usize)> = HashMap::new();
+        for &t in &cavity {
+            tetrahedra[t].alive = false;
+            for k in 0..4 {
+                let outside = tetrahedra[t].neighbors[k];
+                if outside != NONE && cavity_of[outside] == i {
+                    continue;
+                }
+                let [a, b, c] = tetrahedra[t].face(k);
+                let created = tetrahedra.len();
+                let mut tetrahedron = Tetrahedron::new([a, b, c, i], &xyz);
+                tetrahedron.set_neighbor(i, outside);
+                if outside != NONE {
+                    let k = tetrahedra[outside].neighbors.iter().position(|&f| f == t);
+                    tetrahedra[outside].neighbors[k.expect("neighbors link both ways")] = created;
+                }
+                tetrahedra.push(tetrahedron);
+                cavity_of.push(NONE);
+                for (edge, opposite) in [([a, b], c), ([a, c], b), ([b, c], a)] {
+                    match around_edge.remove(&edge) {
+                        Some((other, other_opposite)) => {
+                            tetrahedra[created].set_neighbor(opposite, other);
+                            tetrahedra[other].set_neighbor(other_opposite, created);
+                        }
+                        None => {
+                            around_edge.insert(edge, (created, opposite));
+                        }
+                    }
+                }
+                last = created;
+            }
+        }
+    }
+
+    tetrahedra
+        .into_iter()
+        .filter(|t| t.alive && t.vertices.iter().all(|&v| v < n))
+        .map(|t| t.vertices)
+        .collect()
+}
+
+/// Writes the points as vertices with their colors, and the triangles as faces, to an obj file.
+pub fn triangulation_to_obj(
+    cloud: &PointCloud<PointXyzRgba>,
+    triangles: &[[usize; 3]],
+    path: &Path,
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for p in &cloud.points {
+        writeln!(
+            writer,
+            "v {} {} {} {} {} {}",
+            p.x,
+            p.y,
+            p.z,
+            p.r as f32 / 255.0,
+            p.g as f32 / 255.0,
+            p.b as f32 / 255.0
+        )?;
+    }
+    for [a, b, c] in triangles {
+        // obj indices start at 1
+        writeln!(writer, "f {} {} {}", a + 1, b + 1, c + 1)?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_three_points_make_one_triangle() {
+        let points = vec![
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(0.0, 1.0, 2.0),
+            Point3::new(1.0, 0.0, 3.0),
+        ];
+        // counter-clockwise in the XY plane
+        assert_eq!(delaunay_2d(&points), vec![[0, 2, 1]]);
+    }
+
+    #[test]
+    fn test_random_points_are_delaunay() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let points: Vec<Point3<f32>> = (0..200)
+            .map(|_| Point3::new(rng.gen_range(0.0..10.0), rng.gen_range(0.0..10.0), 0.0))
+            .collect();
+        let xy: Vec<(f64, f64)> = points.iter().map(|p| (p.x as f64, p.y as f64)).collect();
+        let triangles = delaunay_2d(&points);
+
+        // no point is inside the circumcircle of a triangle
+        for t in &triangles {
+            let triangle = Triangle::new(*t, &xy);
+            assert_eq!(triangle.vertices, *t);
+            for (i, &p) in xy.iter().enumerate() {
+                if !t.contains(&i) {
+                    assert!(
+                        !triangle.circumcircle_contains(p) || {
+                            let d = (p.0 - triangle.center.0).powi(2)
+                                + (p.1 - triangle.center.1).powi(2);
+                            triangle.radius_squared - d < 1e-9
+                        }
+                    );
+                }
+            }
+        }
+        // Euler's formula bounds the triangle count for n points in general position
+        assert!(triangles.len() <= 2 * points.len() - 5);
+        assert!(triangles.len() >= points.len());
+    }
+
+    #[test]
+    fn test_grid_is_covered() {
+        // cocircular points, every cell of the grid is split into two triangles
+        let points: Vec<Point3<f32>> = (0..900)
+            .map(|i| Point3::new((i % 30) as f32, (i / 30) as f32, 0.0))
+            .collect();
+        let triangles = delaunay_2d(&points);
+        assert_eq!(triangles.len(), 2 * 29 * 29);
+        let area: f32 = triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let (a, b, c) = (points[a], points[b], points[c]);
+                ((b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)) / 2.0
+            })
+            .sum();
+        assert_eq!(area, 29.0 * 29.0);
+    }
+
+    #[test]
+    fn test_four_points_make_one_tetrahedron() {
+        let points = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ];
+        assert_eq!(delaunay_3d(&points), vec![[0, 1, 2, 3]]);
+    }
+
+    #[test]
+    fn test_random_points_are_delaunay_3d() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let points: Vec<Point3<f32>> = (0..100)
+            .map(|_| {
+                Point3::new(
+                    rng.gen_range(0.0..1.0),
+                    rng.gen_range(0.0..1.0),
+                    rng.gen_range(0.0..1.0),
+                )
+            })
+            .collect();
+        let xyz: Vec<[f64; 3]> = points
+            .iter()
+            .map(|p| [p.x as f64, p.y as f64, p.z as f64])
+            .collect();
+        let tetrahedra = delaunay_3d(&points);
+        assert!(!tetrahedra.is_empty());
+
+        // no point is inside the circumsphere of a tetrahedron
+        for t in &tetrahedra {
+            let (center, radius_squared) = circumsphere(t.map(|i| xyz[i])).unwrap();
+            for (i, &p) in xyz.iter().enumerate() {
+                if !t.contains(&i) {
+                    assert!(squared_distance(p, center) > radius_squared - 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_obj() {
+        let cloud = PointCloud::from_points(
+            [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]
+                .into_iter()
+                .map(|(x, y)| PointXyzRgba {
+                    x,
+                    y,
+                    z: 0.0,
+                    r: 255,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                })
+                .collect(),
+        );
+        let path = std::env::temp_dir().join("vivotk_test_triangulation.obj");
+        triangulation_to_obj(&cloud, &[[0, 1, 2]], &path).unwrap();
+        let obj = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(obj.lines().count(), 4);
+        assert!(obj.starts_with("v 0 0 0 1 0 0\n"));
+        assert!(obj.ends_with("f 1 2 3\n"));
+    }
+}
@@ -0,0 +1,115 @@
+use cgmath::{InnerSpace, Point3};
+use kiddo::{distance::squared_euclidean, KdTree};
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Whether each point is visible from `viewpoint`, by casting a ray from the viewpoint to it:
+/// a point is occluded when another point within `angular_tolerance` radians of the ray is more
+/// than `depth_margin` nearer to the viewpoint.
+///
+/// The tolerance stands for the gaps between the points of an occluding surface, and is best set
+/// to about the angle between neighbouring points seen from the viewpoint. The margin keeps the
+/// neighbours of a point on a surface seen at an angle from hiding it.
+pub fn visibility(
+    cloud: &PointCloud<PointXyzRgba>,
+    viewpoint: Point3<f32>,
+    angular_tolerance: f32,
+    depth_margin: f32,
+) -> Vec<bool> {
+    // rays within the tolerance have unit directions within this chord of each other
+    let chord = 2.0 * (angular_tolerance / 2.0).sin();
+    let rays: Vec<_> = cloud
+        .points
+        .iter()
+        .map(|p| {
+            let offset = Point3::new(p.x, p.y, p.z) - viewpoint;
+            let depth = offset.magnitude();
+            (offset / depth, depth)
+        })
+        .collect();
+    let mut tree: KdTree<f32, usize, 3> = KdTree::new();
+    for (i, (direction, depth)) in rays.iter().enumerate() {
+        // a point at the viewpoint has no direction and hides nothing
+        if *depth > 0.0 {
+            tree.add(&[direction.x, direction.y, direction.z], i)
+                .expect("Failed to add point to kd tree");
+        }
+    }
+
+    rays.iter()
+        .map(|(direction, depth)| {
+            if *depth <= 0.0 || tree.size() == 0 {
+                return true;
+            }
+            !tree
+                .within(
+                    &[direction.x, direction.y, direction.z],
+                    chord * chord,
+                    &squared_euclidean,
+                )
+                .expect("Failed to query kd tree")
+                .into_iter()
+                .any(|(_, &j)| rays[j].1 < depth - depth_margin)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(x: f32, y: f32, z: f32) -> PointXyzRgba {
+        PointXyzRgba::from_xyz_rgba([x, y, z], [255; 4])
+    }
+
+    #[test]
+    fn test_box_in_front_of_wall() {
+        // the box [-0.5, 0.5] x [-0.5, 0.5] x [0, 1] in front of a wall at z = -1, both
+        // sampled every 0.05, seen from above the box
+        let grid = |n: usize| {
+            (0..=n)
+                .flat_map(move |i| (0..=n).map(move |j| (i as f32 / n as f32, j as f32 / n as f32)))
+        };
+        let mut points = vec![];
+        for (s, t) in grid(20) {
+            let (u, v) = (s - 0.5, t - 0.5);
+            points.extend([
+                point(u, v, 1.0),
+                point(u, v, 0.0),
+                point(-0.5, u, t),
+                point(0.5, u, t),
+                point(u, -0.5, t),
+                point(u, 0.5, t),
+            ]);
+        }
+        for (s, t) in grid(60) {
+            points.push(point(3.0 * s - 1.5, 3.0 * t - 1.5, -1.0));
+        }
+        let cloud = PointCloud::from_points(points);
+        let visible = visibility(&cloud, Point3::new(0.0, 0.0, 5.0), 1f32.to_radians(), 0.05);
+
+        for (p, &visible) in cloud.points.iter().zip(&visible) {
+            let extent = p.x.abs().max(p.y.abs());
+            if p.z == -1.0 {
+                // the front of the box hides the wall within 0.75 of the center, 0.5 scaled by
+                // the ratio of the distances to the wall and to the front
+                if extent < 0.65 {
+                    assert!(!visible, "{:?}", p);
+                } else if extent > 0.9 {
+                    assert!(visible, "{:?}", p);
+                }
+            } else {
+                // the sides of the box face away from the viewpoint
+                assert_eq!(visible, p.z == 1.0, "{:?}", p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_and_viewpoint() {
+        let viewpoint = Point3::new(1.0, 2.0, 3.0);
+        assert!(visibility(&PointCloud::from_points(vec![]), viewpoint, 0.01, 0.0).is_empty());
+        let cloud = PointCloud::from_points(vec![point(1.0, 2.0, 3.0), point(1.0, 2.0, 4.0)]);
+        assert_eq!(visibility(&cloud, viewpoint, 0.01, 0.0), vec![true, true]);
+    }
+}
@@ -0,0 +1,98 @@
+use kiddo::{distance::squared_euclidean, KdTree};
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+use super::sampling::farthest_point_indices;
+
+/// Partitions the cloud into the Voronoi cells of `num_seeds` seeds selected by farthest point
+/// sampling. Returns one cell per seed, in selection order, holding the indices of the points
+/// nearer to that seed than to any other, the seed included. Each point is in exactly one cell.
+pub fn voronoi_cells(cloud: &PointCloud<PointXyzRgba>, num_seeds: usize) -> Vec<Vec<usize>> {
+    assign_to_seeds(cloud, &farthest_point_indices(cloud, num_seeds))
+}
+
+/// Returns the indices of the points nearest to each seed.
+fn assign_to_seeds(cloud: &PointCloud<PointXyzRgba>, seeds: &[usize]) -> Vec<Vec<usize>> {
+    let mut cells = vec![vec![]; seeds.len()];
+    if seeds.is_empty() {
+        return cells;
+    }
+
+    let mut tree = KdTree::new();
+    for (cell, &i) in seeds.iter().enumerate() {
+        let p = &cloud.points[i];
+        tree.add(&p.xyz(), cell)
+            .expect("Failed to add to seed tree");
+    }
+    for (i, p) in cloud.points.iter().enumerate() {
+        let nearest = tree
+            .nearest(&p.xyz(), 1, &squared_euclidean)
+            .expect("Failed to query seed tree");
+        cells[*nearest[0].1].push(i);
+    }
+    cells
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(x: f32, y: f32, z: f32) -> PointXyzRgba {
+        PointXyzRgba {
+            x,
+            y,
+            z,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        }
+    }
+
+    fn distance2(a: &PointXyzRgba, b: &PointXyzRgba) -> f32 {
+        (a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)
+    }
+
+    #[test]
+    fn test_clusters_are_cells() {
+        // two 5x5 grids of spacing 0.1, 10 apart along x
+        let points: Vec<PointXyzRgba> = (0..50)
+            .map(|i| {
+                let offset = if i < 25 { 0.0 } else { 10.0 };
+                point(
+                    offset + (i % 5) as f32 * 0.1,
+                    (i % 25 / 5) as f32 * 0.1,
+                    0.0,
+                )
+            })
+            .collect();
+        let cloud = PointCloud::from_points(points);
+
+        let mut cells = voronoi_cells(&cloud, 2);
+        cells.sort();
+        assert_eq!(cells, vec![(0..25).collect::<Vec<_>>(), (25..50).collect()]);
+    }
+
+    #[test]
+    fn test_points_belong_to_nearest_seed() {
+        let cloud = PointCloud::from_points(
+            (0..1000)
+                .map(|i| point((i % 10) as f32, (i / 10 % 10) as f32, (i / 100) as f32))
+                .collect(),
+        );
+        let seeds = farthest_point_indices(&cloud, 8);
+        let cells = assign_to_seeds(&cloud, &seeds);
+        assert_eq!(cells.len(), 8);
+        assert_eq!(cells.iter().map(|c| c.len()).sum::<usize>(), 1000);
+
+        for (cell, points) in cells.iter().enumerate() {
+            assert!(points.contains(&seeds[cell]));
+            for &i in points {
+                let p = &cloud.points[i];
+                let own = distance2(p, &cloud.points[seeds[cell]]);
+                assert!(seeds.iter().all(|&s| own <= distance2(p, &cloud.points[s])));
+            }
+        }
+        assert!(voronoi_cells(&PointCloud::from_points(vec![]), 3).is_empty());
+    }
+}
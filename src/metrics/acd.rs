@@ -14,9 +14,7 @@ impl Acd {
         let acd_sum: f32 = p1
             .par_iter()
             .map(|pt| {
-                let nearest_points = p2_tree
-                    .nearest(&[pt.x, pt.y, pt.z], 2, &squared_euclidean)
-                    .unwrap();
+                let nearest_points = p2_tree.nearest(&pt.xyz(), 2, &squared_euclidean).unwrap();
                 let (dist, _) = nearest_points[0];
                 dist
             })
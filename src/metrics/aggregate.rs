@@ -0,0 +1,158 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use super::Metrics;
+
+/// Summary statistics of a metric over many point clouds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p95: f64,
+}
+
+impl Summary {
+    /// Returns `None` if there are no values. The percentile uses the nearest rank method.
+    fn from_values(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let count = sorted.len();
+        let rank = ((0.95 * count as f64).ceil() as usize).max(1);
+        Some(Self {
+            count,
+            mean: sorted.iter().sum::<f64>() / count as f64,
+            min: sorted[0],
+            max: sorted[count - 1],
+            p95: sorted[rank - 1],
+        })
+    }
+}
+
+/// Collects the numeric values of each metric, values that are not numbers are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsAggregator {
+    values: BTreeMap<String, Vec<f64>>,
+}
+
+impl MetricsAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, metrics: &Metrics) {
+        for (key, value) in metrics.metrics() {
+            if let Ok(value) = value.trim().parse::<f64>() {
+                self.values.entry(key).or_default().push(value);
+            }
+        }
+    }
+
+    pub fn summarize(&self) -> BTreeMap<String, Summary> {
+        self.values
+            .iter()
+            .filter_map(|(key, values)| Some((key.clone(), Summary::from_values(values)?)))
+            .collect()
+    }
+
+    /// Writes one row per metric with its count, mean, min, max and 95th percentile.
+    pub fn write_table<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let summaries = self.summarize();
+        let width = summaries.keys().map(|k| k.len()).max().unwrap_or(0).max(6);
+        writeln!(
+            writer,
+            "{:<width$} {:>8} {:>12} {:>12} {:>12} {:>12}",
+            "metric",
+            "count",
+            "mean",
+            "min",
+            "max",
+            "p95",
+            width = width
+        )?;
+        for (key, s) in summaries {
+            writeln!(
+                writer,
+                "{:<width$} {:>8} {:>12.5} {:>12.5} {:>12.5} {:>12.5}",
+                key,
+                s.count,
+                s.mean,
+                s.min,
+                s.max,
+                s.p95,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn write_json<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        Ok(serde_json::to_writer_pretty(writer, &self.summarize())?)
+    }
+
+    /// Flattens the summaries into metrics named `<metric>_<statistic>`.
+    pub fn to_metrics(&self) -> Metrics {
+        let mut metrics = Metrics::new();
+        for (key, s) in self.summarize() {
+            metrics.insert(format!("{}_mean", key), format!("{:.5}", s.mean));
+            metrics.insert(format!("{}_min", key), format!("{:.5}", s.min));
+            metrics.insert(format!("{}_max", key), format!("{:.5}", s.max));
+            metrics.insert(format!("{}_p95", key), format!("{:.5}", s.p95));
+        }
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_ten_messages() {
+        let mut aggregator = MetricsAggregator::new();
+        for i in 1..=10 {
+            let mut metrics = Metrics::new();
+            metrics.insert("cd".to_string(), format!("{:.5}", i as f64));
+            metrics.insert("hd".to_string(), format!("{:.5}", (i * i) as f64));
+            metrics.insert("name".to_string(), "frame".to_string());
+            aggregator.add(&metrics);
+        }
+
+        let summaries = aggregator.summarize();
+        assert_eq!(summaries.len(), 2);
+        let cd = summaries["cd"];
+        assert_eq!(cd.count, 10);
+        assert!((cd.mean - 5.5).abs() < 1e-9);
+        assert_eq!(cd.min, 1.0);
+        assert_eq!(cd.max, 10.0);
+        assert_eq!(cd.p95, 10.0);
+        let hd = summaries["hd"];
+        assert!((hd.mean - 38.5).abs() < 1e-9);
+        assert_eq!(hd.p95, 100.0);
+
+        let metrics = aggregator.to_metrics();
+        assert_eq!(metrics.metrics().len(), 8);
+
+        let mut json = vec![];
+        aggregator.write_json(&mut json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed["cd"]["max"], 10.0);
+
+        let mut table = vec![];
+        aggregator.write_table(&mut table).unwrap();
+        assert_eq!(String::from_utf8(table).unwrap().lines().count(), 3);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<f64> = (1..=100).map(|i| i as f64).collect();
+        assert_eq!(Summary::from_values(&values).unwrap().p95, 95.0);
+        assert_eq!(Summary::from_values(&[3.0]).unwrap().p95, 3.0);
+        assert!(Summary::from_values(&[]).is_none());
+    }
+}
@@ -35,7 +35,7 @@ impl CdPsnr {
             .par_iter()
             .map(|pt| {
                 let nearest_points = original_tree
-                    .nearest(&[pt.x, pt.y, pt.z], 2, &negative_squared_euclidean)
+                    .nearest(&pt.xyz(), 2, &negative_squared_euclidean)
                     .unwrap();
                 let (dist, _) = nearest_points[0];
                 FloatOrd(dist)
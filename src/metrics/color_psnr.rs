@@ -0,0 +1,159 @@
+use rayon::prelude::*;
+
+use crate::formats::pointxyzrgba::PointXyzRgba;
+use crate::structures::neighbours::NearestNeighbours;
+
+use super::Metrics;
+
+const PEAK: f64 = 255.0;
+
+/// Converts an RGB color to full range YCbCr with the BT.709 matrix.
+fn rgb_to_ycbcr(pt: &PointXyzRgba) -> [f64; 3] {
+    let (r, g, b) = (pt.r as f64, pt.g as f64, pt.b as f64);
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let cb = (b - y) / 1.8556 + 128.0;
+    let cr = (r - y) / 1.5748 + 128.0;
+    [y, cb, cr]
+}
+
+fn get_psnr(mse: f64) -> f64 {
+    10f64 * (PEAK * PEAK / mse).log(10f64)
+}
+
+pub struct ColorPsnr;
+
+impl ColorPsnr {
+    /// Inserts the PSNR of the Y, Cb and Cr channels. Each point is paired with its nearest
+    /// neighbour in the other cloud, in both directions, and the larger mean squared error of the
+    /// two directions is kept. Identical clouds have an infinite PSNR.
+    pub fn calculate_metric(
+        original: &Vec<PointXyzRgba>,
+        original_tree: &impl NearestNeighbours,
+        reconstructed: &Vec<PointXyzRgba>,
+        reconstructed_tree: &impl NearestNeighbours,
+        metrics: &mut Metrics,
+    ) {
+        let mse_or = ColorPsnr::get_mse(original, reconstructed, reconstructed_tree);
+        let mse_ro = ColorPsnr::get_mse(reconstructed, original, original_tree);
+
+        for (i, channel) in ["y", "cb", "cr"].iter().enumerate() {
+            let psnr = get_psnr(f64::max(mse_or[i], mse_ro[i]));
+            metrics.insert(format!("psnr_{}", channel), format!("{:.5}", psnr));
+        }
+    }
+
+    pub(super) fn get_mse(
+        p1: &Vec<PointXyzRgba>,
+        p2: &Vec<PointXyzRgba>,
+        p2_tree: &impl NearestNeighbours,
+    ) -> [f64; 3] {
+        let error = p1
+            .par_iter()
+            .map(|pt| {
+                let nearest_points = p2_tree.nearest_neighbours(&pt.xyz(), 1);
+                let (_, idx) = nearest_points[0];
+                let c1 = rgb_to_ycbcr(pt);
+                let c2 = rgb_to_ycbcr(&p2[idx]);
+                [
+                    (c1[0] - c2[0]).powi(2),
+                    (c1[1] - c2[1]).powi(2),
+                    (c1[2] - c2[2]).powi(2),
+                ]
+            })
+            .reduce(|| [0.0; 3], |a, b| [a[0] + b[0], a[1] + b[1], a[2] + b[2]]);
+
+        let n = p1.len() as f64;
+        [error[0] / n, error[1] / n, error[2] / n]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use kiddo::KdTree;
+
+    fn cloud(offset: u8) -> Vec<PointXyzRgba> {
+        (0..27)
+            .map(|i| PointXyzRgba {
+                x: (i / 9) as f32,
+                y: (i / 3 % 3) as f32,
+                z: (i % 3) as f32,
+                r: (i * 9) as u8,
+                g: 100 + offset,
+                b: 255 - (i * 9) as u8,
+                a: 255,
+            })
+            .collect()
+    }
+
+    fn tree(points: &[PointXyzRgba]) -> KdTree<f32, usize, 3> {
+        let mut tree = KdTree::new();
+        for (i, pt) in points.iter().enumerate() {
+            tree.add(&pt.xyz(), i).unwrap();
+        }
+        tree
+    }
+
+    fn psnr(metrics: &Metrics, key: &str) -> f64 {
+        metrics
+            .metrics()
+            .into_iter()
+            .find(|(k, _)| k == key)
+            .unwrap()
+            .1
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rgb_to_ycbcr() {
+        let white = PointXyzRgba {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            r: 255,
+            g: 255,
+            b: 255,
+            a: 255,
+        };
+        let [y, cb, cr] = rgb_to_ycbcr(&white);
+        assert!((y - 255.0).abs() < 1e-9);
+        assert!((cb - 128.0).abs() < 1e-9);
+        assert!((cr - 128.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_identical_clouds() {
+        let original = cloud(0);
+        let mut metrics = Metrics::new();
+        ColorPsnr::calculate_metric(
+            &original,
+            &tree(&original),
+            &original,
+            &tree(&original),
+            &mut metrics,
+        );
+        for key in ["psnr_y", "psnr_cb", "psnr_cr"] {
+            assert!(psnr(&metrics, key).is_infinite());
+        }
+    }
+
+    #[test]
+    fn test_green_shift() {
+        let original = cloud(0);
+        let reconstructed = cloud(10);
+        let mut metrics = Metrics::new();
+        ColorPsnr::calculate_metric(
+            &original,
+            &tree(&original),
+            &reconstructed,
+            &tree(&reconstructed),
+            &mut metrics,
+        );
+        // a green shift of 10 moves Y by 7.152
+        let expected_y = 10.0 * (255.0f64 * 255.0 / (7.152 * 7.152)).log10();
+        assert!((psnr(&metrics, "psnr_y") - expected_y).abs() < 1e-3);
+        assert!(psnr(&metrics, "psnr_cb") > psnr(&metrics, "psnr_y"));
+        assert!(psnr(&metrics, "psnr_cr") > psnr(&metrics, "psnr_y"));
+    }
+}
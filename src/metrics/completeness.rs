@@ -0,0 +1,81 @@
+use rayon::prelude::*;
+
+use crate::formats::pointxyzrgba::PointXyzRgba;
+use crate::structures::neighbours::NearestNeighbours;
+
+pub struct Completeness;
+
+impl Completeness {
+    /// Fraction of the reference points with a reconstructed point within `threshold`.
+    pub fn calculate_metric(
+        reference: &Vec<PointXyzRgba>,
+        reconstructed: &Vec<PointXyzRgba>,
+        reconstructed_tree: &impl NearestNeighbours,
+        threshold: f64,
+    ) -> f64 {
+        if reference.is_empty() {
+            return 1.0;
+        }
+        if reconstructed.is_empty() {
+            return 0.0;
+        }
+
+        let threshold = (threshold * threshold) as f32;
+        let covered = reference
+            .par_iter()
+            .filter(|pt| {
+                let nearest_points = reconstructed_tree.nearest_neighbours(&pt.xyz(), 1);
+                nearest_points[0].0 <= threshold
+            })
+            .count();
+
+        covered as f64 / reference.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use kiddo::KdTree;
+
+    fn point(x: f32, y: f32) -> PointXyzRgba {
+        PointXyzRgba {
+            x,
+            y,
+            z: 0.0,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        }
+    }
+
+    fn tree(points: &[PointXyzRgba]) -> KdTree<f32, usize, 3> {
+        let mut tree = KdTree::new();
+        for (i, pt) in points.iter().enumerate() {
+            tree.add(&pt.xyz(), i).unwrap();
+        }
+        tree
+    }
+
+    #[test]
+    fn test_half_covered() {
+        // 10x10 grid of spacing 1, the reconstruction covers x < 5 with a small offset
+        let reference: Vec<PointXyzRgba> = (0..100)
+            .map(|i| point((i / 10) as f32, (i % 10) as f32))
+            .collect();
+        let reconstructed: Vec<PointXyzRgba> = reference
+            .iter()
+            .filter(|pt| pt.x < 5.0)
+            .map(|pt| point(pt.x + 0.05, pt.y - 0.05))
+            .collect();
+
+        let completeness =
+            Completeness::calculate_metric(&reference, &reconstructed, &tree(&reconstructed), 0.1);
+        assert_eq!(completeness, 0.5);
+
+        let completeness =
+            Completeness::calculate_metric(&reference, &reconstructed, &tree(&reconstructed), 0.01);
+        assert_eq!(completeness, 0.0);
+    }
+}
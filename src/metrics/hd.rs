@@ -27,9 +27,7 @@ impl Hd {
         let hd_max = p1
             .par_iter()
             .map(|pt| {
-                let nearest_points = p2_tree
-                    .nearest(&[pt.x, pt.y, pt.z], 2, &squared_euclidean)
-                    .unwrap();
+                let nearest_points = p2_tree.nearest(&pt.xyz(), 2, &squared_euclidean).unwrap();
                 let (dist, _) = nearest_points[0];
                 FloatOrd(dist)
             })
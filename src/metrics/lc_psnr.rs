@@ -21,7 +21,7 @@ impl LcPsnr {
             .par_iter()
             .map(|pt| {
                 let nearest_points = reconstructed_tree
-                    .nearest(&[pt.x, pt.y, pt.z], 2, &squared_euclidean)
+                    .nearest(&pt.xyz(), 2, &squared_euclidean)
                     .unwrap();
                 let (_, idx) = nearest_points[0];
                 let rgb_p2 = Rgb::new(
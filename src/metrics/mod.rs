@@ -1,9 +1,14 @@
 mod acd;
+mod aggregate;
 mod cd;
 mod cd_psnr;
+mod color_psnr;
+mod completeness;
 mod hd;
 mod lc_psnr;
+mod pairwise;
 mod psnr;
+mod uniformity;
 mod vqoe;
 
 use std::{
@@ -16,11 +21,16 @@ use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
 use kiddo::KdTree;
 
 use self::acd::Acd;
+pub use self::aggregate::{MetricsAggregator, Summary};
 use self::cd::Cd;
 use self::cd_psnr::CdPsnr;
+pub use self::color_psnr::ColorPsnr;
+pub use self::completeness::Completeness;
 use self::hd::Hd;
 use self::lc_psnr::LcPsnr;
+pub use self::pairwise::{d1_errors, evaluate_pairs, pair_files, write_pairwise_csv, PairMetrics};
 use self::psnr::Psnr;
+pub use self::uniformity::Uniformity;
 use self::vqoe::VQoE;
 
 #[derive(clap::ValueEnum, Clone, Copy, PartialEq)]
@@ -108,13 +118,13 @@ pub fn calculate_metrics(
     let mut original_tree = KdTree::new();
     for (i, pt) in original.points.iter().enumerate() {
         original_tree
-            .add(&[pt.x, pt.y, pt.z], i)
+            .add(&pt.xyz(), i)
             .expect("Failed to add to original tree");
     }
     let mut reconstructed_tree = KdTree::new();
     for (i, pt) in reconstructed.points.iter().enumerate() {
         reconstructed_tree
-            .add(&[pt.x, pt.y, pt.z], i)
+            .add(&pt.xyz(), i)
             .expect("Failed to add to original tree");
     }
 
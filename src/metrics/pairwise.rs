@@ -0,0 +1,298 @@
+use kiddo::KdTree;
+use rayon::prelude::*;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::color_psnr::ColorPsnr;
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::methods::plane::Plane;
+use crate::structures::neighbours::NearestNeighbours;
+use crate::utils::{expand_directory, read_file_to_point_cloud};
+
+/// Quality of a distorted frame against its reference. Each point is paired with its nearest
+/// neighbour in the other cloud, in both directions, and the larger mean squared error of the two
+/// directions is kept.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairMetrics {
+    /// point to point mean squared error
+    pub d1_mse: f64,
+    /// point to plane mean squared error, along the normal of the nearest neighbour
+    pub d2_mse: f64,
+    /// mean squared error of the Y, Cb and Cr channels
+    pub color_mse: [f64; 3],
+}
+
+fn build_tree(points: &[PointXyzRgba]) -> KdTree<f32, usize, 3> {
+    let mut tree = KdTree::new();
+    for (i, p) in points.iter().enumerate() {
+        tree.add(&p.xyz(), i).expect("Failed to add to tree");
+    }
+    tree
+}
+
+/// Normal of each point from the plane fitted to its `k` nearest neighbours, zero if they are
+/// collinear.
+fn estimate_normals(
+    points: &[PointXyzRgba],
+    tree: &KdTree<f32, usize, 3>,
+    k: usize,
+) -> Vec<[f32; 3]> {
+    points
+        .par_iter()
+        .map(|p| {
+            let neighbours = tree
+                .nearest_neighbours(&p.xyz(), k)
+                .into_iter()
+                .map(|(_, i)| points[i])
+                .collect();
+            match Plane::fit(&PointCloud::from_points(neighbours)) {
+                Some(plane) => [plane.normal.x, plane.normal.y, plane.normal.z],
+                None => [0.0; 3],
+            }
+        })
+        .collect()
+}
+
+/// Point to point and point to plane mean squared errors from `from` to `to`.
+fn geometry_mse(
+    from: &[PointXyzRgba],
+    to: &[PointXyzRgba],
+    to_tree: &KdTree<f32, usize, 3>,
+    to_normals: &[[f32; 3]],
+) -> (f64, f64) {
+    let (d1, d2) = from
+        .par_iter()
+        .map(|p| {
+            let (distance, i) = to_tree.nearest_neighbours(&p.xyz(), 1)[0];
+            let (q, n) = (&to[i], to_normals[i]);
+            let along_normal = (p.x - q.x) * n[0] + (p.y - q.y) * n[1] + (p.z - q.z) * n[2];
+            (distance as f64, (along_normal * along_normal) as f64)
+        })
+        .reduce(|| (0.0, 0.0), |a, b| (a.0 + b.0, a.1 + b.1));
+    let n = from.len() as f64;
+    (d1 / n, d2 / n)
+}
+
+/// PSNR of a geometric error given the peak, e.g. 1023 for 10 bit voxelized clouds, following
+/// the MPEG convention of a peak energy of `3 * peak^2`.
+pub fn geometry_psnr(mse: f64, peak: f64) -> f64 {
+    10.0 * (3.0 * peak * peak / mse).log10()
+}
+
+fn color_psnr(mse: f64) -> f64 {
+    10.0 * (255.0 * 255.0 / mse).log10()
+}
+
+impl PairMetrics {
+    /// Evaluates the distorted cloud against the reference, estimating the normals of both clouds
+    /// from their `k` nearest neighbours.
+    pub fn calculate(
+        reference: &PointCloud<PointXyzRgba>,
+        distorted: &PointCloud<PointXyzRgba>,
+        k: usize,
+    ) -> Self {
+        assert!(
+            !reference.points.is_empty() && !distorted.points.is_empty(),
+            "Expecting non empty point clouds"
+        );
+        let reference_tree = build_tree(&reference.points);
+        let distorted_tree = build_tree(&distorted.points);
+        let reference_normals = estimate_normals(&reference.points, &reference_tree, k);
+        let distorted_normals = estimate_normals(&distorted.points, &distorted_tree, k);
+
+        let (d1_rd, d2_rd) = geometry_mse(
+            &reference.points,
+            &distorted.points,
+            &distorted_tree,
+            &distorted_normals,
+        );
+        let (d1_dr, d2_dr) = geometry_mse(
+            &distorted.points,
+            &reference.points,
+            &reference_tree,
+            &reference_normals,
+        );
+        let color_rd = ColorPsnr::get_mse(&reference.points, &distorted.points, &distorted_tree);
+        let color_dr = ColorPsnr::get_mse(&distorted.points, &reference.points, &reference_tree);
+
+        Self {
+            d1_mse: d1_rd.max(d1_dr),
+            d2_mse: d2_rd.max(d2_dr),
+            color_mse: [
+                color_rd[0].max(color_dr[0]),
+                color_rd[1].max(color_dr[1]),
+                color_rd[2].max(color_dr[2]),
+            ],
+        }
+    }
+}
+
+/// Point to point (D1) error of every distorted point, the distance to its nearest reference
+/// point.
+pub fn d1_errors(
+    reference: &PointCloud<PointXyzRgba>,
+    distorted: &PointCloud<PointXyzRgba>,
+) -> Vec<f32> {
+    let reference_tree = build_tree(&reference.points);
+    distorted
+        .points
+        .par_iter()
+        .map(|p| {
+            reference_tree
+                .nearest_neighbours(&p.xyz(), 1)
+                .first()
+                .map_or(f32::INFINITY, |&(distance, _)| distance.sqrt())
+        })
+        .collect()
+}
+
+/// Pairs every file of the reference directory with the file of the same name in the distorted
+/// directory, sorted by name. Reference files without a distorted counterpart are skipped.
+pub fn pair_files(reference_dir: &Path, distorted_dir: &Path) -> Vec<(String, PathBuf, PathBuf)> {
+    let mut pairs: Vec<(String, PathBuf, PathBuf)> = expand_directory(reference_dir)
+        .into_iter()
+        .filter_map(|reference| {
+            let name = reference.file_name()?.to_string_lossy().into_owned();
+            let distorted = distorted_dir.join(&name);
+            if !distorted.is_file() {
+                println!("Skipping {}, missing from {:?}", name, distorted_dir);
+                return None;
+            }
+            Some((name, reference, distorted))
+        })
+        .collect();
+    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    pairs
+}
+
+/// Evaluates every pair of files, on the rayon thread pool if `parallel`, keeping the order of
+/// the pairs. Pairs with an unreadable file are skipped.
+pub fn evaluate_pairs(
+    pairs: &[(String, PathBuf, PathBuf)],
+    k: usize,
+    parallel: bool,
+) -> Vec<(String, PairMetrics)> {
+    let evaluate = |(name, reference, distorted): &(String, PathBuf, PathBuf)| {
+        let (reference, distorted) = match (
+            read_file_to_point_cloud(reference),
+            read_file_to_point_cloud(distorted),
+        ) {
+            (Some(reference), Some(distorted)) => (reference, distorted),
+            _ => {
+                println!("Skipping {}, failed to read the point clouds", name);
+                return None;
+            }
+        };
+        Some((
+            name.clone(),
+            PairMetrics::calculate(&reference, &distorted, k),
+        ))
+    };
+    if parallel {
+        pairs.par_iter().filter_map(evaluate).collect()
+    } else {
+        pairs.iter().filter_map(evaluate).collect()
+    }
+}
+
+/// Writes one row per frame with the mean squared errors and PSNRs, the geometric PSNRs given
+/// the `peak` of the coordinates.
+pub fn write_pairwise_csv<W: Write>(
+    rows: &[(String, PairMetrics)],
+    peak: f64,
+    writer: &mut W,
+) -> std::io::Result<()> {
+    writeln!(
+        writer,
+        "frame,d1_mse,d1_psnr,d2_mse,d2_psnr,y_mse,y_psnr,cb_mse,cb_psnr,cr_mse,cr_psnr"
+    )?;
+    for (name, m) in rows {
+        write!(
+            writer,
+            "{},{:.5},{:.5},{:.5},{:.5}",
+            name,
+            m.d1_mse,
+            geometry_psnr(m.d1_mse, peak),
+            m.d2_mse,
+            geometry_psnr(m.d2_mse, peak)
+        )?;
+        for mse in m.color_mse {
+            write!(writer, ",{:.5},{:.5}", mse, color_psnr(mse))?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ply::write_ply_with_alpha;
+
+    fn grid(offset: f32) -> PointCloud<PointXyzRgba> {
+        let points = (0..100)
+            .map(|i| PointXyzRgba {
+                x: (i / 10) as f32,
+                y: (i % 10) as f32,
+                z: offset,
+                r: (i * 2) as u8,
+                g: 100,
+                b: 200,
+                a: 255,
+            })
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    #[test]
+    fn test_identical_clouds() {
+        let metrics = PairMetrics::calculate(&grid(0.0), &grid(0.0), 8);
+        assert_eq!(metrics.d1_mse, 0.0);
+        assert_eq!(metrics.d2_mse, 0.0);
+        assert_eq!(metrics.color_mse, [0.0; 3]);
+        assert!(geometry_psnr(metrics.d1_mse, 1023.0).is_infinite());
+    }
+
+    #[test]
+    fn test_offset_along_normal() {
+        let metrics = PairMetrics::calculate(&grid(0.0), &grid(0.5), 8);
+        assert!((metrics.d1_mse - 0.25).abs() < 1e-5);
+        assert!((metrics.d2_mse - 0.25).abs() < 1e-5);
+        assert!(d1_errors(&grid(0.0), &grid(0.5))
+            .iter()
+            .all(|e| (e - 0.5).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_directories() {
+        let root = std::env::temp_dir().join("vivotk_test_pairwise_metrics");
+        let (reference_dir, distorted_dir) = (root.join("reference"), root.join("distorted"));
+        std::fs::create_dir_all(&reference_dir).unwrap();
+        std::fs::create_dir_all(&distorted_dir).unwrap();
+        for name in ["b.ply", "a.ply", "c.ply"] {
+            write_ply_with_alpha(&grid(0.0), reference_dir.join(name)).unwrap();
+        }
+        // c.ply has no distorted counterpart
+        for name in ["a.ply", "b.ply"] {
+            write_ply_with_alpha(&grid(0.0), distorted_dir.join(name)).unwrap();
+        }
+
+        let pairs = pair_files(&reference_dir, &distorted_dir);
+        let names: Vec<&str> = pairs.iter().map(|p| p.0.as_str()).collect();
+        assert_eq!(names, ["a.ply", "b.ply"]);
+        let rows = evaluate_pairs(&pairs, 8, true);
+        assert_eq!(rows, evaluate_pairs(&pairs, 8, false));
+        assert!(rows.iter().all(|(_, m)| m.d1_mse == 0.0 && m.d2_mse == 0.0));
+
+        let mut csv = vec![];
+        write_pairwise_csv(&rows, 1023.0, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("a.ply,0.00000,inf,"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
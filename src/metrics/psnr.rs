@@ -27,7 +27,7 @@ impl Psnr {
             .par_iter()
             .map(|pt| {
                 let nearest_points = reconstructed_tree
-                    .nearest(&[pt.x, pt.y, pt.z], RESULTS, &squared_euclidean)
+                    .nearest(&pt.xyz(), RESULTS, &squared_euclidean)
                     .unwrap();
                 let (dist, _) = nearest_points[0];
                 dist
@@ -0,0 +1,136 @@
+use rayon::prelude::*;
+
+use crate::formats::pointxyzrgba::PointXyzRgba;
+use crate::structures::neighbours::NearestNeighbours;
+
+use super::Metrics;
+
+pub struct Uniformity;
+
+impl Uniformity {
+    /// Inserts the mean, the maximum and the coefficient of variation (std / mean) of the
+    /// distance from each point to its nearest neighbour. A low coefficient of variation
+    /// means that the points are evenly spread. Nothing is inserted for less than two points.
+    pub fn calculate_metric(
+        points: &Vec<PointXyzRgba>,
+        tree: &impl NearestNeighbours,
+        metrics: &mut Metrics,
+    ) {
+        if let Some((mean, max, cv)) = Uniformity::nearest_neighbour_stats(points, tree) {
+            metrics.insert("nn_mean".to_string(), format!("{:.5}", mean));
+            metrics.insert("nn_max".to_string(), format!("{:.5}", max));
+            metrics.insert("nn_cv".to_string(), format!("{:.5}", cv));
+        }
+    }
+
+    fn nearest_neighbour_stats(
+        points: &Vec<PointXyzRgba>,
+        tree: &impl NearestNeighbours,
+    ) -> Option<(f64, f64, f64)> {
+        if points.len() < 2 {
+            return None;
+        }
+
+        let distances: Vec<f64> = points
+            .par_iter()
+            .enumerate()
+            .map(|(i, pt)| {
+                let nearest_points = tree.nearest_neighbours(&pt.xyz(), 2);
+                // the point itself is usually the nearest, unless it has duplicates
+                let (dist, _) = nearest_points.into_iter().find(|&(_, j)| j != i).unwrap();
+                (dist as f64).sqrt()
+            })
+            .collect();
+
+        let n = distances.len() as f64;
+        let mean = distances.iter().sum::<f64>() / n;
+        let max = distances.iter().copied().fold(0.0, f64::max);
+        let variance = distances
+            .iter()
+            .map(|d| (d - mean) * (d - mean))
+            .sum::<f64>()
+            / n;
+        let cv = if mean > 0.0 {
+            variance.sqrt() / mean
+        } else {
+            0.0
+        };
+        Some((mean, max, cv))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use kiddo::KdTree;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::collections::HashMap;
+
+    fn point(x: f32, y: f32) -> PointXyzRgba {
+        PointXyzRgba {
+            x,
+            y,
+            z: 0.0,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        }
+    }
+
+    fn tree(points: &[PointXyzRgba]) -> KdTree<f32, usize, 3> {
+        let mut tree = KdTree::new();
+        for (i, pt) in points.iter().enumerate() {
+            tree.add(&pt.xyz(), i).unwrap();
+        }
+        tree
+    }
+
+    /// Keeps the point closest to the center of each unit voxel.
+    fn voxel_downsample(points: &[PointXyzRgba]) -> Vec<PointXyzRgba> {
+        let mut voxels: HashMap<(i32, i32), PointXyzRgba> = HashMap::new();
+        let offset = |p: &PointXyzRgba| {
+            let (dx, dy) = (p.x - p.x.floor() - 0.5, p.y - p.y.floor() - 0.5);
+            dx * dx + dy * dy
+        };
+        for p in points {
+            let voxel = (p.x.floor() as i32, p.y.floor() as i32);
+            let kept = voxels.entry(voxel).or_insert(*p);
+            if offset(p) < offset(kept) {
+                *kept = *p;
+            }
+        }
+        voxels.into_values().collect()
+    }
+
+    #[test]
+    fn test_voxel_downsample_is_more_uniform() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let original: Vec<PointXyzRgba> = (0..20_000)
+            .map(|_| point(rng.gen_range(0.0..20.0), rng.gen_range(0.0..20.0)))
+            .collect();
+        let downsampled = voxel_downsample(&original);
+
+        let (_, _, original_cv) =
+            Uniformity::nearest_neighbour_stats(&original, &tree(&original)).unwrap();
+        let (mean, max, downsampled_cv) =
+            Uniformity::nearest_neighbour_stats(&downsampled, &tree(&downsampled)).unwrap();
+        assert!(downsampled_cv < original_cv);
+        assert!(mean <= max);
+
+        let mut metrics = Metrics::new();
+        Uniformity::calculate_metric(&downsampled, &tree(&downsampled), &mut metrics);
+        assert_eq!(metrics.metrics().len(), 3);
+    }
+
+    #[test]
+    fn test_regular_grid() {
+        let points: Vec<PointXyzRgba> = (0..100)
+            .map(|i| point((i / 10) as f32, (i % 10) as f32))
+            .collect();
+        let (mean, max, cv) = Uniformity::nearest_neighbour_stats(&points, &tree(&points)).unwrap();
+        assert!((mean - 1.0).abs() < 1e-6);
+        assert!((max - 1.0).abs() < 1e-6);
+        assert!(cv < 1e-6);
+    }
+}
@@ -170,7 +170,6 @@ mod tests {
         PCDVersion, PointCloudData,
     };
     use byteorder::{NativeEndian, WriteBytesExt};
-    use image::EncodableLayout;
     use std::io::{BufReader, BufWriter};
 
     #[test]
@@ -267,7 +266,7 @@ mod tests {
         let mut buf = BufWriter::new(Vec::new());
         write_pcd(&pcd, PCDDataType::Binary, &mut buf).unwrap();
         let vec = buf.into_inner().unwrap();
-        let rdr = BufReader::new(vec.as_bytes());
+        let rdr = BufReader::new(vec.as_slice());
         let new_pcd = read_pcd(rdr).unwrap();
         assert_eq!(new_pcd.header(), pcd.header());
         assert_eq!(new_pcd.data(), pcd.data());
@@ -289,10 +288,10 @@ pub fn create_pcd(point_cloud: &PointCloud<PointXyzRgba>) -> PointCloudData {
             )
             .unwrap(),
         ],
-        point_cloud.number_of_points as u64,
+        point_cloud.len() as u64,
         1,
         [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0],
-        point_cloud.number_of_points as u64,
+        point_cloud.len() as u64,
         PCDDataType::Ascii, // this is a placeholder value, it will be overwritten accoradingly in write_pcd_file()
     )
     .unwrap();
@@ -300,7 +299,7 @@ pub fn create_pcd(point_cloud: &PointCloud<PointXyzRgba>) -> PointCloudData {
         let mut points = std::mem::ManuallyDrop::new(point_cloud.points.clone());
         Vec::from_raw_parts(
             points.as_mut_ptr() as *mut u8,
-            point_cloud.number_of_points * std::mem::size_of::<PointXyzRgba>(),
+            point_cloud.len() * std::mem::size_of::<PointXyzRgba>(),
             points.capacity() * std::mem::size_of::<PointXyzRgba>(),
         )
     };
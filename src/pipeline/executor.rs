@@ -91,6 +91,17 @@ impl ExecutorBuilder {
             || cmd.as_str() == "convert"
             || cmd.as_str() == "info"
             || cmd.as_str() == "dash"
+            || cmd.as_str() == "voxel-to-cloud"
+            || cmd.as_str() == "render-sequence"
+            || cmd.as_str() == "rosbag-read"
+            || cmd.as_str() == "import-npz"
+            || cmd.as_str() == "udp-source"
+            || cmd.as_str() == "deproject-lidar"
+            || cmd.as_str() == "timestamp-filter"
+            || cmd.as_str() == "estimate-transform"
+            || cmd.as_str() == "pairwise-metrics"
+            || cmd.as_str() == "cloud-compare"
+            || cmd.as_str() == "surface-sample"
             || has_help
         {
         } else {
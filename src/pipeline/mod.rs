@@ -3,10 +3,11 @@ mod executor;
 pub mod subcommands;
 use clap::Parser;
 use crossbeam_channel::Receiver;
+use std::path::PathBuf;
 // use std::sync::mpsc::Receiver;
 
 use crate::{
-    formats::{pointxyzrgba::PointXyzRgba, PointCloud},
+    formats::{pointxyzrgba::PointXyzRgba, pointxyzrgbanormal::PointXyzRgbaNormal, PointCloud},
     metrics::Metrics,
 };
 
@@ -14,11 +15,52 @@ use self::{
     executor::Executor,
     executor::ExecutorBuilder,
     subcommands::{
-        convert, dash, downsample, info, metrics, read, render, upsample, write, Convert, Dash,
-        Downsampler, Info, MetricsCalculator, Read, Render, Subcommand, Upsampler, Write,
+        aggregate_metrics, alpha_shape, apply_transform, auto_scale, background_subtract,
+        cloud_compare, color_psnr, colorize_height, completeness, compute_fpfh, confusion_matrix,
+        convert, convex_hull, crop_aabb, crop_sphere, curvature, dash, delta_decode, delta_encode,
+        descriptor_match, downsample, draco_compress, draco_decompress, dump_delta,
+        estimate_transform, fit_cylinder, fps, fps_resample, global_register, gpcc_decode,
+        gpcc_encode, icp, info, label_to_color, metrics, mincut, motion_compensate, multiview_fuse,
+        ndt, noise_model, normal_consistency, normal_estimation, normal_flip, occupancy_grid,
+        octree_decode, octree_encode, paint_cloud, pairwise_metrics, plane_project, poisson_sample,
+        pose_graph_optimize, read, registration_chain, render, render_sequence, repair, sdf_sample,
+        semantic_color, simulate_scan, skeleton, super_resolution, surface_area, surface_sample,
+        symmetry_detect, timestamp_filter, track_objects, triangulate, udp_source, uniformity,
+        upsample, validate, visibility, voxel_downsample, voxel_to_cloud, vpcc_decode, vpcc_encode,
+        write, AabbCropper, AlphaShape, AutoScaler, BackgroundSubtract, ChainRegistration,
+        CloudCompare, ColorPsnrCalculator, CompletenessCalculator, ConfusionMatrixCalculator,
+        Convert, Curvature, CylinderFitter, Dash, DeltaDecode, DeltaEncode, DescriptorMatcher,
+        Downsampler, DracoCompress, DracoDecompress, DumpDelta, FarthestPointSampler,
+        FpfhCalculator, FpsResampler, GlobalRegistration, GpccDecoder, GpccEncoder,
+        HeightColorizer, HullProjector, IcpRegistration, Info, LabelColorizer, MetricsAggregate,
+        MetricsCalculator, MinCutSegmenter, MotionCompensate, MultiviewFuser, NdtRegistration,
+        NoiseSimulator, NormalConsistency, NormalEstimation, NormalFlipper, ObjectTracker,
+        OccupancyGrid, OctreeDecode, OctreeEncode, PaintCloud, PairwiseMetrics, PlaneProjector,
+        PoseGraphOptimizer, RandomSampler, Read, Render, RenderSequence, Repairer, ScanSimulator,
+        SdfSampler, SemanticColorizer, Skeleton, SphereCropper, Subcommand, SuperResolution,
+        SurfaceArea, SurfaceSampler, SymmetryDetector, TimestampFilter, TransformApplier,
+        TransformEstimator, Triangulator, UdpSource, UniformityCalculator, Upsampler, Validator,
+        VisibilityComputer, VoxelDownsampler, VoxelToCloud, VpccDecoder, VpccEncoder, Write,
     },
 };
 
+#[cfg(feature = "io")]
+use self::subcommands::{
+    attribute_histogram, bev_image, lidar_deproject, AttributeHistogram, BevImage, DeprojectLidar,
+};
+#[cfg(feature = "gpu")]
+use self::subcommands::{compute_normals_gpu, GpuNormalEstimation};
+#[cfg(feature = "hdf5")]
+use self::subcommands::{export_hdf5, ExportHdf5};
+#[cfg(feature = "npz")]
+use self::subcommands::{export_npz, import_npz, ExportNpz, ImportNpz};
+#[cfg(feature = "grpc")]
+use self::subcommands::{grpc_serve, GrpcServe};
+#[cfg(feature = "ros2")]
+use self::subcommands::{ros2_publish, Ros2Publish};
+#[cfg(feature = "rosbag")]
+use self::subcommands::{rosbag_read, RosbagRead};
+
 pub type SubcommandCreator = Box<dyn Fn(Vec<String>) -> Box<dyn Subcommand>>;
 
 fn subcommand(s: &str) -> Option<SubcommandCreator> {
@@ -32,6 +74,94 @@ fn subcommand(s: &str) -> Option<SubcommandCreator> {
         "convert" => Some(Box::from(Convert::from_args)),
         "dash" => Some(Box::from(Dash::from_args)),
         "info" => Some(Box::from(Info::from_args)),
+        "fit-cylinder" => Some(Box::from(CylinderFitter::from_args)),
+        "sdf-sample" => Some(Box::from(SdfSampler::from_args)),
+        "occupancy-grid" => Some(Box::from(OccupancyGrid::from_args)),
+        "voxel-to-cloud" => Some(Box::from(VoxelToCloud::from_args)),
+        "colorize-height" => Some(Box::from(HeightColorizer::from_args)),
+        #[cfg(feature = "io")]
+        "cloud-to-image" => Some(Box::from(BevImage::from_args)),
+        "crop-sphere" => Some(Box::from(SphereCropper::from_args)),
+        "crop-aabb" => Some(Box::from(AabbCropper::from_args)),
+        "random-sample" => Some(Box::from(RandomSampler::from_args)),
+        "farthest-point-sample" => Some(Box::from(FarthestPointSampler::from_args)),
+        "normal-flip" => Some(Box::from(NormalFlipper::from_args)),
+        "compute-fpfh" => Some(Box::from(FpfhCalculator::from_args)),
+        "descriptor-match" => Some(Box::from(DescriptorMatcher::from_args)),
+        "label-to-color" => Some(Box::from(LabelColorizer::from_args)),
+        "confusion-matrix" => Some(Box::from(ConfusionMatrixCalculator::from_args)),
+        "noise-model" => Some(Box::from(NoiseSimulator::from_args)),
+        "simulate-scan" => Some(Box::from(ScanSimulator::from_args)),
+        "background-subtract" => Some(Box::from(BackgroundSubtract::from_args)),
+        "track-objects" => Some(Box::from(ObjectTracker::from_args)),
+        "multiview-fuse" => Some(Box::from(MultiviewFuser::from_args)),
+        #[cfg(feature = "io")]
+        "attribute-histogram" => Some(Box::from(AttributeHistogram::from_args)),
+        "completeness" => Some(Box::from(CompletenessCalculator::from_args)),
+        "uniformity" => Some(Box::from(UniformityCalculator::from_args)),
+        "color-psnr" => Some(Box::from(ColorPsnrCalculator::from_args)),
+        "aggregate-metrics" => Some(Box::from(MetricsAggregate::from_args)),
+        "validate" => Some(Box::from(Validator::from_args)),
+        "repair" => Some(Box::from(Repairer::from_args)),
+        "plane-project" => Some(Box::from(PlaneProjector::from_args)),
+        "symmetry-detect" => Some(Box::from(SymmetryDetector::from_args)),
+        "hull-project" => Some(Box::from(HullProjector::from_args)),
+        "triangulate" => Some(Box::from(Triangulator::from_args)),
+        "skeleton" => Some(Box::from(Skeleton::from_args)),
+        "alpha-shape" => Some(Box::from(AlphaShape::from_args)),
+        "mincut" => Some(Box::from(MinCutSegmenter::from_args)),
+        "normal-consistency" => Some(Box::from(NormalConsistency::from_args)),
+        "curvature" => Some(Box::from(Curvature::from_args)),
+        "surface-area" => Some(Box::from(SurfaceArea::from_args)),
+        "ndt" => Some(Box::from(NdtRegistration::from_args)),
+        "icp" => Some(Box::from(IcpRegistration::from_args)),
+        "global-register" => Some(Box::from(GlobalRegistration::from_args)),
+        "render-sequence" => Some(Box::from(RenderSequence::from_args)),
+        #[cfg(feature = "rosbag")]
+        "rosbag-read" => Some(Box::from(RosbagRead::from_args)),
+        "paint-cloud" => Some(Box::from(PaintCloud::from_args)),
+        #[cfg(feature = "npz")]
+        "export-npz" => Some(Box::from(ExportNpz::from_args)),
+        #[cfg(feature = "npz")]
+        "import-npz" => Some(Box::from(ImportNpz::from_args)),
+        #[cfg(feature = "hdf5")]
+        "export-hdf5" => Some(Box::from(ExportHdf5::from_args)),
+        #[cfg(feature = "ros2")]
+        "publish-ros2" => Some(Box::from(Ros2Publish::from_args)),
+        #[cfg(feature = "grpc")]
+        "grpc-serve" => Some(Box::from(GrpcServe::from_args)),
+        "udp-source" => Some(Box::from(UdpSource::from_args)),
+        #[cfg(feature = "io")]
+        "deproject-lidar" => Some(Box::from(DeprojectLidar::from_args)),
+        "super-resolution" => Some(Box::from(SuperResolution::from_args)),
+        "semantic-color" => Some(Box::from(SemanticColorizer::from_args)),
+        "timestamp-filter" => Some(Box::from(TimestampFilter::from_args)),
+        "estimate-transform" => Some(Box::from(TransformEstimator::from_args)),
+        "apply-transform" => Some(Box::from(TransformApplier::from_args)),
+        "registration-chain" => Some(Box::from(ChainRegistration::from_args)),
+        "pairwise-metrics" => Some(Box::from(PairwiseMetrics::from_args)),
+        "cloud-compare" => Some(Box::from(CloudCompare::from_args)),
+        "normal-estimation" => Some(Box::from(NormalEstimation::from_args)),
+        #[cfg(feature = "gpu")]
+        "compute-normals-gpu" => Some(Box::from(GpuNormalEstimation::from_args)),
+        "voxel-downsample" => Some(Box::from(VoxelDownsampler::from_args)),
+        "gpcc-encode" => Some(Box::from(GpccEncoder::from_args)),
+        "gpcc-decode" => Some(Box::from(GpccDecoder::from_args)),
+        "vpcc-encode" => Some(Box::from(VpccEncoder::from_args)),
+        "vpcc-decode" => Some(Box::from(VpccDecoder::from_args)),
+        "draco-compress" => Some(Box::from(DracoCompress::from_args)),
+        "draco-decompress" => Some(Box::from(DracoDecompress::from_args)),
+        "octree-encode" => Some(Box::from(OctreeEncode::from_args)),
+        "octree-decode" => Some(Box::from(OctreeDecode::from_args)),
+        "fps-resample" => Some(Box::from(FpsResampler::from_args)),
+        "surface-sample" => Some(Box::from(SurfaceSampler::from_args)),
+        "pose-graph-optimize" => Some(Box::from(PoseGraphOptimizer::from_args)),
+        "auto-scale" => Some(Box::from(AutoScaler::from_args)),
+        "delta-encode" => Some(Box::from(DeltaEncode::from_args)),
+        "delta-decode" => Some(Box::from(DeltaDecode::from_args)),
+        "motion-compensate" => Some(Box::from(MotionCompensate::from_args)),
+        "compute-visibility" => Some(Box::from(VisibilityComputer::from_args)),
+        "dump-delta" => Some(Box::from(DumpDelta::from_args)),
         _ => None,
     }
 }
@@ -39,6 +169,12 @@ fn subcommand(s: &str) -> Option<SubcommandCreator> {
 #[derive(Debug, Clone)]
 pub enum PipelineMessage {
     IndexedPointCloud(PointCloud<PointXyzRgba>, u32),
+    IndexedPointCloudNormal(PointCloud<PointXyzRgbaNormal>, u32),
+    IndexedPointCloudFpfh(PointCloud<PointXyzRgbaNormal>, Vec<[f32; 33]>, u32),
+    Correspondences(Vec<(usize, usize)>),
+    IndexedPointCloudLabel(PointCloud<PointXyzRgba>, Vec<u32>, u32),
+    /// path of a compressed bitstream and the index of its first frame
+    CompressedStream(PathBuf, u32),
     // PointCloud(PointCloud<PointXyzRgba>),
     Metrics(Metrics),
     End,
@@ -201,6 +337,172 @@ enum VVSubCommand {
     Info(info::Args),
     #[clap(name = "dash")]
     Dash(dash::Args),
+    #[clap(name = "fit-cylinder")]
+    FitCylinder(fit_cylinder::Args),
+    #[clap(name = "sdf-sample")]
+    SdfSample(sdf_sample::Args),
+    #[clap(name = "occupancy-grid")]
+    OccupancyGrid(occupancy_grid::Args),
+    #[clap(name = "voxel-to-cloud")]
+    VoxelToCloud(voxel_to_cloud::Args),
+    #[clap(name = "colorize-height")]
+    ColorizeHeight(colorize_height::Args),
+    #[cfg(feature = "io")]
+    #[clap(name = "cloud-to-image")]
+    CloudToImage(bev_image::Args),
+    #[clap(name = "crop-sphere")]
+    CropSphere(crop_sphere::Args),
+    #[clap(name = "crop-aabb")]
+    CropAabb(crop_aabb::Args),
+    #[clap(name = "random-sample")]
+    RandomSample(poisson_sample::Args),
+    #[clap(name = "farthest-point-sample")]
+    FarthestPointSample(fps::Args),
+    #[clap(name = "normal-flip")]
+    NormalFlip(normal_flip::Args),
+    #[clap(name = "compute-fpfh")]
+    ComputeFpfh(compute_fpfh::Args),
+    #[clap(name = "descriptor-match")]
+    DescriptorMatch(descriptor_match::Args),
+    #[clap(name = "label-to-color")]
+    LabelToColor(label_to_color::Args),
+    #[clap(name = "confusion-matrix")]
+    ConfusionMatrix(confusion_matrix::Args),
+    #[clap(name = "noise-model")]
+    NoiseModel(noise_model::Args),
+    #[clap(name = "simulate-scan")]
+    SimulateScan(simulate_scan::Args),
+    #[clap(name = "background-subtract")]
+    BackgroundSubtract(background_subtract::Args),
+    #[clap(name = "track-objects")]
+    TrackObjects(track_objects::Args),
+    #[clap(name = "multiview-fuse")]
+    MultiviewFuse(multiview_fuse::Args),
+    #[cfg(feature = "io")]
+    #[clap(name = "attribute-histogram")]
+    AttributeHistogram(attribute_histogram::Args),
+    #[clap(name = "completeness")]
+    Completeness(completeness::Args),
+    #[clap(name = "uniformity")]
+    Uniformity(uniformity::Args),
+    #[clap(name = "color-psnr")]
+    ColorPsnr(color_psnr::Args),
+    #[clap(name = "aggregate-metrics")]
+    AggregateMetrics(aggregate_metrics::Args),
+    #[clap(name = "validate")]
+    Validate(validate::Args),
+    #[clap(name = "repair")]
+    Repair(repair::Args),
+    #[clap(name = "plane-project")]
+    PlaneProject(plane_project::Args),
+    #[clap(name = "symmetry-detect")]
+    SymmetryDetect(symmetry_detect::Args),
+    #[clap(name = "hull-project")]
+    HullProject(convex_hull::Args),
+    #[clap(name = "triangulate")]
+    Triangulate(triangulate::Args),
+    #[clap(name = "skeleton")]
+    Skeleton(skeleton::Args),
+    #[clap(name = "alpha-shape")]
+    AlphaShape(alpha_shape::Args),
+    #[clap(name = "mincut")]
+    Mincut(mincut::Args),
+    #[clap(name = "normal-consistency")]
+    NormalConsistency(normal_consistency::Args),
+    #[clap(name = "curvature")]
+    Curvature(curvature::Args),
+    #[clap(name = "surface-area")]
+    SurfaceArea(surface_area::Args),
+    #[clap(name = "ndt")]
+    Ndt(ndt::Args),
+    #[clap(name = "icp")]
+    Icp(icp::Args),
+    #[clap(name = "global-register")]
+    GlobalRegister(global_register::Args),
+    #[clap(name = "render-sequence")]
+    RenderSequence(render_sequence::Args),
+    #[cfg(feature = "rosbag")]
+    #[clap(name = "rosbag-read")]
+    RosbagRead(rosbag_read::Args),
+    #[clap(name = "paint-cloud")]
+    PaintCloud(paint_cloud::Args),
+    #[cfg(feature = "npz")]
+    #[clap(name = "export-npz")]
+    ExportNpz(export_npz::Args),
+    #[cfg(feature = "npz")]
+    #[clap(name = "import-npz")]
+    ImportNpz(import_npz::Args),
+    #[cfg(feature = "hdf5")]
+    #[clap(name = "export-hdf5")]
+    ExportHdf5(export_hdf5::Args),
+    #[clap(name = "udp-source")]
+    UdpSource(udp_source::Args),
+    #[cfg(feature = "ros2")]
+    #[clap(name = "publish-ros2")]
+    Ros2Publish(ros2_publish::Args),
+    #[cfg(feature = "grpc")]
+    #[clap(name = "grpc-serve")]
+    GrpcServe(grpc_serve::Args),
+    #[cfg(feature = "io")]
+    #[clap(name = "deproject-lidar")]
+    DeprojectLidar(lidar_deproject::Args),
+    #[clap(name = "super-resolution")]
+    SuperResolution(super_resolution::Args),
+    #[clap(name = "semantic-color")]
+    SemanticColor(semantic_color::Args),
+    #[clap(name = "timestamp-filter")]
+    TimestampFilter(timestamp_filter::Args),
+    #[clap(name = "estimate-transform")]
+    EstimateTransform(estimate_transform::Args),
+    #[clap(name = "apply-transform")]
+    ApplyTransform(apply_transform::Args),
+    #[clap(name = "registration-chain")]
+    RegistrationChain(registration_chain::Args),
+    #[clap(name = "pairwise-metrics")]
+    PairwiseMetrics(pairwise_metrics::Args),
+    #[clap(name = "cloud-compare")]
+    CloudCompare(cloud_compare::Args),
+    #[clap(name = "normal-estimation")]
+    NormalEstimation(normal_estimation::Args),
+    #[cfg(feature = "gpu")]
+    #[clap(name = "compute-normals-gpu")]
+    ComputeNormalsGpu(compute_normals_gpu::Args),
+    #[clap(name = "voxel-downsample")]
+    VoxelDownsample(voxel_downsample::Args),
+    #[clap(name = "gpcc-encode")]
+    GpccEncode(gpcc_encode::Args),
+    #[clap(name = "gpcc-decode")]
+    GpccDecode(gpcc_decode::Args),
+    #[clap(name = "vpcc-encode")]
+    VpccEncode(vpcc_encode::Args),
+    #[clap(name = "vpcc-decode")]
+    VpccDecode(vpcc_decode::Args),
+    #[clap(name = "draco-compress")]
+    DracoCompress(draco_compress::Args),
+    #[clap(name = "draco-decompress")]
+    DracoDecompress(draco_decompress::Args),
+    #[clap(name = "octree-encode")]
+    OctreeEncode(octree_encode::Args),
+    #[clap(name = "octree-decode")]
+    OctreeDecode(octree_decode::Args),
+    #[clap(name = "fps-resample")]
+    FpsResample(fps_resample::Args),
+    #[clap(name = "surface-sample")]
+    SurfaceSample(surface_sample::Args),
+    #[clap(name = "pose-graph-optimize")]
+    PoseGraphOptimize(pose_graph_optimize::Args),
+    #[clap(name = "auto-scale")]
+    AutoScale(auto_scale::Args),
+    #[clap(name = "delta-encode")]
+    DeltaEncode(delta_encode::Args),
+    #[clap(name = "delta-decode")]
+    DeltaDecode(delta_decode::Args),
+    #[clap(name = "motion-compensate")]
+    MotionCompensate(motion_compensate::Args),
+    #[clap(name = "compute-visibility")]
+    ComputeVisibility(visibility::Args),
+    #[clap(name = "dump-delta")]
+    DumpDelta(dump_delta::Args),
 }
 
 fn display_main_help_msg() {
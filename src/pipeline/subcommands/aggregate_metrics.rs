@@ -0,0 +1,65 @@
+use clap::Parser;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use crate::{
+    metrics::MetricsAggregator,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Summarizes the metrics from the stream with their mean, min, max and 95th percentile.
+#[derive(Parser)]
+pub struct Args {
+    /// save the summary as json
+    #[clap(long)]
+    output_json: Option<PathBuf>,
+}
+
+pub struct MetricsAggregate {
+    output_json: Option<PathBuf>,
+    aggregator: MetricsAggregator,
+}
+
+impl MetricsAggregate {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(MetricsAggregate {
+            output_json: args.output_json,
+            aggregator: MetricsAggregator::new(),
+        })
+    }
+}
+
+impl Subcommand for MetricsAggregate {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::Metrics(metrics) => {
+                    self.aggregator.add(&metrics);
+                }
+                PipelineMessage::IndexedPointCloud(_, _)
+                | PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    self.aggregator
+                        .write_table(&mut std::io::stdout())
+                        .expect("Should be able to print the summary");
+                    if let Some(path) = &self.output_json {
+                        File::create(path)
+                            .and_then(|f| self.aggregator.write_json(&mut BufWriter::new(f)))
+                            .expect("Should be able to create file to write summary to");
+                    }
+                    channel.send(PipelineMessage::Metrics(self.aggregator.to_metrics()));
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use clap::Parser;
+use std::path::Path;
+
+use crate::formats::HasCoordinate;
+use crate::methods::alpha_shape::alpha_shape;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+use crate::ply::write_ply_mesh;
+
+use super::Subcommand;
+
+/// Computes the alpha shape of point clouds from the stream, a concave hull, and writes its
+/// boundary as ply triangle meshes (<index>.ply).
+#[derive(Parser)]
+pub struct Args {
+    /// output directory to store the meshes
+    output_dir: String,
+
+    /// tetrahedra with a circumradius above 1 / alpha are carved out, 0 gives the convex hull
+    #[clap(short, long)]
+    alpha: f32,
+
+    #[clap(long, default_value_t = 5)]
+    name_length: usize,
+}
+
+pub struct AlphaShape {
+    args: Args,
+}
+
+impl AlphaShape {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args = Args::parse_from(args);
+        std::fs::create_dir_all(Path::new(&args.output_dir))
+            .expect("Failed to create output directory");
+        Box::from(AlphaShape { args })
+    }
+}
+
+impl Subcommand for AlphaShape {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match &message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let coordinates: Vec<_> = pc.points.iter().map(|p| p.coordinate()).collect();
+                    let faces = alpha_shape(&coordinates, self.args.alpha);
+                    let file_name = format!("{:0width$}.ply", i, width = self.args.name_length);
+                    let output_file = Path::new(&self.args.output_dir).join(file_name);
+                    if let Err(e) = write_ply_mesh(pc, &faces, &output_file) {
+                        println!("Failed to write {:?}\n{e}", output_file);
+                    }
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::End
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+            }
+            channel.send(message);
+        }
+    }
+}
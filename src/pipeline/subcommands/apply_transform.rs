@@ -0,0 +1,74 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    methods::fusion::{
+        invert_rigid_extrinsic, read_extrinsic, transform_cloud, transform_cloud_normal, Extrinsic,
+    },
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Applies a rigid transform, read as a json array of the rows of a 4x4 matrix as written by
+/// estimate-transform or icp --output, to every pointcloud from the stream. Normals are rotated.
+#[derive(Parser)]
+pub struct Args {
+    /// json file of the transform
+    transform: PathBuf,
+
+    /// apply the inverse of the transform
+    #[clap(short, long)]
+    invert: bool,
+}
+
+pub struct TransformApplier {
+    extrinsic: Extrinsic,
+}
+
+impl TransformApplier {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let extrinsic = read_extrinsic(&args.transform).expect("Failed to read transform");
+        Box::new(TransformApplier {
+            extrinsic: if args.invert {
+                invert_rigid_extrinsic(&extrinsic)
+            } else {
+                extrinsic
+            },
+        })
+    }
+}
+
+impl Subcommand for TransformApplier {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let pc = transform_cloud(&pc, &self.extrinsic);
+                    channel.send(PipelineMessage::IndexedPointCloud(pc, i));
+                }
+                PipelineMessage::IndexedPointCloudLabel(pc, labels, i) => {
+                    let pc = transform_cloud(&pc, &self.extrinsic);
+                    channel.send(PipelineMessage::IndexedPointCloudLabel(pc, labels, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(pc, i) => {
+                    let pc = transform_cloud_normal(&pc, &self.extrinsic);
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(pc, i));
+                }
+                PipelineMessage::IndexedPointCloudFpfh(pc, descriptors, i) => {
+                    // the descriptors only depend on the relative positions and normals
+                    let pc = transform_cloud_normal(&pc, &self.extrinsic);
+                    channel.send(PipelineMessage::IndexedPointCloudFpfh(pc, descriptors, i));
+                }
+                PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::End
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use clap::Parser;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use crate::{
+    methods::histogram::{Attribute, Histogram},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Computes the histogram of a point attribute over all point clouds from the stream.
+#[derive(Parser)]
+pub struct Args {
+    #[clap(short, long, value_enum)]
+    attribute: Attribute,
+
+    #[clap(short, long, default_value_t = 50)]
+    bins: usize,
+
+    /// csv file to write the bin centers and counts to
+    #[clap(short, long)]
+    output: PathBuf,
+
+    /// png file to draw the histogram as a bar chart to
+    #[clap(short, long)]
+    plot: Option<PathBuf>,
+}
+
+pub struct AttributeHistogram {
+    args: Args,
+    values: Vec<f32>,
+}
+
+impl AttributeHistogram {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(AttributeHistogram {
+            args,
+            values: vec![],
+        })
+    }
+
+    fn write(&self) {
+        let histogram = match Histogram::from_values(&self.values, self.args.bins) {
+            Some(histogram) => histogram,
+            None => {
+                eprintln!("No points to compute the histogram of");
+                return;
+            }
+        };
+        File::create(&self.args.output)
+            .and_then(|f| histogram.write_csv(&mut BufWriter::new(f)))
+            .expect("Should be able to create file to write histogram to");
+        if let Some(plot) = &self.args.plot {
+            histogram
+                .plot_png(plot, 800, 600)
+                .expect("Should be able to draw histogram");
+        }
+    }
+}
+
+impl Subcommand for AttributeHistogram {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, _)
+                | PipelineMessage::IndexedPointCloudLabel(pc, _, _) => {
+                    let attribute = self.args.attribute;
+                    assert!(
+                        !attribute.is_normal(),
+                        "Normal attributes need a pointcloud with normals, use read --normals"
+                    );
+                    self.values
+                        .extend(pc.points.iter().filter_map(|p| attribute.of(p)));
+                }
+                PipelineMessage::IndexedPointCloudNormal(pc, _)
+                | PipelineMessage::IndexedPointCloudFpfh(pc, _, _) => {
+                    let attribute = self.args.attribute;
+                    self.values
+                        .extend(pc.points.iter().map(|p| attribute.of_normal(p)));
+                }
+                PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    self.write();
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
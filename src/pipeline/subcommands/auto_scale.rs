@@ -0,0 +1,98 @@
+use cgmath::MetricSpace;
+use clap::Parser;
+
+use crate::formats::grid::bounding_box;
+use crate::formats::{HasCoordinate, PointCloud};
+use crate::pipeline::{channel::Channel, PipelineMessage};
+
+use super::Subcommand;
+
+/// Scales the pointclouds from the stream about the origin so that the bounding box diagonal of
+/// the first one equals the target, making datasets in mm, cm or m comparable. The scale factor
+/// is inferred once and applied to every frame, keeping the sequence consistent.
+#[derive(Parser)]
+pub struct Args {
+    /// bounding box diagonal after scaling, in meters
+    #[clap(short, long, default_value_t = 1.0)]
+    target: f32,
+}
+
+pub struct AutoScaler {
+    target: f32,
+    scale: Option<f32>,
+}
+
+impl AutoScaler {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(AutoScaler {
+            target: args.target,
+            scale: None,
+        })
+    }
+
+    /// The scale factor, inferred from the first cloud with a nonzero diagonal.
+    fn scale_for<T: HasCoordinate>(&mut self, pc: &PointCloud<T>, i: u32) -> f32 {
+        if let Some(scale) = self.scale {
+            return scale;
+        }
+        let diagonal = bounding_box(pc.points.iter().map(|p| p.coordinate()))
+            .map_or(0.0, |(min, max)| min.distance(max));
+        if diagonal <= 0.0 {
+            eprintln!("auto-scale: point cloud {} has no extent, left unscaled", i);
+            return 1.0;
+        }
+        let scale = self.target / diagonal;
+        eprintln!(
+            "auto-scale: point cloud {} has a diagonal of {}, scaling by {}",
+            i, diagonal, scale
+        );
+        self.scale = Some(scale);
+        scale
+    }
+}
+
+impl Subcommand for AutoScaler {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(mut pc, i) => {
+                    let scale = self.scale_for(&pc, i);
+                    for p in pc.points.iter_mut() {
+                        p.x *= scale;
+                        p.y *= scale;
+                        p.z *= scale;
+                    }
+                    channel.send(PipelineMessage::IndexedPointCloud(pc, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(mut pc, i) => {
+                    // a uniform scale leaves the normals unchanged
+                    let scale = self.scale_for(&pc, i);
+                    for p in pc.points.iter_mut() {
+                        p.x *= scale;
+                        p.y *= scale;
+                        p.z *= scale;
+                    }
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(pc, i));
+                }
+                PipelineMessage::IndexedPointCloudLabel(mut pc, labels, i) => {
+                    let scale = self.scale_for(&pc, i);
+                    for p in pc.points.iter_mut() {
+                        p.x *= scale;
+                        p.y *= scale;
+                        p.z *= scale;
+                    }
+                    channel.send(PipelineMessage::IndexedPointCloudLabel(pc, labels, i));
+                }
+                PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    methods::background::BackgroundSubtractor,
+    pipeline::{channel::Channel, PipelineMessage},
+    utils::read_file_to_point_cloud,
+};
+
+use super::Subcommand;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keep {
+    Foreground,
+    Background,
+}
+
+/// Removes the points of a static background from a pointcloud from the stream.
+#[derive(Parser)]
+pub struct Args {
+    /// point cloud file of the static background
+    #[clap(short, long)]
+    background: PathBuf,
+
+    /// maximum distance to the background for a point to be background
+    #[clap(short, long, default_value_t = 0.01)]
+    threshold: f64,
+
+    /// which part of the scene to emit
+    #[clap(short, long, value_enum, default_value_t = Keep::Foreground)]
+    keep: Keep,
+}
+
+pub struct BackgroundSubtract {
+    subtractor: BackgroundSubtractor,
+    keep: Keep,
+}
+
+impl BackgroundSubtract {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let background = read_file_to_point_cloud(&args.background)
+            .expect("Failed to read background point cloud");
+        Box::new(BackgroundSubtract {
+            subtractor: BackgroundSubtractor::new(&background, args.threshold as f32),
+            keep: args.keep,
+        })
+    }
+}
+
+impl Subcommand for BackgroundSubtract {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let (foreground, background) = self.subtractor.split(&pc);
+                    let kept = match self.keep {
+                        Keep::Foreground => foreground,
+                        Keep::Background => background,
+                    };
+                    channel.send(PipelineMessage::IndexedPointCloud(kept, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use clap::Parser;
+use std::path::Path;
+
+use crate::methods::bev::bev_image;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+
+use super::Subcommand;
+
+/// Renders point clouds from the stream as top-down bird's eye view images (<index>.png).
+#[derive(Parser)]
+pub struct Args {
+    /// output directory to store the images
+    output_dir: String,
+
+    #[clap(long, default_value_t = 512)]
+    width: u32,
+
+    #[clap(long, default_value_t = 512)]
+    height: u32,
+
+    /// size of a pixel in meters
+    #[clap(short, long, default_value_t = 0.1)]
+    resolution: f32,
+
+    #[clap(long, default_value_t = 5)]
+    name_length: usize,
+}
+
+pub struct BevImage {
+    args: Args,
+}
+
+impl BevImage {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args = Args::parse_from(args);
+        std::fs::create_dir_all(Path::new(&args.output_dir))
+            .expect("Failed to create output directory");
+        Box::from(BevImage { args })
+    }
+}
+
+impl Subcommand for BevImage {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match &message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let image =
+                        bev_image(pc, self.args.width, self.args.height, self.args.resolution);
+                    let file_name = format!("{:0width$}.png", i, width = self.args.name_length);
+                    let output_file = Path::new(&self.args.output_dir).join(file_name);
+                    if let Err(e) = image.save(&output_file) {
+                        println!("Failed to write {:?}\n{e}", output_file);
+                    }
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::End
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+            }
+            channel.send(message);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use super::Subcommand;
+use crate::methods::colormap::{colorize_values, Colormap};
+use crate::metrics::d1_errors;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+use crate::render::wgpu::{builder::RenderBuilder, camera::Camera, compare::CompareViewer};
+use crate::utils::read_file_to_point_cloud;
+
+/// Shows the reference and distorted point clouds side by side in a window, split by a vertical
+/// divider dragged with the right mouse button. The distorted point cloud on the right is colored
+/// by the point to point (D1) error of every point, from blue for no error to red for the largest.
+#[derive(Parser)]
+pub struct Args {
+    #[clap(long)]
+    reference: PathBuf,
+    #[clap(long)]
+    distorted: PathBuf,
+    /// error mapped to red, defaults to the largest error
+    #[clap(long)]
+    max_error: Option<f32>,
+    #[clap(
+        short = 'x',
+        long,
+        default_value_t = 0.0,
+        allow_negative_numbers = true
+    )]
+    camera_x: f32,
+    #[clap(
+        short = 'y',
+        long,
+        default_value_t = 0.0,
+        allow_negative_numbers = true
+    )]
+    camera_y: f32,
+    #[clap(
+        short = 'z',
+        long,
+        default_value_t = 1.3,
+        allow_negative_numbers = true
+    )]
+    camera_z: f32,
+    #[clap(long = "yaw", default_value_t = -90.0, allow_negative_numbers = true)]
+    camera_yaw: f32,
+    #[clap(long = "pitch", default_value_t = 0.0, allow_negative_numbers = true)]
+    camera_pitch: f32,
+    #[clap(long, default_value_t = 1600)]
+    width: u32,
+    #[clap(long, default_value_t = 900)]
+    height: u32,
+    #[clap(long, default_value = "rgb(255,255,255)")]
+    bg_color: String,
+}
+
+pub struct CloudCompare {
+    args: Args,
+}
+
+impl CloudCompare {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::from(CloudCompare {
+            args: Args::parse_from(args),
+        })
+    }
+}
+
+impl Subcommand for CloudCompare {
+    fn handle(&mut self, _messages: Vec<PipelineMessage>, _channel: &Channel) {
+        let args = &self.args;
+        let reference =
+            read_file_to_point_cloud(&args.reference).expect("Failed to read reference");
+        let mut distorted =
+            read_file_to_point_cloud(&args.distorted).expect("Failed to read distorted");
+
+        let errors = d1_errors(&reference, &distorted);
+        let max_error = args
+            .max_error
+            .unwrap_or_else(|| errors.iter().copied().fold(0.0, f32::max));
+        colorize_values(&mut distorted, &errors, max_error, Colormap::Jet);
+
+        let camera = Camera::new(
+            (args.camera_x, args.camera_y, args.camera_z),
+            cgmath::Deg(args.camera_yaw),
+            cgmath::Deg(args.camera_pitch),
+        );
+        let viewer = CompareViewer::new(
+            reference,
+            distorted,
+            ("reference", "D1 error"),
+            camera,
+            (args.width, args.height),
+            &args.bg_color,
+        );
+
+        // the pipeline runs every subcommand in its own thread
+        let mut builder = RenderBuilder::any_thread();
+        builder.add_window(viewer);
+        // only returns by exiting the process once the window is closed
+        builder.run();
+    }
+}
@@ -0,0 +1,64 @@
+use clap::Parser;
+
+use crate::{
+    metrics::{ColorPsnr, Metrics},
+    pipeline::{channel::Channel, PipelineMessage},
+    structures::neighbours::NeighbourIndex,
+};
+
+use super::Subcommand;
+
+/// Calculates the PSNR of the Y, Cb and Cr channels given two input streams.
+/// First input stream is the original, second is the reconstructed.
+#[derive(Parser)]
+pub struct Args {
+    /// search the nearest neighbours with a random projection forest instead of a kd tree
+    #[clap(short, long)]
+    approximate: bool,
+}
+
+pub struct ColorPsnrCalculator {
+    approximate: bool,
+}
+
+impl ColorPsnrCalculator {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(ColorPsnrCalculator {
+            approximate: args.approximate,
+        })
+    }
+}
+
+impl Subcommand for ColorPsnrCalculator {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        let mut messages_iter = messages.into_iter();
+        let message_one = messages_iter
+            .next()
+            .expect("Expecting two input streams for color-psnr");
+        let message_two = messages_iter
+            .next()
+            .expect("Expecting two input streams for color-psnr");
+
+        match (&message_one, &message_two) {
+            (
+                PipelineMessage::IndexedPointCloud(original, _),
+                PipelineMessage::IndexedPointCloud(reconstructed, _),
+            ) => {
+                let mut metrics = Metrics::new();
+                ColorPsnr::calculate_metric(
+                    &original.points,
+                    &NeighbourIndex::build(&original.points, self.approximate),
+                    &reconstructed.points,
+                    &NeighbourIndex::build(&reconstructed.points, self.approximate),
+                    &mut metrics,
+                );
+                channel.send(PipelineMessage::Metrics(metrics));
+            }
+            (PipelineMessage::End, _) | (_, PipelineMessage::End) => {
+                channel.send(PipelineMessage::End);
+            }
+            (_, _) => {}
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use clap::Parser;
+
+use crate::{
+    methods::colormap::{colorize_height, Colormap},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Colors a pointcloud from the stream by the height (z) of its points.
+#[derive(Parser)]
+pub struct Args {
+    /// height mapped to the lowest color of the colormap
+    #[clap(long, allow_hyphen_values = true)]
+    min_z: f32,
+
+    /// height mapped to the highest color of the colormap
+    #[clap(long, allow_hyphen_values = true)]
+    max_z: f32,
+
+    #[clap(short, long, value_enum, default_value_t = Colormap::Jet)]
+    colormap: Colormap,
+
+    /// wrap heights outside the range around the colormap instead of clipping them
+    #[clap(long)]
+    wrap: bool,
+}
+
+pub struct HeightColorizer {
+    args: Args,
+}
+
+impl HeightColorizer {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(HeightColorizer { args })
+    }
+}
+
+impl Subcommand for HeightColorizer {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(mut pc, i) => {
+                    colorize_height(
+                        &mut pc,
+                        self.args.min_z,
+                        self.args.max_z,
+                        self.args.colormap,
+                        self.args.wrap,
+                    );
+                    channel.send(PipelineMessage::IndexedPointCloud(pc, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    formats::{pointxyzrgba::PointXyzRgba, PointCloud},
+    metrics::{Completeness, Metrics},
+    pipeline::{channel::Channel, PipelineMessage},
+    structures::neighbours::NeighbourIndex,
+    utils::read_file_to_point_cloud,
+};
+
+use super::Subcommand;
+
+/// Calculates the fraction of a reference pointcloud covered by each pointcloud from the stream.
+#[derive(Parser)]
+pub struct Args {
+    /// point cloud file of the reference surface
+    #[clap(short, long)]
+    reference: PathBuf,
+
+    /// maximum distance to a reconstructed point for a reference point to be covered
+    #[clap(short, long)]
+    threshold: f64,
+
+    /// search the nearest neighbours with a random projection forest instead of a kd tree
+    #[clap(short, long)]
+    approximate: bool,
+}
+
+pub struct CompletenessCalculator {
+    reference: PointCloud<PointXyzRgba>,
+    threshold: f64,
+    approximate: bool,
+}
+
+impl CompletenessCalculator {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let reference = read_file_to_point_cloud(&args.reference)
+            .expect("Failed to read reference point cloud");
+        Box::new(CompletenessCalculator {
+            reference,
+            threshold: args.threshold,
+            approximate: args.approximate,
+        })
+    }
+}
+
+impl Subcommand for CompletenessCalculator {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, _) => {
+                    let reconstructed_tree = NeighbourIndex::build(&pc.points, self.approximate);
+                    let completeness = Completeness::calculate_metric(
+                        &self.reference.points,
+                        &pc.points,
+                        &reconstructed_tree,
+                        self.threshold,
+                    );
+
+                    let mut metrics = Metrics::new();
+                    metrics.insert("completeness".to_string(), format!("{:.5}", completeness));
+                    channel.send(PipelineMessage::Metrics(metrics));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use clap::Parser;
+
+use crate::{
+    methods::fpfh::compute_fpfh,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Computes the FPFH descriptor of every point of a pointcloud with normals from the stream.
+#[derive(Parser)]
+pub struct Args {
+    /// radius of the neighbourhood used for the descriptors
+    #[clap(short, long)]
+    radius: f64,
+}
+
+pub struct FpfhCalculator {
+    radius: f32,
+}
+
+impl FpfhCalculator {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(FpfhCalculator {
+            radius: args.radius as f32,
+        })
+    }
+}
+
+impl Subcommand for FpfhCalculator {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloudNormal(pc, i) => {
+                    let descriptors = compute_fpfh(&pc, self.radius);
+                    channel.send(PipelineMessage::IndexedPointCloudFpfh(pc, descriptors, i));
+                }
+                PipelineMessage::IndexedPointCloud(_, i) => {
+                    println!(
+                        "compute-fpfh requires point clouds with normals, skipping point cloud {}",
+                        i
+                    );
+                }
+                PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use clap::Parser;
+
+use crate::{
+    formats::{pointxyzrgbanormal::PointXyzRgbaNormal, PointCloud},
+    gpu::{normals::compute_normals_gpu, GpuContext},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Estimates the normal of every point on the GPU, from the principal component analysis of its
+/// nearest neighbours.
+#[derive(Parser)]
+pub struct Args {
+    /// number of nearest neighbours of every point, the point itself included
+    #[clap(short, long, default_value_t = 16)]
+    knn: usize,
+}
+
+pub struct GpuNormalEstimation {
+    context: GpuContext,
+    knn: usize,
+}
+
+impl GpuNormalEstimation {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(GpuNormalEstimation {
+            context: GpuContext::new().expect("No GPU adapter available"),
+            knn: args.knn,
+        })
+    }
+}
+
+impl Subcommand for GpuNormalEstimation {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let points = pc
+                        .points
+                        .iter()
+                        .zip(compute_normals_gpu(&self.context, &pc, self.knn))
+                        .map(|(p, normal)| {
+                            PointXyzRgbaNormal::from_xyz_rgba_normal(p.xyz(), p.rgba(), normal)
+                        })
+                        .collect();
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(
+                        PointCloud::from_points(points),
+                        i,
+                    ));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use clap::Parser;
+use std::fs::File;
+use std::io::BufWriter;
+
+use crate::{
+    methods::segmentation::ConfusionMatrix,
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Evaluates the labels of two input streams with the per class IoU and the mean IoU.
+/// First input stream is the prediction, second is the ground truth.
+#[derive(Parser)]
+pub struct Args {
+    /// save the confusion matrix accumulated over all point clouds as csv
+    #[clap(long)]
+    output_csv: Option<String>,
+}
+
+pub struct ConfusionMatrixCalculator {
+    output_csv: Option<String>,
+    total: ConfusionMatrix,
+}
+
+impl ConfusionMatrixCalculator {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(ConfusionMatrixCalculator {
+            output_csv: args.output_csv,
+            total: ConfusionMatrix::new(),
+        })
+    }
+}
+
+impl Subcommand for ConfusionMatrixCalculator {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        let mut messages_iter = messages.into_iter();
+        let message_one = messages_iter
+            .next()
+            .expect("Expecting two input streams for confusion-matrix");
+        let message_two = messages_iter
+            .next()
+            .expect("Expecting two input streams for confusion-matrix");
+
+        match (&message_one, &message_two) {
+            (
+                PipelineMessage::IndexedPointCloudLabel(predicted, predicted_labels, _),
+                PipelineMessage::IndexedPointCloudLabel(ground_truth, ground_truth_labels, _),
+            ) => {
+                let matrix = ConfusionMatrix::from_clouds(
+                    predicted,
+                    predicted_labels,
+                    ground_truth,
+                    ground_truth_labels,
+                );
+                self.total.merge(&matrix);
+
+                let mut metrics = Metrics::new();
+                for (class, iou) in matrix.iou() {
+                    metrics.insert(format!("iou_{}", class), format!("{:.5}", iou));
+                }
+                if let Some(miou) = matrix.mean_iou() {
+                    metrics.insert("miou".to_string(), format!("{:.5}", miou));
+                }
+                channel.send(PipelineMessage::Metrics(metrics));
+            }
+            (PipelineMessage::End, _) | (_, PipelineMessage::End) => {
+                if let Some(path) = &self.output_csv {
+                    File::create(path)
+                        .and_then(|f| self.total.write_csv(&mut BufWriter::new(f)))
+                        .expect("Should be able to create file to write confusion matrix to");
+                }
+                channel.send(PipelineMessage::End);
+            }
+            (_, _) => {}
+        }
+    }
+}
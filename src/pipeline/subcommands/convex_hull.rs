@@ -0,0 +1,84 @@
+use clap::Parser;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::formats::PointCloud;
+use crate::methods::hull::convex_hull_xy;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+
+use super::Subcommand;
+
+/// Computes the convex hull of point clouds from the stream projected onto the XY plane,
+/// and writes its vertices as a json polygon (<index>.json).
+#[derive(Parser)]
+pub struct Args {
+    /// output directory to store the polygons
+    output_dir: String,
+
+    #[clap(long, default_value_t = 5)]
+    name_length: usize,
+
+    /// forward the hull vertices as a pointcloud instead of the input pointcloud
+    #[clap(short, long)]
+    boundary: bool,
+}
+
+pub struct HullProjector {
+    args: Args,
+}
+
+impl HullProjector {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args = Args::parse_from(args);
+        std::fs::create_dir_all(Path::new(&args.output_dir))
+            .expect("Failed to create output directory");
+        Box::from(HullProjector { args })
+    }
+}
+
+fn write_polygon(path: &Path, polygon: &[[f32; 2]]) -> Result<(), Box<dyn std::error::Error>> {
+    let writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(writer, polygon)?;
+    Ok(())
+}
+
+impl Subcommand for HullProjector {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match &message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let hull = convex_hull_xy(pc);
+                    let polygon: Vec<[f32; 2]> = hull
+                        .iter()
+                        .map(|&j| [pc.points[j].x, pc.points[j].y])
+                        .collect();
+                    let file_name = format!("{:0width$}.json", i, width = self.args.name_length);
+                    let output_file = Path::new(&self.args.output_dir).join(file_name);
+                    if let Err(e) = write_polygon(&output_file, &polygon) {
+                        println!("Failed to write {:?}\n{e}", output_file);
+                    }
+
+                    if self.args.boundary {
+                        let boundary = hull.into_iter().map(|j| pc.points[j]).collect();
+                        channel.send(PipelineMessage::IndexedPointCloud(
+                            PointCloud::from_points(boundary),
+                            *i,
+                        ));
+                        continue;
+                    }
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::End
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+            }
+            channel.send(message);
+        }
+    }
+}
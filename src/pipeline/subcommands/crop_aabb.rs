@@ -0,0 +1,64 @@
+use cgmath::Point3;
+use clap::Parser;
+
+use crate::pipeline::{channel::Channel, PipelineMessage};
+
+use super::Subcommand;
+
+/// Crops a pointcloud from the stream to the points inside an axis aligned bounding box.
+#[derive(Parser)]
+pub struct Args {
+    #[clap(long, allow_hyphen_values = true)]
+    min_x: f32,
+    #[clap(long, allow_hyphen_values = true)]
+    min_y: f32,
+    #[clap(long, allow_hyphen_values = true)]
+    min_z: f32,
+    #[clap(long, allow_hyphen_values = true)]
+    max_x: f32,
+    #[clap(long, allow_hyphen_values = true)]
+    max_y: f32,
+    #[clap(long, allow_hyphen_values = true)]
+    max_z: f32,
+}
+
+pub struct AabbCropper {
+    min: Point3<f32>,
+    max: Point3<f32>,
+}
+
+impl AabbCropper {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(AabbCropper {
+            min: Point3::new(args.min_x, args.min_y, args.min_z),
+            max: Point3::new(args.max_x, args.max_y, args.max_z),
+        })
+    }
+}
+
+impl Subcommand for AabbCropper {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let cropped_pc = pc.crop_aabb(self.min, self.max);
+                    channel.send(PipelineMessage::IndexedPointCloud(cropped_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(pc, i) => {
+                    let cropped_pc = pc.crop_aabb(self.min, self.max);
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(cropped_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
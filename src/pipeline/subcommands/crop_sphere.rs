@@ -0,0 +1,60 @@
+use cgmath::Point3;
+use clap::Parser;
+
+use crate::pipeline::{channel::Channel, PipelineMessage};
+
+use super::Subcommand;
+
+/// Crops a pointcloud from the stream to the points within a sphere.
+#[derive(Parser)]
+pub struct Args {
+    #[clap(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    cx: f32,
+    #[clap(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    cy: f32,
+    #[clap(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    cz: f32,
+    #[clap(short, long)]
+    radius: f32,
+}
+
+pub struct SphereCropper {
+    center: Point3<f32>,
+    radius: f32,
+}
+
+impl SphereCropper {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(SphereCropper {
+            center: Point3::new(args.cx, args.cy, args.cz),
+            radius: args.radius,
+        })
+    }
+}
+
+impl Subcommand for SphereCropper {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let cropped_pc = pc.crop_sphere(self.center, self.radius);
+                    channel.send(PipelineMessage::IndexedPointCloud(cropped_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(pc, i) => {
+                    let cropped_pc = pc.crop_sphere(self.center, self.radius);
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(cropped_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
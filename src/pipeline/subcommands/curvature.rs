@@ -0,0 +1,68 @@
+use clap::Parser;
+use std::path::Path;
+
+use crate::methods::curvature::principal_curvatures;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+use crate::ply::write_ply_curvature;
+
+use super::Subcommand;
+
+/// Estimates the principal curvatures of point clouds with normals from the stream, and writes
+/// them with the mean and gaussian curvatures as ply files (<index>.ply).
+#[derive(Parser)]
+pub struct Args {
+    /// output directory to store the point clouds with curvatures
+    output_dir: String,
+
+    /// radius of the neighbourhood the shape operator of each point is fitted to
+    #[clap(short, long, default_value_t = 0.05)]
+    radius: f64,
+
+    #[clap(long, default_value_t = 5)]
+    name_length: usize,
+}
+
+pub struct Curvature {
+    args: Args,
+}
+
+impl Curvature {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args = Args::parse_from(args);
+        std::fs::create_dir_all(Path::new(&args.output_dir))
+            .expect("Failed to create output directory");
+        Box::from(Curvature { args })
+    }
+}
+
+impl Subcommand for Curvature {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match &message {
+                PipelineMessage::IndexedPointCloudNormal(pc, i)
+                | PipelineMessage::IndexedPointCloudFpfh(pc, _, i) => {
+                    let curvatures = principal_curvatures(pc, self.args.radius);
+                    let file_name = format!("{:0width$}.ply", i, width = self.args.name_length);
+                    let output_file = Path::new(&self.args.output_dir).join(file_name);
+                    if let Err(e) = write_ply_curvature(&curvatures, &output_file) {
+                        println!("Failed to write {:?}\n{e}", output_file);
+                    }
+                }
+                PipelineMessage::IndexedPointCloud(_, i) => {
+                    println!(
+                        "curvature requires point clouds with normals, skipping point cloud {}",
+                        i
+                    );
+                }
+                PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::End
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+            }
+            channel.send(message);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use clap::Parser;
+
+use crate::{
+    codec::delta::DeltaDecoder,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Decodes the files written by delta-encode from the stream into pointclouds, in the order
+/// they were encoded.
+#[derive(Parser)]
+pub struct Args {}
+
+pub struct DeltaDecode {
+    decoder: DeltaDecoder,
+}
+
+impl DeltaDecode {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let _args: Args = Args::parse_from(args);
+        Box::new(DeltaDecode {
+            decoder: DeltaDecoder::new(),
+        })
+    }
+}
+
+impl Subcommand for DeltaDecode {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::CompressedStream(path, i) => {
+                    let pc = std::fs::read(&path)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|bytes| self.decoder.decode(&bytes))
+                        .unwrap_or_else(|e| panic!("Failed to decode frame {}: {:?}", i, e));
+                    channel.send(PipelineMessage::IndexedPointCloud(pc, i));
+                }
+                PipelineMessage::IndexedPointCloud(_, _)
+                | PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    codec::delta::DeltaEncoder,
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Losslessly compresses the pointclouds from the stream, the first one as is and every other
+/// one as its differences with the previous one, and outputs the size of the compressed files
+/// as metrics.
+#[derive(Parser)]
+pub struct Args {
+    /// directory to write the compressed files to
+    #[clap(short, long)]
+    output_dir: PathBuf,
+}
+
+pub struct DeltaEncode {
+    output_dir: PathBuf,
+    encoder: DeltaEncoder,
+}
+
+impl DeltaEncode {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        std::fs::create_dir_all(&args.output_dir).expect("Failed to create output directory");
+        Box::new(DeltaEncode {
+            output_dir: args.output_dir,
+            encoder: DeltaEncoder::new(),
+        })
+    }
+}
+
+impl Subcommand for DeltaEncode {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let path = self.output_dir.join(format!("{:05}.dlt", i));
+                    let bytes = self.encoder.encode(&pc);
+                    std::fs::write(&path, &bytes)
+                        .unwrap_or_else(|e| panic!("Failed to write {}: {:?}", path.display(), e));
+
+                    let mut metrics = Metrics::new();
+                    metrics.insert("compressed_bytes".to_string(), format!("{}", bytes.len()));
+                    metrics.insert(
+                        "bits_per_point".to_string(),
+                        format!("{:.5}", (bytes.len() * 8) as f64 / pc.points.len() as f64),
+                    );
+                    channel.send(PipelineMessage::Metrics(metrics));
+                    channel.send(PipelineMessage::CompressedStream(path, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use clap::Parser;
+
+use crate::{
+    methods::matching::match_descriptors,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Matches the FPFH descriptors of two input streams by mutual nearest neighbour.
+/// First input stream is the source, second is the target.
+#[derive(Parser)]
+pub struct Args {
+    /// maximum ratio between the distances to the nearest and second nearest descriptor
+    #[clap(short, long, default_value_t = 0.8)]
+    ratio_threshold: f32,
+}
+
+pub struct DescriptorMatcher {
+    ratio_threshold: f32,
+}
+
+impl DescriptorMatcher {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(DescriptorMatcher {
+            ratio_threshold: args.ratio_threshold,
+        })
+    }
+}
+
+impl Subcommand for DescriptorMatcher {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        let mut messages_iter = messages.into_iter();
+        let message_one = messages_iter
+            .next()
+            .expect("Expecting two input streams for descriptor-match");
+        let message_two = messages_iter
+            .next()
+            .expect("Expecting two input streams for descriptor-match");
+
+        match (&message_one, &message_two) {
+            (
+                PipelineMessage::IndexedPointCloudFpfh(_, source, _),
+                PipelineMessage::IndexedPointCloudFpfh(_, target, _),
+            ) => {
+                let correspondences = match_descriptors(source, target, self.ratio_threshold);
+                channel.send(PipelineMessage::Correspondences(correspondences));
+            }
+            (PipelineMessage::End, _) | (_, PipelineMessage::End) => {
+                channel.send(PipelineMessage::End);
+            }
+            (_, _) => {}
+        }
+    }
+}
@@ -8,6 +8,8 @@ use crate::{
 use super::Subcommand;
 
 /// Downsample a pointcloud from the stream.
+///
+/// Pointclouds with normals keep them, averaged over the points merged into each voxel.
 #[derive(Parser)]
 pub struct Args {
     #[clap(short, long)]
@@ -35,7 +37,16 @@ impl Subcommand for Downsampler {
                     let downsampled_pc = downsample(pc, self.points_per_voxel);
                     channel.send(PipelineMessage::IndexedPointCloud(downsampled_pc, i));
                 }
-                PipelineMessage::Metrics(_) | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::IndexedPointCloudNormal(pc, i) => {
+                    let downsampled_pc = downsample(pc, self.points_per_voxel);
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(downsampled_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
                 PipelineMessage::End => {
                     channel.send(message);
                 }
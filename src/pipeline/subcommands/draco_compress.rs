@@ -0,0 +1,111 @@
+use clap::Parser;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    codec::draco::DracoCodec,
+    formats::{pointxyzrgba::PointXyzRgba, PointCloud},
+    metrics::{Metrics, PairMetrics},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Bytes of a point without compression: three f32 coordinates and three u8 colors.
+const RAW_POINT_BYTES: usize = 15;
+
+/// Compresses each pointcloud from the stream with Draco, and outputs the compression ratio and
+/// the D1 error of the decoded pointcloud as metrics.
+#[derive(Parser)]
+pub struct Args {
+    /// directory to write the compressed files to
+    #[clap(short, long)]
+    output_dir: PathBuf,
+
+    /// quantization bits of the positions
+    #[clap(long, default_value_t = 11)]
+    qp: u8,
+
+    /// path of the draco_encoder binary
+    #[clap(long, default_value = "draco_encoder")]
+    encoder: PathBuf,
+
+    /// path of the draco_decoder binary, used to measure the error
+    #[clap(long, default_value = "draco_decoder")]
+    decoder: PathBuf,
+}
+
+pub struct DracoCompress {
+    codec: DracoCodec,
+    output_dir: PathBuf,
+    qp: u8,
+}
+
+impl DracoCompress {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        std::fs::create_dir_all(&args.output_dir).expect("Failed to create output directory");
+        Box::new(DracoCompress {
+            codec: DracoCodec::new(args.encoder, args.decoder),
+            output_dir: args.output_dir,
+            qp: args.qp,
+        })
+    }
+
+    /// Compresses the pointcloud to `path` and measures the result.
+    fn compress(&self, pc: &PointCloud<PointXyzRgba>, path: &Path) -> Metrics {
+        self.codec
+            .encode(pc, path, self.qp)
+            .unwrap_or_else(|e| panic!("Failed to compress {}: {:?}", path.display(), e));
+        let bytes = std::fs::metadata(path)
+            .expect("Failed to read the size of the compressed file")
+            .len() as usize;
+        let decoded = self
+            .codec
+            .decode(path)
+            .unwrap_or_else(|e| panic!("Failed to decompress {}: {:?}", path.display(), e));
+
+        let mut metrics = Metrics::new();
+        metrics.insert("compressed_bytes".to_string(), format!("{}", bytes));
+        metrics.insert(
+            "compression_ratio".to_string(),
+            format!(
+                "{:.5}",
+                (pc.points.len() * RAW_POINT_BYTES) as f64 / bytes as f64
+            ),
+        );
+        metrics.insert(
+            "bits_per_point".to_string(),
+            format!("{:.5}", (bytes * 8) as f64 / pc.points.len() as f64),
+        );
+        if !pc.points.is_empty() && !decoded.points.is_empty() {
+            let d1_mse = PairMetrics::calculate(pc, &decoded, 12).d1_mse;
+            metrics.insert("d1_mse".to_string(), format!("{:.8}", d1_mse));
+        }
+        metrics
+    }
+}
+
+impl Subcommand for DracoCompress {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let path = self.output_dir.join(format!("{:05}.drc", i));
+                    let metrics = self.compress(&pc, &path);
+                    channel.send(PipelineMessage::Metrics(metrics));
+                    channel.send(PipelineMessage::CompressedStream(path, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    codec::draco::DracoCodec,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Decompresses the Draco files from the stream into pointclouds.
+#[derive(Parser)]
+pub struct Args {
+    /// path of the draco_decoder binary
+    #[clap(long, default_value = "draco_decoder")]
+    decoder: PathBuf,
+}
+
+pub struct DracoDecompress {
+    codec: DracoCodec,
+}
+
+impl DracoDecompress {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(DracoDecompress {
+            // only decodes, the encoder is never run
+            codec: DracoCodec::new(PathBuf::from("draco_encoder"), args.decoder),
+        })
+    }
+}
+
+impl Subcommand for DracoDecompress {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::CompressedStream(path, i) => {
+                    let pc = self
+                        .codec
+                        .decode(&path)
+                        .unwrap_or_else(|e| panic!("Failed to decompress frame {}: {:?}", i, e));
+                    channel.send(PipelineMessage::IndexedPointCloud(pc, i));
+                }
+                PipelineMessage::IndexedPointCloud(_, _)
+                | PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use clap::Parser;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use crate::{
+    codec::delta::frame_delta,
+    formats::{pointxyzrgba::PointXyzRgba, PointCloud},
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+const CHANNELS: [&str; 6] = ["dx", "dy", "dz", "dr", "dg", "db"];
+
+/// Writes the differences that delta-encode codes between each pointcloud from the stream and the
+/// previous one as csv, one dx,dy,dz,dr,dg,db row per point. Positions differ by the bit
+/// patterns of the coordinates. Outputs the pointcloud unchanged.
+#[derive(Parser)]
+pub struct Args {
+    /// directory to write the differences to, one csv file per pointcloud named by its index
+    output_dir: PathBuf,
+
+    /// also output the mean and maximum absolute difference of each channel as metrics
+    #[clap(short, long)]
+    summarize: bool,
+}
+
+pub struct DumpDelta {
+    args: Args,
+    previous: Option<PointCloud<PointXyzRgba>>,
+}
+
+impl DumpDelta {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        std::fs::create_dir_all(&args.output_dir).expect("Failed to create output directory");
+        Box::new(DumpDelta {
+            args,
+            previous: None,
+        })
+    }
+}
+
+/// The csv channels of the differences of each point.
+fn delta_rows(
+    previous: &PointCloud<PointXyzRgba>,
+    current: &PointCloud<PointXyzRgba>,
+) -> Vec<[i64; 6]> {
+    frame_delta(previous, current)
+        .into_iter()
+        .map(|(xyz, rgba)| {
+            [
+                xyz[0],
+                xyz[1],
+                xyz[2],
+                rgba[0] as i64,
+                rgba[1] as i64,
+                rgba[2] as i64,
+            ]
+        })
+        .collect()
+}
+
+fn summarize(rows: &[[i64; 6]]) -> Metrics {
+    let mut metrics = Metrics::new();
+    for (c, name) in CHANNELS.iter().enumerate() {
+        let magnitudes = rows.iter().map(|row| row[c].unsigned_abs());
+        let mean = magnitudes.clone().sum::<u64>() as f64 / rows.len().max(1) as f64;
+        let max = magnitudes.max().unwrap_or(0);
+        metrics.insert(format!("mean_abs_{}", name), format!("{:.5}", mean));
+        metrics.insert(format!("max_abs_{}", name), max.to_string());
+    }
+    let unchanged = rows
+        .iter()
+        .filter(|row| row.iter().all(|&d| d == 0))
+        .count();
+    metrics.insert("unchanged_points".to_string(), unchanged.to_string());
+    metrics
+}
+
+impl Subcommand for DumpDelta {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    // the first pointcloud has nothing to differ from
+                    if let Some(previous) = &self.previous {
+                        let rows = delta_rows(previous, &pc);
+                        let path = self.args.output_dir.join(format!("{:05}.csv", i));
+                        let mut writer = BufWriter::new(
+                            File::create(path).expect("Failed to create delta output file"),
+                        );
+                        writeln!(writer, "{}", CHANNELS.join(","))
+                            .expect("Failed to write delta output");
+                        for row in &rows {
+                            let values: Vec<String> = row.iter().map(|d| d.to_string()).collect();
+                            writeln!(writer, "{}", values.join(","))
+                                .expect("Failed to write delta output");
+                        }
+                        writer.flush().expect("Failed to write delta output");
+                        if self.args.summarize {
+                            channel.send(PipelineMessage::Metrics(summarize(&rows)));
+                        }
+                    }
+                    self.previous = Some(pc.clone());
+                    channel.send(PipelineMessage::IndexedPointCloud(pc, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_static_cloud_zero_deltas() {
+        let cloud = PointCloud::from_points(
+            (0..50)
+                .map(|i| {
+                    PointXyzRgba::from_xyz_rgba(
+                        [i as f32 * 0.3, (i as f32).sin(), -2.5],
+                        [i as u8, 3 * i as u8, 255 - i as u8, 255],
+                    )
+                })
+                .collect(),
+        );
+        let output_dir = std::env::temp_dir().join("vivotk_test_dump_delta");
+        let mut dump = DumpDelta::from_args(vec![
+            "dump-delta".to_string(),
+            output_dir.to_str().unwrap().to_string(),
+            "--summarize".to_string(),
+        ]);
+        let (progress_tx, _progress_rx) = crossbeam_channel::unbounded();
+        let mut channel = Channel::new(progress_tx);
+        let rx = channel.subscribe();
+        dump.handle(
+            vec![
+                PipelineMessage::IndexedPointCloud(cloud.clone(), 0),
+                PipelineMessage::IndexedPointCloud(cloud, 1),
+            ],
+            &channel,
+        );
+        let csv = std::fs::read_to_string(output_dir.join("00001.csv")).unwrap();
+        let first_exists = output_dir.join("00000.csv").exists();
+        std::fs::remove_dir_all(&output_dir).unwrap();
+
+        assert!(!first_exists);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("dx,dy,dz,dr,dg,db"));
+        let rows: Vec<&str> = lines.collect();
+        assert_eq!(rows.len(), 50);
+        assert!(rows.iter().all(|&row| row == "0,0,0,0,0,0"));
+
+        let metrics = rx
+            .try_iter()
+            .find_map(|message| match message {
+                PipelineMessage::Metrics(metrics) => Some(metrics),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(metrics.metrics().len(), 13);
+        for (key, value) in metrics.metrics() {
+            match key.as_str() {
+                "unchanged_points" => assert_eq!(value, "50"),
+                _ => assert_eq!(value.parse::<f64>().unwrap(), 0.0, "{}", key),
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    methods::{
+        fusion::{transform_cloud, write_extrinsic},
+        icp::matched_points_transform,
+    },
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+    utils::read_file_to_point_cloud,
+};
+
+use super::Subcommand;
+
+/// Estimates the rigid transform aligning matched keypoints, the i-th point of the source file
+/// with the i-th point of the target file. Writes the row-major 4x4 matrix as json, and outputs
+/// it as metrics with the root mean square distance of the aligned keypoints.
+#[derive(Parser)]
+pub struct Args {
+    /// keypoints to move
+    source: PathBuf,
+
+    /// keypoints to align to, in the same order as the source keypoints
+    target: PathBuf,
+
+    /// json file to write the transform to
+    #[clap(short, long, default_value = "transform.json")]
+    output: PathBuf,
+}
+
+pub struct TransformEstimator {
+    args: Args,
+}
+
+impl TransformEstimator {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::new(TransformEstimator {
+            args: Args::parse_from(args),
+        })
+    }
+}
+
+impl Subcommand for TransformEstimator {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        if messages.is_empty() {
+            let source = read_file_to_point_cloud(&self.args.source)
+                .expect("Failed to read source keypoints");
+            let target = read_file_to_point_cloud(&self.args.target)
+                .expect("Failed to read target keypoints");
+            let extrinsic = matched_points_transform(&source, &target);
+            write_extrinsic(&extrinsic, &self.args.output)
+                .expect("Should be able to write the transform");
+
+            let mut metrics = Metrics::new();
+            for (row, values) in extrinsic.iter().take(3).enumerate() {
+                for (col, value) in values.iter().enumerate() {
+                    metrics.insert(format!("transform_{}{}", row, col), format!("{:.5}", value));
+                }
+            }
+            let aligned = transform_cloud(&source, &extrinsic);
+            let squared_error: f32 = aligned
+                .points
+                .iter()
+                .zip(&target.points)
+                .map(|(p, q)| (p.x - q.x).powi(2) + (p.y - q.y).powi(2) + (p.z - q.z).powi(2))
+                .sum();
+            let rmse = (squared_error / target.points.len().max(1) as f32).sqrt();
+            metrics.insert("rmse".to_string(), format!("{:.5}", rmse));
+            channel.send(PipelineMessage::Metrics(metrics));
+            channel.send(PipelineMessage::End);
+        } else {
+            for message in messages {
+                channel.send(message);
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::formats::{hdf5::Hdf5Writer, pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+
+use super::Subcommand;
+
+/// Writes the point clouds from the stream to a single HDF5 file, each frame as the datasets
+/// /clouds/<index>/xyz and /clouds/<index>/rgb. Normals are not written.
+#[derive(Parser)]
+pub struct Args {
+    /// HDF5 file to create
+    #[clap(short, long)]
+    output: PathBuf,
+
+    /// frame rate the timestamp attribute of each frame, index / fps in seconds, is computed at
+    #[clap(long, default_value_t = 30.0)]
+    fps: f64,
+}
+
+pub struct ExportHdf5 {
+    writer: Option<Hdf5Writer>,
+    fps: f64,
+}
+
+impl ExportHdf5 {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let writer = Hdf5Writer::create(&args.output).expect("Failed to create HDF5 file");
+        Box::from(ExportHdf5 {
+            writer: Some(writer),
+            fps: args.fps,
+        })
+    }
+
+    fn write(&mut self, pc: &PointCloud<PointXyzRgba>, i: u32) {
+        if let Some(writer) = self.writer.as_mut() {
+            if let Err(e) = writer.write_frame(i, i as f64 / self.fps, pc) {
+                println!("Failed to write frame {}\n{e}", i);
+            }
+        }
+    }
+}
+
+impl Subcommand for ExportHdf5 {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match &message {
+                PipelineMessage::IndexedPointCloud(pc, i)
+                | PipelineMessage::IndexedPointCloudLabel(pc, _, i) => self.write(pc, *i),
+                PipelineMessage::IndexedPointCloudNormal(pc, i)
+                | PipelineMessage::IndexedPointCloudFpfh(pc, _, i) => {
+                    let points = pc
+                        .points
+                        .iter()
+                        .map(|p| PointXyzRgba::from_xyz_rgba(p.xyz(), p.rgba()))
+                        .collect();
+                    self.write(&PointCloud::from_points(points), *i);
+                }
+                PipelineMessage::End => {
+                    if let Some(writer) = self.writer.take() {
+                        writer.finish().expect("Failed to finish HDF5 file");
+                    }
+                }
+                PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+            }
+            channel.send(message);
+        }
+    }
+}
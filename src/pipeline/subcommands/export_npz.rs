@@ -0,0 +1,66 @@
+use clap::Parser;
+use std::path::{Path, PathBuf};
+
+use crate::formats::npz::{write_npz, write_npz_normal};
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+
+use super::Subcommand;
+
+/// Writes point clouds from the stream as NumPy .npz files (<index>.npz) with the xyz and rgb
+/// arrays, and the normals array for point clouds with normals.
+#[derive(Parser)]
+pub struct Args {
+    /// output directory to store the npz files
+    #[clap(short, long)]
+    output: PathBuf,
+
+    #[clap(long, default_value_t = 5)]
+    name_length: usize,
+}
+
+pub struct ExportNpz {
+    args: Args,
+}
+
+impl ExportNpz {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args = Args::parse_from(args);
+        std::fs::create_dir_all(&args.output).expect("Failed to create output directory");
+        Box::from(ExportNpz { args })
+    }
+
+    fn output_file(&self, i: u32) -> PathBuf {
+        let file_name = format!("{:0width$}.npz", i, width = self.args.name_length);
+        Path::new(&self.args.output).join(file_name)
+    }
+}
+
+impl Subcommand for ExportNpz {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match &message {
+                PipelineMessage::IndexedPointCloud(pc, i)
+                | PipelineMessage::IndexedPointCloudLabel(pc, _, i) => {
+                    let output_file = self.output_file(*i);
+                    if let Err(e) = write_npz(pc, &output_file) {
+                        println!("Failed to write {:?}\n{e}", output_file);
+                    }
+                }
+                PipelineMessage::IndexedPointCloudNormal(pc, i)
+                | PipelineMessage::IndexedPointCloudFpfh(pc, _, i) => {
+                    let output_file = self.output_file(*i);
+                    if let Err(e) = write_npz_normal(pc, &output_file) {
+                        println!("Failed to write {:?}\n{e}", output_file);
+                    }
+                }
+                PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::End
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+            }
+            channel.send(message);
+        }
+    }
+}
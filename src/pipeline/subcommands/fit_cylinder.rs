@@ -0,0 +1,70 @@
+use cgmath::Vector3;
+use clap::Parser;
+
+use crate::{
+    methods::fitting::fit_cylinder,
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Fits a cylinder with a known axis direction to a pointcloud from the stream.
+/// Outputs the center of the axis and the radius as metrics.
+#[derive(Parser)]
+pub struct Args {
+    #[clap(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    axis_x: f32,
+    #[clap(long, default_value_t = 1.0, allow_hyphen_values = true)]
+    axis_y: f32,
+    #[clap(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    axis_z: f32,
+}
+
+pub struct CylinderFitter {
+    axis: Vector3<f32>,
+}
+
+impl CylinderFitter {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(CylinderFitter {
+            axis: Vector3::new(args.axis_x, args.axis_y, args.axis_z),
+        })
+    }
+}
+
+impl Subcommand for CylinderFitter {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let mut metrics = Metrics::new();
+                    match fit_cylinder(&pc, self.axis) {
+                        Some((center, radius)) => {
+                            metrics.insert("center_x".to_string(), format!("{:.5}", center.x));
+                            metrics.insert("center_y".to_string(), format!("{:.5}", center.y));
+                            metrics.insert("center_z".to_string(), format!("{:.5}", center.z));
+                            metrics.insert("radius".to_string(), format!("{:.5}", radius));
+                        }
+                        None => {
+                            println!("Failed to fit a cylinder to point cloud {}", i);
+                            continue;
+                        }
+                    }
+                    channel.send(PipelineMessage::Metrics(metrics));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use clap::Parser;
+
+use crate::{
+    methods::sampling::farthest_point_sample,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Downsamples a pointcloud from the stream with farthest point sampling.
+#[derive(Parser)]
+pub struct Args {
+    /// number of points to keep
+    #[clap(short, long)]
+    num_points: usize,
+}
+
+pub struct FarthestPointSampler {
+    num_points: usize,
+}
+
+impl FarthestPointSampler {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(FarthestPointSampler {
+            num_points: args.num_points,
+        })
+    }
+}
+
+impl Subcommand for FarthestPointSampler {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let sampled_pc = farthest_point_sample(&pc, self.num_points);
+                    channel.send(PipelineMessage::IndexedPointCloud(sampled_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(pc, i) => {
+                    let sampled_pc = farthest_point_sample(&pc, self.num_points);
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(sampled_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use clap::Parser;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    formats::{HasCoordinate, PointCloud},
+    methods::sampling::farthest_point_indices_flat,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Resamples each pointcloud from the stream with farthest point sampling on flat arrays,
+/// vectorized with the simd feature.
+#[derive(Parser)]
+pub struct Args {
+    /// number of points to keep
+    #[clap(short, long)]
+    num_points: usize,
+
+    /// seed of the random first point, for reproducible samples
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+pub struct FpsResampler {
+    num_points: usize,
+    rng: StdRng,
+}
+
+impl FpsResampler {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let rng = match args.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Box::new(FpsResampler {
+            num_points: args.num_points,
+            rng,
+        })
+    }
+
+    fn resample<T: HasCoordinate + Clone>(&mut self, pc: &PointCloud<T>) -> PointCloud<T> {
+        if self.num_points >= pc.points.len() {
+            return pc.clone();
+        }
+        let start = self.rng.gen_range(0..pc.points.len());
+        let points: Vec<T> = farthest_point_indices_flat(pc, self.num_points, start)
+            .into_iter()
+            .map(|i| pc.points[i].clone())
+            .collect();
+        PointCloud::from_points(points)
+    }
+}
+
+impl Subcommand for FpsResampler {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let sampled_pc = self.resample(&pc);
+                    channel.send(PipelineMessage::IndexedPointCloud(sampled_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(pc, i) => {
+                    let sampled_pc = self.resample(&pc);
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(sampled_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    formats::{pointxyzrgba::PointXyzRgba, PointCloud},
+    methods::{four_pcs::four_pcs, fusion::transform_cloud},
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+    utils::read_file_to_point_cloud,
+};
+
+use super::Subcommand;
+
+/// Registers each pointcloud from the stream to a reference pointcloud without an initial
+/// estimate with 4-points congruent sets. Outputs the row-major rigid transform and the inlier
+/// ratio as metrics, followed by the aligned pointcloud.
+#[derive(Parser)]
+pub struct Args {
+    /// point cloud file to align to
+    #[clap(short, long)]
+    reference: PathBuf,
+
+    /// approximation level, the distance within which points, pair lengths and crossings match
+    #[clap(long, default_value_t = 0.01)]
+    delta: f32,
+
+    /// number of points sampled from each point cloud
+    #[clap(long, default_value_t = 200)]
+    num_samples: usize,
+}
+
+pub struct GlobalRegistration {
+    reference: PointCloud<PointXyzRgba>,
+    delta: f32,
+    num_samples: usize,
+}
+
+impl GlobalRegistration {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let reference = read_file_to_point_cloud(&args.reference)
+            .expect("Failed to read reference point cloud");
+        Box::new(GlobalRegistration {
+            reference,
+            delta: args.delta,
+            num_samples: args.num_samples,
+        })
+    }
+}
+
+impl Subcommand for GlobalRegistration {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let (extrinsic, inlier_ratio) = match four_pcs(
+                        &self.reference,
+                        &pc,
+                        self.delta,
+                        self.num_samples,
+                        &mut rand::thread_rng(),
+                    ) {
+                        Some(result) => result,
+                        None => {
+                            println!("No congruent sets found, skipping point cloud {}", i);
+                            continue;
+                        }
+                    };
+
+                    let mut metrics = Metrics::new();
+                    for (row, values) in extrinsic.iter().take(3).enumerate() {
+                        for (col, value) in values.iter().enumerate() {
+                            metrics.insert(
+                                format!("transform_{}{}", row, col),
+                                format!("{:.5}", value),
+                            );
+                        }
+                    }
+                    metrics.insert("inlier_ratio".to_string(), format!("{:.5}", inlier_ratio));
+                    channel.send(PipelineMessage::Metrics(metrics));
+                    channel.send(PipelineMessage::IndexedPointCloud(
+                        transform_cloud(&pc, &extrinsic),
+                        i,
+                    ));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    codec::gpcc::GpccCodec,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Decompresses the G-PCC bitstreams from the stream with the MPEG G-PCC reference decoder.
+#[derive(Parser)]
+pub struct Args {
+    /// path of the tmc3 binary
+    #[clap(long, default_value = "tmc3")]
+    tmc3: PathBuf,
+}
+
+pub struct GpccDecoder {
+    codec: GpccCodec,
+}
+
+impl GpccDecoder {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(GpccDecoder {
+            codec: GpccCodec::new(args.tmc3, None),
+        })
+    }
+}
+
+impl Subcommand for GpccDecoder {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::CompressedStream(path, i) => {
+                    let pc = self
+                        .codec
+                        .decode(&path)
+                        .unwrap_or_else(|e| panic!("Failed to decode frame {}: {:?}", i, e));
+                    channel.send(PipelineMessage::IndexedPointCloud(pc, i));
+                }
+                PipelineMessage::IndexedPointCloud(_, _)
+                | PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    codec::gpcc::GpccCodec,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Compresses each pointcloud from the stream with the MPEG G-PCC reference encoder.
+#[derive(Parser)]
+pub struct Args {
+    /// directory to write the bitstreams to
+    #[clap(short, long)]
+    output_dir: PathBuf,
+
+    /// path of the tmc3 binary
+    #[clap(long, default_value = "tmc3")]
+    tmc3: PathBuf,
+
+    /// tmc3 configuration file, without which only the geometry is coded
+    #[clap(short, long)]
+    config: Option<PathBuf>,
+}
+
+pub struct GpccEncoder {
+    codec: GpccCodec,
+    output_dir: PathBuf,
+}
+
+impl GpccEncoder {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        std::fs::create_dir_all(&args.output_dir).expect("Failed to create output directory");
+        Box::new(GpccEncoder {
+            codec: GpccCodec::new(args.tmc3, args.config),
+            output_dir: args.output_dir,
+        })
+    }
+}
+
+impl Subcommand for GpccEncoder {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let path = self.output_dir.join(format!("{:05}.bin", i));
+                    self.codec
+                        .encode(&pc, &path)
+                        .unwrap_or_else(|e| panic!("Failed to encode frame {}: {:?}", i, e));
+                    channel.send(PipelineMessage::CompressedStream(path, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use clap::Parser;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::grpc::{to_frame, FrameServer};
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+
+use super::Subcommand;
+
+/// How long the end of the stream waits for the clients to receive the last frames.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Streams the point clouds from the stream to gRPC clients of the PointCloudService defined in
+/// proto/pointcloud.proto. Normals are not streamed.
+#[derive(Parser)]
+pub struct Args {
+    /// port to serve on
+    #[clap(short, long, default_value_t = 50051)]
+    port: u16,
+
+    /// number of clients to wait for before streaming the first frame
+    #[clap(short, long, default_value_t = 0)]
+    clients: usize,
+}
+
+pub struct GrpcServe {
+    server: FrameServer,
+    clients: usize,
+}
+
+impl GrpcServe {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let server = FrameServer::spawn(("0.0.0.0", args.port)).expect("Failed to start server");
+        Box::from(GrpcServe {
+            server,
+            clients: args.clients,
+        })
+    }
+
+    fn send(&mut self, pc: &PointCloud<PointXyzRgba>, i: u32) {
+        while self.server.client_count() < self.clients {
+            thread::sleep(Duration::from_millis(100));
+        }
+        // later frames are streamed to whoever is connected
+        self.clients = 0;
+        self.server.send(to_frame(pc, i));
+    }
+
+    fn end(&self) {
+        self.server.end();
+        let start = Instant::now();
+        while self.server.client_count() > 0 && start.elapsed() < DRAIN_TIMEOUT {
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+impl Subcommand for GrpcServe {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match &message {
+                PipelineMessage::IndexedPointCloud(pc, i)
+                | PipelineMessage::IndexedPointCloudLabel(pc, _, i) => self.send(pc, *i),
+                PipelineMessage::IndexedPointCloudNormal(pc, i)
+                | PipelineMessage::IndexedPointCloudFpfh(pc, _, i) => {
+                    let points = pc
+                        .points
+                        .iter()
+                        .map(|p| PointXyzRgba::from_xyz_rgba(p.xyz(), p.rgba()))
+                        .collect();
+                    self.send(&PointCloud::from_points(points), *i);
+                }
+                PipelineMessage::End => self.end(),
+                PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+            }
+            channel.send(message);
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    formats::{pointxyzrgba::PointXyzRgba, PointCloud},
+    methods::{
+        fusion::{transform_cloud, write_extrinsic},
+        icp::{Icp, Weighting},
+    },
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+    utils::read_file_to_point_cloud,
+};
+
+use super::Subcommand;
+
+/// Registers each pointcloud from the stream to a reference pointcloud with point-to-point
+/// iterative closest point. Outputs the row-major rigid transform as metrics, followed by the
+/// aligned pointcloud, and writes it as json with --output.
+#[derive(Parser)]
+pub struct Args {
+    /// point cloud file to align to
+    #[clap(short, long)]
+    reference: PathBuf,
+
+    #[clap(short, long, default_value_t = 50)]
+    max_iterations: usize,
+
+    /// fraction of the closest correspondences kept at every iteration, below 1 for clouds that
+    /// only partially overlap
+    #[clap(short, long, default_value_t = 1.0)]
+    overlap_ratio: f32,
+
+    /// weight of the color distance, with colors in [0, 1], added to the geometric distance of a
+    /// correspondence, for textured but geometrically flat scenes
+    #[clap(short, long, default_value_t = 0.0)]
+    color_weight: f32,
+
+    /// weighting of the correspondences by their distance, downweighting the far ones, likely
+    /// outliers
+    #[clap(short, long, value_enum, default_value_t = Weighting::None)]
+    weighting: Weighting,
+
+    /// distance from which huber and geman_mcclure weighting downweight a correspondence
+    #[clap(long, default_value_t = 0.05)]
+    weighting_delta: f32,
+
+    /// register voxel grid samples with 8 and 4 times the leaf size first, for clouds far apart
+    #[clap(long)]
+    multi_scale: bool,
+
+    /// leaf size of the voxel grid samples of --multi-scale
+    #[clap(long, default_value_t = 0.01)]
+    leaf_size: f32,
+
+    /// json file to write the transform to, as read by apply-transform, overwritten by every
+    /// pointcloud of the stream
+    #[clap(long)]
+    output: Option<PathBuf>,
+}
+
+pub struct IcpRegistration {
+    reference: PointCloud<PointXyzRgba>,
+    icp: Icp,
+    /// leaf size of multi-scale registration
+    multi_scale: Option<f32>,
+    output: Option<PathBuf>,
+}
+
+impl IcpRegistration {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let reference = read_file_to_point_cloud(&args.reference)
+            .expect("Failed to read reference point cloud");
+        Box::new(IcpRegistration {
+            reference,
+            icp: Icp {
+                max_iterations: args.max_iterations,
+                overlap_ratio: args.overlap_ratio,
+                color_weight: args.color_weight,
+                weighting: args.weighting,
+                weighting_delta: args.weighting_delta,
+            },
+            multi_scale: args.multi_scale.then_some(args.leaf_size),
+            output: args.output,
+        })
+    }
+}
+
+impl Subcommand for IcpRegistration {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let extrinsic = match self.multi_scale {
+                        Some(leaf_size) => {
+                            self.icp
+                                .register_multi_scale(&self.reference, &pc, leaf_size)
+                        }
+                        None => self.icp.register(&self.reference, &pc),
+                    };
+                    if let Some(output) = &self.output {
+                        write_extrinsic(&extrinsic, output)
+                            .expect("Should be able to write the transform");
+                    }
+
+                    let mut metrics = Metrics::new();
+                    for (row, values) in extrinsic.iter().take(3).enumerate() {
+                        for (col, value) in values.iter().enumerate() {
+                            metrics.insert(
+                                format!("transform_{}{}", row, col),
+                                format!("{:.5}", value),
+                            );
+                        }
+                    }
+                    channel.send(PipelineMessage::Metrics(metrics));
+                    channel.send(PipelineMessage::IndexedPointCloud(
+                        transform_cloud(&pc, &extrinsic),
+                        i,
+                    ));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pipeline::subcommands::apply_transform::TransformApplier;
+
+    /// Runs a subcommand on one pointcloud and returns the pointcloud it outputs.
+    fn run(subcommand: &mut Box<dyn Subcommand>, pc: PointCloud<PointXyzRgba>) -> Vec<[f32; 3]> {
+        let (progress_tx, _progress_rx) = crossbeam_channel::unbounded();
+        let mut channel = Channel::new(progress_tx);
+        let rx = channel.subscribe();
+        subcommand.handle(vec![PipelineMessage::IndexedPointCloud(pc, 0)], &channel);
+        rx.try_iter()
+            .find_map(|message| match message {
+                PipelineMessage::IndexedPointCloud(pc, _) => {
+                    Some(pc.points.iter().map(|p| [p.x, p.y, p.z]).collect())
+                }
+                _ => None,
+            })
+            .expect("Expecting a pointcloud")
+    }
+
+    fn assert_close(a: &[[f32; 3]], b: &[[f32; 3]]) {
+        assert_eq!(a.len(), b.len());
+        for (p, q) in a.iter().zip(b) {
+            for (u, v) in p.iter().zip(q) {
+                assert!((u - v).abs() < 1e-3, "{:?} {:?}", p, q);
+            }
+        }
+    }
+
+    #[test]
+    fn test_output_applies_as_aligned() {
+        let reference = "./test_files/ply_ascii/longdress_vox10_1213_short.ply";
+        let source = read_file_to_point_cloud(&PathBuf::from(reference)).unwrap();
+        let source = PointCloud::from_points(
+            source
+                .points
+                .iter()
+                .map(|p| PointXyzRgba {
+                    x: p.x + 0.3,
+                    y: p.y - 0.2,
+                    z: p.z + 0.1,
+                    ..*p
+                })
+                .collect(),
+        );
+        let output = std::env::temp_dir().join("vivotk_test_icp_output.json");
+        let output = output.to_str().unwrap().to_string();
+
+        let args = ["icp", "--reference", reference, "--output", &output];
+        let mut icp = IcpRegistration::from_args(args.map(String::from).to_vec());
+        let aligned = run(&mut icp, source.clone());
+
+        let mut apply = TransformApplier::from_args(vec!["apply-transform".into(), output.clone()]);
+        let applied = run(&mut apply, source.clone());
+        assert_close(&applied, &aligned);
+
+        let mut invert = TransformApplier::from_args(
+            ["apply-transform", &output, "--invert"]
+                .map(String::from)
+                .to_vec(),
+        );
+        let aligned = PointCloud::from_points(
+            aligned
+                .iter()
+                .map(|&[x, y, z]| PointXyzRgba {
+                    x,
+                    y,
+                    z,
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                })
+                .collect(),
+        );
+        let restored = run(&mut invert, aligned);
+        std::fs::remove_file(&output).unwrap();
+        let source: Vec<[f32; 3]> = source.points.iter().map(|p| [p.x, p.y, p.z]).collect();
+        assert_close(&restored, &source);
+    }
+}
@@ -0,0 +1,54 @@
+use clap::Parser;
+use std::ffi::OsString;
+
+use super::Subcommand;
+use crate::formats::npz::{read_npz, NpzPointCloud};
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+use crate::utils::find_all_files;
+
+/// Reads NumPy .npz files with an xyz array and optional rgb and normals arrays, the inverse of
+/// export-npz. Point clouds with normals are read as with read --normals.
+#[derive(Parser)]
+pub struct Args {
+    /// Files or directories of npz files, read in lexicographical order
+    files: Vec<OsString>,
+}
+
+pub struct ImportNpz {
+    args: Args,
+}
+
+impl ImportNpz {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::from(ImportNpz {
+            args: Args::parse_from(args),
+        })
+    }
+}
+
+impl Subcommand for ImportNpz {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        if messages.is_empty() {
+            let mut files = find_all_files(&self.args.files);
+            files.retain(|file| file.extension().and_then(|ext| ext.to_str()) == Some("npz"));
+            files.sort();
+            for (i, file) in files.iter().enumerate() {
+                match read_npz(file) {
+                    Ok(NpzPointCloud::Plain(pc)) => {
+                        channel.send(PipelineMessage::IndexedPointCloud(pc, i as u32));
+                    }
+                    Ok(NpzPointCloud::Normal(pc)) => {
+                        channel.send(PipelineMessage::IndexedPointCloudNormal(pc, i as u32));
+                    }
+                    Err(e) => println!("Failed to read {:?}\n{e}", file),
+                }
+            }
+            channel.send(PipelineMessage::End);
+        } else {
+            for message in messages {
+                channel.send(message);
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use clap::Parser;
+
+use crate::{
+    methods::labels::LabelPalette,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Colors a labelled pointcloud from the stream with a distinct color per label.
+#[derive(Parser)]
+pub struct Args {
+    /// seed for the colors of the labels beyond the 20 palette colors
+    #[clap(short, long, default_value_t = 0)]
+    seed: u64,
+}
+
+pub struct LabelColorizer {
+    palette: LabelPalette,
+}
+
+impl LabelColorizer {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(LabelColorizer {
+            palette: LabelPalette::new(args.seed),
+        })
+    }
+}
+
+impl Subcommand for LabelColorizer {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloudLabel(mut pc, labels, i) => {
+                    self.palette.colorize(&mut pc, &labels);
+                    channel.send(PipelineMessage::IndexedPointCloud(pc, i));
+                }
+                PipelineMessage::IndexedPointCloud(_, i) => {
+                    println!(
+                        "label-to-color requires labelled point clouds, skipping point cloud {}",
+                        i
+                    );
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
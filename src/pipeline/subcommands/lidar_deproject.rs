@@ -0,0 +1,115 @@
+use clap::Parser;
+use image::DynamicImage;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use super::Subcommand;
+use crate::methods::scan::deproject_range_image;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+use crate::utils::find_all_files;
+
+/// Converts range images of a spinning LiDAR, one row per beam, to point clouds.
+/// 8 and 16 bit grayscale images are scaled by --range-scale, 32 bit float images (tiff, exr)
+/// are ranges in meters. A zero range is no return.
+#[derive(Parser)]
+pub struct Args {
+    /// Range images or directories of range images, read in lexicographical order
+    files: Vec<OsString>,
+
+    /// text file with the elevation of each beam in degrees, from the first row to the last
+    #[clap(short, long)]
+    calibration: PathBuf,
+
+    /// number of beams, checked against the rows of the images
+    #[clap(short, long)]
+    num_beams: Option<usize>,
+
+    /// horizontal field of view spanned by the columns, in degrees
+    #[clap(long, default_value_t = 360.0)]
+    hfov_deg: f32,
+
+    /// meters per unit of an integer range image
+    #[clap(long, default_value_t = 0.001)]
+    range_scale: f32,
+}
+
+pub struct DeprojectLidar {
+    args: Args,
+}
+
+impl DeprojectLidar {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::from(DeprojectLidar {
+            args: Args::parse_from(args),
+        })
+    }
+
+    fn read_ranges(&self, path: &Path) -> Result<(Vec<f32>, u32), String> {
+        let image = image::open(path).map_err(|e| e.to_string())?;
+        let rows = image.height();
+        let scale = self.args.range_scale;
+        let ranges = match image {
+            DynamicImage::ImageLuma8(image) => image
+                .into_raw()
+                .into_iter()
+                .map(|r| r as f32 * scale)
+                .collect(),
+            DynamicImage::ImageLuma16(image) => image
+                .into_raw()
+                .into_iter()
+                .map(|r| r as f32 * scale)
+                .collect(),
+            DynamicImage::ImageRgb32F(image) => image.pixels().map(|p| p.0[0]).collect(),
+            DynamicImage::ImageRgba32F(image) => image.pixels().map(|p| p.0[0]).collect(),
+            _ => return Err("Expecting a grayscale or float range image".to_string()),
+        };
+        Ok((ranges, rows))
+    }
+}
+
+fn read_elevations(path: &Path) -> Vec<f32> {
+    std::fs::read_to_string(path)
+        .expect("Failed to read calibration file")
+        .split_whitespace()
+        .map(|angle| angle.parse().expect("Expecting elevations in degrees"))
+        .collect()
+}
+
+impl Subcommand for DeprojectLidar {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        if messages.is_empty() {
+            let elevations = read_elevations(&self.args.calibration);
+            if let Some(num_beams) = self.args.num_beams {
+                assert_eq!(
+                    elevations.len(),
+                    num_beams,
+                    "Expecting one elevation per beam in the calibration file"
+                );
+            }
+
+            let mut files = find_all_files(&self.args.files);
+            files.sort();
+            for (i, file) in files.iter().enumerate() {
+                match self.read_ranges(file) {
+                    Ok((ranges, rows)) if rows as usize == elevations.len() => {
+                        let pc = deproject_range_image(&ranges, &elevations, self.args.hfov_deg);
+                        channel.send(PipelineMessage::IndexedPointCloud(pc, i as u32));
+                    }
+                    Ok((_, rows)) => println!(
+                        "Failed to read {:?}\nExpecting {} rows, got {}",
+                        file,
+                        elevations.len(),
+                        rows
+                    ),
+                    Err(e) => println!("Failed to read {:?}\n{e}", file),
+                }
+            }
+            channel.send(PipelineMessage::End);
+        } else {
+            for message in messages {
+                channel.send(message);
+            }
+        }
+    }
+}
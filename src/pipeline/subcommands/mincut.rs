@@ -0,0 +1,103 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    formats::PointCloud,
+    methods::mincut::{min_cut_segment, nearest_points, read_seeds},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Segments a pointcloud from the stream into foreground and background with a minimum cut of
+/// its k nearest neighbour graph, given seed points of each.
+/// Outputs the pointcloud labelled 1 for the foreground and 0 for the background,
+/// or the two parts as separate pointclouds with --split.
+#[derive(Parser)]
+pub struct Args {
+    /// json array of [x, y, z] foreground seeds, each seed is the point nearest to it
+    #[clap(long)]
+    foreground_seeds: PathBuf,
+
+    /// json array of [x, y, z] background seeds, each seed is the point nearest to it
+    #[clap(long)]
+    background_seeds: PathBuf,
+
+    #[clap(short, long, default_value_t = 10)]
+    knn: usize,
+
+    /// scale of the gaussian weight exp(-d^2 / (2 sigma^2)) of an edge of length d
+    #[clap(short, long, default_value_t = 0.05)]
+    sigma: f32,
+
+    /// output the foreground with index 2i and the background with index 2i + 1 instead of the
+    /// labelled pointcloud of index i
+    #[clap(long)]
+    split: bool,
+}
+
+pub struct MinCutSegmenter {
+    args: Args,
+    foreground_seeds: Vec<[f32; 3]>,
+    background_seeds: Vec<[f32; 3]>,
+}
+
+impl MinCutSegmenter {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let foreground_seeds =
+            read_seeds(&args.foreground_seeds).expect("Failed to read foreground seeds");
+        let background_seeds =
+            read_seeds(&args.background_seeds).expect("Failed to read background seeds");
+        Box::new(MinCutSegmenter {
+            args,
+            foreground_seeds,
+            background_seeds,
+        })
+    }
+}
+
+impl Subcommand for MinCutSegmenter {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let foreground = nearest_points(&pc, &self.foreground_seeds);
+                    let background = nearest_points(&pc, &self.background_seeds);
+                    let is_foreground = min_cut_segment(
+                        &pc,
+                        &foreground,
+                        &background,
+                        self.args.knn,
+                        self.args.sigma,
+                    );
+
+                    if self.args.split {
+                        let (foreground, background): (Vec<_>, Vec<_>) =
+                            pc.points.iter().zip(&is_foreground).partition(|&(_, &f)| f);
+                        for (part, index) in [(foreground, 2 * i), (background, 2 * i + 1)] {
+                            let points = part.into_iter().map(|(p, _)| *p).collect();
+                            channel.send(PipelineMessage::IndexedPointCloud(
+                                PointCloud::from_points(points),
+                                index,
+                            ));
+                        }
+                    } else {
+                        let labels = is_foreground.iter().map(|&f| f as u32).collect();
+                        channel.send(PipelineMessage::IndexedPointCloudLabel(pc, labels, i));
+                    }
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -1,21 +1,197 @@
+pub mod aggregate_metrics;
+pub mod alpha_shape;
+pub mod apply_transform;
+#[cfg(feature = "io")]
+pub mod attribute_histogram;
+pub mod auto_scale;
+pub mod background_subtract;
+#[cfg(feature = "io")]
+pub mod bev_image;
+pub mod cloud_compare;
+pub mod color_psnr;
+pub mod colorize_height;
+pub mod completeness;
+pub mod compute_fpfh;
+#[cfg(feature = "gpu")]
+pub mod compute_normals_gpu;
+pub mod confusion_matrix;
 pub mod convert;
+pub mod convex_hull;
+pub mod crop_aabb;
+pub mod crop_sphere;
+pub mod curvature;
 pub mod dash;
+pub mod delta_decode;
+pub mod delta_encode;
+pub mod descriptor_match;
 pub mod downsample;
+pub mod draco_compress;
+pub mod draco_decompress;
+pub mod dump_delta;
+pub mod estimate_transform;
+#[cfg(feature = "hdf5")]
+pub mod export_hdf5;
+#[cfg(feature = "npz")]
+pub mod export_npz;
+pub mod fit_cylinder;
+pub mod fps;
+pub mod fps_resample;
+pub mod global_register;
+pub mod gpcc_decode;
+pub mod gpcc_encode;
+#[cfg(feature = "grpc")]
+pub mod grpc_serve;
+pub mod icp;
+#[cfg(feature = "npz")]
+pub mod import_npz;
 pub mod info;
+pub mod label_to_color;
+#[cfg(feature = "io")]
+pub mod lidar_deproject;
 pub mod metrics;
+pub mod mincut;
+pub mod motion_compensate;
+pub mod multiview_fuse;
+pub mod ndt;
+pub mod noise_model;
+pub mod normal_consistency;
+pub mod normal_estimation;
+pub mod normal_flip;
+pub mod occupancy_grid;
+pub mod octree_decode;
+pub mod octree_encode;
+pub mod paint_cloud;
+pub mod pairwise_metrics;
+pub mod plane_project;
+pub mod poisson_sample;
+pub mod pose_graph_optimize;
 pub mod read;
+pub mod registration_chain;
 pub mod render;
+pub mod render_sequence;
+pub mod repair;
+#[cfg(feature = "ros2")]
+pub mod ros2_publish;
+#[cfg(feature = "rosbag")]
+pub mod rosbag_read;
+pub mod sdf_sample;
+pub mod semantic_color;
+pub mod simulate_scan;
+pub mod skeleton;
+pub mod super_resolution;
+pub mod surface_area;
+pub mod surface_sample;
+pub mod symmetry_detect;
+pub mod timestamp_filter;
+pub mod track_objects;
+pub mod triangulate;
+pub mod udp_source;
+pub mod uniformity;
 pub mod upsample;
+pub mod validate;
+pub mod visibility;
+pub mod voxel_downsample;
+pub mod voxel_to_cloud;
+pub mod vpcc_decode;
+pub mod vpcc_encode;
 pub mod write;
 
+pub use aggregate_metrics::MetricsAggregate;
+pub use alpha_shape::AlphaShape;
+pub use apply_transform::TransformApplier;
+#[cfg(feature = "io")]
+pub use attribute_histogram::AttributeHistogram;
+pub use auto_scale::AutoScaler;
+pub use background_subtract::BackgroundSubtract;
+#[cfg(feature = "io")]
+pub use bev_image::BevImage;
+pub use cloud_compare::CloudCompare;
+pub use color_psnr::ColorPsnrCalculator;
+pub use colorize_height::HeightColorizer;
+pub use completeness::CompletenessCalculator;
+pub use compute_fpfh::FpfhCalculator;
+#[cfg(feature = "gpu")]
+pub use compute_normals_gpu::GpuNormalEstimation;
+pub use confusion_matrix::ConfusionMatrixCalculator;
 pub use convert::Convert;
+pub use convex_hull::HullProjector;
+pub use crop_aabb::AabbCropper;
+pub use crop_sphere::SphereCropper;
+pub use curvature::Curvature;
 pub use dash::Dash;
+pub use delta_decode::DeltaDecode;
+pub use delta_encode::DeltaEncode;
+pub use descriptor_match::DescriptorMatcher;
 pub use downsample::Downsampler;
+pub use draco_compress::DracoCompress;
+pub use draco_decompress::DracoDecompress;
+pub use dump_delta::DumpDelta;
+pub use estimate_transform::TransformEstimator;
+#[cfg(feature = "hdf5")]
+pub use export_hdf5::ExportHdf5;
+#[cfg(feature = "npz")]
+pub use export_npz::ExportNpz;
+pub use fit_cylinder::CylinderFitter;
+pub use fps::FarthestPointSampler;
+pub use fps_resample::FpsResampler;
+pub use global_register::GlobalRegistration;
+pub use gpcc_decode::GpccDecoder;
+pub use gpcc_encode::GpccEncoder;
+#[cfg(feature = "grpc")]
+pub use grpc_serve::GrpcServe;
+pub use icp::IcpRegistration;
+#[cfg(feature = "npz")]
+pub use import_npz::ImportNpz;
 pub use info::Info;
+pub use label_to_color::LabelColorizer;
+#[cfg(feature = "io")]
+pub use lidar_deproject::DeprojectLidar;
 pub use metrics::MetricsCalculator;
+pub use mincut::MinCutSegmenter;
+pub use motion_compensate::MotionCompensate;
+pub use multiview_fuse::MultiviewFuser;
+pub use ndt::NdtRegistration;
+pub use noise_model::NoiseSimulator;
+pub use normal_consistency::NormalConsistency;
+pub use normal_estimation::NormalEstimation;
+pub use normal_flip::NormalFlipper;
+pub use occupancy_grid::OccupancyGrid;
+pub use octree_decode::OctreeDecode;
+pub use octree_encode::OctreeEncode;
+pub use paint_cloud::PaintCloud;
+pub use pairwise_metrics::PairwiseMetrics;
+pub use plane_project::PlaneProjector;
+pub use poisson_sample::RandomSampler;
+pub use pose_graph_optimize::PoseGraphOptimizer;
 pub use read::Read;
+pub use registration_chain::ChainRegistration;
 pub use render::Render;
+pub use render_sequence::RenderSequence;
+pub use repair::Repairer;
+#[cfg(feature = "ros2")]
+pub use ros2_publish::Ros2Publish;
+#[cfg(feature = "rosbag")]
+pub use rosbag_read::RosbagRead;
+pub use sdf_sample::SdfSampler;
+pub use semantic_color::SemanticColorizer;
+pub use simulate_scan::ScanSimulator;
+pub use skeleton::Skeleton;
+pub use super_resolution::SuperResolution;
+pub use surface_area::SurfaceArea;
+pub use surface_sample::SurfaceSampler;
+pub use symmetry_detect::SymmetryDetector;
+pub use timestamp_filter::TimestampFilter;
+pub use track_objects::ObjectTracker;
+pub use triangulate::Triangulator;
+pub use udp_source::UdpSource;
+pub use uniformity::UniformityCalculator;
 pub use upsample::Upsampler;
+pub use validate::Validator;
+pub use visibility::VisibilityComputer;
+pub use voxel_downsample::VoxelDownsampler;
+pub use voxel_to_cloud::VoxelToCloud;
+pub use vpcc_decode::VpccDecoder;
+pub use vpcc_encode::VpccEncoder;
 pub use write::Write;
 
 use super::{channel::Channel, PipelineMessage};
@@ -0,0 +1,63 @@
+use cgmath::Vector3;
+use clap::Parser;
+
+use crate::{
+    codec::delta::{motion_vectors, predict_frame, prediction_residual},
+    formats::{pointxyzrgba::PointXyzRgba, PointCloud},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Predicts each pointcloud from the stream by moving the points of the previous one by their
+/// motion vectors, the displacements of the points at the same index between the two frames
+/// before, and outputs the residual of the prediction minus the actual positions.
+#[derive(Parser)]
+pub struct Args {}
+
+pub struct MotionCompensate {
+    previous: Option<PointCloud<PointXyzRgba>>,
+    motion: Vec<Vector3<f32>>,
+}
+
+impl MotionCompensate {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let _args: Args = Args::parse_from(args);
+        Box::new(MotionCompensate {
+            previous: None,
+            motion: vec![],
+        })
+    }
+}
+
+impl Subcommand for MotionCompensate {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    // the first frame is predicted by an empty frame, the second by the first
+                    let predicted = match &self.previous {
+                        Some(previous) => predict_frame(previous, &self.motion),
+                        None => PointCloud::from_points(vec![]),
+                    };
+                    let residual = prediction_residual(&predicted, &pc);
+                    if let Some(previous) = &self.previous {
+                        self.motion = motion_vectors(previous, &pc);
+                    }
+                    self.previous = Some(pc);
+                    channel.send(PipelineMessage::IndexedPointCloud(residual, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    methods::fusion::{fuse_views, read_extrinsics, Extrinsic},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Fuses the point clouds of calibrated cameras into a single pointcloud.
+/// Each input stream is a camera, the i-th input stream uses the i-th extrinsic.
+#[derive(Parser)]
+pub struct Args {
+    /// json array of the row-major 4x4 extrinsic matrix of each input stream
+    #[clap(short, long)]
+    extrinsics: PathBuf,
+
+    /// points closer than this distance to a point of an earlier stream are dropped
+    #[clap(short, long, default_value_t = 0.0)]
+    dedup_distance: f64,
+}
+
+pub struct MultiviewFuser {
+    extrinsics: Vec<Extrinsic>,
+    dedup_distance: f32,
+}
+
+impl MultiviewFuser {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let extrinsics = read_extrinsics(&args.extrinsics).expect("Failed to read extrinsics");
+        Box::new(MultiviewFuser {
+            extrinsics,
+            dedup_distance: args.dedup_distance as f32,
+        })
+    }
+}
+
+impl Subcommand for MultiviewFuser {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        if messages
+            .iter()
+            .any(|message| matches!(message, PipelineMessage::End))
+        {
+            channel.send(PipelineMessage::End);
+            return;
+        }
+
+        assert_eq!(
+            messages.len(),
+            self.extrinsics.len(),
+            "Expecting one input stream per extrinsic for multiview-fuse"
+        );
+
+        let mut frame_index = None;
+        let mut views = Vec::with_capacity(messages.len());
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    // the streams are read in lockstep, so all views share the frame index
+                    frame_index.get_or_insert(i);
+                    views.push(pc);
+                }
+                _ => return,
+            }
+        }
+
+        let fused = fuse_views(&views, &self.extrinsics, self.dedup_distance);
+        channel.send(PipelineMessage::IndexedPointCloud(
+            fused,
+            frame_index.unwrap_or(0),
+        ));
+    }
+}
@@ -0,0 +1,82 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    methods::{fusion::transform_cloud, ndt::NdtGrid},
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+    utils::read_file_to_point_cloud,
+};
+
+use super::Subcommand;
+
+/// Registers each pointcloud from the stream to a reference pointcloud with the normal
+/// distributions transform. Outputs the row-major rigid transform as metrics, followed by the
+/// aligned pointcloud.
+#[derive(Parser)]
+pub struct Args {
+    /// point cloud file to align to
+    #[clap(short, long)]
+    reference: PathBuf,
+
+    /// size of the voxels a gaussian is fitted to, larger voxels converge from farther away
+    #[clap(short, long, default_value_t = 0.1)]
+    voxel_size: f32,
+
+    #[clap(short, long, default_value_t = 30)]
+    max_iterations: usize,
+}
+
+pub struct NdtRegistration {
+    grid: NdtGrid,
+    max_iterations: usize,
+}
+
+impl NdtRegistration {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let reference = read_file_to_point_cloud(&args.reference)
+            .expect("Failed to read reference point cloud");
+        Box::new(NdtRegistration {
+            grid: NdtGrid::new(&reference, args.voxel_size),
+            max_iterations: args.max_iterations,
+        })
+    }
+}
+
+impl Subcommand for NdtRegistration {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let extrinsic = self.grid.register(&pc, self.max_iterations);
+
+                    let mut metrics = Metrics::new();
+                    for (row, values) in extrinsic.iter().take(3).enumerate() {
+                        for (col, value) in values.iter().enumerate() {
+                            metrics.insert(
+                                format!("transform_{}{}", row, col),
+                                format!("{:.5}", value),
+                            );
+                        }
+                    }
+                    channel.send(PipelineMessage::Metrics(metrics));
+                    channel.send(PipelineMessage::IndexedPointCloud(
+                        transform_cloud(&pc, &extrinsic),
+                        i,
+                    ));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
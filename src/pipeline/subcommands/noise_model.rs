@@ -0,0 +1,70 @@
+use clap::Parser;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    methods::noise::NoiseModel,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Simulates LiDAR noise on a pointcloud from the stream, with the sensor at the origin.
+#[derive(Parser)]
+pub struct Args {
+    /// standard deviation of the gaussian noise along the ray from the origin
+    #[clap(long, default_value_t = 0.0)]
+    range_noise_std: f32,
+
+    /// standard deviation of the gaussian noise on the azimuth and elevation, in radians
+    #[clap(long, default_value_t = 0.0)]
+    angular_noise_std: f32,
+
+    /// fraction of points removed at random to simulate occlusions
+    #[clap(long, default_value_t = 0.0)]
+    dropout_rate: f32,
+
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+}
+
+pub struct NoiseSimulator {
+    model: NoiseModel,
+    rng: StdRng,
+}
+
+impl NoiseSimulator {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(NoiseSimulator {
+            model: NoiseModel {
+                range_std: args.range_noise_std,
+                angular_std: args.angular_noise_std,
+                dropout_rate: args.dropout_rate,
+            },
+            rng: StdRng::seed_from_u64(args.seed),
+        })
+    }
+}
+
+impl Subcommand for NoiseSimulator {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let noisy_pc = self.model.apply(&pc, &mut self.rng);
+                    channel.send(PipelineMessage::IndexedPointCloud(noisy_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
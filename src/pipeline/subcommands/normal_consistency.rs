@@ -0,0 +1,106 @@
+use clap::Parser;
+
+use crate::{
+    formats::{pointxyzrgbanormal::PointXyzRgbaNormal, PointCloud},
+    methods::normals::{neighbour_normal_dots, normal_consistency},
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Measures how well the normals of a pointcloud with normals agree with their neighbours.
+/// Outputs the mean consistency and a histogram of the absolute dot products between
+/// neighbouring normals as metrics, followed by the pointcloud with inconsistent points in red.
+#[derive(Parser)]
+pub struct Args {
+    /// number of neighbours to compare the normal of each point with
+    #[clap(short, long, default_value_t = 10)]
+    knn: usize,
+
+    /// points with a mean absolute dot product below this value are colored red
+    #[clap(short, long, default_value_t = 0.8)]
+    threshold: f32,
+
+    /// number of bins of the histogram over [0, 1]
+    #[clap(short, long, default_value_t = 10)]
+    bins: usize,
+}
+
+pub struct NormalConsistency {
+    args: Args,
+}
+
+impl NormalConsistency {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        assert!(args.bins > 0, "Expecting at least one bin");
+        Box::new(NormalConsistency { args })
+    }
+
+    /// Computes the metrics and colors the inconsistent points of the pointcloud red.
+    fn check(&self, pc: &mut PointCloud<PointXyzRgbaNormal>) -> Metrics {
+        let dots = neighbour_normal_dots(pc, self.args.knn);
+        let consistency = normal_consistency(&dots);
+
+        let bins = self.args.bins;
+        let mut counts = vec![0; bins];
+        for &d in dots.iter().flatten() {
+            counts[((d * bins as f32) as usize).min(bins - 1)] += 1;
+        }
+
+        let mut flagged = 0;
+        for (p, &c) in pc.points.iter_mut().zip(&consistency) {
+            if c < self.args.threshold {
+                p.r = 255;
+                p.g = 0;
+                p.b = 0;
+                flagged += 1;
+            }
+        }
+
+        let mut metrics = Metrics::new();
+        let mean = consistency.iter().sum::<f32>() / consistency.len().max(1) as f32;
+        metrics.insert("consistency_mean".to_string(), format!("{:.5}", mean));
+        metrics.insert("inconsistent_points".to_string(), format!("{}", flagged));
+        for (i, count) in counts.iter().enumerate() {
+            let low = i as f32 / bins as f32;
+            let high = (i + 1) as f32 / bins as f32;
+            metrics.insert(format!("dot_{:.2}_{:.2}", low, high), format!("{}", count));
+        }
+        metrics
+    }
+}
+
+impl Subcommand for NormalConsistency {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloudNormal(mut pc, i) => {
+                    let metrics = self.check(&mut pc);
+                    channel.send(PipelineMessage::Metrics(metrics));
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(pc, i));
+                }
+                PipelineMessage::IndexedPointCloudFpfh(mut pc, descriptors, i) => {
+                    let metrics = self.check(&mut pc);
+                    channel.send(PipelineMessage::Metrics(metrics));
+                    channel.send(PipelineMessage::IndexedPointCloudFpfh(pc, descriptors, i));
+                }
+                PipelineMessage::IndexedPointCloud(_, i) => {
+                    println!(
+                        "normal-consistency requires point clouds with normals, skipping point cloud {}",
+                        i
+                    );
+                }
+                PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
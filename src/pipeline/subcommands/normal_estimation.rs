@@ -0,0 +1,76 @@
+use clap::Parser;
+
+use crate::{
+    formats::{pointxyzrgbanormal::PointXyzRgbaNormal, PointCloud},
+    methods::normals::{adaptive_radii, estimate_normals},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Estimates the normal of every point from the plane fitted to its neighbours within a radius.
+#[derive(Parser)]
+pub struct Args {
+    /// radius of the neighbourhood of every point
+    #[clap(short, long, default_value_t = 0.05, conflicts_with = "adaptive")]
+    radius: f32,
+
+    /// adapt the radius of every point to the local density, as --k-scale times the mean
+    /// distance to its 5 nearest neighbours, instead of using --radius
+    #[clap(long)]
+    adaptive: bool,
+
+    /// multiple of the mean distance to the 5 nearest neighbours used as adaptive radius
+    #[clap(short, long, default_value_t = 2.0)]
+    k_scale: f32,
+}
+
+pub struct NormalEstimation {
+    args: Args,
+}
+
+impl NormalEstimation {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::new(NormalEstimation {
+            args: Args::parse_from(args),
+        })
+    }
+}
+
+impl Subcommand for NormalEstimation {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let radii = if self.args.adaptive {
+                        adaptive_radii(&pc, self.args.k_scale)
+                    } else {
+                        vec![self.args.radius; pc.points.len()]
+                    };
+                    let points = pc
+                        .points
+                        .iter()
+                        .zip(estimate_normals(&pc, &radii))
+                        .map(|(p, normal)| {
+                            PointXyzRgbaNormal::from_xyz_rgba_normal(p.xyz(), p.rgba(), normal)
+                        })
+                        .collect();
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(
+                        PointCloud::from_points(points),
+                        i,
+                    ));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
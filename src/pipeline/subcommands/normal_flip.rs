@@ -0,0 +1,73 @@
+use cgmath::Point3;
+use clap::Parser;
+
+use crate::{
+    methods::normals::{flip_normals_towards, orient_normals, Orientation},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Flips the normals of a pointcloud with normals from the stream to a consistent orientation.
+#[derive(Parser)]
+pub struct Args {
+    /// flip normals to face the viewpoint, given as x,y,z
+    #[clap(
+        long,
+        value_delimiter = ',',
+        num_args = 3,
+        allow_hyphen_values = true,
+        conflicts_with = "orient"
+    )]
+    viewpoint: Option<Vec<f32>>,
+
+    /// flip normals relative to the centroid, used when no viewpoint is given
+    #[clap(long, value_enum, default_value_t = Orientation::Outward)]
+    orient: Orientation,
+}
+
+pub struct NormalFlipper {
+    viewpoint: Option<Point3<f32>>,
+    orient: Orientation,
+}
+
+impl NormalFlipper {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(NormalFlipper {
+            viewpoint: args.viewpoint.map(|v| Point3::new(v[0], v[1], v[2])),
+            orient: args.orient,
+        })
+    }
+}
+
+impl Subcommand for NormalFlipper {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloudNormal(mut pc, i) => {
+                    match self.viewpoint {
+                        Some(viewpoint) => flip_normals_towards(&mut pc, viewpoint),
+                        None => orient_normals(&mut pc, self.orient),
+                    }
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(pc, i));
+                }
+                PipelineMessage::IndexedPointCloud(_, i) => {
+                    println!(
+                        "normal-flip requires point clouds with normals, skipping point cloud {}",
+                        i
+                    );
+                }
+                PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
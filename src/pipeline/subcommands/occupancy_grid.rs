@@ -0,0 +1,78 @@
+use clap::Parser;
+use std::path::Path;
+
+use crate::formats::grid::write_bit_grid;
+use crate::methods::occupancy::occupancy_grid;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+
+use super::Subcommand;
+
+/// Computes a binary occupancy grid over the bounding box of point clouds from the stream.
+/// Each grid is written with one bit per voxel (<index>.occ) with a json header (<index>.json).
+#[derive(Parser)]
+pub struct Args {
+    /// output directory to store the occupancy grids
+    output_dir: String,
+
+    /// number of voxels along each axis
+    #[clap(
+        short,
+        long,
+        default_value_t = 64,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    resolution: usize,
+
+    #[clap(long, default_value_t = 5)]
+    name_length: usize,
+}
+
+pub struct OccupancyGrid {
+    args: Args,
+}
+
+impl OccupancyGrid {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args = Args::parse_from(args);
+        std::fs::create_dir_all(Path::new(&args.output_dir))
+            .expect("Failed to create output directory");
+        Box::from(OccupancyGrid { args })
+    }
+}
+
+impl Subcommand for OccupancyGrid {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match &message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let (header, occupied) = match occupancy_grid(pc, self.args.resolution) {
+                        Some(grid) => grid,
+                        None => {
+                            println!("Skipping empty point cloud {}", i);
+                            channel.send(message);
+                            continue;
+                        }
+                    };
+
+                    let name = format!("{:0width$}", i, width = self.args.name_length);
+                    let output_dir = Path::new(&self.args.output_dir);
+                    write_bit_grid(output_dir.join(format!("{name}.occ")), &occupied)
+                        .expect("Failed to write occupancy grid");
+                    header
+                        .write_to(output_dir.join(format!("{name}.json")))
+                        .expect("Failed to write grid header");
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::End
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+            }
+            channel.send(message);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use clap::Parser;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    codec::octree::encode_octree,
+    formats::{pointxyzrgba::PointXyzRgba, PointCloud},
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Compresses the geometry of each pointcloud from the stream into an arithmetic coded octree,
+/// and outputs the size of the compressed files as metrics.
+#[derive(Parser)]
+pub struct Args {
+    /// directory to write the compressed files to
+    #[clap(short, long)]
+    output_dir: PathBuf,
+
+    /// levels of the octree, from 1 to 21, the positions being quantized to 2^depth voxels
+    /// along each axis
+    #[clap(short, long, default_value_t = 10)]
+    depth: u8,
+}
+
+pub struct OctreeEncode {
+    output_dir: PathBuf,
+    depth: u8,
+}
+
+impl OctreeEncode {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        std::fs::create_dir_all(&args.output_dir).expect("Failed to create output directory");
+        Box::new(OctreeEncode {
+            output_dir: args.output_dir,
+            depth: args.depth,
+        })
+    }
+
+    /// Compresses the pointcloud to `path` and measures the result.
+    fn encode(&self, pc: &PointCloud<PointXyzRgba>, path: &Path) -> Metrics {
+        let bytes = encode_octree(pc, self.depth);
+        std::fs::write(path, &bytes)
+            .unwrap_or_else(|e| panic!("Failed to write {}: {:?}", path.display(), e));
+
+        let mut metrics = Metrics::new();
+        metrics.insert("compressed_bytes".to_string(), format!("{}", bytes.len()));
+        metrics.insert(
+            "bits_per_point".to_string(),
+            format!("{:.5}", (bytes.len() * 8) as f64 / pc.points.len() as f64),
+        );
+        metrics
+    }
+}
+
+impl Subcommand for OctreeEncode {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let path = self.output_dir.join(format!("{:05}.oct", i));
+                    let metrics = self.encode(&pc, &path);
+                    channel.send(PipelineMessage::Metrics(metrics));
+                    channel.send(PipelineMessage::CompressedStream(path, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    formats::{pointxyzrgba::PointXyzRgba, PointCloud},
+    methods::paint::paint_cloud,
+    pipeline::{channel::Channel, PipelineMessage},
+    utils::read_file_to_point_cloud,
+};
+
+use super::Subcommand;
+
+/// Colors each pointcloud from the stream with the colors of the nearest points of a colored
+/// reference pointcloud, such as a depth scan with the scan of a color camera.
+#[derive(Parser)]
+pub struct Args {
+    /// colored point cloud file to take the colors from
+    #[clap(short, long)]
+    reference: PathBuf,
+
+    /// maximum distance to the nearest reference point to take its color
+    #[clap(short, long, default_value_t = 0.01)]
+    search_radius: f64,
+
+    /// color of the points without a reference point within the search radius, given as r,g,b
+    #[clap(long, value_delimiter = ',', num_args = 3, default_value = "0,0,0")]
+    missing_color: Vec<u8>,
+}
+
+pub struct PaintCloud {
+    reference: PointCloud<PointXyzRgba>,
+    search_radius: f64,
+    missing_color: [u8; 3],
+}
+
+impl PaintCloud {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let reference = read_file_to_point_cloud(&args.reference)
+            .expect("Failed to read reference point cloud");
+        let c = &args.missing_color;
+        Box::new(PaintCloud {
+            reference,
+            search_radius: args.search_radius,
+            missing_color: [c[0], c[1], c[2]],
+        })
+    }
+}
+
+impl Subcommand for PaintCloud {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let painted =
+                        paint_cloud(&pc, &self.reference, self.search_radius, self.missing_color);
+                    channel.send(PipelineMessage::IndexedPointCloud(painted, i));
+                }
+                PipelineMessage::IndexedPointCloudLabel(pc, labels, i) => {
+                    let painted =
+                        paint_cloud(&pc, &self.reference, self.search_radius, self.missing_color);
+                    channel.send(PipelineMessage::IndexedPointCloudLabel(painted, labels, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use clap::Parser;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use crate::{
+    metrics::{evaluate_pairs, pair_files, write_pairwise_csv},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Evaluates every distorted frame against the reference frame of the same file name, writing
+/// the point to point (D1) and point to plane (D2) errors and the color PSNR of every frame to a
+/// csv file.
+#[derive(Parser)]
+pub struct Args {
+    /// directory of the reference frames
+    #[clap(long)]
+    reference_dir: PathBuf,
+
+    /// directory of the distorted frames, named like their reference frame
+    #[clap(long)]
+    distorted_dir: PathBuf,
+
+    /// csv file to write the metrics of every frame to
+    #[clap(short, long, default_value = "metrics.csv")]
+    output: PathBuf,
+
+    /// peak value of the coordinates for the geometric PSNR, e.g. 1023 for 10 bit voxelized frames
+    #[clap(long, default_value_t = 1023.0)]
+    peak: f64,
+
+    /// number of nearest neighbours the normals are estimated from for D2
+    #[clap(short, long, default_value_t = 12)]
+    knn: usize,
+
+    /// evaluate the frames in parallel
+    #[clap(long)]
+    parallel: bool,
+}
+
+pub struct PairwiseMetrics {
+    args: Args,
+}
+
+impl PairwiseMetrics {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::new(PairwiseMetrics {
+            args: Args::parse_from(args),
+        })
+    }
+}
+
+impl Subcommand for PairwiseMetrics {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        if messages.is_empty() {
+            let pairs = pair_files(&self.args.reference_dir, &self.args.distorted_dir);
+            let rows = evaluate_pairs(&pairs, self.args.knn, self.args.parallel);
+            File::create(&self.args.output)
+                .and_then(|f| write_pairwise_csv(&rows, self.args.peak, &mut BufWriter::new(f)))
+                .expect("Should be able to write the metrics");
+            println!("Evaluated {} frames", rows.len());
+            channel.send(PipelineMessage::End);
+        } else {
+            for message in messages {
+                channel.send(message);
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use cgmath::Vector3;
+use clap::Parser;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use crate::{
+    methods::plane::Plane,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Projects the points of each pointcloud from the stream orthogonally onto a plane,
+/// the least squares plane of the pointcloud unless a plane is given.
+#[derive(Parser)]
+pub struct Args {
+    /// normal of the plane, the plane holds the points p with normal . p = offset
+    #[clap(long, num_args = 3, value_delimiter = ',', allow_hyphen_values = true)]
+    plane_normal: Option<Vec<f32>>,
+
+    #[clap(
+        long,
+        default_value_t = 0.0,
+        allow_hyphen_values = true,
+        requires = "plane_normal"
+    )]
+    plane_offset: f32,
+
+    /// csv file to write the coordinates of the points on the plane to, as index,u,v rows
+    #[clap(long)]
+    uv_output: Option<PathBuf>,
+}
+
+pub struct PlaneProjector {
+    plane: Option<Plane>,
+    uv_writer: Option<BufWriter<File>>,
+}
+
+impl PlaneProjector {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let plane = args.plane_normal.map(|n| {
+            Plane::new(Vector3::new(n[0], n[1], n[2]), args.plane_offset)
+                .expect("Expecting a non-zero plane normal")
+        });
+        let uv_writer = args.uv_output.map(|path| {
+            let mut writer =
+                BufWriter::new(File::create(path).expect("Failed to create uv output file"));
+            writeln!(writer, "index,u,v").expect("Failed to write uv output");
+            writer
+        });
+        Box::new(PlaneProjector { plane, uv_writer })
+    }
+}
+
+impl Subcommand for PlaneProjector {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let plane = match self.plane.or_else(|| Plane::fit(&pc)) {
+                        Some(plane) => plane,
+                        None => {
+                            println!("Failed to fit a plane to point cloud {}", i);
+                            continue;
+                        }
+                    };
+                    if let Some(writer) = &mut self.uv_writer {
+                        for (u, v) in plane.uv(&pc) {
+                            writeln!(writer, "{},{},{}", i, u, v)
+                                .expect("Failed to write uv output");
+                        }
+                    }
+                    channel.send(PipelineMessage::IndexedPointCloud(plane.project(&pc), i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    if let Some(writer) = &mut self.uv_writer {
+                        writer.flush().expect("Failed to write uv output");
+                    }
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use clap::Parser;
+
+use crate::{
+    methods::sampling::{poisson_disk_sample, uniform_sample},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleMode {
+    /// keep a random ratio of the points
+    Uniform,
+    /// keep points at least min distance apart from each other
+    Poisson,
+}
+
+/// Randomly samples a pointcloud from the stream.
+#[derive(Parser)]
+pub struct Args {
+    #[clap(short, long, value_enum, default_value_t = SampleMode::Uniform)]
+    mode: SampleMode,
+
+    /// ratio of points to keep in uniform mode
+    #[clap(short, long, default_value_t = 0.5)]
+    ratio: f32,
+
+    /// minimum distance between the kept points in poisson mode
+    #[clap(long, default_value_t = 0.01)]
+    min_distance: f32,
+}
+
+pub struct RandomSampler {
+    args: Args,
+}
+
+impl RandomSampler {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(RandomSampler { args })
+    }
+}
+
+impl Subcommand for RandomSampler {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let sampled_pc = match self.args.mode {
+                        SampleMode::Uniform => uniform_sample(&pc, self.args.ratio),
+                        SampleMode::Poisson => poisson_disk_sample(&pc, self.args.min_distance),
+                    };
+                    channel.send(PipelineMessage::IndexedPointCloud(sampled_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(pc, i) => {
+                    let sampled_pc = match self.args.mode {
+                        SampleMode::Uniform => uniform_sample(&pc, self.args.ratio),
+                        SampleMode::Poisson => poisson_disk_sample(&pc, self.args.min_distance),
+                    };
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(sampled_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
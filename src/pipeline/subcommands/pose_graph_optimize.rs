@@ -0,0 +1,125 @@
+use clap::Parser;
+
+use crate::{
+    formats::{pointxyzrgba::PointXyzRgba, PointCloud},
+    methods::{
+        fusion::{compose_extrinsics, invert_rigid_extrinsic, transform_cloud},
+        icp::{Icp, Weighting},
+        pose_graph::{PoseEdge, PoseGraph},
+        registration_chain::RegistrationChain,
+    },
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Builds a map from sequential pointclouds like registration-chain, then closes the loops
+/// between revisited places and optimizes the pose graph to remove the accumulated drift.
+/// Outputs the merged map once the stream ends.
+#[derive(Parser)]
+pub struct Args {
+    #[clap(short, long, default_value_t = 50)]
+    max_iterations: usize,
+
+    /// fraction of the closest correspondences kept at every iteration, below 1 for clouds that
+    /// only partially overlap
+    #[clap(short, long, default_value_t = 1.0)]
+    overlap_ratio: f32,
+
+    /// distance between two poses below which their pointclouds are registered to close a loop
+    #[clap(long, default_value_t = 0.5)]
+    loop_distance: f32,
+
+    /// minimum number of frames between the two pointclouds of a loop
+    #[clap(long, default_value_t = 2)]
+    min_loop_gap: usize,
+
+    /// iterations of gradient descent on the poses
+    #[clap(long, default_value_t = 500)]
+    optimizer_iterations: usize,
+}
+
+pub struct PoseGraphOptimizer {
+    chain: RegistrationChain,
+    loop_distance: f32,
+    min_loop_gap: usize,
+    optimizer_iterations: usize,
+}
+
+impl PoseGraphOptimizer {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(PoseGraphOptimizer {
+            chain: RegistrationChain::new(Icp {
+                max_iterations: args.max_iterations,
+                overlap_ratio: args.overlap_ratio,
+                color_weight: 0.0,
+                weighting: Weighting::None,
+                weighting_delta: 0.0,
+            }),
+            loop_distance: args.loop_distance,
+            min_loop_gap: args.min_loop_gap,
+            optimizer_iterations: args.optimizer_iterations,
+        })
+    }
+
+    /// Optimizes the poses of the chain with a loop closure edge for every revisited place, and
+    /// merges the pointclouds at their optimized poses.
+    fn optimized_map(&self) -> PointCloud<PointXyzRgba> {
+        let clouds = self.chain.clouds();
+        let mut graph = PoseGraph::from_odometry(self.chain.poses().to_vec());
+        for (i, j) in graph.loop_candidates(self.loop_distance, self.min_loop_gap) {
+            // registers cloud j, moved by the odometry, to cloud i
+            let guess =
+                compose_extrinsics(&invert_rigid_extrinsic(&graph.poses[i]), &graph.poses[j]);
+            let correction = self
+                .chain
+                .icp()
+                .register(&clouds[i], &transform_cloud(&clouds[j], &guess));
+            graph.edges.push(PoseEdge {
+                from: i,
+                to: j,
+                measurement: compose_extrinsics(&correction, &guess),
+            });
+        }
+        let loops = graph.edges.len() - clouds.len().saturating_sub(1);
+        let error = graph.error();
+        graph.optimize(self.optimizer_iterations);
+        println!(
+            "pose-graph-optimize: {} loop closures, error {:.6} -> {:.6}",
+            loops,
+            error,
+            graph.error()
+        );
+
+        let points = clouds
+            .iter()
+            .zip(&graph.poses)
+            .flat_map(|(cloud, pose)| transform_cloud(cloud, pose).points)
+            .collect();
+        PointCloud::from_points(points)
+    }
+}
+
+impl Subcommand for PoseGraphOptimizer {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, _) => {
+                    self.chain.push(pc);
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(PipelineMessage::IndexedPointCloud(self.optimized_map(), 0));
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
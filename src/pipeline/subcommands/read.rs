@@ -4,6 +4,7 @@ use std::ffi::OsString;
 use super::Subcommand;
 use crate::pipeline::channel::Channel;
 use crate::pipeline::PipelineMessage;
+use crate::ply::{read_ply_label, read_ply_normal};
 use crate::utils::{find_all_files, read_file_to_point_cloud};
 
 #[derive(clap::ValueEnum, Clone, Copy)]
@@ -28,6 +29,14 @@ pub struct Args {
     #[clap(short, long)]
     /// read previous n files after sorting lexicalgraphically
     num: Option<usize>,
+
+    #[clap(long, conflicts_with = "labels")]
+    /// keep the vertex normals of ply files, other file types are skipped
+    normals: bool,
+
+    #[clap(long)]
+    /// keep the vertex labels of ply files, other file types are skipped
+    labels: bool,
 }
 
 pub struct Read {
@@ -74,6 +83,22 @@ impl Subcommand for Read {
                     }
                 }
 
+                if self.args.normals || self.args.labels {
+                    if file.extension().and_then(|ext| ext.to_str()) != Some("ply") {
+                        continue;
+                    }
+                    if self.args.normals {
+                        if let Some(pc) = read_ply_normal(file) {
+                            channel.send(PipelineMessage::IndexedPointCloudNormal(pc, i as u32));
+                        }
+                    } else if let Some((pc, labels)) = read_ply_label(file) {
+                        channel.send(PipelineMessage::IndexedPointCloudLabel(
+                            pc, labels, i as u32,
+                        ));
+                    }
+                    continue;
+                }
+
                 let point_cloud = read_file_to_point_cloud(file);
                 if let Some(pc) = point_cloud {
                     channel.send(PipelineMessage::IndexedPointCloud(pc, i as u32));
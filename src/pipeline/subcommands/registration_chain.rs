@@ -0,0 +1,83 @@
+use clap::Parser;
+
+use crate::{
+    methods::{
+        icp::{Icp, Weighting},
+        registration_chain::RegistrationChain,
+    },
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Builds a map from sequential pointclouds, registering each pointcloud to the previous one with
+/// iterative closest point and chaining the transforms. Outputs the merged map once the stream
+/// ends.
+#[derive(Parser)]
+pub struct Args {
+    #[clap(short, long, default_value_t = 50)]
+    max_iterations: usize,
+
+    /// fraction of the closest correspondences kept at every iteration, below 1 for clouds that
+    /// only partially overlap
+    #[clap(short, long, default_value_t = 1.0)]
+    overlap_ratio: f32,
+
+    /// register the last pointcloud to the first one and spread the correction along the chain
+    /// if the last pose is within --loop-distance of the first
+    #[clap(long)]
+    loop_closure: bool,
+
+    /// distance between the first and last poses below which a loop is closed
+    #[clap(long, default_value_t = 0.5)]
+    loop_distance: f32,
+}
+
+pub struct ChainRegistration {
+    chain: RegistrationChain,
+    loop_distance: Option<f32>,
+}
+
+impl ChainRegistration {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(ChainRegistration {
+            chain: RegistrationChain::new(Icp {
+                max_iterations: args.max_iterations,
+                overlap_ratio: args.overlap_ratio,
+                color_weight: 0.0,
+                weighting: Weighting::None,
+                weighting_delta: 0.0,
+            }),
+            loop_distance: args.loop_closure.then_some(args.loop_distance),
+        })
+    }
+}
+
+impl Subcommand for ChainRegistration {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, _) => {
+                    self.chain.push(pc);
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    if let Some(loop_distance) = self.loop_distance {
+                        if !self.chain.close_loop(loop_distance) {
+                            println!("No loop closed, the last pose is too far from the first");
+                        }
+                    }
+                    channel.send(PipelineMessage::IndexedPointCloud(self.chain.merged(), 0));
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use super::Subcommand;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+use crate::render::wgpu::{
+    builder::RenderBuilder,
+    camera::Camera,
+    reader::{PointCloudFileReader, RenderReader},
+    renderer::{Playback, Renderer},
+};
+
+/// Plays the ply files of a directory in lexicographical order in a window.
+/// The window and its GPU context are created once, and every frame only uploads its points.
+#[derive(Parser)]
+pub struct Args {
+    /// Directory with the ply files to play
+    dir: PathBuf,
+    /// frames per second, 0 plays the frames as fast as they can be read and uploaded
+    #[clap(long, default_value_t = 30.0)]
+    fps: f32,
+    /// restart from the first frame after the last one instead of stopping
+    #[clap(long = "loop")]
+    looping: bool,
+    /// index of the frame to start from
+    #[clap(long, default_value_t = 0)]
+    start_frame: usize,
+    #[clap(
+        short = 'x',
+        long,
+        default_value_t = 0.0,
+        allow_negative_numbers = true
+    )]
+    camera_x: f32,
+    #[clap(
+        short = 'y',
+        long,
+        default_value_t = 0.0,
+        allow_negative_numbers = true
+    )]
+    camera_y: f32,
+    #[clap(
+        short = 'z',
+        long,
+        default_value_t = 1.3,
+        allow_negative_numbers = true
+    )]
+    camera_z: f32,
+    #[clap(long = "yaw", default_value_t = -90.0, allow_negative_numbers = true)]
+    camera_yaw: f32,
+    #[clap(long = "pitch", default_value_t = 0.0, allow_negative_numbers = true)]
+    camera_pitch: f32,
+    #[clap(long, default_value_t = 1600)]
+    width: u32,
+    #[clap(long, default_value_t = 900)]
+    height: u32,
+    #[clap(long, default_value = "rgb(255,255,255)")]
+    bg_color: String,
+}
+
+pub struct RenderSequence {
+    args: Args,
+}
+
+impl RenderSequence {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::from(RenderSequence {
+            args: Args::parse_from(args),
+        })
+    }
+}
+
+impl Subcommand for RenderSequence {
+    fn handle(&mut self, _messages: Vec<PipelineMessage>, channel: &Channel) {
+        let args = &self.args;
+        let reader = PointCloudFileReader::from_directory(&args.dir, "ply");
+        if args.start_frame >= reader.len() {
+            eprintln!(
+                "Start frame {} is out of range, {:?} has {} ply files",
+                args.start_frame,
+                args.dir,
+                reader.len()
+            );
+            channel.send(PipelineMessage::End);
+            return;
+        }
+
+        let camera = Camera::new(
+            (args.camera_x, args.camera_y, args.camera_z),
+            cgmath::Deg(args.camera_yaw),
+            cgmath::Deg(args.camera_pitch),
+        );
+        let renderer = Renderer::new(
+            reader,
+            args.fps,
+            camera,
+            (args.width, args.height),
+            None,
+            &args.bg_color,
+        )
+        .with_playback(Playback {
+            start_frame: args.start_frame,
+            looping: args.looping,
+            autoplay: true,
+        });
+
+        // the pipeline runs every subcommand in its own thread
+        let mut builder = RenderBuilder::any_thread();
+        builder.add_window(renderer);
+        // only returns by exiting the process once the window is closed
+        builder.run();
+    }
+}
@@ -0,0 +1,53 @@
+use clap::Parser;
+
+use crate::{
+    methods::integrity::{repair, repair_normals, Repair},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Fixes the integrity issues of each pointcloud from the stream found by validate.
+#[derive(Parser)]
+pub struct Args {}
+
+pub struct Repairer;
+
+impl Repairer {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let _args: Args = Args::parse_from(args);
+        Box::new(Repairer)
+    }
+}
+
+fn log(repairs: &[Repair], i: u32) {
+    for repair in repairs {
+        eprintln!("repair: point cloud {}: {}", i, repair);
+    }
+}
+
+impl Subcommand for Repairer {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(mut pc, i) => {
+                    log(&repair(&mut pc), i);
+                    channel.send(PipelineMessage::IndexedPointCloud(pc, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(mut pc, i) => {
+                    log(&repair_normals(&mut pc), i);
+                    channel.send(PipelineMessage::IndexedPointCloudNormal(pc, i));
+                }
+                PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use clap::Parser;
+
+use crate::formats::ros2::{PointCloud2Publisher, TopicPublisher};
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+
+use super::Subcommand;
+
+/// Publishes the point clouds from the stream as sensor_msgs/msg/PointCloud2 messages on a
+/// ROS 2 topic. Normals are not published.
+#[derive(Parser)]
+pub struct Args {
+    /// absolute name of the topic to publish on
+    #[clap(short, long, default_value = "/vvtk/points")]
+    topic: String,
+
+    /// frame_id of the message headers
+    #[clap(long, default_value = "map")]
+    frame_id: String,
+
+    /// name of the ROS 2 node
+    #[clap(long, default_value = "vvtk")]
+    node_name: String,
+}
+
+pub struct Ros2Publish {
+    publisher: Box<dyn PointCloud2Publisher>,
+    frame_id: String,
+}
+
+impl Ros2Publish {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        let publisher =
+            TopicPublisher::new(&args.node_name, &args.topic).expect("Failed to create publisher");
+        Box::from(Ros2Publish {
+            publisher: Box::new(publisher),
+            frame_id: args.frame_id,
+        })
+    }
+
+    fn publish(&mut self, pc: &PointCloud<PointXyzRgba>, i: u32) {
+        if let Err(e) = self.publisher.publish_cloud(pc, &self.frame_id) {
+            println!("Failed to publish frame {}\n{e}", i);
+        }
+    }
+}
+
+impl Subcommand for Ros2Publish {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match &message {
+                PipelineMessage::IndexedPointCloud(pc, i)
+                | PipelineMessage::IndexedPointCloudLabel(pc, _, i) => self.publish(pc, *i),
+                PipelineMessage::IndexedPointCloudNormal(pc, i)
+                | PipelineMessage::IndexedPointCloudFpfh(pc, _, i) => {
+                    let points = pc
+                        .points
+                        .iter()
+                        .map(|p| PointXyzRgba::from_xyz_rgba(p.xyz(), p.rgba()))
+                        .collect();
+                    self.publish(&PointCloud::from_points(points), *i);
+                }
+                PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::End
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+            }
+            channel.send(message);
+        }
+    }
+}
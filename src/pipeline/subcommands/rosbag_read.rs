@@ -0,0 +1,59 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use super::Subcommand;
+use crate::formats::rosbag::read_rosbag_point_clouds;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+
+/// Reads the sensor_msgs/PointCloud2 messages of a topic from a ROS bag file.
+#[derive(Parser)]
+pub struct Args {
+    /// ROS bag file to read
+    bag: PathBuf,
+
+    /// topic of the PointCloud2 messages
+    #[clap(long)]
+    topic: String,
+
+    /// seconds since the first message of the topic to start reading at
+    #[clap(long)]
+    start: Option<f64>,
+
+    /// seconds since the first message of the topic to stop reading at
+    #[clap(long)]
+    end: Option<f64>,
+}
+
+pub struct RosbagRead {
+    args: Args,
+}
+
+impl RosbagRead {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::from(RosbagRead {
+            args: Args::parse_from(args),
+        })
+    }
+}
+
+impl Subcommand for RosbagRead {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        if messages.is_empty() {
+            let args = &self.args;
+            match read_rosbag_point_clouds(&args.bag, &args.topic, args.start, args.end) {
+                Ok(clouds) => {
+                    for (i, pc) in clouds.into_iter().enumerate() {
+                        channel.send(PipelineMessage::IndexedPointCloud(pc, i as u32));
+                    }
+                }
+                Err(e) => eprintln!("Failed to read {:?}: {}", args.bag, e),
+            }
+            channel.send(PipelineMessage::End);
+        } else {
+            for message in messages {
+                channel.send(message);
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use cgmath::{Point3, Vector3};
+use clap::Parser;
+use std::path::Path;
+
+use crate::formats::grid::{bounding_box, write_f32_grid, GridHeader};
+use crate::methods::sdf::sample_sdf;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+
+use super::Subcommand;
+
+/// Samples the signed distance field of point clouds with normals on a cubic grid.
+/// Each point cloud is written as a raw little endian f32 grid (<index>.sdf) with a json header (<index>.json).
+#[derive(Parser)]
+pub struct Args {
+    /// output directory to store the sampled grids
+    output_dir: String,
+
+    /// number of voxels along each axis
+    #[clap(
+        short,
+        long,
+        default_value_t = 64,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    grid_resolution: usize,
+
+    /// padding added around the bounding box, as a fraction of its largest extent
+    #[clap(short, long, default_value_t = 0.1)]
+    padding: f32,
+
+    #[clap(long, default_value_t = 5)]
+    name_length: usize,
+}
+
+pub struct SdfSampler {
+    args: Args,
+}
+
+impl SdfSampler {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args = Args::parse_from(args);
+        std::fs::create_dir_all(Path::new(&args.output_dir))
+            .expect("Failed to create output directory");
+        Box::from(SdfSampler { args })
+    }
+}
+
+impl Subcommand for SdfSampler {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match &message {
+                PipelineMessage::IndexedPointCloudNormal(pc, i) => {
+                    let (min, max) =
+                        match bounding_box(pc.points.iter().map(|p| Point3::new(p.x, p.y, p.z))) {
+                            Some(bounds) => bounds,
+                            None => {
+                                println!("Skipping empty point cloud {}", i);
+                                channel.send(message);
+                                continue;
+                            }
+                        };
+                    let extent = (max.x - min.x).max(max.y - min.y).max(max.z - min.z);
+                    let pad = Vector3::new(1.0, 1.0, 1.0) * (extent * self.args.padding);
+                    let header = GridHeader::new(min - pad, max + pad, self.args.grid_resolution);
+
+                    let query_points: Vec<Point3<f32>> = (0..header.num_voxels())
+                        .map(|idx| header.voxel_center(idx))
+                        .collect();
+                    let sdf = sample_sdf(pc, &query_points);
+
+                    let name = format!("{:0width$}", i, width = self.args.name_length);
+                    let output_dir = Path::new(&self.args.output_dir);
+                    write_f32_grid(output_dir.join(format!("{name}.sdf")), &sdf)
+                        .expect("Failed to write sdf grid");
+                    header
+                        .write_to(output_dir.join(format!("{name}.json")))
+                        .expect("Failed to write grid header");
+                }
+                PipelineMessage::IndexedPointCloud(_, i) => {
+                    println!(
+                        "sdf-sample requires point clouds with normals, skipping point cloud {}",
+                        i
+                    );
+                }
+                PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::End
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+            }
+            channel.send(message);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use clap::Parser;
+
+use crate::{
+    methods::labels::SemanticDataset,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Colors a labelled pointcloud from the stream with the canonical colors of the classes of a
+/// dataset. Labels the dataset does not define are black.
+#[derive(Parser)]
+pub struct Args {
+    /// dataset whose class palette the labels index
+    #[clap(short, long, value_enum)]
+    dataset: SemanticDataset,
+}
+
+pub struct SemanticColorizer {
+    dataset: SemanticDataset,
+}
+
+impl SemanticColorizer {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(SemanticColorizer {
+            dataset: args.dataset,
+        })
+    }
+}
+
+impl Subcommand for SemanticColorizer {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloudLabel(mut pc, labels, i) => {
+                    self.dataset.colorize(&mut pc, &labels);
+                    channel.send(PipelineMessage::IndexedPointCloud(pc, i));
+                }
+                PipelineMessage::IndexedPointCloud(_, i) => {
+                    println!(
+                        "semantic-color requires labelled point clouds, skipping point cloud {}",
+                        i
+                    );
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
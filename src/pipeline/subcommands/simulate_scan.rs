@@ -0,0 +1,89 @@
+use cgmath::Point3;
+use clap::Parser;
+
+use crate::{
+    methods::scan::{simulate_scan, ScanPattern},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Simulates a spinning LiDAR scan of a dense pointcloud from the stream.
+#[derive(Parser)]
+pub struct Args {
+    #[clap(long, default_value_t = 64)]
+    num_beams: usize,
+
+    /// angle between two consecutive firings of a beam, in degrees
+    #[clap(long, default_value_t = 0.2)]
+    horizontal_resolution: f32,
+
+    /// elevation of the highest beam, in degrees
+    #[clap(long, default_value_t = 2.0, allow_hyphen_values = true)]
+    fov_up: f32,
+
+    /// elevation of the lowest beam, in degrees
+    #[clap(long, default_value_t = -24.8, allow_hyphen_values = true)]
+    fov_down: f32,
+
+    /// position of the sensor, given as x,y,z
+    #[clap(
+        long,
+        value_delimiter = ',',
+        num_args = 3,
+        default_values_t = vec![0.0, 0.0, 0.0],
+        allow_hyphen_values = true
+    )]
+    sensor_origin: Vec<f32>,
+
+    #[clap(long, default_value_t = 120.0)]
+    max_range: f32,
+}
+
+pub struct ScanSimulator {
+    origin: Point3<f32>,
+    pattern: ScanPattern,
+}
+
+impl ScanSimulator {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(ScanSimulator {
+            origin: Point3::new(
+                args.sensor_origin[0],
+                args.sensor_origin[1],
+                args.sensor_origin[2],
+            ),
+            pattern: ScanPattern {
+                num_beams: args.num_beams,
+                horizontal_resolution: args.horizontal_resolution,
+                fov_up: args.fov_up,
+                fov_down: args.fov_down,
+                max_range: args.max_range,
+            },
+        })
+    }
+}
+
+impl Subcommand for ScanSimulator {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let scan = simulate_scan(&pc, self.origin, &self.pattern);
+                    channel.send(PipelineMessage::IndexedPointCloud(scan, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use clap::Parser;
+
+use crate::{
+    methods::skeleton::extract_skeleton,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Extracts the curve skeleton of a pointcloud from the stream by contracting it towards its
+/// medial axis, and outputs the skeleton nodes as a sparse pointcloud.
+#[derive(Parser)]
+pub struct Args {
+    #[clap(long, default_value_t = 10)]
+    num_iterations: usize,
+
+    /// fraction of the way towards the centroid of its neighbours a point moves in each iteration
+    #[clap(long, default_value_t = 0.5)]
+    contraction_strength: f32,
+
+    /// radius of the neighbourhood of a point, about the width of the thinnest part of the shape
+    #[clap(long, default_value_t = 0.1)]
+    neighbour_radius: f32,
+
+    /// minimum distance between two nodes of the skeleton
+    #[clap(long, default_value_t = 0.05)]
+    sample_distance: f32,
+}
+
+pub struct Skeleton {
+    args: Args,
+}
+
+impl Skeleton {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(Skeleton { args })
+    }
+}
+
+impl Subcommand for Skeleton {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let skeleton = extract_skeleton(
+                        &pc,
+                        self.args.num_iterations,
+                        self.args.contraction_strength,
+                        self.args.neighbour_radius,
+                        self.args.sample_distance,
+                    );
+                    channel.send(PipelineMessage::IndexedPointCloud(skeleton, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
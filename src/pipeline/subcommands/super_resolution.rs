@@ -0,0 +1,56 @@
+use clap::Parser;
+
+use crate::{
+    pipeline::{channel::Channel, PipelineMessage},
+    upsample::super_resolution::midpoint_upsample,
+};
+
+use super::Subcommand;
+
+/// Upsamples a pointcloud from the stream by inserting the midpoints to the nearest neighbours
+/// of every point, then smoothing them with moving least squares.
+#[derive(Parser)]
+pub struct Args {
+    /// the output has this many times the points of the input, e.g. 2 or 4
+    #[clap(short, long, default_value_t = 2)]
+    factor: usize,
+
+    /// radius of the plane fitted to smooth each midpoint, 0 disables the smoothing
+    #[clap(short, long, default_value_t = 0.01)]
+    smooth_radius: f64,
+}
+
+pub struct SuperResolution {
+    args: Args,
+}
+
+impl SuperResolution {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        assert!(args.factor > 0, "Expecting a factor of at least 1");
+        Box::new(SuperResolution { args })
+    }
+}
+
+impl Subcommand for SuperResolution {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let upsampled =
+                        midpoint_upsample(&pc, self.args.factor, self.args.smooth_radius);
+                    channel.send(PipelineMessage::IndexedPointCloud(upsampled, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_) => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+                PipelineMessage::CompressedStream(_, _) | PipelineMessage::DummyForIncrement => {}
+            };
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use clap::Parser;
+
+use crate::{
+    methods::surface_area::point_areas,
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Estimates the surface area of a pointcloud from the stream by summing the areas of the
+/// Voronoi cells of its points. Outputs the total and the statistics of the point areas as
+/// metrics.
+#[derive(Parser)]
+pub struct Args {
+    /// number of neighbours the Voronoi cell of each point is computed among
+    #[clap(short, long, default_value_t = 12)]
+    knn: usize,
+
+    /// also output the area of every point, as point_area_<point index>
+    #[clap(long)]
+    per_point: bool,
+}
+
+pub struct SurfaceArea {
+    args: Args,
+}
+
+impl SurfaceArea {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(SurfaceArea { args })
+    }
+}
+
+impl Subcommand for SurfaceArea {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let areas = point_areas(&pc, self.args.knn);
+                    if areas.is_empty() {
+                        println!("Point cloud {} has no points to estimate the area of", i);
+                        continue;
+                    }
+                    let total: f32 = areas.iter().sum();
+                    let min = areas.iter().copied().fold(f32::INFINITY, f32::min);
+                    let max = areas.iter().copied().fold(0.0, f32::max);
+
+                    let mut metrics = Metrics::new();
+                    metrics.insert("surface_area".to_string(), format!("{:.5}", total));
+                    metrics.insert(
+                        "mean_point_area".to_string(),
+                        format!("{:.5}", total / areas.len() as f32),
+                    );
+                    metrics.insert("min_point_area".to_string(), format!("{:.5}", min));
+                    metrics.insert("max_point_area".to_string(), format!("{:.5}", max));
+                    if self.args.per_point {
+                        // zero padded so that the metrics are sorted by point index
+                        let width = (areas.len() - 1).to_string().len();
+                        for (j, area) in areas.iter().enumerate() {
+                            metrics.insert(
+                                format!("point_area_{:0width$}", j, width = width),
+                                format!("{:.5}", area),
+                            );
+                        }
+                    }
+                    channel.send(PipelineMessage::Metrics(metrics));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use clap::Parser;
+use std::ffi::OsString;
+
+use crate::formats::grid::{read_f32_grid, GridHeader};
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::methods::isosurface::{marching_cubes, sample_triangles};
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+use crate::utils::find_all_files;
+
+use super::Subcommand;
+
+/// Reads signed distance fields written by sdf-sample and samples points uniformly on their zero
+/// level set. Each grid is given by its json header (<name>.json), with the distances stored next
+/// to it in <name>.sdf.
+#[derive(Parser)]
+pub struct Args {
+    /// Grid headers, glob patterns, directories
+    files: Vec<OsString>,
+
+    /// number of points to sample on each surface
+    #[clap(short, long, default_value_t = 10000)]
+    num_points: usize,
+}
+
+pub struct SurfaceSampler {
+    args: Args,
+}
+
+impl SurfaceSampler {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::from(SurfaceSampler {
+            args: Args::parse_from(args),
+        })
+    }
+}
+
+impl Subcommand for SurfaceSampler {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        if messages.is_empty() {
+            let mut files = find_all_files(&self.args.files);
+            files.retain(|file| file.extension().and_then(|ext| ext.to_str()) == Some("json"));
+            files.sort();
+
+            let mut rng = rand::thread_rng();
+            for (i, file) in files.iter().enumerate() {
+                let header = match GridHeader::read_from(file) {
+                    Ok(header) => header,
+                    Err(e) => {
+                        println!("Failed to read grid header {:?}\n{e}", file);
+                        continue;
+                    }
+                };
+                match read_f32_grid(file.with_extension("sdf"), &header) {
+                    Ok(sdf) => {
+                        let triangles = marching_cubes(&header, &sdf);
+                        let points = sample_triangles(&triangles, self.args.num_points, &mut rng)
+                            .into_iter()
+                            .map(|p| PointXyzRgba::from_xyz_rgba(p.into(), [255, 255, 255, 255]))
+                            .collect();
+                        let pc = PointCloud::from_points(points);
+                        channel.send(PipelineMessage::IndexedPointCloud(pc, i as u32));
+                    }
+                    Err(e) => println!("Failed to read grid for {:?}\n{e}", file),
+                }
+            }
+            channel.send(PipelineMessage::End);
+        } else {
+            for message in messages {
+                channel.send(message);
+            }
+        }
+    }
+}
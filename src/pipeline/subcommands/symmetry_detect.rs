@@ -0,0 +1,77 @@
+use clap::Parser;
+
+use crate::{
+    methods::symmetry::detect_symmetry,
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Estimates the plane of reflective symmetry of a pointcloud from the stream.
+/// Outputs the normal and offset of the plane, and the fraction of mirrored points, as metrics.
+#[derive(Parser)]
+pub struct Args {
+    /// number of random triplets of points to try
+    #[clap(short, long, default_value_t = 1000)]
+    iterations: usize,
+
+    /// maximum distance from the mirror image of a point to the nearest point
+    #[clap(short, long, default_value_t = 0.01)]
+    tolerance: f32,
+}
+
+pub struct SymmetryDetector {
+    args: Args,
+}
+
+impl SymmetryDetector {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(SymmetryDetector { args })
+    }
+}
+
+impl Subcommand for SymmetryDetector {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let mut metrics = Metrics::new();
+                    match detect_symmetry(
+                        &pc,
+                        self.args.iterations,
+                        self.args.tolerance,
+                        &mut rand::thread_rng(),
+                    ) {
+                        Some((plane, score)) => {
+                            metrics
+                                .insert("normal_x".to_string(), format!("{:.5}", plane.normal.x));
+                            metrics
+                                .insert("normal_y".to_string(), format!("{:.5}", plane.normal.y));
+                            metrics
+                                .insert("normal_z".to_string(), format!("{:.5}", plane.normal.z));
+                            metrics.insert("offset".to_string(), format!("{:.5}", plane.offset));
+                            metrics.insert("symmetry_score".to_string(), format!("{:.5}", score));
+                        }
+                        None => {
+                            println!("Failed to find a symmetry plane of point cloud {}", i);
+                            continue;
+                        }
+                    }
+                    channel.send(PipelineMessage::Metrics(metrics));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
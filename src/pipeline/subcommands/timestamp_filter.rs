@@ -0,0 +1,63 @@
+use clap::Parser;
+use std::ffi::OsString;
+
+use super::Subcommand;
+use crate::formats::pointxyzrgbat::filter_by_timestamp;
+use crate::formats::PointCloud;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+use crate::ply::read_ply_timestamp;
+use crate::utils::find_all_files;
+
+/// Reads ply files with a per point timestamp, keeping the points measured within a time range,
+/// e.g. a sector of a LiDAR sweep. The timestamps are dropped from the output point clouds.
+#[derive(Parser)]
+pub struct Args {
+    /// Files or directories of ply files with a timestamp, time or t vertex property in seconds
+    files: Vec<OsString>,
+
+    /// earliest timestamp to keep, in seconds
+    #[clap(long, default_value_t = f64::NEG_INFINITY)]
+    min: f64,
+
+    /// latest timestamp to keep, in seconds
+    #[clap(long, default_value_t = f64::INFINITY)]
+    max: f64,
+}
+
+pub struct TimestampFilter {
+    args: Args,
+}
+
+impl TimestampFilter {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::from(TimestampFilter {
+            args: Args::parse_from(args),
+        })
+    }
+}
+
+impl Subcommand for TimestampFilter {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        if messages.is_empty() {
+            let mut files = find_all_files(&self.args.files);
+            files.retain(|file| file.extension().and_then(|ext| ext.to_str()) == Some("ply"));
+            files.sort();
+            for (i, file) in files.iter().enumerate() {
+                if let Some(pc) = read_ply_timestamp(file) {
+                    let filtered = filter_by_timestamp(&pc, self.args.min, self.args.max);
+                    let points = filtered.points.iter().map(|p| p.point()).collect();
+                    channel.send(PipelineMessage::IndexedPointCloud(
+                        PointCloud::from_points(points),
+                        i as u32,
+                    ));
+                }
+            }
+            channel.send(PipelineMessage::End);
+        } else {
+            for message in messages {
+                channel.send(message);
+            }
+        }
+    }
+}
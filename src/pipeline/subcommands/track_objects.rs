@@ -0,0 +1,143 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    methods::tracking::{split_by_label, Tracker},
+    pipeline::{channel::Channel, PipelineMessage},
+    ply::write_ply_label,
+};
+
+use super::Subcommand;
+
+/// Tracks the clusters of a clustered pointcloud from the stream across frames.
+/// Outputs the pointcloud labelled with the track ids, stable across frames, instead of the
+/// cluster labels.
+#[derive(Parser)]
+pub struct Args {
+    /// maximum displacement of a cluster centroid between two frames
+    #[clap(long, default_value_t = 1.0)]
+    max_distance: f64,
+
+    /// number of frames a track can be missing before it is deleted
+    #[clap(long, default_value_t = 5)]
+    max_missing_frames: usize,
+
+    /// directory to write every tracked cluster to, as <track id>/<frame index>.ply
+    #[clap(long)]
+    output_dir: Option<PathBuf>,
+}
+
+pub struct ObjectTracker {
+    tracker: Tracker,
+    output_dir: Option<PathBuf>,
+}
+
+impl ObjectTracker {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(ObjectTracker {
+            tracker: Tracker::new(args.max_distance as f32, args.max_missing_frames),
+            output_dir: args.output_dir,
+        })
+    }
+}
+
+impl Subcommand for ObjectTracker {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloudLabel(pc, labels, i) => {
+                    let track_ids = self.tracker.relabel(&pc, &labels);
+                    if let Some(output_dir) = &self.output_dir {
+                        for (track_id, cluster) in split_by_label(&pc, &track_ids) {
+                            let track_dir = output_dir.join(track_id.to_string());
+                            std::fs::create_dir_all(&track_dir)
+                                .expect("Failed to create track directory");
+                            let labels = vec![track_id; cluster.points.len()];
+                            write_ply_label(
+                                &cluster,
+                                &labels,
+                                track_dir.join(format!("{:05}.ply", i)),
+                            )
+                            .expect("Failed to write tracked cluster");
+                        }
+                    }
+                    channel.send(PipelineMessage::IndexedPointCloudLabel(pc, track_ids, i));
+                }
+                PipelineMessage::IndexedPointCloud(_, i) => {
+                    println!(
+                        "track-objects requires clustered point clouds, skipping point cloud {}",
+                        i
+                    );
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+    use crate::ply::read_ply_label;
+
+    #[test]
+    fn test_every_frame_of_every_track() {
+        let point = |x: f32| PointXyzRgba::from_xyz_rgba([x, 0.0, 0.0], [0, 0, 0, 255]);
+        let output_dir = std::env::temp_dir().join("vivotk_test_track_objects");
+        let mut tracker = ObjectTracker::from_args(vec![
+            "track-objects".to_string(),
+            "--output-dir".to_string(),
+            output_dir.to_str().unwrap().to_string(),
+        ]);
+        let (progress_tx, _progress_rx) = crossbeam_channel::unbounded();
+        let mut channel = Channel::new(progress_tx);
+        let rx = channel.subscribe();
+        // two objects moving apart, with cluster labels swapped in the second frame
+        let first = PointCloud::from_points(vec![point(0.0), point(10.0), point(0.2)]);
+        let second = PointCloud::from_points(vec![point(10.5), point(0.5)]);
+        tracker.handle(
+            vec![
+                PipelineMessage::IndexedPointCloudLabel(first, vec![7, 3, 7], 4),
+                PipelineMessage::IndexedPointCloudLabel(second, vec![0, 1], 5),
+            ],
+            &channel,
+        );
+
+        let frames: Vec<(Vec<u32>, u32)> = rx
+            .try_iter()
+            .map(|message| match message {
+                PipelineMessage::IndexedPointCloudLabel(_, labels, i) => (labels, i),
+                _ => panic!("Expecting labelled point clouds"),
+            })
+            .collect();
+        assert_eq!(frames, vec![(vec![1, 0, 1], 4), (vec![0, 1], 5)]);
+
+        let read = |track: &str, frame: &str| {
+            read_ply_label(output_dir.join(track).join(frame)).map(|(pc, labels)| {
+                let xs: Vec<f32> = pc.points.iter().map(|p| p.x).collect();
+                (xs, labels)
+            })
+        };
+        let files = [
+            read("0", "00004.ply"),
+            read("1", "00004.ply"),
+            read("0", "00005.ply"),
+            read("1", "00005.ply"),
+        ];
+        std::fs::remove_dir_all(&output_dir).unwrap();
+        assert_eq!(files[0], Some((vec![10.0], vec![0])));
+        assert_eq!(files[1], Some((vec![0.0, 0.2], vec![1, 1])));
+        assert_eq!(files[2], Some((vec![10.5], vec![0])));
+        assert_eq!(files[3], Some((vec![0.5], vec![1])));
+    }
+}
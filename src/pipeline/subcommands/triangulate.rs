@@ -0,0 +1,81 @@
+use clap::Parser;
+use std::path::{Path, PathBuf};
+
+use crate::formats::HasCoordinate;
+use crate::methods::triangulation::{delaunay_2d, triangulation_to_obj};
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+
+use super::Subcommand;
+
+/// Triangulates point clouds from the stream with the 2D Delaunay triangulation of their
+/// projection onto the XY plane, and writes the meshes as obj files (<output>_<index>.obj).
+#[derive(Parser)]
+pub struct Args {
+    /// obj file to write the meshes to, the index of the point cloud is appended to the file name
+    #[clap(short, long)]
+    output: PathBuf,
+
+    #[clap(long, default_value_t = 5)]
+    name_length: usize,
+}
+
+pub struct Triangulator {
+    args: Args,
+}
+
+impl Triangulator {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args = Args::parse_from(args);
+        if let Some(parent) = args.output.parent() {
+            std::fs::create_dir_all(parent).expect("Failed to create output directory");
+        }
+        Box::from(Triangulator { args })
+    }
+
+    fn output_file(&self, index: u32) -> PathBuf {
+        let stem = self
+            .args
+            .output
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("mesh");
+        let file_name = format!(
+            "{}_{:0width$}.obj",
+            stem,
+            index,
+            width = self.args.name_length
+        );
+        self.args
+            .output
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(file_name)
+    }
+}
+
+impl Subcommand for Triangulator {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match &message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let coordinates: Vec<_> = pc.points.iter().map(|p| p.coordinate()).collect();
+                    let triangles = delaunay_2d(&coordinates);
+                    let output_file = self.output_file(*i);
+                    if let Err(e) = triangulation_to_obj(pc, &triangles, &output_file) {
+                        println!("Failed to write {:?}\n{e}", output_file);
+                    }
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::End
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+            }
+            channel.send(message);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use clap::Parser;
+use std::io::ErrorKind;
+use std::net::UdpSocket;
+use std::time::Duration;
+
+use super::Subcommand;
+use crate::formats::udp::{FrameAssembler, UdpFormat};
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+
+/// Receives point clouds streamed over UDP, e.g. from a depth camera.
+/// Every frame is split into datagrams with an 8 byte header: the little endian frame id (u32),
+/// fragment index (u16) and fragment count (u16). Incomplete frames older than the last
+/// complete frame are dropped.
+#[derive(Parser)]
+pub struct Args {
+    /// port to listen on
+    #[clap(short, long, default_value_t = 9000)]
+    port: u16,
+
+    /// encoding of the frames
+    #[clap(short, long, value_enum, default_value_t = UdpFormat::Ply)]
+    format: UdpFormat,
+
+    /// stop after receiving this many frames
+    #[clap(short, long)]
+    num_frames: Option<usize>,
+
+    /// stop after this many seconds without a datagram
+    #[clap(short, long)]
+    timeout: Option<f64>,
+}
+
+pub struct UdpSource {
+    args: Args,
+}
+
+impl UdpSource {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::from(UdpSource {
+            args: Args::parse_from(args),
+        })
+    }
+
+    fn receive(&self, channel: &Channel) -> std::io::Result<()> {
+        let args = &self.args;
+        let socket = UdpSocket::bind(("0.0.0.0", args.port))?;
+        socket.set_read_timeout(args.timeout.map(Duration::from_secs_f64))?;
+
+        let mut assembler = FrameAssembler::new();
+        let mut buf = vec![0u8; u16::MAX as usize];
+        let mut received = 0;
+        while args.num_frames.map_or(true, |n| received < n) {
+            let len = match socket.recv(&mut buf) {
+                Ok(len) => len,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break,
+                Err(e) => return Err(e),
+            };
+            if let Some((frame_id, payload)) = assembler.push(&buf[..len]) {
+                match args.format.decode(&payload) {
+                    Some(pc) => {
+                        channel.send(PipelineMessage::IndexedPointCloud(pc, received as u32));
+                        received += 1;
+                    }
+                    None => eprintln!("Failed to decode frame {}", frame_id),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Subcommand for UdpSource {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        if messages.is_empty() {
+            if let Err(e) = self.receive(channel) {
+                eprintln!("Failed to receive on port {}: {}", self.args.port, e);
+            }
+            channel.send(PipelineMessage::End);
+        } else {
+            for message in messages {
+                channel.send(message);
+            }
+        }
+    }
+}
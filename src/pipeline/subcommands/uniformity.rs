@@ -0,0 +1,55 @@
+use clap::Parser;
+
+use crate::{
+    metrics::{Metrics, Uniformity},
+    pipeline::{channel::Channel, PipelineMessage},
+    structures::neighbours::NeighbourIndex,
+};
+
+use super::Subcommand;
+
+/// Calculates how evenly the points of each pointcloud from the stream are spread.
+#[derive(Parser)]
+pub struct Args {
+    /// search the nearest neighbours with a random projection forest instead of a kd tree
+    #[clap(short, long)]
+    approximate: bool,
+}
+
+pub struct UniformityCalculator {
+    approximate: bool,
+}
+
+impl UniformityCalculator {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(UniformityCalculator {
+            approximate: args.approximate,
+        })
+    }
+}
+
+impl Subcommand for UniformityCalculator {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, _) => {
+                    let tree = NeighbourIndex::build(&pc.points, self.approximate);
+                    let mut metrics = Metrics::new();
+                    Uniformity::calculate_metric(&pc.points, &tree, &mut metrics);
+                    channel.send(PipelineMessage::Metrics(metrics));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
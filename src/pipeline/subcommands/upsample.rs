@@ -35,11 +35,15 @@ impl Subcommand for Upsampler {
                     let upsampled_pc = upsample(pc, self.factor);
                     channel.send(PipelineMessage::IndexedPointCloud(upsampled_pc, i));
                 }
-                PipelineMessage::Metrics(_) => {}
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_) => {}
                 PipelineMessage::End => {
                     channel.send(message);
                 }
-                PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::CompressedStream(_, _) | PipelineMessage::DummyForIncrement => {}
             };
         }
     }
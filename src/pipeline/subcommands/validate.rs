@@ -0,0 +1,77 @@
+use clap::Parser;
+
+use crate::{
+    methods::integrity::{validate, validate_normals, Issue},
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Number of issues printed per point cloud.
+const MAX_REPORTED_ISSUES: usize = 10;
+
+/// Checks the integrity of each pointcloud from the stream and forwards it unchanged.
+#[derive(Parser)]
+pub struct Args {
+    /// treat issues as errors and drop the point clouds that have any
+    #[clap(long)]
+    strict: bool,
+}
+
+pub struct Validator {
+    strict: bool,
+}
+
+impl Validator {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(Validator {
+            strict: args.strict,
+        })
+    }
+
+    /// Reports the issues and returns whether the point cloud should be forwarded.
+    fn report(&self, issues: &[Issue], i: u32) -> bool {
+        if issues.is_empty() {
+            return true;
+        }
+        let level = if self.strict { "error" } else { "warning" };
+        for issue in issues.iter().take(MAX_REPORTED_ISSUES) {
+            eprintln!("{}: point cloud {}: {}", level, i, issue);
+        }
+        if issues.len() > MAX_REPORTED_ISSUES {
+            eprintln!(
+                "{}: point cloud {}: and {} more issues",
+                level,
+                i,
+                issues.len() - MAX_REPORTED_ISSUES
+            );
+        }
+        !self.strict
+    }
+}
+
+impl Subcommand for Validator {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            let forward = match &message {
+                PipelineMessage::IndexedPointCloud(pc, i)
+                | PipelineMessage::IndexedPointCloudLabel(pc, _, i) => {
+                    self.report(&validate(pc), *i)
+                }
+                PipelineMessage::IndexedPointCloudNormal(pc, i)
+                | PipelineMessage::IndexedPointCloudFpfh(pc, _, i) => {
+                    self.report(&validate_normals(pc), *i)
+                }
+                PipelineMessage::Correspondences(_)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => false,
+                PipelineMessage::End => true,
+            };
+            if forward {
+                channel.send(message);
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use cgmath::Point3;
+use clap::{Parser, ValueEnum};
+
+use crate::{
+    formats::PointCloud,
+    methods::visibility::visibility,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Part {
+    Visible,
+    Occluded,
+}
+
+/// Finds the points of a pointcloud from the stream visible from a viewpoint, casting a ray to
+/// each point. Outputs the pointcloud labelled 1 for the visible points and 0 for the occluded
+/// ones, or only the points of one part with --keep.
+#[derive(Parser)]
+pub struct Args {
+    /// position of the viewpoint, given as x,y,z
+    #[clap(long, value_delimiter = ',', num_args = 3, allow_hyphen_values = true)]
+    viewpoint: Vec<f32>,
+
+    /// angle from a ray within which a nearer point occludes, in degrees, about the angle
+    /// between neighbouring points seen from the viewpoint
+    #[clap(short, long, default_value_t = 0.5)]
+    angular_tolerance: f32,
+
+    /// depth by which a point must be nearer to occlude, keeping surfaces seen at an angle
+    /// from hiding themselves
+    #[clap(short, long, default_value_t = 0.05)]
+    depth_margin: f32,
+
+    /// output the points of this part instead of the labelled pointcloud
+    #[clap(long, value_enum)]
+    keep: Option<Part>,
+}
+
+pub struct VisibilityComputer {
+    viewpoint: Point3<f32>,
+    args: Args,
+}
+
+impl VisibilityComputer {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(VisibilityComputer {
+            viewpoint: Point3::new(args.viewpoint[0], args.viewpoint[1], args.viewpoint[2]),
+            args,
+        })
+    }
+}
+
+impl Subcommand for VisibilityComputer {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    let visible = visibility(
+                        &pc,
+                        self.viewpoint,
+                        self.args.angular_tolerance.to_radians(),
+                        self.args.depth_margin,
+                    );
+
+                    match self.args.keep {
+                        Some(part) => {
+                            let keep_visible = part == Part::Visible;
+                            let points = pc
+                                .points
+                                .iter()
+                                .zip(&visible)
+                                .filter(|&(_, &v)| v == keep_visible)
+                                .map(|(p, _)| *p)
+                                .collect();
+                            channel.send(PipelineMessage::IndexedPointCloud(
+                                PointCloud::from_points(points),
+                                i,
+                            ));
+                        }
+                        None => {
+                            let labels = visible.iter().map(|&v| v as u32).collect();
+                            channel.send(PipelineMessage::IndexedPointCloudLabel(pc, labels, i));
+                        }
+                    }
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use clap::Parser;
+
+#[cfg(feature = "gpu")]
+use crate::gpu::{downsample::voxel_downsample_gpu, GpuContext};
+use crate::{
+    methods::sampling::voxel_centroid_sample,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// average the points of every voxel on the CPU
+    Cpu,
+    /// sort the points by voxel on the GPU, needs the gpu feature
+    Gpu,
+}
+
+/// Replaces the points of every voxel of a pointcloud from the stream by their centroid.
+#[derive(Parser)]
+pub struct Args {
+    /// side of the voxels
+    #[clap(short, long, default_value_t = 0.01)]
+    voxel_size: f32,
+
+    #[clap(short, long, value_enum, default_value_t = Backend::Cpu)]
+    backend: Backend,
+}
+
+pub struct VoxelDownsampler {
+    voxel_size: f32,
+    #[cfg(feature = "gpu")]
+    gpu: Option<GpuContext>,
+}
+
+impl VoxelDownsampler {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        #[cfg(not(feature = "gpu"))]
+        if args.backend == Backend::Gpu {
+            panic!("The gpu backend needs vvtk built with `--features gpu`");
+        }
+        Box::new(VoxelDownsampler {
+            voxel_size: args.voxel_size,
+            #[cfg(feature = "gpu")]
+            gpu: match args.backend {
+                Backend::Cpu => None,
+                Backend::Gpu => Some(GpuContext::new().expect("No GPU adapter available")),
+            },
+        })
+    }
+}
+
+impl Subcommand for VoxelDownsampler {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    #[cfg(feature = "gpu")]
+                    if let Some(context) = &self.gpu {
+                        let downsampled_pc = voxel_downsample_gpu(context, &pc, self.voxel_size);
+                        channel.send(PipelineMessage::IndexedPointCloud(downsampled_pc, i));
+                        continue;
+                    }
+                    let downsampled_pc = voxel_centroid_sample(&pc, self.voxel_size);
+                    channel.send(PipelineMessage::IndexedPointCloud(downsampled_pc, i));
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use clap::Parser;
+use std::ffi::OsString;
+
+use crate::formats::grid::{read_bit_grid, read_f32_grid, GridHeader};
+use crate::methods::occupancy::occupancy_to_cloud;
+use crate::pipeline::channel::Channel;
+use crate::pipeline::PipelineMessage;
+use crate::utils::find_all_files;
+
+use super::Subcommand;
+
+/// Reads occupancy grids written by occupancy-grid and emits a point at the center of each occupied voxel.
+/// Each grid is given by its json header (<name>.json), with the voxels stored next to it in <name>.occ.
+#[derive(Parser)]
+pub struct Args {
+    /// Grid headers, glob patterns, directories
+    files: Vec<OsString>,
+
+    /// read <name>.prob grids of one little endian f32 probability per voxel instead,
+    /// voxels with a probability of at least the threshold are occupied
+    #[clap(short, long)]
+    threshold: Option<f32>,
+}
+
+pub struct VoxelToCloud {
+    args: Args,
+}
+
+impl VoxelToCloud {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        Box::from(VoxelToCloud {
+            args: Args::parse_from(args),
+        })
+    }
+}
+
+impl Subcommand for VoxelToCloud {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        if messages.is_empty() {
+            let mut files = find_all_files(&self.args.files);
+            files.retain(|file| file.extension().and_then(|ext| ext.to_str()) == Some("json"));
+            files.sort();
+
+            for (i, file) in files.iter().enumerate() {
+                let header = match GridHeader::read_from(file) {
+                    Ok(header) => header,
+                    Err(e) => {
+                        println!("Failed to read grid header {:?}\n{e}", file);
+                        continue;
+                    }
+                };
+                let occupied = match self.args.threshold {
+                    Some(threshold) => read_f32_grid(file.with_extension("prob"), &header)
+                        .map(|probs| probs.into_iter().map(|p| p >= threshold).collect()),
+                    None => read_bit_grid(file.with_extension("occ"), &header),
+                };
+                match occupied {
+                    Ok(occupied) => {
+                        let pc = occupancy_to_cloud(&header, &occupied);
+                        channel.send(PipelineMessage::IndexedPointCloud(pc, i as u32));
+                    }
+                    Err(e) => println!("Failed to read grid for {:?}\n{e}", file),
+                }
+            }
+            channel.send(PipelineMessage::End);
+        } else {
+            for message in messages {
+                channel.send(message);
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    codec::vpcc::Tmc2Decoder,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Decompresses the V-PCC bitstreams from the stream with the MPEG V-PCC reference decoder.
+#[derive(Parser)]
+pub struct Args {
+    /// path of the TMC2 decoder binary
+    #[clap(long, default_value = "PccAppDecoder")]
+    decoder: PathBuf,
+}
+
+pub struct VpccDecoder {
+    decoder: Tmc2Decoder,
+}
+
+impl VpccDecoder {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(VpccDecoder {
+            decoder: Tmc2Decoder::new(args.decoder),
+        })
+    }
+}
+
+impl Subcommand for VpccDecoder {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::CompressedStream(path, first_index) => {
+                    let frames = self
+                        .decoder
+                        .decode(&path)
+                        .unwrap_or_else(|e| panic!("Failed to decode the sequence: {:?}", e));
+                    for (i, pc) in (first_index..).zip(frames) {
+                        channel.send(PipelineMessage::IndexedPointCloud(pc, i));
+                    }
+                }
+                PipelineMessage::IndexedPointCloud(_, _)
+                | PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
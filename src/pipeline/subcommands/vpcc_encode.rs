@@ -0,0 +1,95 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::{
+    codec::vpcc::Tmc2Encoder,
+    formats::{pointxyzrgba::PointXyzRgba, PointCloud},
+    metrics::Metrics,
+    pipeline::{channel::Channel, PipelineMessage},
+};
+
+use super::Subcommand;
+
+/// Compresses the pointclouds from the stream as one sequence with the MPEG V-PCC reference
+/// encoder once the stream ends, and outputs the bits per point of the sequence as metrics.
+#[derive(Parser)]
+pub struct Args {
+    /// bitstream file to write
+    #[clap(short, long)]
+    output: PathBuf,
+
+    /// path of the TMC2 encoder binary
+    #[clap(long, default_value = "PccAppEncoder")]
+    encoder: PathBuf,
+
+    /// TMC2 configuration file, repeated for the common, condition, sequence and rate files
+    #[clap(short, long)]
+    config: Vec<PathBuf>,
+}
+
+pub struct VpccEncoder {
+    encoder: Tmc2Encoder,
+    output: PathBuf,
+    frames: Vec<PointCloud<PointXyzRgba>>,
+    first_index: Option<u32>,
+}
+
+impl VpccEncoder {
+    pub fn from_args(args: Vec<String>) -> Box<dyn Subcommand> {
+        let args: Args = Args::parse_from(args);
+        Box::new(VpccEncoder {
+            encoder: Tmc2Encoder::new(args.encoder, args.config),
+            output: args.output,
+            frames: vec![],
+            first_index: None,
+        })
+    }
+
+    fn encode(&self) -> Metrics {
+        let bytes = self
+            .encoder
+            .encode(&self.frames, &self.output)
+            .unwrap_or_else(|e| panic!("Failed to encode the sequence: {:?}", e));
+        let points: usize = self.frames.iter().map(|frame| frame.points.len()).sum();
+
+        let mut metrics = Metrics::new();
+        metrics.insert("frames".to_string(), format!("{}", self.frames.len()));
+        metrics.insert("points".to_string(), format!("{}", points));
+        metrics.insert("bitstream_bytes".to_string(), format!("{}", bytes));
+        metrics.insert(
+            "bits_per_point".to_string(),
+            format!("{:.5}", (bytes * 8) as f64 / points.max(1) as f64),
+        );
+        metrics
+    }
+}
+
+impl Subcommand for VpccEncoder {
+    fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
+        for message in messages {
+            match message {
+                PipelineMessage::IndexedPointCloud(pc, i) => {
+                    self.first_index.get_or_insert(i);
+                    self.frames.push(pc);
+                }
+                PipelineMessage::IndexedPointCloudNormal(_, _)
+                | PipelineMessage::IndexedPointCloudFpfh(_, _, _)
+                | PipelineMessage::Correspondences(_)
+                | PipelineMessage::IndexedPointCloudLabel(_, _, _)
+                | PipelineMessage::Metrics(_)
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
+                PipelineMessage::End => {
+                    if let Some(first_index) = self.first_index {
+                        channel.send(PipelineMessage::Metrics(self.encode()));
+                        channel.send(PipelineMessage::CompressedStream(
+                            self.output.clone(),
+                            first_index,
+                        ));
+                    }
+                    channel.send(message);
+                }
+            };
+        }
+    }
+}
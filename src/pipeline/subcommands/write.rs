@@ -4,9 +4,10 @@ use clap::Parser;
 use crate::pcd::{create_pcd, write_pcd_file, PCDDataType};
 use crate::pipeline::channel::Channel;
 use crate::pipeline::PipelineMessage;
+use crate::ply::{write_ply_label, write_ply_normal};
 use crate::utils::{pcd_to_ply_from_data, ConvertOutputFormat};
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use super::Subcommand;
 
@@ -40,12 +41,29 @@ impl Write {
             .expect("Failed to create output directory");
         Box::from(Write { args, count: 0 })
     }
+
+    /// Path of the file for the pointcloud with index `i`.
+    fn output_file(&mut self, i: u32, output_format: &str, channel: &Channel) -> PathBuf {
+        // !! use index(i) instead of count to make sure the order of files
+        let padded_count = format!("{:0width$}", i, width = self.args.name_length);
+        let file_name = format!("{}.{}", padded_count, output_format);
+        self.count += 1;
+        if self.count >= pow(10, self.args.name_length) {
+            channel.send(PipelineMessage::End);
+            panic!("Too many files, please increase the name length by setting --name-length")
+        }
+
+        let output_path = Path::new(&self.args.output_dir);
+        if !output_path.exists() {
+            std::fs::create_dir_all(output_path).expect("Failed to create output directory");
+        }
+        output_path.join(file_name)
+    }
 }
 
 impl Subcommand for Write {
     fn handle(&mut self, messages: Vec<PipelineMessage>, channel: &Channel) {
-        let output_path = Path::new(&self.args.output_dir);
-        let max_count = pow(10, self.args.name_length);
+        let output_path = PathBuf::from(&self.args.output_dir);
         for message in messages {
             match &message {
                 PipelineMessage::IndexedPointCloud(pc, i) => {
@@ -56,21 +74,7 @@ impl Subcommand for Write {
                         .expect("PCD data type should be provided");
                     let output_format = self.args.output_format.to_string();
 
-                    // !! use index(i) instead of count to make sure the order of files
-                    let padded_count = format!("{:0width$}", i, width = self.args.name_length);
-                    let file_name = format!("{}.{}", padded_count, output_format);
-                    self.count += 1;
-                    if self.count >= max_count {
-                        channel.send(PipelineMessage::End);
-                        panic!("Too many files, please increase the name length by setting --name-length")
-                    }
-
-                    let file_name = Path::new(&file_name);
-                    let output_file = output_path.join(file_name);
-                    if !output_path.exists() {
-                        std::fs::create_dir_all(output_path)
-                            .expect("Failed to create output directory");
-                    }
+                    let output_file = self.output_file(*i, &output_format, channel);
 
                     // use pcd format as a trasition format now
                     let pcd = create_pcd(pc);
@@ -101,9 +105,74 @@ impl Subcommand for Write {
                         .and_then(|mut f| metrics.write_to(&mut f))
                         .expect("Should be able to create file to write metrics to");
                 }
-                PipelineMessage::End | PipelineMessage::DummyForIncrement => {}
+                // normals and labels have no pcd fields here, so these are always written as ply
+                PipelineMessage::IndexedPointCloudNormal(pc, i)
+                | PipelineMessage::IndexedPointCloudFpfh(pc, _, i) => {
+                    let output_file = self.output_file(*i, "ply", channel);
+                    if let Err(e) = write_ply_normal(pc, &output_file) {
+                        println!("Failed to write {:?}\n{e}", output_file);
+                    }
+                }
+                PipelineMessage::IndexedPointCloudLabel(pc, labels, i) => {
+                    let output_file = self.output_file(*i, "ply", channel);
+                    if let Err(e) = write_ply_label(pc, labels, &output_file) {
+                        println!("Failed to write {:?}\n{e}", output_file);
+                    }
+                }
+                PipelineMessage::Correspondences(_)
+                | PipelineMessage::End
+                | PipelineMessage::CompressedStream(_, _)
+                | PipelineMessage::DummyForIncrement => {}
             }
             channel.send(message);
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::formats::pointxyzrgbanormal::PointXyzRgbaNormal;
+    use crate::formats::PointCloud;
+    use crate::ply::read_ply_normal;
+
+    #[test]
+    fn test_write_normals() {
+        let points = (0..10)
+            .map(|i| PointXyzRgbaNormal {
+                x: i as f32,
+                y: 2.0 * i as f32,
+                z: -(i as f32),
+                r: i as u8,
+                g: 100,
+                b: 200,
+                a: 255,
+                nx: 0.0,
+                ny: (i as f32).sin(),
+                nz: (i as f32).cos(),
+            })
+            .collect();
+        let cloud = PointCloud::from_points(points);
+
+        let output_dir = std::env::temp_dir().join("vivotk_test_write_normals");
+        let mut write = Write::from_args(vec![
+            "write".to_string(),
+            output_dir.to_str().unwrap().to_string(),
+        ]);
+        let (progress_tx, _progress_rx) = crossbeam_channel::unbounded();
+        let mut channel = Channel::new(progress_tx);
+        let rx = channel.subscribe();
+        write.handle(
+            vec![PipelineMessage::IndexedPointCloudNormal(cloud.clone(), 3)],
+            &channel,
+        );
+        let read = read_ply_normal(output_dir.join("00003.ply")).unwrap();
+        std::fs::remove_dir_all(&output_dir).unwrap();
+
+        assert_eq!(read.points, cloud.points);
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(PipelineMessage::IndexedPointCloudNormal(_, 3))
+        ));
+    }
+}
@@ -1,10 +1,20 @@
+use std::collections::HashSet;
+use std::fs::File;
 use std::path::Path;
+use std::sync::{Mutex, OnceLock};
 
-use ply_rs::ply::Property;
+use ply_rs::ply::{
+    DefaultElement, ElementDef, Encoding, Payload, Ply, Property, PropertyDef, PropertyType,
+    ScalarType,
+};
 
 use ply_rs::ply::Header;
 
-use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::formats::{
+    pointxyzrgba::PointXyzRgba, pointxyzrgbacurvature::PointXyzRgbaCurvature,
+    pointxyzrgbaf64::PointXyzRgbaF64, pointxyzrgbanormal::PointXyzRgbaNormal,
+    pointxyzrgbat::PointXyzRgbaT, PointCloud,
+};
 
 pub fn read_ply_header<P: AsRef<Path>>(path_buf: P) -> Result<Header, String> {
     let vertex_parser = ply_rs::parser::Parser::<PointXyzRgba>::new();
@@ -21,7 +31,308 @@ pub fn read_ply_header<P: AsRef<Path>>(path_buf: P) -> Result<Header, String> {
 }
 
 pub fn read_ply<P: AsRef<Path>>(path_buf: P) -> Option<PointCloud<PointXyzRgba>> {
-    let vertex_parser = ply_rs::parser::Parser::<PointXyzRgba>::new();
+    read_ply_vertices(path_buf)
+}
+
+/// Reads a ply file keeping the per vertex normals (nx, ny, nz), which default to zero if absent.
+pub fn read_ply_normal<P: AsRef<Path>>(path_buf: P) -> Option<PointCloud<PointXyzRgbaNormal>> {
+    read_ply_vertices(path_buf)
+}
+
+/// Reads a ply file keeping double precision coordinates.
+pub fn read_ply_f64<P: AsRef<Path>>(path_buf: P) -> Option<PointCloud<PointXyzRgbaF64>> {
+    read_ply_vertices(path_buf)
+}
+
+/// Reads a ply file keeping the per vertex `timestamp`, `time` or `t` property in seconds, which
+/// defaults to zero if absent.
+pub fn read_ply_timestamp<P: AsRef<Path>>(path_buf: P) -> Option<PointCloud<PointXyzRgbaT>> {
+    read_ply_vertices(path_buf)
+}
+
+/// Reads a ply file keeping the per vertex `label` property, which defaults to zero if absent.
+pub fn read_ply_label<P: AsRef<Path>>(path_buf: P) -> Option<(PointCloud<PointXyzRgba>, Vec<u32>)> {
+    let labeled: PointCloud<LabeledPoint> = read_ply_vertices(path_buf)?;
+    let (points, labels): (Vec<PointXyzRgba>, Vec<u32>) = labeled
+        .points
+        .into_iter()
+        .map(|p| (p.point, p.label))
+        .unzip();
+    Some((PointCloud::from_points(points), labels))
+}
+
+/// Writes a binary ply file with the positions, colors and normals (nx, ny, nz) of the points.
+pub fn write_ply_normal<P: AsRef<Path>>(
+    cloud: &PointCloud<PointXyzRgbaNormal>,
+    path_buf: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let vertices = cloud
+        .points
+        .iter()
+        .map(|point| {
+            let mut vertex = DefaultElement::new();
+            vertex.insert("x".to_string(), Property::Float(point.x));
+            vertex.insert("y".to_string(), Property::Float(point.y));
+            vertex.insert("z".to_string(), Property::Float(point.z));
+            vertex.insert("red".to_string(), Property::UChar(point.r));
+            vertex.insert("green".to_string(), Property::UChar(point.g));
+            vertex.insert("blue".to_string(), Property::UChar(point.b));
+            vertex.insert("nx".to_string(), Property::Float(point.nx));
+            vertex.insert("ny".to_string(), Property::Float(point.ny));
+            vertex.insert("nz".to_string(), Property::Float(point.nz));
+            vertex
+        })
+        .collect();
+    write_ply_vertices(
+        path_buf,
+        &[
+            ("x", ScalarType::Float),
+            ("y", ScalarType::Float),
+            ("z", ScalarType::Float),
+            ("red", ScalarType::UChar),
+            ("green", ScalarType::UChar),
+            ("blue", ScalarType::UChar),
+            ("nx", ScalarType::Float),
+            ("ny", ScalarType::Float),
+            ("nz", ScalarType::Float),
+        ],
+        vertices,
+    )
+}
+
+/// Writes a binary ply file with the positions, colors and labels of the points, the labels as the
+/// `label` property read by [`read_ply_label`].
+pub fn write_ply_label<P: AsRef<Path>>(
+    cloud: &PointCloud<PointXyzRgba>,
+    labels: &[u32],
+    path_buf: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let vertices = cloud
+        .points
+        .iter()
+        .zip(labels)
+        .map(|(point, &label)| {
+            let mut vertex = DefaultElement::new();
+            vertex.insert("x".to_string(), Property::Float(point.x));
+            vertex.insert("y".to_string(), Property::Float(point.y));
+            vertex.insert("z".to_string(), Property::Float(point.z));
+            vertex.insert("red".to_string(), Property::UChar(point.r));
+            vertex.insert("green".to_string(), Property::UChar(point.g));
+            vertex.insert("blue".to_string(), Property::UChar(point.b));
+            vertex.insert("label".to_string(), Property::UInt(label));
+            vertex
+        })
+        .collect();
+    write_ply_vertices(
+        path_buf,
+        &[
+            ("x", ScalarType::Float),
+            ("y", ScalarType::Float),
+            ("z", ScalarType::Float),
+            ("red", ScalarType::UChar),
+            ("green", ScalarType::UChar),
+            ("blue", ScalarType::UChar),
+            ("label", ScalarType::UInt),
+        ],
+        vertices,
+    )
+}
+
+/// Writes a binary ply file with the positions, colors and curvatures (k1, k2, mean_curvature,
+/// gaussian_curvature) of the points.
+pub fn write_ply_curvature<P: AsRef<Path>>(
+    cloud: &PointCloud<PointXyzRgbaCurvature>,
+    path_buf: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let vertices = cloud
+        .points
+        .iter()
+        .map(|point| {
+            let mut vertex = DefaultElement::new();
+            vertex.insert("x".to_string(), Property::Float(point.x));
+            vertex.insert("y".to_string(), Property::Float(point.y));
+            vertex.insert("z".to_string(), Property::Float(point.z));
+            vertex.insert("red".to_string(), Property::UChar(point.r));
+            vertex.insert("green".to_string(), Property::UChar(point.g));
+            vertex.insert("blue".to_string(), Property::UChar(point.b));
+            vertex.insert("k1".to_string(), Property::Float(point.k1));
+            vertex.insert("k2".to_string(), Property::Float(point.k2));
+            vertex.insert(
+                "mean_curvature".to_string(),
+                Property::Float(point.mean_curvature),
+            );
+            vertex.insert(
+                "gaussian_curvature".to_string(),
+                Property::Float(point.gaussian_curvature),
+            );
+            vertex
+        })
+        .collect();
+    write_ply_vertices(
+        path_buf,
+        &[
+            ("x", ScalarType::Float),
+            ("y", ScalarType::Float),
+            ("z", ScalarType::Float),
+            ("red", ScalarType::UChar),
+            ("green", ScalarType::UChar),
+            ("blue", ScalarType::UChar),
+            ("k1", ScalarType::Float),
+            ("k2", ScalarType::Float),
+            ("mean_curvature", ScalarType::Float),
+            ("gaussian_curvature", ScalarType::Float),
+        ],
+        vertices,
+    )
+}
+
+/// Writes a binary ply file with the positions and colors of the points, including the alpha
+/// channel which the other writers drop.
+pub fn write_ply_with_alpha<P: AsRef<Path>>(
+    cloud: &PointCloud<PointXyzRgba>,
+    path_buf: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let vertices = cloud
+        .points
+        .iter()
+        .map(|point| {
+            let mut vertex = DefaultElement::new();
+            vertex.insert("x".to_string(), Property::Float(point.x));
+            vertex.insert("y".to_string(), Property::Float(point.y));
+            vertex.insert("z".to_string(), Property::Float(point.z));
+            vertex.insert("red".to_string(), Property::UChar(point.r));
+            vertex.insert("green".to_string(), Property::UChar(point.g));
+            vertex.insert("blue".to_string(), Property::UChar(point.b));
+            vertex.insert("alpha".to_string(), Property::UChar(point.a));
+            vertex
+        })
+        .collect();
+    write_ply_vertices(
+        path_buf,
+        &[
+            ("x", ScalarType::Float),
+            ("y", ScalarType::Float),
+            ("z", ScalarType::Float),
+            ("red", ScalarType::UChar),
+            ("green", ScalarType::UChar),
+            ("blue", ScalarType::UChar),
+            ("alpha", ScalarType::UChar),
+        ],
+        vertices,
+    )
+}
+
+/// Writes an ascii ply triangle mesh with the points as vertices, keeping their positions and
+/// colors, and the faces as lists of vertex indices.
+pub fn write_ply_mesh<P: AsRef<Path>>(
+    cloud: &PointCloud<PointXyzRgba>,
+    faces: &[[usize; 3]],
+    path_buf: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let vertices = cloud
+        .points
+        .iter()
+        .map(|point| {
+            let mut vertex = DefaultElement::new();
+            vertex.insert("x".to_string(), Property::Float(point.x));
+            vertex.insert("y".to_string(), Property::Float(point.y));
+            vertex.insert("z".to_string(), Property::Float(point.z));
+            vertex.insert("red".to_string(), Property::UChar(point.r));
+            vertex.insert("green".to_string(), Property::UChar(point.g));
+            vertex.insert("blue".to_string(), Property::UChar(point.b));
+            vertex
+        })
+        .collect();
+    write_ply_elements(
+        path_buf,
+        &[
+            ("x", ScalarType::Float),
+            ("y", ScalarType::Float),
+            ("z", ScalarType::Float),
+            ("red", ScalarType::UChar),
+            ("green", ScalarType::UChar),
+            ("blue", ScalarType::UChar),
+        ],
+        vertices,
+        faces,
+    )
+}
+
+/// Writes the vertices as a binary ply file, with the properties declared in the given order.
+fn write_ply_vertices<P: AsRef<Path>>(
+    path_buf: P,
+    properties: &[(&str, ScalarType)],
+    vertices: Vec<DefaultElement>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    write_ply_elements(path_buf, properties, vertices, &[])
+}
+
+/// Writes the vertices as a binary ply file, or as an ascii one with the faces if there are any.
+fn write_ply_elements<P: AsRef<Path>>(
+    path_buf: P,
+    properties: &[(&str, ScalarType)],
+    vertices: Vec<DefaultElement>,
+    faces: &[[usize; 3]],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut element = ElementDef::new("vertex".to_string());
+    for (name, scalar_type) in properties {
+        element.properties.insert(
+            name.to_string(),
+            PropertyDef::new(name.to_string(), PropertyType::Scalar(scalar_type.clone())),
+        );
+    }
+    element.count = vertices.len();
+
+    let mut header = Header::new();
+    header.encoding = Encoding::BinaryLittleEndian;
+    header.elements.insert("vertex".to_string(), element);
+
+    let mut payload = Payload::<DefaultElement>::new();
+    payload.insert("vertex".to_string(), vertices);
+
+    if !faces.is_empty() {
+        // the binary writer of ply-rs writes the number of elements as the length of every list
+        header.encoding = Encoding::Ascii;
+        let mut element = ElementDef::new("face".to_string());
+        element.properties.insert(
+            "vertex_indices".to_string(),
+            PropertyDef::new(
+                "vertex_indices".to_string(),
+                PropertyType::List(ScalarType::UChar, ScalarType::Int),
+            ),
+        );
+        element.count = faces.len();
+        header.elements.insert("face".to_string(), element);
+
+        let faces = faces
+            .iter()
+            .map(|face| {
+                let mut element = DefaultElement::new();
+                element.insert(
+                    "vertex_indices".to_string(),
+                    Property::ListInt(face.iter().map(|&i| i as i32).collect()),
+                );
+                element
+            })
+            .collect();
+        payload.insert("face".to_string(), faces);
+    }
+
+    let mut ply = Ply::<DefaultElement>::new();
+    ply.header = header;
+    ply.payload = payload;
+
+    let mut file = File::create(path_buf.as_ref())?;
+    ply_rs::writer::Writer::<DefaultElement>::new().write_ply(&mut file, &mut ply)?;
+    Ok(())
+}
+
+fn read_ply_vertices<T, P>(path_buf: P) -> Option<PointCloud<T>>
+where
+    T: ply_rs::ply::PropertyAccess,
+    P: AsRef<Path>,
+{
+    let vertex_parser = ply_rs::parser::Parser::<T>::new();
     let f = std::fs::File::open(path_buf.as_ref())
         .expect(&format!("Unable to open file {:?}", path_buf.as_ref()));
     let mut f = std::io::BufReader::new(f);
@@ -31,23 +342,54 @@ pub fn read_ply<P: AsRef<Path>>(path_buf: P) -> Option<PointCloud<PointXyzRgba>>
         path_buf.as_ref()
     ));
 
+    match read_vertex_payload(&vertex_parser, &mut f, &header) {
+        Ok(vertex_list) => Some(PointCloud::from_points(vertex_list)),
+        Err(e) => {
+            println!("Failed to convert {:?}\n{e}", path_buf.as_ref());
+            None
+        }
+    }
+}
+
+/// Reads a ply file held in memory, e.g. received over the network.
+/// Returns `None` if the header or the vertices cannot be parsed.
+pub fn read_ply_bytes(bytes: &[u8]) -> Option<PointCloud<PointXyzRgba>> {
+    let vertex_parser = ply_rs::parser::Parser::<PointXyzRgba>::new();
+    let mut reader = bytes;
+    let header = vertex_parser.read_header(&mut reader).ok()?;
+    let vertex_list = read_vertex_payload(&vertex_parser, &mut reader, &header).ok()?;
+    Some(PointCloud::from_points(vertex_list))
+}
+
+fn read_vertex_payload<T, R>(
+    vertex_parser: &ply_rs::parser::Parser<T>,
+    reader: &mut R,
+    header: &Header,
+) -> std::io::Result<Vec<T>>
+where
+    T: ply_rs::ply::PropertyAccess,
+    R: std::io::BufRead,
+{
     let mut vertex_list = Vec::new();
     for (_, element) in &header.elements {
         if element.name.as_str() == "vertex" {
-            vertex_list = match vertex_parser.read_payload_for_element(&mut f, element, &header) {
-                Ok(v) => v,
-                Err(e) => {
-                    println!("Failed to convert {:?}\n{e}", path_buf.as_ref());
-                    return None;
-                }
-            }
+            vertex_list = vertex_parser.read_payload_for_element(reader, element, header)?;
         }
     }
+    Ok(vertex_list)
+}
 
-    Some(PointCloud {
-        number_of_points: vertex_list.len(),
-        points: vertex_list,
-    })
+/// Unknown properties, e.g. normals read into a point without normals, are ignored with a warning
+/// printed once per property name.
+fn warn_unknown_property(key: &str) {
+    static WARNED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    let mut warned = WARNED
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .unwrap();
+    if warned.insert(key.to_string()) {
+        eprintln!("Ignoring unsupported ply property {}", key);
+    }
 }
 
 impl ply_rs::ply::PropertyAccess for PointXyzRgba {
@@ -74,8 +416,392 @@ impl ply_rs::ply::PropertyAccess for PointXyzRgba {
             ("red", Property::UChar(v)) => self.r = v,
             ("green", Property::UChar(v)) => self.g = v,
             ("blue", Property::UChar(v)) => self.b = v,
-            ("alpha", Property::UChar(v)) => self.a = v,
-            _ => {}
+            ("alpha", Property::UChar(v)) | ("a", Property::UChar(v)) => self.a = v,
+            (key, _) => warn_unknown_property(key),
+        }
+    }
+}
+
+impl ply_rs::ply::PropertyAccess for PointXyzRgbaNormal {
+    fn new() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+            nx: 0.0,
+            ny: 0.0,
+            nz: 0.0,
+        }
+    }
+
+    fn set_property(&mut self, key: &String, property: Property) {
+        match (key.as_ref(), property) {
+            ("x", Property::Double(v)) => self.x = v as f32,
+            ("y", Property::Double(v)) => self.y = v as f32,
+            ("z", Property::Double(v)) => self.z = v as f32,
+            ("x", Property::Float(v)) => self.x = v,
+            ("y", Property::Float(v)) => self.y = v,
+            ("z", Property::Float(v)) => self.z = v,
+            ("nx", Property::Double(v)) => self.nx = v as f32,
+            ("ny", Property::Double(v)) => self.ny = v as f32,
+            ("nz", Property::Double(v)) => self.nz = v as f32,
+            ("nx", Property::Float(v)) => self.nx = v,
+            ("ny", Property::Float(v)) => self.ny = v,
+            ("nz", Property::Float(v)) => self.nz = v,
+            ("red", Property::UChar(v)) => self.r = v,
+            ("green", Property::UChar(v)) => self.g = v,
+            ("blue", Property::UChar(v)) => self.b = v,
+            ("alpha", Property::UChar(v)) | ("a", Property::UChar(v)) => self.a = v,
+            (key, _) => warn_unknown_property(key),
+        }
+    }
+}
+
+impl ply_rs::ply::PropertyAccess for PointXyzRgbaF64 {
+    fn new() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        }
+    }
+
+    fn set_property(&mut self, key: &String, property: Property) {
+        match (key.as_ref(), property) {
+            ("x", Property::Double(v)) => self.x = v,
+            ("y", Property::Double(v)) => self.y = v,
+            ("z", Property::Double(v)) => self.z = v,
+            ("x", Property::Float(v)) => self.x = v as f64,
+            ("y", Property::Float(v)) => self.y = v as f64,
+            ("z", Property::Float(v)) => self.z = v as f64,
+            ("red", Property::UChar(v)) => self.r = v,
+            ("green", Property::UChar(v)) => self.g = v,
+            ("blue", Property::UChar(v)) => self.b = v,
+            ("alpha", Property::UChar(v)) | ("a", Property::UChar(v)) => self.a = v,
+            (key, _) => warn_unknown_property(key),
+        }
+    }
+}
+
+impl ply_rs::ply::PropertyAccess for PointXyzRgbaT {
+    fn new() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+            timestamp: 0.0,
+        }
+    }
+
+    fn set_property(&mut self, key: &String, property: Property) {
+        match (key.as_ref(), property) {
+            ("x", Property::Double(v)) => self.x = v as f32,
+            ("y", Property::Double(v)) => self.y = v as f32,
+            ("z", Property::Double(v)) => self.z = v as f32,
+            ("x", Property::Float(v)) => self.x = v,
+            ("y", Property::Float(v)) => self.y = v,
+            ("z", Property::Float(v)) => self.z = v,
+            ("red", Property::UChar(v)) => self.r = v,
+            ("green", Property::UChar(v)) => self.g = v,
+            ("blue", Property::UChar(v)) => self.b = v,
+            ("alpha", Property::UChar(v)) | ("a", Property::UChar(v)) => self.a = v,
+            ("timestamp" | "time" | "t", Property::Double(v)) => self.timestamp = v,
+            ("timestamp" | "time" | "t", Property::Float(v)) => self.timestamp = v as f64,
+            (key, _) => warn_unknown_property(key),
         }
     }
 }
+
+struct LabeledPoint {
+    point: PointXyzRgba,
+    label: u32,
+}
+
+impl ply_rs::ply::PropertyAccess for LabeledPoint {
+    fn new() -> Self {
+        Self {
+            point: PointXyzRgba::new(),
+            label: 0,
+        }
+    }
+
+    fn set_property(&mut self, key: &String, property: Property) {
+        match (key.as_ref(), property) {
+            ("label", Property::UChar(v)) => self.label = v as u32,
+            ("label", Property::UShort(v)) => self.label = v as u32,
+            ("label", Property::UInt(v)) => self.label = v,
+            ("label", Property::Char(v)) => self.label = v as u32,
+            ("label", Property::Short(v)) => self.label = v as u32,
+            ("label", Property::Int(v)) => self.label = v as u32,
+            (_, property) => self.point.set_property(key, property),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_alpha() {
+        for key in ["alpha", "a"] {
+            let ply = format!(
+                concat!(
+                    "ply\n",
+                    "format ascii 1.0\n",
+                    "element vertex 2\n",
+                    "property float x\n",
+                    "property float y\n",
+                    "property float z\n",
+                    "property uchar red\n",
+                    "property uchar green\n",
+                    "property uchar blue\n",
+                    "property uchar {}\n",
+                    "end_header\n",
+                    "1 2 3 10 20 30 0\n",
+                    "4 5 6 40 50 60 128\n",
+                ),
+                key
+            );
+            let path = std::env::temp_dir().join(format!("vivotk_test_read_alpha_{}.ply", key));
+            std::fs::write(&path, ply).unwrap();
+            let cloud = read_ply(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!(cloud.len(), 2);
+            assert_eq!(
+                cloud.points[0],
+                PointXyzRgba {
+                    x: 1.0,
+                    y: 2.0,
+                    z: 3.0,
+                    r: 10,
+                    g: 20,
+                    b: 30,
+                    a: 0
+                }
+            );
+            assert_eq!(cloud.points[1].a, 128);
+        }
+    }
+
+    #[test]
+    fn test_read_normals_into_pointxyzrgba() {
+        let ply = concat!(
+            "ply\n",
+            "format ascii 1.0\n",
+            "element vertex 2\n",
+            "property float x\n",
+            "property float y\n",
+            "property float z\n",
+            "property float nx\n",
+            "property float ny\n",
+            "property float nz\n",
+            "property uchar red\n",
+            "property uchar green\n",
+            "property uchar blue\n",
+            "end_header\n",
+            "1 2 3 0 0 1 10 20 30\n",
+            "4 5 6 1 0 0 40 50 60\n",
+        );
+        let path = std::env::temp_dir().join("vivotk_test_read_normals_into_pointxyzrgba.ply");
+        std::fs::write(&path, ply).unwrap();
+        let cloud = read_ply(&path).unwrap();
+        let with_normals = read_ply_normal(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            cloud.points,
+            vec![
+                PointXyzRgba {
+                    x: 1.0,
+                    y: 2.0,
+                    z: 3.0,
+                    r: 10,
+                    g: 20,
+                    b: 30,
+                    a: 255
+                },
+                PointXyzRgba {
+                    x: 4.0,
+                    y: 5.0,
+                    z: 6.0,
+                    r: 40,
+                    g: 50,
+                    b: 60,
+                    a: 255
+                }
+            ]
+        );
+        assert_eq!(
+            (with_normals.points[1].nx, with_normals.points[1].nz),
+            (1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_ply_with_alpha_round_trip() {
+        let points: Vec<PointXyzRgba> = (0..=255)
+            .map(|i| PointXyzRgba {
+                x: i as f32,
+                y: 0.5,
+                z: -1.0,
+                r: 1,
+                g: 2,
+                b: 3,
+                a: i as u8,
+            })
+            .collect();
+        let cloud = PointCloud::from_points(points);
+
+        let path = std::env::temp_dir().join("vivotk_test_ply_with_alpha_round_trip.ply");
+        write_ply_with_alpha(&cloud, &path).unwrap();
+        let read = read_ply(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.points, cloud.points);
+    }
+
+    #[test]
+    fn test_write_ply_mesh() {
+        let points: Vec<PointXyzRgba> = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]
+            .into_iter()
+            .map(|(x, y)| PointXyzRgba {
+                x,
+                y,
+                z: 0.0,
+                r: 10,
+                g: 20,
+                b: 30,
+                a: 255,
+            })
+            .collect();
+        let cloud = PointCloud::from_points(points);
+
+        let path = std::env::temp_dir().join("vivotk_test_write_ply_mesh.ply");
+        write_ply_mesh(&cloud, &[[0, 1, 2], [1, 3, 2]], &path).unwrap();
+        let read = read_ply(&path).unwrap();
+        let mut f = std::io::BufReader::new(File::open(&path).unwrap());
+        let ply = ply_rs::parser::Parser::<DefaultElement>::new()
+            .read_ply(&mut f)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.points, cloud.points);
+        let faces: Vec<&Property> = ply.payload["face"]
+            .iter()
+            .map(|face| &face["vertex_indices"])
+            .collect();
+        assert_eq!(
+            faces,
+            vec![
+                &Property::ListInt(vec![0, 1, 2]),
+                &Property::ListInt(vec![1, 3, 2])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_double_precision_ply() {
+        let coordinates = [
+            [0.1f64, -2.5, 1e-9],
+            [123456.789012345, 6543210.123456789, -0.000123456789],
+            [std::f64::consts::PI, std::f64::consts::E, 1.0 / 3.0],
+        ];
+        let mut ply = String::from(concat!(
+            "ply\n",
+            "format ascii 1.0\n",
+            "element vertex 3\n",
+            "property double x\n",
+            "property double y\n",
+            "property double z\n",
+            "property uchar red\n",
+            "property uchar green\n",
+            "property uchar blue\n",
+            "end_header\n",
+        ));
+        for [x, y, z] in coordinates {
+            ply.push_str(&format!("{} {} {} 10 20 30\n", x, y, z));
+        }
+        let path = std::env::temp_dir().join("vivotk_test_read_double_precision_ply.ply");
+        std::fs::write(&path, ply).unwrap();
+
+        let cloud = read_ply_f64(&path).unwrap();
+        let truncated = read_ply(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(cloud.len(), 3);
+        for (p, [x, y, z]) in cloud.points.iter().zip(coordinates) {
+            assert_eq!((p.x, p.y, p.z), (x, y, z));
+            assert_eq!((p.r, p.g, p.b, p.a), (10, 20, 30, 255));
+        }
+        for (p, [x, y, z]) in truncated.points.iter().zip(coordinates) {
+            assert_eq!((p.x, p.y, p.z), (x as f32, y as f32, z as f32));
+        }
+    }
+
+    #[test]
+    fn test_ply_normal_round_trip() {
+        let points: Vec<PointXyzRgbaNormal> = (0..100)
+            .map(|i| {
+                let (theta, phi) = (i as f32 * 0.1, i as f32 * 0.37);
+                PointXyzRgbaNormal {
+                    x: i as f32 * 0.25,
+                    y: -(i as f32) / 3.0,
+                    z: 1e3 + i as f32,
+                    r: i as u8,
+                    g: 255 - i as u8,
+                    b: (i * 2) as u8,
+                    a: 255,
+                    nx: theta.sin() * phi.cos(),
+                    ny: theta.sin() * phi.sin(),
+                    nz: theta.cos(),
+                }
+            })
+            .collect();
+        let cloud = PointCloud::from_points(points);
+
+        let path = std::env::temp_dir().join("vivotk_test_ply_normal_round_trip.ply");
+        write_ply_normal(&cloud, &path).unwrap();
+        let read = read_ply_normal(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.len(), cloud.len());
+        assert_eq!(read.points, cloud.points);
+        for (p, q) in read.points.iter().zip(&cloud.points) {
+            let magnitude =
+                |p: &PointXyzRgbaNormal| (p.nx * p.nx + p.ny * p.ny + p.nz * p.nz).sqrt();
+            assert_eq!(magnitude(p), magnitude(q));
+        }
+    }
+
+    #[test]
+    fn test_ply_label_round_trip() {
+        let points: Vec<PointXyzRgba> = (0..20)
+            .map(|i| {
+                PointXyzRgba::from_xyz_rgba([i as f32, 0.5, -(i as f32)], [i as u8, 1, 2, 255])
+            })
+            .collect();
+        let labels: Vec<u32> = (0..20).map(|i| i % 3 + 70000).collect();
+        let cloud = PointCloud::from_points(points);
+
+        let path = std::env::temp_dir().join("vivotk_test_ply_label_round_trip.ply");
+        write_ply_label(&cloud, &labels, &path).unwrap();
+        let (read, read_labels) = read_ply_label(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.points, cloud.points);
+        assert_eq!(read_labels, labels);
+    }
+}
@@ -60,6 +60,27 @@ impl Default for RenderBuilder {
 }
 
 impl RenderBuilder {
+    /// Builder whose event loop may run outside of the main thread, such as in a pipeline
+    /// subcommand. macOS only allows windows on the main thread, where it still panics.
+    pub fn any_thread() -> Self {
+        #[allow(unused_mut)]
+        let mut builder = EventLoopBuilder::<RenderEvent>::with_user_event();
+        #[cfg(target_os = "linux")]
+        {
+            use winit::platform::unix::EventLoopBuilderExtUnix;
+            builder.with_any_thread(true);
+        }
+        #[cfg(target_os = "windows")]
+        {
+            use winit::platform::windows::EventLoopBuilderExtWindows;
+            builder.with_any_thread(true);
+        }
+        Self {
+            event_loop: builder.build(),
+            window_objects: HashMap::new(),
+        }
+    }
+
     pub fn add_window<T>(&mut self, attachable: T) -> WindowId
     where
         T: Attachable,
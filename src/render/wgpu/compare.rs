@@ -0,0 +1,242 @@
+use std::iter;
+use std::time::Instant;
+
+use color_space::Rgb;
+use wgpu::util::StagingBelt;
+use wgpu_glyph::{ab_glyph, GlyphBrush, GlyphBrushBuilder, Section, Text};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{DeviceEvent, ElementState, Event, MouseButton, WindowEvent};
+use winit::event_loop::EventLoop;
+use winit::window::{Window, WindowBuilder, WindowId};
+
+use super::builder::{Attachable, RenderEvent, Windowed};
+use super::camera::{Camera, CameraState};
+use super::gpu::WindowGpu;
+use super::renderer::{parse_bg_color, PointCloudRenderer};
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+
+/// Shows two point clouds side by side through the same camera, the left one left of a vertical
+/// divider and the right one right of it. Dragging with the right mouse button moves the divider.
+pub struct CompareViewer {
+    left: PointCloud<PointXyzRgba>,
+    right: PointCloud<PointXyzRgba>,
+    labels: (String, String),
+    camera_state: CameraState,
+    size: PhysicalSize<u32>,
+    bg_color: Rgb,
+}
+
+impl CompareViewer {
+    pub fn new(
+        left: PointCloud<PointXyzRgba>,
+        right: PointCloud<PointXyzRgba>,
+        labels: (&str, &str),
+        camera: Camera,
+        (width, height): (u32, u32),
+        bg_color_str: &str,
+    ) -> Self {
+        Self {
+            left,
+            right,
+            labels: (labels.0.to_string(), labels.1.to_string()),
+            camera_state: CameraState::new(camera, width, height),
+            size: PhysicalSize { width, height },
+            bg_color: parse_bg_color(bg_color_str).unwrap(),
+        }
+    }
+}
+
+impl Attachable for CompareViewer {
+    type Output = CompareState;
+
+    fn attach(self, event_loop: &EventLoop<RenderEvent>) -> (Self::Output, Window) {
+        let window = WindowBuilder::new()
+            .with_title("Point Cloud Compare")
+            .with_position(PhysicalPosition { x: 0, y: 0 })
+            .with_resizable(true)
+            .with_min_inner_size(self.size)
+            .with_max_inner_size(PhysicalSize::new(2048, 2048))
+            .build(event_loop)
+            .unwrap();
+
+        let gpu = pollster::block_on(WindowGpu::new(&window));
+        let renderer = |cloud: &PointCloud<PointXyzRgba>| {
+            PointCloudRenderer::new(
+                &gpu.device,
+                gpu.config.format,
+                cloud,
+                gpu.size,
+                &self.camera_state,
+                self.bg_color,
+            )
+        };
+        let left = renderer(&self.left);
+        let right = renderer(&self.right);
+
+        let font = ab_glyph::FontArc::try_from_slice(include_bytes!("Inconsolata-Regular.ttf"))
+            .expect("Could not initialize font");
+        let glyph_brush = GlyphBrushBuilder::using_font(font).build(&gpu.device, gpu.config.format);
+
+        let state = CompareState {
+            gpu,
+            camera_state: self.camera_state,
+            left,
+            right,
+            labels: self.labels,
+            divider: 0.5,
+            dragging: false,
+            cursor_x: 0.0,
+            glyph_brush,
+            staging_belt: StagingBelt::new(1024),
+            last_render_time: None,
+        };
+        (state, window)
+    }
+}
+
+pub struct CompareState {
+    gpu: WindowGpu,
+    camera_state: CameraState,
+    left: PointCloudRenderer<PointCloud<PointXyzRgba>>,
+    right: PointCloudRenderer<PointCloud<PointXyzRgba>>,
+    labels: (String, String),
+    /// position of the divider as a fraction of the width of the window
+    divider: f32,
+    dragging: bool,
+    cursor_x: f64,
+    glyph_brush: GlyphBrush<()>,
+    staging_belt: StagingBelt,
+    last_render_time: Option<Instant>,
+}
+
+impl Windowed for CompareState {
+    fn add_output(&mut self, _window_id: WindowId) {}
+
+    fn handle_event(&mut self, event: &Event<RenderEvent>, window: &Window) {
+        match event {
+            Event::DeviceEvent { ref event, .. } => {
+                if let DeviceEvent::Key(_) = event {
+                    return;
+                }
+                self.camera_state.process_input(event);
+            }
+            Event::WindowEvent { event, window_id } if *window_id == window.id() => match event {
+                WindowEvent::KeyboardInput { input, .. } => {
+                    self.camera_state.process_input(&DeviceEvent::Key(*input));
+                }
+                WindowEvent::MouseInput {
+                    button: MouseButton::Right,
+                    state,
+                    ..
+                } => {
+                    self.dragging = *state == ElementState::Pressed;
+                    if self.dragging {
+                        self.move_divider();
+                    }
+                }
+                WindowEvent::CursorMoved { position, .. } => {
+                    self.cursor_x = position.x;
+                    if self.dragging {
+                        self.move_divider();
+                    }
+                }
+                _ => {}
+            },
+            Event::RedrawRequested(window_id) if *window_id == window.id() => {
+                let now = Instant::now();
+                let dt = now - self.last_render_time.unwrap_or(now);
+                self.last_render_time = Some(now);
+                self.camera_state.update(dt);
+                let camera_uniform = self.camera_state.camera_uniform();
+                self.left.update_camera(&self.gpu.queue, camera_uniform);
+                self.right.update_camera(&self.gpu.queue, camera_uniform);
+                match self.render() {
+                    Ok(_) => {}
+                    Err(wgpu::SurfaceError::OutOfMemory) => {}
+                    Err(e) => eprintln!("Dropped frame due to {:?}", e),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+        self.camera_state.resize(new_size);
+        self.left.resize(new_size, &self.gpu.device);
+        self.right.resize(new_size, &self.gpu.device);
+    }
+}
+
+impl CompareState {
+    fn move_divider(&mut self) {
+        let width = self.gpu.size.width.max(1) as f64;
+        self.divider = (self.cursor_x / width).clamp(0.0, 1.0) as f32;
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let (output, view) = self.gpu.create_view()?;
+        let mut encoder = self.gpu.create_encoder();
+
+        let PhysicalSize { width, height } = self.gpu.size;
+        // both viewports keep at least one column, as scissor rectangles cannot be empty
+        let split = ((self.divider * width as f32).round() as u32).clamp(1, width.max(2) - 1);
+        self.left
+            .render_region(&mut encoder, &view, true, Some([0, 0, split, height]));
+        self.right.render_region(
+            &mut encoder,
+            &view,
+            false,
+            Some([split, 0, width - split, height]),
+        );
+        self.draw_divider(&mut encoder, &view, split);
+
+        self.staging_belt.finish();
+        self.gpu.queue.submit(iter::once(encoder.finish()));
+        output.present();
+        self.staging_belt.recall();
+        Ok(())
+    }
+
+    /// Draws the divider as a column of bars, with the labels of both sides next to it.
+    fn draw_divider(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        x: u32,
+    ) {
+        let PhysicalSize { width, height } = self.gpu.size;
+        let bounds = (width as f32, height as f32);
+        let color = [0.0, 0.0, 0.0, 1.0];
+        let bars = "|\n".repeat(height as usize / 20 + 1);
+        self.glyph_brush.queue(Section {
+            screen_position: (x as f32 - 5.0, 0.0),
+            bounds,
+            text: vec![Text::new(&bars).with_color(color).with_scale(20.0)],
+            ..Section::default()
+        });
+        let (left, right) = &self.labels;
+        self.glyph_brush.queue(Section {
+            screen_position: (x as f32 - 15.0 - 10.0 * left.len() as f32, 30.0),
+            bounds,
+            text: vec![Text::new(left).with_color(color).with_scale(20.0)],
+            ..Section::default()
+        });
+        self.glyph_brush.queue(Section {
+            screen_position: (x as f32 + 15.0, 30.0),
+            bounds,
+            text: vec![Text::new(right).with_color(color).with_scale(20.0)],
+            ..Section::default()
+        });
+        self.glyph_brush
+            .draw_queued(
+                &self.gpu.device,
+                &mut self.staging_belt,
+                encoder,
+                view,
+                width,
+                height,
+            )
+            .expect("Draw queued");
+    }
+}
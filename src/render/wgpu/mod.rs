@@ -1,6 +1,7 @@
 pub mod antialias;
 pub mod builder;
 pub mod camera;
+pub mod compare;
 pub mod controls;
 mod gpu;
 pub mod metrics_reader;
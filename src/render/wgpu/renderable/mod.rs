@@ -162,13 +162,13 @@ impl Renderable for PointCloud<PointXyzRgba> {
         unsafe {
             std::slice::from_raw_parts(
                 (self.points.as_ptr()) as *const u8,
-                self.number_of_points * std::mem::size_of::<PointXyzRgba>(),
+                self.len() * std::mem::size_of::<PointXyzRgba>(),
             )
         }
     }
 
     fn vertices(&self) -> usize {
-        self.number_of_points
+        self.len()
     }
 
     fn create_depth_texture(
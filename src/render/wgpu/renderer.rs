@@ -57,6 +57,24 @@ pub enum PlaybackState {
     Play,
 }
 
+/// How a sequence is played back, the defaults start paused on the first frame and loop.
+#[derive(Debug, Clone, Copy)]
+pub struct Playback {
+    pub start_frame: usize,
+    pub looping: bool,
+    pub autoplay: bool,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            start_frame: 0,
+            looping: true,
+            autoplay: false,
+        }
+    }
+}
+
 pub struct Renderer<T, U>
 where
     T: RenderReader<U>,
@@ -69,6 +87,7 @@ where
     metrics_reader: Option<MetricsReader>,
     _data: PhantomData<U>,
     bg_color: Rgb,
+    playback: Playback,
 }
 
 impl<T, U> Renderer<T, U>
@@ -92,8 +111,14 @@ where
             metrics_reader,
             _data: PhantomData::default(),
             bg_color: parse_bg_color(bg_color_str).unwrap(),
+            playback: Playback::default(),
         }
     }
+
+    pub fn with_playback(mut self, playback: Playback) -> Self {
+        self.playback = playback;
+        self
+    }
 }
 
 impl<T, U> Attachable for Renderer<T, U>
@@ -123,6 +148,7 @@ where
             self.camera_state,
             self.metrics_reader,
             self.bg_color,
+            self.playback,
         );
         (state, window)
     }
@@ -148,6 +174,7 @@ where
     fps: f32, // the average playout fps
     time_to_advance: std::time::Duration,
     state: PlaybackState,
+    looping: bool,
     time_since_last_update: std::time::Duration,
     reader: T,
 
@@ -227,10 +254,11 @@ where
         camera_state: CameraState,
         metrics_reader: Option<MetricsReader>,
         bg_color: Rgb,
+        playback: Playback,
     ) -> Self {
         let initial_render = reader
-            .start()
-            .expect("There should be at least one point cloud to render!");
+            .get_at(playback.start_frame)
+            .expect("There should be a point cloud to render at the start frame!");
         let pcd_renderer = PointCloudRenderer::new(
             &gpu.device,
             gpu.config.format,
@@ -251,10 +279,20 @@ where
             pcd_renderer,
             camera_state,
 
-            current_position: 0,
+            current_position: playback.start_frame,
             fps,
-            time_to_advance: std::time::Duration::from_secs(1).div_f32(fps),
-            state: PlaybackState::Paused,
+            // a non-positive fps advances on every redraw, as fast as frames can be uploaded
+            time_to_advance: if fps > 0.0 {
+                std::time::Duration::from_secs(1).div_f32(fps)
+            } else {
+                std::time::Duration::ZERO
+            },
+            state: if playback.autoplay {
+                PlaybackState::Play
+            } else {
+                PlaybackState::Paused
+            },
+            looping: playback.looping,
             time_since_last_update: std::time::Duration::from_secs(0),
             reader,
 
@@ -321,7 +359,11 @@ where
         //     self.current_position
         // );
         if self.current_position == self.reader.len() - 1 {
-            self.move_to(0);
+            if self.looping {
+                self.move_to(0);
+            } else {
+                self.pause();
+            }
         } else {
             self.move_to(self.current_position + 1);
         }
@@ -510,20 +552,35 @@ where
     }
 
     pub fn render(&mut self, encoder: &mut CommandEncoder, view: &TextureView) {
+        self.render_region(encoder, view, true, None);
+    }
+
+    /// Renders the points inside the `[x, y, width, height]` scissor rectangle only, so that
+    /// several renderers can share a view as side by side viewports. The background is cleared
+    /// over the whole view if `clear`, and kept otherwise.
+    pub fn render_region(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        view: &TextureView,
+        clear: bool,
+        scissor: Option<[u32; 4]>,
+    ) {
+        let load = if clear {
+            LoadOp::Clear(wgpu::Color {
+                r: self.bg_color.r / 255.0,
+                g: self.bg_color.g / 255.0,
+                b: self.bg_color.b / 255.0,
+                a: 1.0,
+            })
+        } else {
+            LoadOp::Load
+        };
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view,
                 resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: self.bg_color.r / 255.0,
-                        g: self.bg_color.g / 255.0,
-                        b: self.bg_color.b / 255.0,
-                        a: 1.0,
-                    }),
-                    store: true,
-                },
+                ops: wgpu::Operations { load, store: true },
             })],
             depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
                 view: &self.depth_view,
@@ -535,6 +592,9 @@ where
             }),
         });
 
+        if let Some([x, y, width, height]) = scissor {
+            render_pass.set_scissor_rect(x, y, width, height);
+        }
         render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
         render_pass.set_bind_group(1, &self.antialias_bind_group, &[]);
@@ -0,0 +1,288 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+
+use super::neighbours::NearestNeighbours;
+
+type Rotation = [[f32; 3]; 3];
+
+fn squared_distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+fn rotate(m: &Rotation, p: &[f32; 3]) -> [f32; 3] {
+    [
+        m[0][0] * p[0] + m[0][1] * p[1] + m[0][2] * p[2],
+        m[1][0] * p[0] + m[1][1] * p[1] + m[1][2] * p[2],
+        m[2][0] * p[0] + m[2][1] * p[1] + m[2][2] * p[2],
+    ]
+}
+
+/// Uniformly random rotation, from a random unit quaternion.
+fn random_rotation(rng: &mut StdRng) -> Rotation {
+    let (u1, u2, u3): (f32, f32, f32) = (rng.gen(), rng.gen(), rng.gen());
+    let tau = std::f32::consts::TAU;
+    let (w, x, y, z) = (
+        (1.0 - u1).sqrt() * (tau * u2).sin(),
+        (1.0 - u1).sqrt() * (tau * u2).cos(),
+        u1.sqrt() * (tau * u3).sin(),
+        u1.sqrt() * (tau * u3).cos(),
+    );
+    [
+        [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - w * z),
+            2.0 * (x * z + w * y),
+        ],
+        [
+            2.0 * (x * y + w * z),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - w * x),
+        ],
+        [
+            2.0 * (x * z - w * y),
+            2.0 * (y * z + w * x),
+            1.0 - 2.0 * (x * x + y * y),
+        ],
+    ]
+}
+
+/// The points `indices[start..end]` of a tree, split in two children unless it is a leaf.
+struct Node {
+    start: usize,
+    end: usize,
+    split: Option<Split>,
+}
+
+/// Points below `value` along `axis` are in the `left` node, the others in the `right` node.
+struct Split {
+    axis: usize,
+    value: f32,
+    left: usize,
+    right: usize,
+}
+
+/// A kd tree over the points in a random rotation of the data, which is only descended to a
+/// single leaf when searched.
+struct RpTree {
+    rotation: Rotation,
+    indices: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+impl RpTree {
+    fn build(points: &[[f32; 3]], leaf_size: usize, rng: &mut StdRng) -> Self {
+        let rotation = random_rotation(rng);
+        let rotated: Vec<[f32; 3]> = points.iter().map(|p| rotate(&rotation, p)).collect();
+        let mut tree = RpTree {
+            rotation,
+            indices: (0..points.len()).collect(),
+            nodes: vec![],
+        };
+        tree.build_node(&rotated, 0, points.len(), 0, leaf_size);
+        tree
+    }
+
+    /// Pushes the node over `indices[start..end]` and returns its position in `nodes`.
+    fn build_node(
+        &mut self,
+        rotated: &[[f32; 3]],
+        start: usize,
+        end: usize,
+        depth: usize,
+        leaf_size: usize,
+    ) -> usize {
+        let position = self.nodes.len();
+        self.nodes.push(Node {
+            start,
+            end,
+            split: None,
+        });
+        if end - start <= leaf_size {
+            return position;
+        }
+
+        let axis = depth % 3;
+        let mid = (end - start) / 2;
+        let indices = &mut self.indices[start..end];
+        indices.select_nth_unstable_by(mid, |&a, &b| rotated[a][axis].total_cmp(&rotated[b][axis]));
+        let value = rotated[indices[mid]][axis];
+        // points equal to the median go right, so that a query at a point reaches its leaf
+        indices.sort_unstable_by_key(|&i| rotated[i][axis] >= value);
+        let split = start + indices.partition_point(|&i| rotated[i][axis] < value);
+        if split == start {
+            // all the points share the median, they can't be split further along this axis
+            return position;
+        }
+
+        let left = self.build_node(rotated, start, split, depth + 1, leaf_size);
+        let right = self.build_node(rotated, split, end, depth + 1, leaf_size);
+        self.nodes[position].split = Some(Split {
+            axis,
+            value,
+            left,
+            right,
+        });
+        position
+    }
+
+    /// Returns the points of the deepest node on the path of the query which holds at least `k`
+    /// points, or all the points if there are less than `k`.
+    fn candidates(&self, point: &[f32; 3], k: usize) -> &[usize] {
+        let rotated = rotate(&self.rotation, point);
+        let mut node = &self.nodes[0];
+        while let Some(split) = &node.split {
+            let child = if rotated[split.axis] < split.value {
+                &self.nodes[split.left]
+            } else {
+                &self.nodes[split.right]
+            };
+            if child.end - child.start < k {
+                break;
+            }
+            node = child;
+        }
+        &self.indices[node.start..node.end]
+    }
+}
+
+/// Approximate nearest neighbour search with a forest of random projection trees.
+///
+/// Each tree is a kd tree over a random rotation of the points. A query descends each tree to a
+/// single leaf without backtracking, and the `k` nearest points of the union of the leaves are
+/// returned. More trees and larger leaves give a better recall at the cost of speed.
+pub struct ApproxKnn {
+    points: Vec<[f32; 3]>,
+    trees: Vec<RpTree>,
+}
+
+impl ApproxKnn {
+    pub const DEFAULT_TREES: usize = 8;
+    pub const DEFAULT_LEAF_SIZE: usize = 64;
+
+    pub fn new(points: Vec<[f32; 3]>) -> Self {
+        Self::with_params(points, Self::DEFAULT_TREES, Self::DEFAULT_LEAF_SIZE, 0)
+    }
+
+    pub fn with_params(points: Vec<[f32; 3]>, trees: usize, leaf_size: usize, seed: u64) -> Self {
+        assert!(trees > 0, "Expecting at least one tree");
+        assert!(leaf_size > 0, "Expecting a leaf size of at least one");
+        let seeds: Vec<u64> = {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..trees).map(|_| rng.gen()).collect()
+        };
+        let trees = seeds
+            .into_par_iter()
+            .map(|seed| RpTree::build(&points, leaf_size, &mut StdRng::seed_from_u64(seed)))
+            .collect();
+        Self { points, trees }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+impl NearestNeighbours for ApproxKnn {
+    fn nearest_neighbours(&self, point: &[f32; 3], k: usize) -> Vec<(f32, usize)> {
+        let mut candidates: Vec<usize> = self
+            .trees
+            .iter()
+            .flat_map(|tree| tree.candidates(point, k).iter().copied())
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let mut nearest: Vec<(f32, usize)> = candidates
+            .into_iter()
+            .map(|i| (squared_distance(point, &self.points[i]), i))
+            .collect();
+        nearest.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        nearest.truncate(k);
+        nearest
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use kiddo::KdTree;
+
+    fn random_points(rng: &mut StdRng, n: usize) -> Vec<[f32; 3]> {
+        (0..n)
+            .map(|_| {
+                [
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_random_rotation_is_orthonormal() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let m = random_rotation(&mut rng);
+        for i in 0..3 {
+            for j in 0..3 {
+                let dot: f32 = (0..3).map(|k| m[i][k] * m[j][k]).sum();
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((dot - expected).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_recall() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let points = random_points(&mut rng, 20_000);
+        let mut tree = KdTree::new();
+        for (i, p) in points.iter().enumerate() {
+            tree.add(p, i).unwrap();
+        }
+        let forest = ApproxKnn::new(points.clone());
+        assert_eq!(forest.len(), 20_000);
+
+        let k = 10;
+        let queries = random_points(&mut rng, 500);
+        let mut found = 0;
+        for query in &queries {
+            let exact = tree.nearest_neighbours(query, k);
+            let approximate = forest.nearest_neighbours(query, k);
+            assert_eq!(approximate.len(), k);
+            // the approximate neighbours are real points at their true distance
+            for &(d, i) in &approximate {
+                assert_eq!(d, squared_distance(query, &points[i]));
+            }
+            found += exact
+                .iter()
+                .filter(|(_, i)| approximate.iter().any(|(_, j)| i == j))
+                .count();
+        }
+        let recall = found as f64 / (queries.len() * k) as f64;
+        assert!(recall > 0.8, "{}", recall);
+    }
+
+    #[test]
+    fn test_query_at_a_point_finds_it() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let points = random_points(&mut rng, 1_000);
+        let forest = ApproxKnn::with_params(points.clone(), 1, 4, 0);
+        for (i, p) in points.iter().enumerate() {
+            assert_eq!(forest.nearest_neighbours(p, 1), vec![(0.0, i)]);
+        }
+    }
+
+    #[test]
+    fn test_fewer_points_than_k() {
+        let forest = ApproxKnn::new(vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]]);
+        assert_eq!(forest.nearest_neighbours(&[0.9, 0.0, 0.0], 5).len(), 2);
+        assert!(ApproxKnn::new(vec![])
+            .nearest_neighbours(&[0.0, 0.0, 0.0], 1)
+            .is_empty());
+    }
+}
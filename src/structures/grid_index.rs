@@ -0,0 +1,189 @@
+use cgmath::{MetricSpace, Point3};
+use kiddo::KdTree;
+use std::collections::HashMap;
+
+use crate::formats::HasCoordinate;
+
+type Cell = (i32, i32, i32);
+
+/// A uniform grid over the points, for clouds whose density is roughly even, e.g. after voxel
+/// downsampling. Inserting is constant time, and a radius query only visits the cells overlapping
+/// the query sphere, which is constant time when `cell_size` is close to the query radius.
+pub struct GridIndex<T> {
+    cell_size: f32,
+    points: Vec<T>,
+    cells: HashMap<Cell, Vec<usize>>,
+}
+
+impl<T: HasCoordinate> GridIndex<T> {
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "Expecting a positive cell size");
+        Self {
+            cell_size,
+            points: vec![],
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, p: &Point3<f32>) -> Cell {
+        (
+            (p.x / self.cell_size).floor() as i32,
+            (p.y / self.cell_size).floor() as i32,
+            (p.z / self.cell_size).floor() as i32,
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn points(&self) -> &[T] {
+        &self.points
+    }
+
+    pub fn insert(&mut self, point: T) {
+        let cell = self.cell(&point.coordinate());
+        self.cells.entry(cell).or_default().push(self.points.len());
+        self.points.push(point);
+    }
+
+    /// Returns the points within `radius` of the center.
+    pub fn query_radius(&self, center: &Point3<f32>, radius: f32) -> Vec<&T> {
+        let radius_squared = radius * radius;
+        let min = self.cell(&Point3::new(
+            center.x - radius,
+            center.y - radius,
+            center.z - radius,
+        ));
+        let max = self.cell(&Point3::new(
+            center.x + radius,
+            center.y + radius,
+            center.z + radius,
+        ));
+
+        let mut within = vec![];
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                for z in min.2..=max.2 {
+                    if let Some(indices) = self.cells.get(&(x, y, z)) {
+                        within.extend(
+                            indices
+                                .iter()
+                                .map(|&i| &self.points[i])
+                                .filter(|p| p.coordinate().distance2(*center) <= radius_squared),
+                        );
+                    }
+                }
+            }
+        }
+        within
+    }
+
+    /// Builds a kd tree over the points, for clouds which turn out not to be uniform.
+    /// The items of the tree index into the returned points.
+    pub fn into_kdtree(self) -> (Vec<T>, KdTree<f32, usize, 3>) {
+        let mut tree = KdTree::new();
+        for (i, p) in self.points.iter().enumerate() {
+            let c = p.coordinate();
+            tree.add(&[c.x, c.y, c.z], i)
+                .expect("Failed to add to tree");
+        }
+        (self.points, tree)
+    }
+}
+
+impl<T: HasCoordinate> Extend<T> for GridIndex<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, points: I) {
+        for point in points {
+            self.insert(point);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::formats::pointxyzrgba::PointXyzRgba;
+    use kiddo::distance::squared_euclidean;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn point(x: f32, y: f32, z: f32) -> PointXyzRgba {
+        PointXyzRgba {
+            x,
+            y,
+            z,
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        }
+    }
+
+    #[test]
+    fn test_query_radius_matches_kdtree() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut grid = GridIndex::new(0.5);
+        grid.extend((0..5_000).map(|_| {
+            point(
+                rng.gen_range(-5.0..5.0),
+                rng.gen_range(-5.0..5.0),
+                rng.gen_range(-5.0..5.0),
+            )
+        }));
+        let queries: Vec<PointXyzRgba> = (0..100)
+            .map(|_| {
+                point(
+                    rng.gen_range(-6.0..6.0),
+                    rng.gen_range(-6.0..6.0),
+                    rng.gen_range(-6.0..6.0),
+                )
+            })
+            .collect();
+
+        let mut expected = vec![];
+        for q in &queries {
+            let mut within: Vec<[u32; 3]> = grid
+                .query_radius(&q.coordinate(), 0.7)
+                .into_iter()
+                .map(|p| [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()])
+                .collect();
+            within.sort();
+            expected.push(within);
+        }
+
+        let (points, tree) = grid.into_kdtree();
+        for (q, expected) in queries.iter().zip(expected) {
+            let mut within: Vec<[u32; 3]> = tree
+                .within(&[q.x, q.y, q.z], 0.49, &squared_euclidean)
+                .unwrap()
+                .into_iter()
+                .map(|(_, &i)| {
+                    [
+                        points[i].x.to_bits(),
+                        points[i].y.to_bits(),
+                        points[i].z.to_bits(),
+                    ]
+                })
+                .collect();
+            within.sort();
+            assert_eq!(within, expected);
+        }
+    }
+
+    #[test]
+    fn test_negative_coordinates() {
+        let mut grid = GridIndex::new(1.0);
+        grid.insert(point(-0.1, 0.0, 0.0));
+        grid.insert(point(0.1, 0.0, 0.0));
+        grid.insert(point(2.0, 0.0, 0.0));
+        assert_eq!(grid.len(), 3);
+        assert_eq!(grid.query_radius(&Point3::new(0.0, 0.0, 0.0), 0.2).len(), 2);
+        assert!(grid
+            .query_radius(&Point3::new(5.0, 0.0, 0.0), 1.0)
+            .is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use kiddo::{distance::squared_euclidean, KdTree};
+
+fn squared_distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+/// A kd tree for clouds that grow between frames. New points go into a small buffer which is
+/// searched linearly alongside the tree, and the tree is only rebuilt once the buffer holds more
+/// than `rebuild_threshold` points, instead of on every frame.
+///
+/// Points are identified by their insertion order, starting at 0.
+pub struct IncrementalKdTree {
+    tree: KdTree<f32, usize, 3>,
+    points: Vec<[f32; 3]>,
+    /// `points[..tree_size]` are in the tree, the rest are in the buffer
+    tree_size: usize,
+    rebuild_threshold: usize,
+}
+
+impl IncrementalKdTree {
+    pub fn new(rebuild_threshold: usize) -> Self {
+        Self {
+            tree: KdTree::new(),
+            points: vec![],
+            tree_size: 0,
+            rebuild_threshold,
+        }
+    }
+
+    /// Builds the tree over the points, with an empty buffer.
+    pub fn from_points(points: Vec<[f32; 3]>, rebuild_threshold: usize) -> Self {
+        let mut tree = Self {
+            tree: KdTree::new(),
+            points,
+            tree_size: 0,
+            rebuild_threshold,
+        };
+        tree.rebuild();
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Number of points waiting in the buffer.
+    pub fn buffer_len(&self) -> usize {
+        self.points.len() - self.tree_size
+    }
+
+    /// Inserts the point and returns its index.
+    pub fn insert(&mut self, point: [f32; 3]) -> usize {
+        self.points.push(point);
+        if self.buffer_len() > self.rebuild_threshold {
+            self.rebuild();
+        }
+        self.points.len() - 1
+    }
+
+    pub fn extend<I: IntoIterator<Item = [f32; 3]>>(&mut self, points: I) {
+        for point in points {
+            self.insert(point);
+        }
+    }
+
+    /// Moves the buffer into a new tree over all the points.
+    pub fn rebuild(&mut self) {
+        let mut tree = KdTree::new();
+        for (i, p) in self.points.iter().enumerate() {
+            tree.add(p, i).expect("Failed to add point to kd tree");
+        }
+        self.tree = tree;
+        self.tree_size = self.points.len();
+    }
+
+    /// Returns the squared distance and index of the `k` nearest points, nearest first.
+    pub fn nearest(&self, point: &[f32; 3], k: usize) -> Vec<(f32, usize)> {
+        let mut nearest: Vec<(f32, usize)> = if self.tree_size == 0 {
+            vec![]
+        } else {
+            self.tree
+                .nearest(point, k, &squared_euclidean)
+                .expect("Failed to query kd tree")
+                .into_iter()
+                .map(|(d, &i)| (d, i))
+                .collect()
+        };
+        nearest.extend(
+            self.points[self.tree_size..]
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (squared_distance(point, p), self.tree_size + i)),
+        );
+        nearest.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        nearest.truncate(k);
+        nearest
+    }
+
+    /// Returns the squared distance and index of the points within `radius_squared`, nearest first.
+    pub fn within(&self, point: &[f32; 3], radius_squared: f32) -> Vec<(f32, usize)> {
+        let mut within: Vec<(f32, usize)> = if self.tree_size == 0 {
+            vec![]
+        } else {
+            self.tree
+                .within(point, radius_squared, &squared_euclidean)
+                .expect("Failed to query kd tree")
+                .into_iter()
+                .map(|(d, &i)| (d, i))
+                .collect()
+        };
+        within.extend(
+            self.points[self.tree_size..]
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (squared_distance(point, p), self.tree_size + i))
+                .filter(|&(d, _)| d <= radius_squared),
+        );
+        within.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        within
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn random_points(rng: &mut StdRng, n: usize) -> Vec<[f32; 3]> {
+        (0..n)
+            .map(|_| {
+                [
+                    rng.gen_range(-10.0..10.0),
+                    rng.gen_range(-10.0..10.0),
+                    rng.gen_range(-10.0..10.0),
+                ]
+            })
+            .collect()
+    }
+
+    fn brute_force_nearest(points: &[[f32; 3]], point: &[f32; 3], k: usize) -> Vec<usize> {
+        let mut distances: Vec<(f32, usize)> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (squared_distance(point, p), i))
+            .collect();
+        distances.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        distances.into_iter().take(k).map(|(_, i)| i).collect()
+    }
+
+    #[test]
+    fn test_queries_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut points = random_points(&mut rng, 500);
+        let mut tree = IncrementalKdTree::from_points(points.clone(), 50);
+        assert_eq!(tree.buffer_len(), 0);
+
+        for frame in 0..10 {
+            let new_points = random_points(&mut rng, 20);
+            tree.extend(new_points.iter().copied());
+            points.extend(new_points);
+            assert_eq!(tree.len(), points.len());
+            assert!(tree.buffer_len() <= 50);
+            if frame == 1 {
+                // 40 points are still buffered
+                assert_eq!(tree.buffer_len(), 40);
+            }
+
+            for query in random_points(&mut rng, 20) {
+                let nearest: Vec<usize> = tree
+                    .nearest(&query, 5)
+                    .into_iter()
+                    .map(|(_, i)| i)
+                    .collect();
+                assert_eq!(nearest, brute_force_nearest(&points, &query, 5));
+
+                let within = tree.within(&query, 4.0);
+                let expected = points
+                    .iter()
+                    .filter(|p| squared_distance(&query, p) <= 4.0)
+                    .count();
+                assert_eq!(within.len(), expected);
+                assert!(within
+                    .iter()
+                    .all(|&(d, i)| d == squared_distance(&query, &points[i])));
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let mut tree = IncrementalKdTree::new(4);
+        assert!(tree.is_empty());
+        assert!(tree.nearest(&[0.0, 0.0, 0.0], 3).is_empty());
+
+        assert_eq!(tree.insert([1.0, 0.0, 0.0]), 0);
+        assert_eq!(tree.insert([2.0, 0.0, 0.0]), 1);
+        assert_eq!(tree.buffer_len(), 2);
+        assert_eq!(tree.nearest(&[0.0, 0.0, 0.0], 3), vec![(1.0, 0), (4.0, 1)]);
+    }
+}
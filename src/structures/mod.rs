@@ -0,0 +1,5 @@
+pub mod approx_knn;
+pub mod grid_index;
+pub mod incremental_kdtree;
+pub mod neighbours;
+pub mod octree;
@@ -0,0 +1,162 @@
+use cgmath::{MetricSpace, Point3, Vector3};
+
+use crate::formats::HasCoordinate;
+
+/// Leaves holding more points are split into octants.
+const MAX_LEAF_POINTS: usize = 16;
+/// Leaves at this depth are not split further, which bounds the depth for duplicate points.
+const MAX_DEPTH: usize = 16;
+
+enum Node {
+    Leaf(Vec<usize>),
+    /// children indexed by octant, see [`octant`]
+    Branch(Vec<Node>),
+}
+
+/// Octant of the point relative to the center, bit 0 set for the positive x half, bit 1 for y
+/// and bit 2 for z.
+fn octant(center: &Point3<f32>, p: &Point3<f32>) -> usize {
+    (p.x >= center.x) as usize
+        | ((p.y >= center.y) as usize) << 1
+        | ((p.z >= center.z) as usize) << 2
+}
+
+fn child_center(center: &Point3<f32>, octant: usize, quarter: f32) -> Point3<f32> {
+    let offset = |bit: usize| if octant & bit != 0 { quarter } else { -quarter };
+    *center + Vector3::new(offset(1), offset(2), offset(4))
+}
+
+/// An octree over the points, splitting the bounding cube of the cloud into octants until every
+/// leaf holds at most 16 points. A radius query only visits the cubes overlapping the query
+/// sphere. Points are identified by their index in the slice the tree is built from.
+pub struct Octree {
+    points: Vec<Point3<f32>>,
+    root: Node,
+    center: Point3<f32>,
+    half_size: f32,
+}
+
+impl Octree {
+    pub fn new<T: HasCoordinate>(points: &[T]) -> Self {
+        let points: Vec<Point3<f32>> = points.iter().map(|p| p.coordinate()).collect();
+        let (min, max) = points.iter().fold(
+            (
+                Point3::new(f32::MAX, f32::MAX, f32::MAX),
+                Point3::new(f32::MIN, f32::MIN, f32::MIN),
+            ),
+            |(min, max), p| {
+                (
+                    Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                    Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+                )
+            },
+        );
+        let (center, half_size) = if points.is_empty() {
+            (Point3::new(0.0, 0.0, 0.0), 0.0)
+        } else {
+            let extent = max - min;
+            (
+                min + extent / 2.0,
+                extent.x.max(extent.y).max(extent.z) / 2.0,
+            )
+        };
+        let root = Self::build(&points, (0..points.len()).collect(), center, half_size, 0);
+        Self {
+            points,
+            root,
+            center,
+            half_size,
+        }
+    }
+
+    fn build(
+        points: &[Point3<f32>],
+        indices: Vec<usize>,
+        center: Point3<f32>,
+        half_size: f32,
+        depth: usize,
+    ) -> Node {
+        if indices.len() <= MAX_LEAF_POINTS || depth == MAX_DEPTH {
+            return Node::Leaf(indices);
+        }
+        let mut octants = vec![vec![]; 8];
+        for i in indices {
+            octants[octant(&center, &points[i])].push(i);
+        }
+        let quarter = half_size / 2.0;
+        Node::Branch(
+            octants
+                .into_iter()
+                .enumerate()
+                .map(|(o, indices)| {
+                    Self::build(
+                        points,
+                        indices,
+                        child_center(&center, o, quarter),
+                        quarter,
+                        depth + 1,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the indices of the points within `radius` of the center, in no particular order.
+    pub fn query_radius(&self, center: &Point3<f32>, radius: f32) -> Vec<usize> {
+        let mut within = vec![];
+        self.query_node(
+            &self.root,
+            &self.center,
+            self.half_size,
+            center,
+            radius * radius,
+            &mut within,
+        );
+        within
+    }
+
+    fn query_node(
+        &self,
+        node: &Node,
+        node_center: &Point3<f32>,
+        half_size: f32,
+        center: &Point3<f32>,
+        radius_squared: f32,
+        within: &mut Vec<usize>,
+    ) {
+        // squared distance from the query center to the cube of the node
+        let gap = |d: f32| (d.abs() - half_size).max(0.0).powi(2);
+        let offset = *center - *node_center;
+        if gap(offset.x) + gap(offset.y) + gap(offset.z) > radius_squared {
+            return;
+        }
+        match node {
+            Node::Leaf(indices) => within.extend(
+                indices
+                    .iter()
+                    .filter(|&&i| self.points[i].distance2(*center) <= radius_squared),
+            ),
+            Node::Branch(children) => {
+                let quarter = half_size / 2.0;
+                for (o, child) in children.iter().enumerate() {
+                    self.query_node(
+                        child,
+                        &child_center(node_center, o, quarter),
+                        quarter,
+                        center,
+                        radius_squared,
+                        within,
+                    );
+                }
+            }
+        }
+    }
+}
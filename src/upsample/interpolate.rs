@@ -12,14 +12,12 @@ pub fn upsample(point_cloud: PointCloud<PointXyzRgba>, factor: usize) -> PointCl
         let neighbour_radius = factor as f32 * 2.0 * 9.0;
         let mut kd_tree = KdTree::new();
         for (i, pt) in points.iter().enumerate() {
-            kd_tree
-                .add(&[pt.x, pt.y, pt.z], i)
-                .expect("Failed to add to kd tree");
+            kd_tree.add(&pt.xyz(), i).expect("Failed to add to kd tree");
         }
         let mut new_points = vec![];
         let mut processed = HashSet::new();
 
-        for i in 0..point_cloud.number_of_points {
+        for i in 0..points.len() {
             processed.insert(i);
             let point = points[i];
             let x = point.x;
@@ -80,10 +78,7 @@ pub fn upsample(point_cloud: PointCloud<PointXyzRgba>, factor: usize) -> PointCl
             }
         }
         new_points.extend(points);
-        PointCloud {
-            number_of_points: new_points.len(),
-            points: new_points,
-        }
+        PointCloud::from_points(new_points)
     }
 }
 
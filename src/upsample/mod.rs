@@ -1 +1,2 @@
 pub mod interpolate;
+pub mod super_resolution;
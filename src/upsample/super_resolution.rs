@@ -0,0 +1,135 @@
+use kiddo::{distance::squared_euclidean, KdTree};
+use rayon::prelude::*;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::methods::plane::Plane;
+use crate::structures::neighbours::NearestNeighbours;
+
+/// Upsamples the cloud by `factor`: every point adds the midpoints to its `factor - 1` nearest
+/// neighbours with the average of their colors, so the result has `factor` times the points.
+///
+/// With a positive `smooth_radius`, every midpoint is then projected onto the plane fitted to the
+/// input points within `smooth_radius`, a first order moving least squares smoothing removing the
+/// noise the midpoints inherit from the input.
+pub fn midpoint_upsample(
+    cloud: &PointCloud<PointXyzRgba>,
+    factor: usize,
+    smooth_radius: f64,
+) -> PointCloud<PointXyzRgba> {
+    let points = &cloud.points;
+    let mut tree = KdTree::new();
+    for (i, p) in points.iter().enumerate() {
+        tree.add(&p.xyz(), i).expect("Failed to add to tree");
+    }
+
+    let midpoints: Vec<PointXyzRgba> = points
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(i, p)| {
+            let mut neighbours: Vec<usize> = tree
+                .nearest_neighbours(&p.xyz(), factor)
+                .into_iter()
+                .map(|(_, j)| j)
+                .filter(|&j| j != i)
+                .collect();
+            // clouds with less than `factor` points reuse their neighbours
+            if neighbours.is_empty() {
+                neighbours.push(i);
+            }
+            neighbours
+                .into_iter()
+                .cycle()
+                .take(factor.saturating_sub(1))
+                .map(move |j| midpoint(p, &points[j]))
+        })
+        .collect();
+
+    let radius_squared = (smooth_radius * smooth_radius) as f32;
+    let midpoints = midpoints.into_par_iter().map(|m| {
+        if smooth_radius <= 0.0 {
+            return m;
+        }
+        let neighbours = tree
+            .within(&m.xyz(), radius_squared, &squared_euclidean)
+            .expect("Failed to query tree");
+        let neighbours =
+            PointCloud::from_points(neighbours.iter().map(|&(_, &j)| points[j]).collect());
+        match Plane::fit(&neighbours) {
+            Some(plane) => plane.project(&PointCloud::from_points(vec![m])).points[0],
+            None => m,
+        }
+    });
+
+    let mut upsampled = points.clone();
+    upsampled.par_extend(midpoints);
+    PointCloud::from_points(upsampled)
+}
+
+fn midpoint(p: &PointXyzRgba, q: &PointXyzRgba) -> PointXyzRgba {
+    let average = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+    PointXyzRgba {
+        x: (p.x + q.x) / 2.0,
+        y: (p.y + q.y) / 2.0,
+        z: (p.z + q.z) / 2.0,
+        r: average(p.r, q.r),
+        g: average(p.g, q.g),
+        b: average(p.b, q.b),
+        a: average(p.a, q.a),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Random points of the unit square at z = 0 with noise along z, red on the left half and blue
+    /// on the right half.
+    fn noisy_square(n: usize, noise: f32) -> PointCloud<PointXyzRgba> {
+        let mut rng = StdRng::seed_from_u64(7);
+        let points = (0..n)
+            .map(|_| {
+                let x: f32 = rng.gen_range(0.0..1.0);
+                let y: f32 = rng.gen_range(0.0..1.0);
+                let z: f32 = if noise > 0.0 {
+                    rng.gen_range(-noise..noise)
+                } else {
+                    0.0
+                };
+                let color = if x < 0.5 {
+                    [254, 0, 0, 255]
+                } else {
+                    [0, 0, 254, 255]
+                };
+                PointXyzRgba::from_xyz_rgba([x, y, z], color)
+            })
+            .collect();
+        PointCloud::from_points(points)
+    }
+
+    fn mean_abs_z(points: &[PointXyzRgba]) -> f32 {
+        points.iter().map(|p| p.z.abs()).sum::<f32>() / points.len() as f32
+    }
+
+    #[test]
+    fn test_point_count() {
+        let cloud = noisy_square(500, 0.0);
+        for factor in [1, 2, 4] {
+            let upsampled = midpoint_upsample(&cloud, factor, 0.0);
+            assert_eq!(upsampled.len(), factor * 500);
+            assert_eq!(upsampled.points[..500], cloud.points[..]);
+        }
+        assert_eq!(midpoint_upsample(&noisy_square(1, 0.0), 4, 0.0).len(), 4);
+    }
+
+    #[test]
+    fn test_smoothing() {
+        let cloud = noisy_square(2000, 0.01);
+        let midpoints = midpoint_upsample(&cloud, 4, 0.0);
+        let smoothed = midpoint_upsample(&cloud, 4, 0.1);
+        assert_eq!(smoothed.len(), 8000);
+        assert!(mean_abs_z(&smoothed.points[2000..]) < 0.5 * mean_abs_z(&midpoints.points[2000..]));
+        // midpoints across the middle of the square mix both colors
+        assert!(smoothed.points.iter().any(|p| p.r == 127 && p.b == 127));
+    }
+}
@@ -310,7 +310,7 @@ mod test {
     fn test_read_ply() {
         let ply_ascii_path = PathBuf::from("./test_files/ply_ascii/longdress_vox10_1213_short.ply");
         let pc = read_ply(&ply_ascii_path).unwrap();
-        assert_eq!(pc.number_of_points, 20);
+        assert_eq!(pc.len(), 20);
         assert_eq!(
             pc.points[0],
             PointXyzRgba {
@@ -344,7 +344,7 @@ mod test {
         ply_to_ply(&output_path, PCDDataType::Binary, ply_ascii_path);
         let output_path = output_path.join("longdress_vox10_1213_short.ply");
         let pc = read_file_to_point_cloud(&output_path).unwrap();
-        assert_eq!(pc.number_of_points, 20);
+        assert_eq!(pc.len(), 20);
         assert_eq!(
             pc.points[0],
             PointXyzRgba {
@@ -390,7 +390,7 @@ mod test {
         ply_to_pcd(&output_path, PCDDataType::Binary, ply_ascii_path.clone());
         let output_path = output_path.join("longdress_vox10_1213_short.pcd");
         let pc = read_file_to_point_cloud(&output_path).unwrap();
-        assert_eq!(pc.number_of_points, 20);
+        assert_eq!(pc.len(), 20);
         assert_eq!(
             pc.points[0],
             PointXyzRgba {
@@ -432,7 +432,7 @@ mod test {
         ply_to_pcd(&output_path, PCDDataType::Ascii, ply_ascii_path);
         let output_path = output_path.join("longdress_vox10_1213_short.pcd");
         let pc = read_file_to_point_cloud(&output_path).unwrap();
-        assert_eq!(pc.number_of_points, 20);
+        assert_eq!(pc.len(), 20);
         assert_eq!(
             pc.points[0],
             PointXyzRgba {
@@ -478,7 +478,7 @@ mod test {
         pcd_to_ply(&output_path, PCDDataType::Ascii, pcd_ascii_path);
         let output_path = output_path.join("longdress_vox10_1213_short.ply");
         let pc = read_file_to_point_cloud(&output_path).unwrap();
-        assert_eq!(pc.number_of_points, 20);
+        assert_eq!(pc.len(), 20);
         assert_eq!(
             pc.points[0],
             PointXyzRgba {
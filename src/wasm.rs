@@ -0,0 +1,89 @@
+//! WebAssembly bindings of the core algorithms, built with
+//! `wasm-pack build --target nodejs --out-name vivotk -- --no-default-features --features wasm`.
+//! Point clouds are passed as one array per coordinate and returned as interleaved arrays.
+
+use cgmath::Point3;
+use wasm_bindgen::prelude::*;
+
+use crate::formats::{pointxyzrgba::PointXyzRgba, PointCloud};
+use crate::methods::{
+    normals::{adaptive_radii, estimate_normals},
+    sampling::voxel_grid_sample,
+};
+
+fn to_point_cloud(x: &[f32], y: &[f32], z: &[f32]) -> PointCloud<PointXyzRgba> {
+    assert!(
+        x.len() == y.len() && y.len() == z.len(),
+        "Expecting as many x, y and z coordinates"
+    );
+    let points = x
+        .iter()
+        .zip(y)
+        .zip(z)
+        .map(|((&x, &y), &z)| PointXyzRgba::from_xyz_rgba([x, y, z], [0, 0, 0, 255]))
+        .collect();
+    PointCloud::from_points(points)
+}
+
+fn interleave(cloud: &PointCloud<PointXyzRgba>) -> Vec<f32> {
+    cloud.points.iter().flat_map(|p| p.xyz()).collect()
+}
+
+/// Normal of each point from the plane fitted to its neighbours within `radius`, interleaved as
+/// `nx, ny, nz`. Points with fewer than three non collinear neighbours get a zero normal.
+#[wasm_bindgen]
+pub fn normal_estimation_wasm(x: &[f32], y: &[f32], z: &[f32], radius: f64) -> Vec<f32> {
+    let cloud = to_point_cloud(x, y, z);
+    let radii = vec![radius as f32; cloud.points.len()];
+    estimate_normals(&cloud, &radii).concat()
+}
+
+/// Normal of each point with a radius adapted to the local density, `k_scale` times the mean
+/// distance to its 5 nearest neighbours, interleaved as `nx, ny, nz`.
+#[wasm_bindgen]
+pub fn adaptive_normal_estimation_wasm(x: &[f32], y: &[f32], z: &[f32], k_scale: f32) -> Vec<f32> {
+    let cloud = to_point_cloud(x, y, z);
+    estimate_normals(&cloud, &adaptive_radii(&cloud, k_scale)).concat()
+}
+
+/// Voxel grid downsampling, keeping the point nearest to the center of each voxel of side
+/// `leaf_size`, interleaved as `x, y, z`.
+#[wasm_bindgen]
+pub fn downsample_wasm(x: &[f32], y: &[f32], z: &[f32], leaf_size: f32) -> Vec<f32> {
+    interleave(&voxel_grid_sample(&to_point_cloud(x, y, z), leaf_size))
+}
+
+/// Keeps the points inside the axis aligned box spanned by `min` and `max`, given as `[x, y, z]`,
+/// interleaved as `x, y, z`.
+#[wasm_bindgen]
+pub fn filter_wasm(x: &[f32], y: &[f32], z: &[f32], min: &[f32], max: &[f32]) -> Vec<f32> {
+    assert!(
+        min.len() == 3 && max.len() == 3,
+        "Expecting the bounds as [x, y, z]"
+    );
+    let cloud = to_point_cloud(x, y, z).crop_aabb(
+        Point3::new(min[0], min[1], min[2]),
+        Point3::new(max[0], max[1], max[2]),
+    );
+    interleave(&cloud)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normal_estimation_plane() {
+        // 10x10 grid on the plane z = 1
+        let x: Vec<f32> = (0..100).map(|i| (i / 10) as f32 * 0.1).collect();
+        let y: Vec<f32> = (0..100).map(|i| (i % 10) as f32 * 0.1).collect();
+        let z = vec![1.0; 100];
+
+        let normals = normal_estimation_wasm(&x, &y, &z, 0.15);
+        assert_eq!(normals.len(), 300);
+        for n in normals.chunks(3) {
+            assert!(n[0].abs() < 1e-5 && n[1].abs() < 1e-5, "{:?}", n);
+            assert!((n[2].abs() - 1.0).abs() < 1e-5, "{:?}", n);
+        }
+    }
+}
@@ -0,0 +1,62 @@
+"""Checks the arrays written by export-npz and read back by import-npz.
+
+Run after building the binaries, with the path of vv in $VV (default target/release/vv):
+    python3 tests/npz/test_npz.py
+"""
+import os
+import subprocess
+import sys
+import tempfile
+
+import numpy as np
+
+VV = os.environ.get("VV", "target/release/vv")
+
+PLY = """ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+0 1 0.5 0 0 255
+"""
+
+
+def vv(*args):
+    subprocess.run([VV, *args], check=True)
+
+
+def main():
+    with tempfile.TemporaryDirectory() as tmp:
+        ply_dir = os.path.join(tmp, "ply")
+        os.makedirs(ply_dir)
+        with open(os.path.join(ply_dir, "00000.ply"), "w") as f:
+            f.write(PLY)
+
+        exported = os.path.join(tmp, "exported")
+        vv("read", ply_dir, "+output=plys",
+           "export-npz", "--output", exported, "+input=plys")
+        arrays = np.load(os.path.join(exported, "00000.npz"))
+        assert arrays["xyz"].shape == (3, 3) and arrays["xyz"].dtype == np.float32
+        assert arrays["rgb"].shape == (3, 3) and arrays["rgb"].dtype == np.uint8
+        assert "normals" not in arrays
+        np.testing.assert_array_equal(arrays["xyz"][2], [0, 1, 0.5])
+        np.testing.assert_array_equal(arrays["rgb"][2], [0, 0, 255])
+
+        reexported = os.path.join(tmp, "reexported")
+        vv("import-npz", exported, "+output=clouds",
+           "export-npz", "--output", reexported, "+input=clouds")
+        round_trip = np.load(os.path.join(reexported, "00000.npz"))
+        np.testing.assert_array_equal(round_trip["xyz"], arrays["xyz"])
+        np.testing.assert_array_equal(round_trip["rgb"], arrays["rgb"])
+    print("npz tests passed")
+
+
+if __name__ == "__main__":
+    sys.exit(main())
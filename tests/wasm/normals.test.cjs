@@ -0,0 +1,31 @@
+// Runs against the package built by
+// wasm-pack build --target nodejs --out-name vivotk -- --no-default-features --features wasm
+const assert = require("node:assert/strict");
+const {
+  normal_estimation_wasm,
+  downsample_wasm,
+  filter_wasm,
+} = require("../../pkg/vivotk.js");
+
+// 10x10 grid of spacing 0.1 on the plane z = 1
+const x = Float32Array.from({ length: 100 }, (_, i) => Math.floor(i / 10) * 0.1);
+const y = Float32Array.from({ length: 100 }, (_, i) => (i % 10) * 0.1);
+const z = new Float32Array(100).fill(1.0);
+
+const normals = normal_estimation_wasm(x, y, z, 0.15);
+assert.equal(normals.length, 300);
+for (let i = 0; i < normals.length; i += 3) {
+  assert.ok(Math.abs(normals[i]) < 1e-5);
+  assert.ok(Math.abs(normals[i + 1]) < 1e-5);
+  assert.ok(Math.abs(Math.abs(normals[i + 2]) - 1.0) < 1e-5);
+}
+
+// one point per 0.25 x 0.25 cell, 4x4 cells hold points
+const downsampled = downsample_wasm(x, y, z, 0.25);
+assert.equal(downsampled.length, 16 * 3);
+
+// the 5x5 points with x, y < 0.45
+const filtered = filter_wasm(x, y, z, [-1, -1, -1], [0.45, 0.45, 2]);
+assert.equal(filtered.length, 25 * 3);
+
+console.log("wasm tests passed");